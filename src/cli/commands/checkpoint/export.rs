@@ -0,0 +1,101 @@
+// Copyright 2022-2023 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Export checkpoint cli command
+
+use std::fmt::Debug;
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+use base64::Engine;
+use cid::Cid;
+use clap::Args;
+use fvm_shared::clock::ChainEpoch;
+use serde_json::json;
+use std::str::FromStr;
+
+use crate::cli::commands::{get_ipc_agent_url, print_json};
+use crate::cli::{CommandLineHandler, GlobalArguments};
+use crate::config::json_rpc_methods;
+use crate::jsonrpc::JsonRpcClient;
+use crate::serialization::car::validate_car;
+use crate::server::export_checkpoint::{
+    ExportBottomUpCheckpointParams, ExportBottomUpCheckpointResponse,
+};
+
+/// The only export format supported for now.
+const CAR_FORMAT: &str = "car";
+
+/// The command to export a checkpoint committed in a subnet actor.
+pub(crate) struct ExportBottomUpCheckpoint;
+
+#[async_trait]
+impl CommandLineHandler for ExportBottomUpCheckpoint {
+    type Arguments = ExportBottomUpCheckpointArgs;
+
+    async fn handle(global: &GlobalArguments, arguments: &Self::Arguments) -> anyhow::Result<()> {
+        log::debug!("export checkpoint with args: {:?}", arguments);
+
+        if arguments.format != CAR_FORMAT {
+            return Err(anyhow!("unsupported export format: {}", arguments.format));
+        }
+
+        let url = get_ipc_agent_url(&arguments.ipc_agent_url, global)?;
+        let json_rpc_client = global.json_rpc_client(url);
+
+        let params = ExportBottomUpCheckpointParams {
+            subnet_id: arguments.subnet.clone(),
+            epoch: arguments.epoch,
+            with_cross_msgs: arguments.with_cross_msgs,
+        };
+
+        let response = json_rpc_client
+            .request::<ExportBottomUpCheckpointResponse>(
+                json_rpc_methods::EXPORT_BOTTOMUP_CHECKPOINT,
+                serde_json::to_value(params)?,
+            )
+            .await?;
+
+        let cid = Cid::from_str(&response.cid)?;
+        let car = base64::engine::general_purpose::STANDARD.decode(response.car)?;
+        validate_car(&car, &[cid])?;
+
+        std::fs::write(&arguments.output_file, car)?;
+        if global.json_output() {
+            return print_json(&json!({
+                "cid": cid.to_string(),
+                "epoch": arguments.epoch,
+                "path": arguments.output_file,
+            }));
+        }
+
+        log::info!(
+            "exported checkpoint {} at epoch {} to {}",
+            cid,
+            arguments.epoch,
+            arguments.output_file
+        );
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Args)]
+#[command(about = "Export a bottom-up checkpoint")]
+pub(crate) struct ExportBottomUpCheckpointArgs {
+    #[arg(long, short, help = "The JSON RPC server url for ipc agent")]
+    pub ipc_agent_url: Option<String>,
+    #[arg(long, short, help = "The subnet id of the checkpointing subnet")]
+    pub subnet: String,
+    #[arg(long, short, help = "The epoch of the checkpoint")]
+    pub epoch: ChainEpoch,
+    #[arg(long, default_value = CAR_FORMAT, help = "The export format, only car is supported")]
+    pub format: String,
+    #[arg(
+        long = "output-file",
+        short = 'f',
+        help = "The file to write the checkpoint to"
+    )]
+    pub output_file: String,
+    #[arg(long, help = "Include the cross-messages of the checkpoint")]
+    pub with_cross_msgs: bool,
+}
@@ -4,17 +4,21 @@
 
 use std::fmt::Debug;
 
+use anyhow::anyhow;
 use async_trait::async_trait;
 use clap::Args;
 use fvm_shared::clock::ChainEpoch;
 use serde_json::Value;
 
-use crate::cli::commands::get_ipc_agent_url;
+use crate::cli::commands::{get_ipc_agent_url, print_json};
 use crate::cli::{CommandLineHandler, GlobalArguments};
 use crate::config::json_rpc_methods;
-use crate::jsonrpc::{JsonRpcClient, JsonRpcClientImpl};
+use crate::jsonrpc::JsonRpcClient;
 use crate::server::list_checkpoints::ListBottomUpCheckpointsParams;
 
+/// The columns of the CSV output, see [`CheckpointRow`].
+const CSV_HEADER: &str = "epoch,prev_check,cross_msgs,source";
+
 /// The command to list checkpoints committed in a subnet actor.
 pub(crate) struct ListBottomUpCheckpoints;
 
@@ -25,23 +29,80 @@ impl CommandLineHandler for ListBottomUpCheckpoints {
     async fn handle(global: &GlobalArguments, arguments: &Self::Arguments) -> anyhow::Result<()> {
         log::debug!("list checkpoints with args: {:?}", arguments);
 
+        if arguments.output_file.is_some() && !arguments.csv {
+            return Err(anyhow!("--output-file is only supported with --csv"));
+        }
+        if global.json_output() && arguments.csv {
+            return Err(anyhow!("--csv cannot be combined with --output json"));
+        }
+
         let url = get_ipc_agent_url(&arguments.ipc_agent_url, global)?;
-        let json_rpc_client = JsonRpcClientImpl::new(url, None);
+        let json_rpc_client = global.json_rpc_client(url);
 
-        let params = ListBottomUpCheckpointsParams {
+        let mut params = ListBottomUpCheckpointsParams {
             subnet_id: arguments.subnet.clone(),
             from_epoch: arguments.from_epoch,
             to_epoch: arguments.to_epoch,
+            align: arguments.align,
+            offset: arguments.offset,
+            limit: arguments.limit,
+            with_page_info: true,
         };
 
         let checkpoints = json_rpc_client
             .request::<Value>(
                 json_rpc_methods::LIST_BOTTOMUP_CHECKPOINTS,
-                serde_json::to_value(params)?,
+                serde_json::to_value(&params)?,
             )
             .await?;
 
-        for c in checkpoints.as_array().unwrap().iter() {
+        if global.json_output() {
+            return print_json(&checkpoints);
+        }
+
+        if let Some(note) = checkpoints["note"].as_str() {
+            log::info!("{note}");
+        }
+
+        let list = checkpoints["checkpoints"]
+            .as_array()
+            .ok_or_else(|| anyhow!("no checkpoints in the response"))?;
+        if arguments.csv {
+            // the export covers the whole range, requesting the pages after the first one
+            let mut rows = list
+                .iter()
+                .map(CheckpointRow::from_json)
+                .collect::<anyhow::Result<Vec<_>>>()?;
+            let mut next_offset = checkpoints["next_offset"].as_u64();
+            while let Some(offset) = next_offset {
+                params.offset = offset as usize;
+                let page = json_rpc_client
+                    .request::<Value>(
+                        json_rpc_methods::LIST_BOTTOMUP_CHECKPOINTS,
+                        serde_json::to_value(&params)?,
+                    )
+                    .await?;
+                for c in page["checkpoints"]
+                    .as_array()
+                    .ok_or_else(|| anyhow!("no checkpoints in the response"))?
+                {
+                    rows.push(CheckpointRow::from_json(c)?);
+                }
+                next_offset = page["next_offset"].as_u64();
+            }
+
+            let csv = to_csv(&rows);
+            match &arguments.output_file {
+                Some(path) => {
+                    std::fs::write(path, csv)?;
+                    log::info!("wrote {} checkpoints to {path}", rows.len());
+                }
+                None => print!("{csv}"),
+            }
+            return Ok(());
+        }
+
+        for c in list.iter() {
             let c = &c["data"];
             log::info!(
                 "epoch {} - prev_check={}, cross_msgs={}, child_checks={}",
@@ -52,6 +113,10 @@ impl CommandLineHandler for ListBottomUpCheckpoints {
             );
         }
 
+        if let Some(next_offset) = checkpoints["next_offset"].as_u64() {
+            log::info!("more checkpoints in the range, list them with --offset {next_offset}");
+        }
+
         Ok(())
     }
 }
@@ -67,4 +132,148 @@ pub(crate) struct ListBottomUpCheckpointsArgs {
     pub from_epoch: ChainEpoch,
     #[arg(long, short, help = "Include checkpoints up to this epoch")]
     pub to_epoch: ChainEpoch,
+    #[arg(
+        long,
+        help = "Only query the epochs aligned to the checkpoint period of the subnet"
+    )]
+    pub align: bool,
+    #[arg(
+        long,
+        default_value = "0",
+        help = "The number of checkpoint periods of the range to skip, as given by the previous page"
+    )]
+    pub offset: usize,
+    #[arg(
+        long,
+        help = "The maximum number of checkpoints to list, default to and capped at 100. With --csv, the size of the pages the whole range is exported in"
+    )]
+    pub limit: Option<usize>,
+    #[arg(long, help = "Print a CSV row per checkpoint instead of logging them")]
+    pub csv: bool,
+    #[arg(long, help = "The file to write the csv output to, instead of stdout")]
+    pub output_file: Option<String>,
+}
+
+/// A checkpoint flattened into the columns of the CSV output.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct CheckpointRow {
+    pub epoch: ChainEpoch,
+    pub prev_check: String,
+    pub cross_msgs: usize,
+    pub source: String,
+}
+
+impl CheckpointRow {
+    /// Flattens a checkpoint as serialized by the list checkpoints method of the ipc agent.
+    pub(crate) fn from_json(checkpoint: &Value) -> anyhow::Result<Self> {
+        let data = &checkpoint["data"];
+        let epoch = data["epoch"]
+            .as_i64()
+            .ok_or_else(|| anyhow!("checkpoint without epoch: {data:}"))?;
+        let field = |name: &str| {
+            data[name]
+                .as_str()
+                .map(String::from)
+                .ok_or_else(|| anyhow!("checkpoint at epoch {epoch:} without {name:}"))
+        };
+        let cross_msgs = data["cross_msgs"]["cross_msgs"]
+            .as_array()
+            .map(Vec::len)
+            .unwrap_or_default();
+
+        Ok(CheckpointRow {
+            epoch,
+            prev_check: field("prev_check")?,
+            cross_msgs,
+            source: field("source")?,
+        })
+    }
+}
+
+/// Returns `rows` as CSV, with a header line and a line per row.
+pub(crate) fn to_csv(rows: &[CheckpointRow]) -> String {
+    let mut csv = format!("{CSV_HEADER}\n");
+    for row in rows {
+        csv.push_str(&format!(
+            "{},{},{},{}\n",
+            row.epoch,
+            csv_field(&row.prev_check),
+            row.cross_msgs,
+            csv_field(&row.source)
+        ));
+    }
+    csv
+}
+
+/// Quotes `field` if it contains a separator, a quote or a line break, doubling its quotes.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fvm_ipld_encoding::RawBytes;
+    use fvm_shared::address::Address;
+    use fvm_shared::econ::TokenAmount;
+    use ipc_gateway::{BottomUpCheckpoint, CrossMsg, StorableMsg};
+    use ipc_sdk::address::IPCAddress;
+    use ipc_sdk::subnet_id::{SubnetID, ROOTNET_ID};
+
+    use crate::cli::commands::checkpoint::list_checkpoints::{csv_field, to_csv, CheckpointRow};
+    use crate::serialization::SerializeToJson;
+
+    fn checkpoint_json(checkpoint: BottomUpCheckpoint) -> serde_json::Value {
+        serde_json::to_value(SerializeToJson(checkpoint)).unwrap()
+    }
+
+    #[test]
+    fn test_checkpoint_rows_to_csv() {
+        let child = SubnetID::new_from_parent(&ROOTNET_ID, Address::new_id(1002));
+        let empty = BottomUpCheckpoint::new(child.clone(), 10);
+
+        let mut with_msgs = BottomUpCheckpoint::new(child.clone(), 20);
+        with_msgs.data.cross_msgs.cross_msgs = Some(
+            (0..3)
+                .map(|nonce| CrossMsg {
+                    msg: StorableMsg {
+                        from: IPCAddress::new(&child, &Address::new_id(100)).unwrap(),
+                        to: IPCAddress::new(&ROOTNET_ID, &Address::new_id(100)).unwrap(),
+                        method: 0,
+                        params: RawBytes::default(),
+                        value: TokenAmount::from_whole(1),
+                        nonce,
+                    },
+                    wrapped: false,
+                })
+                .collect(),
+        );
+        let prev_check = with_msgs.data.prev_check.to_string();
+
+        let rows = [empty, with_msgs]
+            .into_iter()
+            .map(|c| CheckpointRow::from_json(&checkpoint_json(c)).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(rows[0].cross_msgs, 0);
+        assert_eq!(rows[1].cross_msgs, 3);
+
+        assert_eq!(
+            to_csv(&rows),
+            format!(
+                "epoch,prev_check,cross_msgs,source\n\
+                 10,{prev_check},0,{child}\n\
+                 20,{prev_check},3,{child}\n"
+            )
+        );
+    }
+
+    #[test]
+    fn test_csv_field() {
+        assert_eq!(csv_field("/root/t01002"), "/root/t01002");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
 }
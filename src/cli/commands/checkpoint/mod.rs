@@ -6,10 +6,14 @@ use crate::cli::commands::checkpoint::list_checkpoints::{
 use crate::cli::{CommandLineHandler, GlobalArguments};
 use clap::{Args, Subcommand};
 
+use self::export::{ExportBottomUpCheckpoint, ExportBottomUpCheckpointArgs};
 use self::topdown_executed::{LastTopDownExec, LastTopDownExecArgs};
+use self::verify_chain::{VerifyCheckpointChain, VerifyCheckpointChainArgs};
 
+mod export;
 mod list_checkpoints;
 mod topdown_executed;
+mod verify_chain;
 
 #[derive(Debug, Args)]
 #[command(name = "checkpoint", about = "checkpoint related commands")]
@@ -24,6 +28,8 @@ impl CheckpointCommandsArgs {
         match &self.command {
             Commands::ListBottomup(args) => ListBottomUpCheckpoints::handle(global, args).await,
             Commands::LastTopdown(args) => LastTopDownExec::handle(global, args).await,
+            Commands::Export(args) => ExportBottomUpCheckpoint::handle(global, args).await,
+            Commands::VerifyChain(args) => VerifyCheckpointChain::handle(global, args).await,
         }
     }
 }
@@ -32,4 +38,6 @@ impl CheckpointCommandsArgs {
 pub(crate) enum Commands {
     ListBottomup(ListBottomUpCheckpointsArgs),
     LastTopdown(LastTopDownExecArgs),
+    Export(ExportBottomUpCheckpointArgs),
+    VerifyChain(VerifyCheckpointChainArgs),
 }
@@ -6,12 +6,12 @@ use std::fmt::Debug;
 
 use async_trait::async_trait;
 use clap::Args;
-use serde_json::Value;
+use serde_json::{json, Value};
 
-use crate::cli::commands::get_ipc_agent_url;
+use crate::cli::commands::{get_ipc_agent_url, print_json};
 use crate::cli::{CommandLineHandler, GlobalArguments};
 use crate::config::json_rpc_methods;
-use crate::jsonrpc::{JsonRpcClient, JsonRpcClientImpl};
+use crate::jsonrpc::JsonRpcClient;
 use crate::server::topdown_executed::LastTopDownExecParams;
 
 /// The command to get the latest epoch executed for a top-down checkpoint
@@ -25,7 +25,7 @@ impl CommandLineHandler for LastTopDownExec {
         log::debug!("last topdown exec with args: {:?}", arguments);
 
         let url = get_ipc_agent_url(&arguments.ipc_agent_url, global)?;
-        let json_rpc_client = JsonRpcClientImpl::new(url, None);
+        let json_rpc_client = global.json_rpc_client(url);
 
         let params = LastTopDownExecParams {
             subnet_id: arguments.subnet.clone(),
@@ -38,6 +38,12 @@ impl CommandLineHandler for LastTopDownExec {
             )
             .await?;
 
+        if global.json_output() {
+            return print_json(&json!({
+                "epoch": epoch,
+            }));
+        }
+
         log::info!("Last top-down checkpoint executed in epoch: {epoch:}");
 
         Ok(())
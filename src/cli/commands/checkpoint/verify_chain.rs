@@ -0,0 +1,87 @@
+// Copyright 2022-2023 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Verify checkpoint chain cli command
+
+use std::fmt::Debug;
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+use clap::Args;
+use fvm_shared::clock::ChainEpoch;
+
+use crate::cli::commands::{get_ipc_agent_url, print_json};
+use crate::cli::{CommandLineHandler, GlobalArguments};
+use crate::config::json_rpc_methods;
+use crate::jsonrpc::JsonRpcClient;
+use crate::server::verify_checkpoint_chain::{
+    VerifyCheckpointChainParams, VerifyCheckpointChainResponse,
+};
+
+/// The command to verify the prev-links of the checkpoints committed by a subnet.
+pub(crate) struct VerifyCheckpointChain;
+
+#[async_trait]
+impl CommandLineHandler for VerifyCheckpointChain {
+    type Arguments = VerifyCheckpointChainArgs;
+
+    async fn handle(global: &GlobalArguments, arguments: &Self::Arguments) -> anyhow::Result<()> {
+        log::debug!("verify checkpoint chain with args: {:?}", arguments);
+
+        let url = get_ipc_agent_url(&arguments.ipc_agent_url, global)?;
+        let json_rpc_client = global.json_rpc_client(url);
+
+        let params = VerifyCheckpointChainParams {
+            subnet_id: arguments.subnet.clone(),
+            from_epoch: arguments.from_epoch,
+            to_epoch: arguments.to_epoch,
+        };
+
+        let response = json_rpc_client
+            .request::<VerifyCheckpointChainResponse>(
+                json_rpc_methods::VERIFY_BOTTOMUP_CHECKPOINT_CHAIN,
+                serde_json::to_value(params)?,
+            )
+            .await?;
+
+        if global.json_output() {
+            print_json(&response)?;
+        }
+
+        if !response.errors.is_empty() {
+            for e in response.errors.iter() {
+                log::error!("{e:}");
+            }
+            return Err(anyhow!(
+                "checkpoint chain of {} is broken in {} places",
+                arguments.subnet,
+                response.errors.len()
+            ));
+        }
+
+        log::info!(
+            "verified {} checkpoints of {} between epochs {} and {}",
+            response.checkpoints,
+            arguments.subnet,
+            arguments.from_epoch,
+            arguments.to_epoch
+        );
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Args)]
+#[command(
+    name = "verify-chain",
+    about = "Verify that the checkpoints of a subnet link to the checkpoints committed in the parent"
+)]
+pub(crate) struct VerifyCheckpointChainArgs {
+    #[arg(long, short, help = "The JSON RPC server url for ipc agent")]
+    pub ipc_agent_url: Option<String>,
+    #[arg(long, short, help = "The subnet id of the checkpointing subnet")]
+    pub subnet: String,
+    #[arg(long, short, help = "Include checkpoints from this epoch")]
+    pub from_epoch: ChainEpoch,
+    #[arg(long, short, help = "Include checkpoints up to this epoch")]
+    pub to_epoch: ChainEpoch,
+}
@@ -0,0 +1,67 @@
+// Copyright 2022-2023 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Shell completions cli command
+
+use async_trait::async_trait;
+use clap::{Args, CommandFactory};
+use clap_complete::Shell;
+use std::fmt::Debug;
+
+use crate::cli::commands::IPCAgentCliCommands;
+use crate::cli::{CommandLineHandler, GlobalArguments};
+
+/// The name of the binary the completions are registered for.
+const BIN_NAME: &str = "ipc-agent";
+
+/// The command to print the completion script of a shell.
+pub(crate) struct Completions;
+
+#[async_trait]
+impl CommandLineHandler for Completions {
+    type Arguments = CompletionsArgs;
+
+    async fn handle(_global: &GlobalArguments, arguments: &Self::Arguments) -> anyhow::Result<()> {
+        log::debug!("completions with args: {:?}", arguments);
+
+        let mut command = IPCAgentCliCommands::command();
+        clap_complete::generate(
+            arguments.shell,
+            &mut command,
+            BIN_NAME,
+            &mut std::io::stdout(),
+        );
+        Ok(())
+    }
+}
+
+#[derive(Debug, Args)]
+#[command(about = "Print the completion script of a shell")]
+pub(crate) struct CompletionsArgs {
+    #[arg(value_enum, help = "The shell to complete in")]
+    pub shell: Shell,
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::{CommandFactory, ValueEnum};
+    use clap_complete::Shell;
+
+    use crate::cli::commands::completions::BIN_NAME;
+    use crate::cli::commands::IPCAgentCliCommands;
+
+    #[test]
+    fn test_generate() {
+        for shell in Shell::value_variants() {
+            let mut script = vec![];
+            clap_complete::generate(
+                *shell,
+                &mut IPCAgentCliCommands::command(),
+                BIN_NAME,
+                &mut script,
+            );
+            let script = String::from_utf8(script).unwrap();
+            assert!(script.contains("ipc-agent"), "{shell}");
+            assert!(script.contains("checkpoint"), "{shell}");
+        }
+    }
+}
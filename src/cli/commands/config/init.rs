@@ -1,8 +1,10 @@
 // Copyright 2022-2023 Protocol Labs
 // SPDX-License-Identifier: MIT
+use crate::cli::commands::print_json;
 use crate::cli::{CommandLineHandler, GlobalArguments};
 use crate::config::DEFAULT_CONFIG_TEMPLATE;
 use async_trait::async_trait;
+use serde_json::json;
 use std::io::Write;
 
 use clap::Args;
@@ -14,11 +16,17 @@ pub(crate) struct InitConfig;
 impl CommandLineHandler for InitConfig {
     type Arguments = InitConfigArgs;
 
-    async fn handle(global: &GlobalArguments, _arguments: &Self::Arguments) -> anyhow::Result<()> {
+    async fn handle(global: &GlobalArguments, arguments: &Self::Arguments) -> anyhow::Result<()> {
         let path = global.config_path();
         log::debug!("initializing empty config file in {}", path);
 
         let file_path = std::path::Path::new(&path);
+        if file_path.exists() && !arguments.force {
+            return Err(anyhow::anyhow!(
+                "config file {path} already exists, use --force to overwrite it"
+            ));
+        }
+
         if let Some(parent) = file_path.parent() {
             std::fs::create_dir_all(parent)?;
         }
@@ -32,6 +40,12 @@ impl CommandLineHandler for InitConfig {
                 e
             })?;
 
+        if global.json_output() {
+            return print_json(&json!({
+                "path": path,
+            }));
+        }
+
         log::info!("Empty config populated successful in {}", &path);
 
         Ok(())
@@ -40,4 +54,7 @@ impl CommandLineHandler for InitConfig {
 
 #[derive(Debug, Args)]
 #[command(about = "Arguments to initialize a new empty config file")]
-pub(crate) struct InitConfigArgs {}
+pub(crate) struct InitConfigArgs {
+    #[arg(long, help = "Overwrite the config file if it already exists")]
+    pub force: bool,
+}
@@ -1,12 +1,14 @@
 // Copyright 2022-2023 Protocol Labs
 // SPDX-License-Identifier: MIT
-use crate::cli::commands::get_ipc_agent_url;
+use crate::cli::commands::{get_ipc_agent_url, print_json};
 use crate::cli::{CommandLineHandler, GlobalArguments};
+use crate::config::diff::ConfigDiff;
 use crate::config::json_rpc_methods;
-use crate::jsonrpc::{JsonRpcClient, JsonRpcClientImpl};
+use crate::jsonrpc::JsonRpcClient;
 use crate::server::ReloadConfigParams;
 use async_trait::async_trait;
 use clap::Args;
+use serde_json::json;
 
 /// The command to reload the agent config after an update
 pub(crate) struct ReloadConfig;
@@ -20,19 +22,44 @@ impl CommandLineHandler for ReloadConfig {
 
         let params = ReloadConfigParams {
             path: arguments.path.clone(),
+            dry_run: arguments.dry_run,
         };
 
         let url = get_ipc_agent_url(&arguments.ipc_agent_url, global)?;
-        let json_rpc_client = JsonRpcClientImpl::new(url, None);
+        let json_rpc_client = global.json_rpc_client(url);
 
-        json_rpc_client
-            .request::<()>(
+        let diff = json_rpc_client
+            .request::<ConfigDiff>(
                 json_rpc_methods::RELOAD_CONFIG,
                 serde_json::to_value(params)?,
             )
             .await?;
 
-        log::info!("Reload json rpc config successful");
+        if global.json_output() {
+            return print_json(&json!({
+                "dry_run": arguments.dry_run,
+                "diff": diff,
+            }));
+        }
+
+        if diff.is_empty() {
+            log::info!("no config changes");
+        }
+        for subnet in diff.added_subnets.iter() {
+            log::info!("added subnet: {subnet}");
+        }
+        for subnet in diff.removed_subnets.iter() {
+            log::info!("removed subnet: {subnet}");
+        }
+        for change in diff.changed.iter() {
+            log::info!("changed {}: {} -> {}", change.field, change.old, change.new);
+        }
+
+        if arguments.dry_run {
+            log::info!("dry run, config not reloaded");
+        } else {
+            log::info!("Reload json rpc config successful");
+        }
 
         Ok(())
     }
@@ -49,4 +76,9 @@ pub(crate) struct ReloadConfigArgs {
     pub path: Option<String>,
     #[arg(short, long, help = "The JSON RPC server url for ipc agent, optional")]
     pub ipc_agent_url: Option<String>,
+    #[arg(
+        long,
+        help = "Only show the changes the reload would apply, without applying them"
+    )]
+    pub dry_run: bool,
 }
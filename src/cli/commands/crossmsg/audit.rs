@@ -0,0 +1,84 @@
+// Copyright 2022-2023 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Cross-message audit cli command
+
+use async_trait::async_trait;
+use clap::Args;
+use fvm_shared::clock::ChainEpoch;
+use std::fmt::Debug;
+
+use crate::cli::commands::{get_ipc_agent_url, print_json};
+use crate::cli::{CommandLineHandler, GlobalArguments};
+use crate::config::json_rpc_methods;
+use crate::jsonrpc::JsonRpcClient;
+use crate::server::crossmsg_audit::{CrossMsgAuditParams, CrossMsgAuditResponse};
+
+/// The command to reconcile the value of the cross-messages of a subnet with its supply.
+pub(crate) struct CrossMsgAudit;
+
+#[async_trait]
+impl CommandLineHandler for CrossMsgAudit {
+    type Arguments = CrossMsgAuditArgs;
+
+    async fn handle(global: &GlobalArguments, arguments: &Self::Arguments) -> anyhow::Result<()> {
+        log::debug!("audit cross messages with args: {:?}", arguments);
+
+        let url = get_ipc_agent_url(&arguments.ipc_agent_url, global)?;
+        let json_rpc_client = global.json_rpc_client(url);
+
+        let params = CrossMsgAuditParams {
+            subnet: arguments.subnet.clone(),
+            from_epoch: arguments.from_epoch,
+            to_epoch: arguments.to_epoch,
+        };
+
+        let r = json_rpc_client
+            .request::<CrossMsgAuditResponse>(
+                json_rpc_methods::CROSS_MSG_AUDIT,
+                serde_json::to_value(params)?,
+            )
+            .await?;
+
+        if global.json_output() {
+            return print_json(&r);
+        }
+
+        log::info!(
+            "subnet {:} from epoch {:} to {:}: top-down {:} FIL, bottom-up {:} FIL, fees {:} FIL",
+            arguments.subnet,
+            arguments.from_epoch,
+            arguments.to_epoch,
+            r.topdown_value,
+            r.bottomup_value,
+            r.fees
+        );
+        match (r.circ_supply, r.discrepancy) {
+            (Some(circ_supply), Some(discrepancy)) if r.flagged => log::warn!(
+                "circulating supply {circ_supply:} FIL differs from the net cross-message value by {discrepancy:} FIL, more than the fees paid"
+            ),
+            (Some(circ_supply), Some(discrepancy)) => log::info!(
+                "circulating supply {circ_supply:} FIL reconciles with the net cross-message value (difference {discrepancy:} FIL)"
+            ),
+            _ => log::info!(
+                "the range does not cover the life of the subnet, circulating supply not reconciled"
+            ),
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Args)]
+#[command(
+    about = "Audit the value of the cross-messages of a subnet against its circulating supply"
+)]
+pub(crate) struct CrossMsgAuditArgs {
+    #[arg(long, short, help = "The JSON RPC server url for ipc agent")]
+    pub ipc_agent_url: Option<String>,
+    #[arg(long, short, help = "The subnet to audit")]
+    pub subnet: String,
+    #[arg(long, short, help = "Include cross-messages from this epoch")]
+    pub from_epoch: ChainEpoch,
+    #[arg(long, short, help = "Include cross-messages up to this epoch")]
+    pub to_epoch: ChainEpoch,
+}
@@ -0,0 +1,71 @@
+// Copyright 2022-2023 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Cross-message backlog cli command
+
+use async_trait::async_trait;
+use clap::Args;
+use std::fmt::Debug;
+
+use crate::cli::commands::{get_ipc_agent_url, print_json};
+use crate::cli::{CommandLineHandler, GlobalArguments};
+use crate::config::json_rpc_methods;
+use crate::jsonrpc::JsonRpcClient;
+use crate::server::crossmsg_backlog::{CrossMsgBacklogParams, CrossMsgBacklogResponse};
+
+/// The command to report the cross-messages of a subnet committed but not applied yet.
+pub(crate) struct CrossMsgBacklog;
+
+#[async_trait]
+impl CommandLineHandler for CrossMsgBacklog {
+    type Arguments = CrossMsgBacklogArgs;
+
+    async fn handle(global: &GlobalArguments, arguments: &Self::Arguments) -> anyhow::Result<()> {
+        log::debug!("cross message backlog with args: {:?}", arguments);
+
+        let url = get_ipc_agent_url(&arguments.ipc_agent_url, global)?;
+        let json_rpc_client = global.json_rpc_client(url);
+
+        let params = CrossMsgBacklogParams {
+            subnet: arguments.subnet.clone(),
+        };
+
+        let r = json_rpc_client
+            .request::<CrossMsgBacklogResponse>(
+                json_rpc_methods::CROSS_MSG_BACKLOG,
+                serde_json::to_value(params)?,
+            )
+            .await?;
+
+        if global.json_output() {
+            return print_json(&r);
+        }
+
+        log::info!(
+            "top-down: {:} pending, committed up to nonce {:} in the parent at epoch {:}, applied up to nonce {:} in the subnet at epoch {:}",
+            r.topdown_pending,
+            r.parent.next_topdown,
+            r.parent_epoch,
+            r.child_applied_topdown,
+            r.child_epoch
+        );
+        log::info!(
+            "bottom-up: {:} pending, sent up to nonce {:} from the subnet at epoch {:}, applied up to nonce {:} in the parent at epoch {:}",
+            r.bottomup_pending,
+            r.child_next_bottomup,
+            r.child_epoch,
+            r.parent.applied_bottomup,
+            r.parent_epoch
+        );
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Args)]
+#[command(about = "Report the cross-messages of a subnet committed but not applied yet")]
+pub(crate) struct CrossMsgBacklogArgs {
+    #[arg(long, short, help = "The JSON RPC server url for ipc agent")]
+    pub ipc_agent_url: Option<String>,
+    #[arg(long, short, help = "The subnet to report the backlog of")]
+    pub subnet: String,
+}
@@ -6,11 +6,11 @@ use async_trait::async_trait;
 use clap::Args;
 use std::fmt::Debug;
 
-use crate::cli::commands::get_ipc_agent_url;
+use crate::cli::commands::{get_ipc_agent_url, print_json};
 use crate::cli::{CommandLineHandler, GlobalArguments};
 use crate::config::json_rpc_methods;
-use crate::jsonrpc::{JsonRpcClient, JsonRpcClientImpl};
-use crate::server::fund::FundParams;
+use crate::jsonrpc::JsonRpcClient;
+use crate::server::fund::{FundParams, FundResponse};
 
 /// The command to send funds to a subnet from parent
 pub(crate) struct Fund;
@@ -23,18 +23,26 @@ impl CommandLineHandler for Fund {
         log::debug!("fund operation with args: {:?}", arguments);
 
         let url = get_ipc_agent_url(&arguments.ipc_agent_url, global)?;
-        let json_rpc_client = JsonRpcClientImpl::new(url, None);
+        let json_rpc_client = global.submit_json_rpc_client(url);
 
         let params = FundParams {
             subnet: arguments.subnet.clone(),
             from: arguments.from.clone(),
             amount: arguments.amount,
         };
-        json_rpc_client
-            .request::<()>(json_rpc_methods::FUND, serde_json::to_value(params)?)
+        let response = json_rpc_client
+            .request::<FundResponse>(json_rpc_methods::FUND, serde_json::to_value(params)?)
             .await?;
 
-        log::info!("funded subnet: {:}", arguments.subnet);
+        if global.json_output() {
+            return print_json(&response);
+        }
+
+        log::info!(
+            "funded subnet: {:} in message: {:}",
+            arguments.subnet,
+            response.cid
+        );
 
         Ok(())
     }
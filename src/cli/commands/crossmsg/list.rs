@@ -0,0 +1,102 @@
+// Copyright 2022-2023 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! List top-down cross-messages cli command
+
+use async_trait::async_trait;
+use clap::Args;
+use ipc_sdk::subnet_id::SubnetID;
+use std::fmt::Debug;
+
+use crate::cli::commands::parse::parse_subnet_id;
+use crate::cli::commands::table::{render, Align};
+use crate::cli::commands::{get_ipc_agent_url, print_json};
+use crate::cli::{CommandLineHandler, GlobalArguments};
+use crate::config::json_rpc_methods;
+use crate::jsonrpc::JsonRpcClient;
+use crate::server::crossmsg_list::{CrossMsgEntry, CrossMsgListParams, CrossMsgListResponse};
+
+/// The command to list the top-down messages committed in the parent of a subnet.
+pub(crate) struct ListCrossMsgs;
+
+#[async_trait]
+impl CommandLineHandler for ListCrossMsgs {
+    type Arguments = ListCrossMsgsArgs;
+
+    async fn handle(global: &GlobalArguments, arguments: &Self::Arguments) -> anyhow::Result<()> {
+        log::debug!("list cross messages with args: {:?}", arguments);
+
+        let url = get_ipc_agent_url(&arguments.ipc_agent_url, global)?;
+        let json_rpc_client = global.json_rpc_client(url);
+
+        let params = CrossMsgListParams {
+            subnet: arguments.subnet.to_string(),
+            from_nonce: arguments.from_nonce,
+        };
+        let r = json_rpc_client
+            .request::<CrossMsgListResponse>(
+                json_rpc_methods::CROSS_MSG_LIST,
+                serde_json::to_value(params)?,
+            )
+            .await?;
+
+        if global.json_output() {
+            return print_json(&r);
+        }
+
+        log::info!(
+            "top-down messages of subnet {:} from nonce {:} at parent epoch {:}",
+            arguments.subnet,
+            arguments.from_nonce,
+            r.epoch
+        );
+        println!("{}", table(&r.msgs));
+        for entry in r.msgs.iter() {
+            if let Some(e) = &entry.error {
+                log::warn!("cannot decode message {:}: {e:}", entry.index);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Renders the decoded messages of `entries` as a table, in the order given.
+fn table(entries: &[CrossMsgEntry]) -> String {
+    let headers = [
+        ("NONCE", Align::Right),
+        ("FROM", Align::Left),
+        ("TO", Align::Left),
+        ("VALUE", Align::Right),
+    ];
+    let rows = entries
+        .iter()
+        .filter_map(|e| e.msg.as_ref())
+        .map(|m| {
+            vec![
+                m.nonce.to_string(),
+                m.from.clone(),
+                m.to.clone(),
+                format!("{} FIL", m.value),
+            ]
+        })
+        .collect::<Vec<_>>();
+    render(&headers, &rows)
+}
+
+#[derive(Debug, Args)]
+#[command(
+    name = "list",
+    about = "List the top-down messages committed in the parent of a subnet"
+)]
+pub(crate) struct ListCrossMsgsArgs {
+    #[arg(long, short, help = "The JSON RPC server url for ipc agent")]
+    pub ipc_agent_url: Option<String>,
+    #[arg(long, short, value_parser = parse_subnet_id, help = "The subnet to list the messages of")]
+    pub subnet: SubnetID,
+    #[arg(
+        long,
+        default_value = "0",
+        help = "The nonce of the first message to list"
+    )]
+    pub from_nonce: u64,
+}
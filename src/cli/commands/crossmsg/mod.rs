@@ -1,18 +1,27 @@
 // Copyright 2022-2023 Protocol Labs
 // SPDX-License-Identifier: MIT
+use crate::cli::commands::crossmsg::audit::CrossMsgAudit;
+use crate::cli::commands::crossmsg::backlog::CrossMsgBacklog;
 use crate::cli::commands::crossmsg::fund::Fund;
+use crate::cli::commands::crossmsg::list::ListCrossMsgs;
 use crate::cli::commands::crossmsg::propagate::Propagate;
 use crate::cli::commands::crossmsg::release::Release;
 use crate::cli::commands::crossmsg::whitelist::WhitelistPropagator;
 use crate::cli::{CommandLineHandler, GlobalArguments};
+use audit::CrossMsgAuditArgs;
+use backlog::CrossMsgBacklogArgs;
 use fund::FundArgs;
+use list::ListCrossMsgsArgs;
 use propagate::PropagateArgs;
 use release::ReleaseArgs;
 use whitelist::WhitelistPropagatorArgs;
 
 use clap::{Args, Subcommand};
 
+pub mod audit;
+pub mod backlog;
 pub mod fund;
+pub mod list;
 pub mod propagate;
 pub mod release;
 pub mod whitelist;
@@ -32,6 +41,9 @@ impl CrossMsgsCommandsArgs {
             Commands::Release(args) => Release::handle(global, args).await,
             Commands::Propagate(args) => Propagate::handle(global, args).await,
             Commands::WhitelistPropagator(args) => WhitelistPropagator::handle(global, args).await,
+            Commands::Audit(args) => CrossMsgAudit::handle(global, args).await,
+            Commands::Backlog(args) => CrossMsgBacklog::handle(global, args).await,
+            Commands::List(args) => ListCrossMsgs::handle(global, args).await,
         }
     }
 }
@@ -42,4 +54,7 @@ pub(crate) enum Commands {
     Release(ReleaseArgs),
     Propagate(PropagateArgs),
     WhitelistPropagator(WhitelistPropagatorArgs),
+    Audit(CrossMsgAuditArgs),
+    Backlog(CrossMsgBacklogArgs),
+    List(ListCrossMsgsArgs),
 }
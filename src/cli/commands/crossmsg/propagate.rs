@@ -5,12 +5,13 @@
 use async_trait::async_trait;
 use cid::Cid;
 use clap::Args;
+use serde_json::json;
 use std::fmt::Debug;
 
-use crate::cli::commands::get_ipc_agent_url;
+use crate::cli::commands::{get_ipc_agent_url, print_json};
 use crate::cli::{CommandLineHandler, GlobalArguments};
 use crate::config::json_rpc_methods;
-use crate::jsonrpc::{JsonRpcClient, JsonRpcClientImpl};
+use crate::jsonrpc::JsonRpcClient;
 use crate::server::propagate::PropagateParams;
 
 /// The command to propagate a message in the postbox.
@@ -24,7 +25,7 @@ impl CommandLineHandler for Propagate {
         log::debug!("propagate operation with args: {:?}", arguments);
 
         let url = get_ipc_agent_url(&arguments.ipc_agent_url, global)?;
-        let json_rpc_client = JsonRpcClientImpl::new(url, None);
+        let json_rpc_client = global.submit_json_rpc_client(url);
 
         let params = PropagateParams {
             subnet: arguments.subnet.clone(),
@@ -35,6 +36,12 @@ impl CommandLineHandler for Propagate {
             .request::<()>(json_rpc_methods::PROPAGATE, serde_json::to_value(params)?)
             .await?;
 
+        if global.json_output() {
+            return print_json(&json!({
+                "subnet": arguments.subnet,
+            }));
+        }
+
         log::info!("propagated subnet: {:}", arguments.subnet);
 
         Ok(())
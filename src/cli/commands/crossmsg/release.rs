@@ -6,11 +6,11 @@ use async_trait::async_trait;
 use clap::Args;
 use std::fmt::Debug;
 
-use crate::cli::commands::get_ipc_agent_url;
+use crate::cli::commands::{get_ipc_agent_url, print_json};
 use crate::cli::{CommandLineHandler, GlobalArguments};
 use crate::config::json_rpc_methods;
-use crate::jsonrpc::{JsonRpcClient, JsonRpcClientImpl};
-use crate::server::release::ReleaseParams;
+use crate::jsonrpc::JsonRpcClient;
+use crate::server::release::{ReleaseParams, ReleaseResponse};
 
 /// The command to release funds from a child to a parent
 pub(crate) struct Release;
@@ -23,18 +23,27 @@ impl CommandLineHandler for Release {
         log::debug!("release operation with args: {:?}", arguments);
 
         let url = get_ipc_agent_url(&arguments.ipc_agent_url, global)?;
-        let json_rpc_client = JsonRpcClientImpl::new(url, None);
+        let json_rpc_client = global.submit_json_rpc_client(url);
 
         let params = ReleaseParams {
             subnet: arguments.subnet.clone(),
             from: arguments.from.clone(),
             amount: arguments.amount,
+            wait: Some(true),
         };
-        json_rpc_client
-            .request::<()>(json_rpc_methods::RELEASE, serde_json::to_value(params)?)
+        let response = json_rpc_client
+            .request::<ReleaseResponse>(json_rpc_methods::RELEASE, serde_json::to_value(params)?)
             .await?;
 
-        log::info!("released subnet: {:}", arguments.subnet);
+        if global.json_output() {
+            return print_json(&response);
+        }
+
+        log::info!(
+            "released subnet: {:} in message: {:}",
+            arguments.subnet,
+            response.cid
+        );
 
         Ok(())
     }
@@ -5,12 +5,13 @@
 use async_trait::async_trait;
 use cid::Cid;
 use clap::Args;
+use serde_json::json;
 use std::fmt::Debug;
 
-use crate::cli::commands::get_ipc_agent_url;
+use crate::cli::commands::{get_ipc_agent_url, print_json};
 use crate::cli::{CommandLineHandler, GlobalArguments};
 use crate::config::json_rpc_methods;
-use crate::jsonrpc::{JsonRpcClient, JsonRpcClientImpl};
+use crate::jsonrpc::JsonRpcClient;
 use crate::server::whitelist::WhitelistPropagatorParams;
 
 /// The command to whitelist a propagator for a message in a postbox
@@ -24,7 +25,7 @@ impl CommandLineHandler for WhitelistPropagator {
         log::debug!("whitelist operation with args: {:?}", arguments);
 
         let url = get_ipc_agent_url(&arguments.ipc_agent_url, global)?;
-        let json_rpc_client = JsonRpcClientImpl::new(url, None);
+        let json_rpc_client = global.submit_json_rpc_client(url);
 
         let params = WhitelistPropagatorParams {
             subnet: arguments.subnet.clone(),
@@ -39,6 +40,12 @@ impl CommandLineHandler for WhitelistPropagator {
             )
             .await?;
 
+        if global.json_output() {
+            return print_json(&json!({
+                "subnet": arguments.subnet,
+            }));
+        }
+
         log::info!("whitelisted subnet: {:}", arguments.subnet);
 
         Ok(())
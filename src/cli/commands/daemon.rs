@@ -8,15 +8,28 @@ use std::time::Duration;
 
 use async_trait::async_trait;
 use clap::Args;
+use futures::future::{join_all, BoxFuture};
+use futures::FutureExt;
+use fvm_shared::address::Address;
 use tokio_graceful_shutdown::{IntoSubsystem, Toplevel};
 
 use crate::cli::{CommandLineHandler, GlobalArguments};
-use crate::config::ReloadableConfig;
+use crate::config::{Config, ReloadableConfig, Subnet};
+use crate::lotus::client::LotusJsonRPCClient;
+use crate::lotus::keystore::Keystore;
+use crate::lotus::message::VersionResponse;
+use crate::lotus::LotusClient;
 use crate::manager::checkpoint::CheckpointSubsystem;
 use crate::server::jsonrpc::JsonRPCServer;
 
 /// The number of seconds to wait for a subsystem to start before returning an error.
 const SUBSYSTEM_WAIT_TIME_SECS: Duration = Duration::from_secs(10);
+/// The oldest lotus version the agent is known to work with, included.
+const MIN_NODE_VERSION: (u64, u64, u64) = (1, 20, 0);
+/// The first lotus version the agent is not known to work with.
+const MAX_NODE_VERSION: (u64, u64, u64) = (2, 0, 0);
+/// How long each check of the subnets run at startup can take before it is skipped.
+const STARTUP_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
 
 /// The command to start the ipc agent json rpc server in the foreground.
 pub(crate) struct LaunchDaemon;
@@ -33,6 +46,7 @@ impl CommandLineHandler for LaunchDaemon {
         );
 
         let reloadable_config = Arc::new(ReloadableConfig::new(global.config_path())?);
+        startup_checks(&reloadable_config.get_config()).await;
 
         // Start subsystems.
         let checkpointing = CheckpointSubsystem::new(reloadable_config.clone());
@@ -48,6 +62,181 @@ impl CommandLineHandler for LaunchDaemon {
     }
 }
 
+/// Logs the version of the node of `subnet`, warning if the agent is not known to work with it.
+async fn check_node_version<T: LotusClient + Sync>(subnet: &Subnet, client: &T) {
+    match client.node_version().await {
+        Ok(version) => {
+            log::info!(
+                "node of subnet {:} runs lotus {} with api {:?}",
+                subnet.id,
+                version.version,
+                version.api_semver()
+            );
+            if !is_supported(&version) {
+                log::warn!(
+                    "lotus {} of subnet {:} is outside the supported versions [{:?}, {:?})",
+                    version.version,
+                    subnet.id,
+                    MIN_NODE_VERSION,
+                    MAX_NODE_VERSION
+                );
+            }
+        }
+        Err(e) => log::warn!("cannot get the node version of subnet {:}: {e:}", subnet.id),
+    }
+}
+
+/// Returns whether the agent is known to work with the node version, which is not the case if
+/// it cannot be parsed.
+fn is_supported(version: &VersionResponse) -> bool {
+    version
+        .semver()
+        .map_or(false, |v| v >= MIN_NODE_VERSION && v < MAX_NODE_VERSION)
+}
+
+/// Runs the checks of the subnets in the config concurrently, each one for at most
+/// [`STARTUP_CHECK_TIMEOUT`], so that an unreachable node does not hold the start of the daemon.
+async fn startup_checks(config: &Config) {
+    let clients = config
+        .subnets
+        .values()
+        .map(|subnet| (subnet, LotusJsonRPCClient::from_subnet(subnet)))
+        .collect::<Vec<_>>();
+
+    let mut checks: Vec<(String, BoxFuture<()>)> = vec![];
+    for (subnet, client) in clients.iter() {
+        checks.push((
+            format!("version check of subnet {:}", subnet.id),
+            check_node_version(subnet, client).boxed(),
+        ));
+        for account in subnet.accounts.iter() {
+            checks.push((
+                format!("check of account {account:} of subnet {:}", subnet.id),
+                check_account(subnet, client, account).boxed(),
+            ));
+        }
+    }
+
+    join_all(checks.into_iter().map(|(name, check)| async move {
+        if tokio::time::timeout(STARTUP_CHECK_TIMEOUT, check)
+            .await
+            .is_err()
+        {
+            log::warn!("{name:} did not finish in {STARTUP_CHECK_TIMEOUT:?}, skipping it");
+        }
+    }))
+    .await;
+}
+
+/// Warns if the key of `account` is neither in the keystore of its `subnet` nor in the wallet of
+/// its node, as messages sent from it would fail.
+async fn check_account<T: LotusClient + Sync>(subnet: &Subnet, client: &T, account: &Address) {
+    // the keys of the keystore sign locally, they don't need to be in the wallet of the node
+    if let Some(path) = &subnet.keystore_path {
+        match Keystore::new(path.clone()).key_info(account) {
+            Ok(Some(_)) => return,
+            Ok(None) => {}
+            Err(e) => {
+                log::warn!(
+                    "cannot read the key of account {account:} of subnet {:}: {e:}",
+                    subnet.id
+                );
+                return;
+            }
+        }
+    }
+
+    match client.wallet_has(account).await {
+        Ok(true) => {}
+        Ok(false) => log::warn!(
+            "account {account:} of subnet {:} is not in the wallet of its node",
+            subnet.id
+        ),
+        Err(e) => log::warn!(
+            "cannot check account {account:} of subnet {:}: {e:}",
+            subnet.id
+        ),
+    }
+}
+
 #[derive(Debug, Args)]
 #[command(about = "Launch the ipc agent daemon process")]
 pub(crate) struct LaunchDaemonArgs {}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use fvm_shared::address::Address;
+    use indoc::formatdoc;
+    use ipc_sdk::subnet_id::ROOTNET_ID;
+    use serde_json::json;
+
+    use crate::cli::commands::daemon::{check_account, is_supported};
+    use crate::config::Config;
+    use crate::jsonrpc::mock::MockJsonRpcClient;
+    use crate::lotus::client::LotusJsonRPCClient;
+    use crate::lotus::keystore::key_address;
+    use crate::lotus::message::wallet::{KeyInfo, WalletKeyType};
+    use crate::lotus::message::VersionResponse;
+
+    #[test]
+    fn test_is_supported() {
+        let version = |v: &str| VersionResponse {
+            version: v.to_string(),
+            api_version: 0x010200,
+            block_delay: 30,
+        };
+        assert!(is_supported(&version("1.20.0")));
+        assert!(is_supported(&version("1.21.0-rc2+mainnet+git.d8b4f8b")));
+        assert!(!is_supported(&version("1.19.9+mainnet")));
+        assert!(!is_supported(&version("2.0.0")));
+        assert!(!is_supported(&version("eudico")));
+    }
+
+    #[tokio::test]
+    async fn test_check_account_in_keystore() {
+        let key_info = KeyInfo {
+            key_type: WalletKeyType::Secp256k1,
+            private_key: vec![7; 32],
+        };
+        let local = key_address(&key_info).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join(local.to_string()),
+            key_info.to_lotus_export().unwrap(),
+        )
+        .unwrap();
+
+        let config = Config::from_toml_str(&formatdoc!(
+            r#"
+                [server]
+                json_rpc_address = "127.0.0.1:3030"
+
+                [[subnets]]
+                id = "/root"
+                gateway_addr = "t064"
+                network_name = "root"
+                jsonrpc_api_http = "http://127.0.0.1:1234/rpc/v1"
+                keystore_path = "{}"
+            "#,
+            dir.path().to_str().unwrap()
+        ))
+        .unwrap();
+        let subnet = &config.subnets[&ROOTNET_ID];
+        let node = MockJsonRpcClient::default().with_result("Filecoin.WalletHas", json!(false));
+        let client = LotusJsonRPCClient::new(node.clone());
+
+        // the account signed locally is not looked up in the wallet of the node
+        check_account(subnet, &client, &local).await;
+        assert!(node.params("Filecoin.WalletHas").is_empty());
+
+        // while the accounts the keystore does not hold are
+        let other = Address::from_str("t1cp4q4lqsdhob23ysywffg2tvbmar5cshia4rweq").unwrap();
+        check_account(subnet, &client, &other).await;
+        assert_eq!(
+            node.params("Filecoin.WalletHas"),
+            vec![json!([other.to_string()])]
+        );
+    }
+}
@@ -3,18 +3,23 @@
 //! This mod contains the different command line implementations.
 
 mod checkpoint;
+mod completions;
 mod config;
 mod crossmsg;
 mod daemon;
+mod parse;
 mod subnet;
+mod table;
 mod wallet;
 
 use crate::cli::commands::checkpoint::CheckpointCommandsArgs;
+use crate::cli::commands::completions::{Completions, CompletionsArgs};
 use crate::cli::commands::crossmsg::CrossMsgsCommandsArgs;
 use crate::cli::commands::daemon::{LaunchDaemon, LaunchDaemonArgs};
 use crate::cli::{CommandLineHandler, GlobalArguments};
 use anyhow::{Context, Result};
 use clap::{Parser, Subcommand};
+use serde::Serialize;
 use std::fmt::Debug;
 use subnet::SubnetCommandsArgs;
 use url::Url;
@@ -39,6 +44,9 @@ enum Commands {
     Wallet(WalletCommandsArgs),
     CrossMsg(CrossMsgsCommandsArgs),
     Checkpoint(CheckpointCommandsArgs),
+    // hidden, as it is only run when installing the cli
+    #[command(hide = true)]
+    Completions(CompletionsArgs),
 }
 #[derive(Debug, Parser)]
 #[command(
@@ -94,11 +102,18 @@ pub async fn cli() -> anyhow::Result<()> {
         Commands::CrossMsg(args) => args.handle(global).await,
         Commands::Wallet(args) => args.handle(global).await,
         Commands::Checkpoint(args) => args.handle(global).await,
+        Commands::Completions(args) => Completions::handle(global, args).await,
     };
 
     r.with_context(|| format!("error processing command {:?}", args.command))
 }
 
+/// Prints `value` to stdout as the json result of a command run with `--output json`.
+pub(crate) fn print_json<T: Serialize + ?Sized>(value: &T) -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
 pub(crate) fn get_ipc_agent_url(
     ipc_agent_url: &Option<String>,
     global: &GlobalArguments,
@@ -116,3 +131,35 @@ pub(crate) fn get_ipc_agent_url(
     };
     Ok(url)
 }
+
+#[cfg(test)]
+mod tests {
+    use clap::{CommandFactory, Parser};
+
+    use crate::cli::commands::{Commands, IPCAgentCliCommands};
+
+    #[test]
+    fn test_commands() {
+        // fails on clashing args, e.g. a command arg with the id of a global one
+        IPCAgentCliCommands::command().debug_assert();
+    }
+
+    #[test]
+    fn test_export_output_file() {
+        let cli = IPCAgentCliCommands::parse_from([
+            "ipc",
+            "checkpoint",
+            "export",
+            "-s",
+            "/root/t01002",
+            "-e",
+            "10",
+            "--output-file",
+            "cp.car",
+            "--output",
+            "json",
+        ]);
+        assert!(cli.global_params.json_output());
+        assert!(matches!(cli.command, Commands::Checkpoint(_)));
+    }
+}
@@ -0,0 +1,77 @@
+// Copyright 2022-2023 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Parsers of the typed cli arguments, so that malformed values are reported when the
+//! arguments are parsed instead of by the agent.
+//!
+//! Use them as the `value_parser` of an argument, e.g.:
+//! ```ignore
+//! #[arg(long, short, value_parser = parse_subnet_id, help = "The subnet")]
+//! pub subnet: SubnetID,
+//! ```
+
+use std::str::FromStr;
+
+use fvm_shared::address::Address;
+use ipc_sdk::subnet_id::SubnetID;
+
+/// Parses a subnet id, e.g. `/root/t01002`.
+pub(crate) fn parse_subnet_id(s: &str) -> Result<SubnetID, String> {
+    SubnetID::from_str(s).map_err(|e| format!("invalid subnet id {s:?}: {e:}"))
+}
+
+/// Parses an address, e.g. `t064`.
+pub(crate) fn parse_address(s: &str) -> Result<Address, String> {
+    Address::from_str(s).map_err(|e| format!("invalid address {s:?}: {e:}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use clap::Parser;
+    use fvm_shared::address::Address;
+    use ipc_sdk::subnet_id::SubnetID;
+
+    use crate::cli::commands::parse::{parse_address, parse_subnet_id};
+
+    #[derive(Debug, Parser)]
+    struct TestArgs {
+        #[arg(long, value_parser = parse_subnet_id)]
+        subnet: SubnetID,
+        #[arg(long, value_parser = parse_address)]
+        gateway_address: Address,
+    }
+
+    #[test]
+    fn test_parse_subnet_id() {
+        let args = TestArgs::try_parse_from([
+            "test",
+            "--subnet",
+            "/root/t01002",
+            "--gateway-address",
+            "t064",
+        ])
+        .unwrap();
+        assert_eq!(args.subnet, SubnetID::from_str("/root/t01002").unwrap());
+        assert_eq!(args.gateway_address, Address::new_id(64));
+
+        let err = TestArgs::try_parse_from([
+            "test",
+            "--subnet",
+            "/root/not-an-address",
+            "--gateway-address",
+            "t064",
+        ])
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains("--subnet"));
+        assert!(err.contains("invalid subnet id \"/root/not-an-address\""));
+    }
+
+    #[test]
+    fn test_parse_address() {
+        assert_eq!(parse_address("t064").unwrap(), Address::new_id(64));
+        let err = parse_address("t0x").unwrap_err();
+        assert!(err.contains("invalid address \"t0x\""));
+    }
+}
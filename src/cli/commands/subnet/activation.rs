@@ -0,0 +1,77 @@
+// Copyright 2022-2023 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Subnet activation status cli command
+
+use async_trait::async_trait;
+use clap::Args;
+use ipc_sdk::subnet_id::SubnetID;
+use std::fmt::Debug;
+
+use crate::cli::commands::parse::parse_subnet_id;
+use crate::cli::commands::{get_ipc_agent_url, print_json};
+use crate::cli::{CommandLineHandler, GlobalArguments};
+use crate::config::json_rpc_methods;
+use crate::jsonrpc::JsonRpcClient;
+use crate::lotus::message::ipc::ActivationStatus;
+use crate::server::validator::QueryActivationStatusParams;
+
+/// The command to check if a subnet has enough validators to be active.
+pub(crate) struct ActivationStatusCmd;
+
+#[async_trait]
+impl CommandLineHandler for ActivationStatusCmd {
+    type Arguments = ActivationStatusArgs;
+
+    async fn handle(global: &GlobalArguments, arguments: &Self::Arguments) -> anyhow::Result<()> {
+        log::debug!("subnet activation status with args: {:?}", arguments);
+
+        let url = get_ipc_agent_url(&arguments.ipc_agent_url, global)?;
+        let json_rpc_client = global.json_rpc_client(url);
+
+        let params = QueryActivationStatusParams {
+            subnet: arguments.subnet.to_string(),
+        };
+
+        let status = json_rpc_client
+            .request::<ActivationStatus>(
+                json_rpc_methods::QUERY_ACTIVATION_STATUS,
+                serde_json::to_value(params)?,
+            )
+            .await?;
+
+        if global.json_output() {
+            return print_json(&status);
+        }
+
+        if status.active {
+            log::info!(
+                "subnet {} is active with {} validators (min {})",
+                arguments.subnet,
+                status.validators,
+                status.min_validators,
+            );
+        } else {
+            log::info!(
+                "subnet {} is collecting validators: {} of {}, {} more needed to activate",
+                arguments.subnet,
+                status.validators,
+                status.min_validators,
+                status.missing_validators(),
+            );
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Args)]
+#[command(
+    name = "activation-status",
+    about = "Show whether a subnet has enough validators to be active"
+)]
+pub(crate) struct ActivationStatusArgs {
+    #[arg(long, short, help = "The JSON RPC server url for ipc agent")]
+    pub ipc_agent_url: Option<String>,
+    #[arg(long, short, value_parser = parse_subnet_id, help = "The subnet id to check")]
+    pub subnet: SubnetID,
+}
@@ -0,0 +1,346 @@
+// Copyright 2022-2023 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Subnet bootstrap cli command handler.
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+use clap::Args;
+use fvm_shared::clock::ChainEpoch;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashSet;
+use std::fmt::Debug;
+use std::path::{Path, PathBuf};
+
+use crate::cli::commands::{get_ipc_agent_url, print_json};
+use crate::cli::{CommandLineHandler, GlobalArguments};
+use crate::config::json_rpc_methods;
+use crate::jsonrpc::JsonRpcClient;
+use crate::lotus::message::ipc::ActivationStatus;
+use crate::server::create::{CreateSubnetParams, CreateSubnetResponse};
+use crate::server::join::{JoinSubnetParams, JoinSubnetResponse};
+use crate::server::validator::{
+    QueryActivationStatusParams, QueryValidatorSetParams, QueryValidatorSetResponse,
+};
+
+/// The command to stand up a new subnet: create its actor, join its initial validators and
+/// report whether it is active.
+pub(crate) struct BootstrapSubnet;
+
+#[async_trait]
+impl CommandLineHandler for BootstrapSubnet {
+    type Arguments = BootstrapSubnetArgs;
+
+    async fn handle(global: &GlobalArguments, arguments: &Self::Arguments) -> anyhow::Result<()> {
+        log::debug!("bootstrap subnet with args: {:?}", arguments);
+
+        let url = get_ipc_agent_url(&arguments.ipc_agent_url, global)?;
+        let json_rpc_client = global.submit_json_rpc_client(url);
+
+        let config: BootstrapConfig = toml::from_str(&std::fs::read_to_string(&arguments.config)?)?;
+        let progress = match &arguments.progress {
+            Some(p) => p.clone(),
+            None => PathBuf::from(format!("{}.progress", arguments.config)),
+        };
+
+        let subnet = bootstrap(&json_rpc_client, &config, &progress).await?;
+        if global.json_output() {
+            return print_json(&json!({
+                "subnet": subnet.to_string(),
+            }));
+        }
+
+        log::info!("bootstrapped subnet: {subnet:}");
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Args)]
+#[command(
+    name = "bootstrap",
+    about = "Create a subnet and join its initial validators as described in a config file"
+)]
+pub(crate) struct BootstrapSubnetArgs {
+    #[arg(long, short, help = "The JSON RPC server url for ipc agent")]
+    pub ipc_agent_url: Option<String>,
+    #[arg(
+        long,
+        short,
+        help = "The toml file describing the subnet and its validators"
+    )]
+    pub config: String,
+    #[arg(
+        long,
+        help = "The file recording the completed steps, defaults to the config path with a .progress suffix"
+    )]
+    pub progress: Option<PathBuf>,
+}
+
+/// The subnet to bootstrap and its initial validators.
+#[derive(Debug, Deserialize)]
+pub(crate) struct BootstrapConfig {
+    /// The address that creates the subnet, the default wallet address if not set.
+    pub from: Option<String>,
+    pub parent: String,
+    pub name: String,
+    /// In whole FIL units.
+    pub min_validator_stake: u64,
+    pub min_validators: u64,
+    pub bottomup_check_period: ChainEpoch,
+    pub topdown_check_period: ChainEpoch,
+    #[serde(default)]
+    pub validators: Vec<BootstrapValidator>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct BootstrapValidator {
+    pub from: Option<String>,
+    /// In whole FIL units.
+    pub collateral: u64,
+    pub net_addr: String,
+}
+
+/// The steps of a bootstrap that are already completed, so that it can be resumed.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct BootstrapProgress {
+    /// The id of the created subnet.
+    subnet: Option<String>,
+}
+
+impl BootstrapProgress {
+    fn load(path: &Path) -> anyhow::Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Ok(toml::from_str(&std::fs::read_to_string(path)?)?)
+    }
+
+    fn save(&self, path: &Path) -> anyhow::Result<()> {
+        std::fs::write(path, toml::to_string(self)?)?;
+        Ok(())
+    }
+}
+
+/// Runs the steps of the bootstrap of `config` that are not completed yet, recording the created
+/// subnet in `progress`. Validators are considered joined once their net address is in the
+/// validator set. Returns the id of the subnet.
+pub(crate) async fn bootstrap<C: JsonRpcClient + Send + Sync>(
+    client: &C,
+    config: &BootstrapConfig,
+    progress: &Path,
+) -> anyhow::Result<String> {
+    let mut state = BootstrapProgress::load(progress)?;
+
+    let subnet = match state.subnet.clone() {
+        Some(subnet) => {
+            log::info!("[1/3] subnet {subnet:} already created");
+            subnet
+        }
+        None => {
+            let params = CreateSubnetParams {
+                from: config.from.clone(),
+                parent: config.parent.clone(),
+                name: config.name.clone(),
+                min_validator_stake: config.min_validator_stake,
+                min_validators: config.min_validators,
+                bottomup_check_period: config.bottomup_check_period,
+                topdown_check_period: config.topdown_check_period,
+            };
+            let address = client
+                .request::<CreateSubnetResponse>(
+                    json_rpc_methods::CREATE_SUBNET,
+                    serde_json::to_value(params)?,
+                )
+                .await?
+                .address;
+            let subnet = format!("{}/{}", config.parent, address);
+
+            state.subnet = Some(subnet.clone());
+            state.save(progress)?;
+            log::info!("[1/3] created subnet {subnet:}");
+            subnet
+        }
+    };
+
+    let params = QueryValidatorSetParams {
+        subnet: subnet.clone(),
+        epoch: None,
+    };
+    let joined = client
+        .request::<QueryValidatorSetResponse>(
+            json_rpc_methods::QUERY_VALIDATOR_SET,
+            serde_json::to_value(params)?,
+        )
+        .await?
+        .validator_set
+        .validators
+        .unwrap_or_default()
+        .into_iter()
+        .map(|v| v.net_addr)
+        .collect::<HashSet<_>>();
+
+    for (i, validator) in config.validators.iter().enumerate() {
+        let step = format!("[2/3] validator {}/{}", i + 1, config.validators.len());
+        if joined.contains(&validator.net_addr) {
+            log::info!("{step:} {:} already joined", validator.net_addr);
+            continue;
+        }
+
+        let params = JoinSubnetParams {
+            subnet: subnet.clone(),
+            from: validator.from.clone(),
+            collateral: validator.collateral,
+            validator_net_addr: validator.net_addr.clone(),
+        };
+        client
+            .request::<JoinSubnetResponse>(
+                json_rpc_methods::JOIN_SUBNET,
+                serde_json::to_value(params)?,
+            )
+            .await
+            .map_err(|e| anyhow!("{step:} {:} cannot join: {e:}", validator.net_addr))?;
+        log::info!("{step:} {:} joined", validator.net_addr);
+    }
+
+    let params = QueryActivationStatusParams {
+        subnet: subnet.clone(),
+    };
+    let status = client
+        .request::<ActivationStatus>(
+            json_rpc_methods::QUERY_ACTIVATION_STATUS,
+            serde_json::to_value(params)?,
+        )
+        .await?;
+    if status.active {
+        log::info!("[3/3] subnet {subnet:} is active");
+    } else {
+        log::info!(
+            "[3/3] subnet {subnet:} needs {} more validators to activate",
+            status.missing_validators()
+        );
+    }
+
+    Ok(subnet)
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::anyhow;
+    use serde_json::json;
+    use std::sync::{Arc, Mutex};
+
+    use crate::cli::commands::subnet::bootstrap::{bootstrap, BootstrapConfig};
+    use crate::config::json_rpc_methods;
+    use crate::jsonrpc::mock::MockJsonRpcClient;
+
+    /// An agent recording the net addresses of the validators joined in `joined`, that fails
+    /// to join the validators in `failing`.
+    fn mock_agent(
+        joined: Arc<Mutex<Vec<String>>>,
+        failing: Arc<Mutex<Vec<String>>>,
+    ) -> MockJsonRpcClient {
+        let validators = joined.clone();
+        let status = joined.clone();
+        MockJsonRpcClient::default()
+            .with_result(
+                json_rpc_methods::CREATE_SUBNET,
+                json!({"address": "t01002"}),
+            )
+            .on(json_rpc_methods::QUERY_VALIDATOR_SET, move |_| {
+                let validators = validators
+                    .lock()
+                    .unwrap()
+                    .iter()
+                    .map(|net_addr| json!({"addr": "t0100", "net_addr": net_addr, "weight": "1"}))
+                    .collect::<Vec<_>>();
+                Ok(json!({
+                    "validator_set": {"validators": validators, "configuration_number": 1},
+                    "min_validators": 2,
+                    "genesis_epoch": 0,
+                }))
+            })
+            .on(json_rpc_methods::JOIN_SUBNET, move |params| {
+                assert_eq!(params["subnet"], "/root/t01002");
+                let net_addr = params["validator_net_addr"].as_str().unwrap().to_string();
+                if failing.lock().unwrap().contains(&net_addr) {
+                    return Err(anyhow!("not enough funds"));
+                }
+                joined.lock().unwrap().push(net_addr);
+                Ok(json!({"cid": "bafy2bzace", "total_stake": "2"}))
+            })
+            .on(json_rpc_methods::QUERY_ACTIVATION_STATUS, move |_| {
+                let validators = status.lock().unwrap().len();
+                Ok(json!({
+                    "min_validators": 2,
+                    "validators": validators,
+                    "active": validators >= 2,
+                }))
+            })
+    }
+
+    fn config() -> BootstrapConfig {
+        toml::from_str(
+            r#"
+            parent = "/root"
+            name = "test"
+            min_validator_stake = 1
+            min_validators = 2
+            bottomup_check_period = 10
+            topdown_check_period = 10
+
+            [[validators]]
+            collateral = 1
+            net_addr = "/ip4/127.0.0.1/tcp/1347/p2p/validator1"
+
+            [[validators]]
+            from = "t1cp4q4lqsdhob23ysywffg2tvbmar5cshia4rweq"
+            collateral = 1
+            net_addr = "/ip4/127.0.0.1/tcp/1348/p2p/validator2"
+            "#,
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_bootstrap() {
+        let dir = tempfile::tempdir().unwrap();
+        let progress = dir.path().join("subnet.toml.progress");
+        let joined = Arc::new(Mutex::new(vec![]));
+        let agent = mock_agent(joined.clone(), Arc::default());
+        let created = || agent.params(json_rpc_methods::CREATE_SUBNET).len();
+
+        let subnet = bootstrap(&agent, &config(), &progress).await.unwrap();
+        assert_eq!(subnet, "/root/t01002");
+        assert_eq!(created(), 1);
+        assert_eq!(joined.lock().unwrap().len(), 2);
+
+        // running it again has nothing left to do
+        bootstrap(&agent, &config(), &progress).await.unwrap();
+        assert_eq!(created(), 1);
+        assert_eq!(joined.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_bootstrap_resume() {
+        let dir = tempfile::tempdir().unwrap();
+        let progress = dir.path().join("subnet.toml.progress");
+        let second = config().validators[1].net_addr.clone();
+        let joined = Arc::new(Mutex::new(vec![]));
+        let failing = Arc::new(Mutex::new(vec![second.clone()]));
+        let agent = mock_agent(joined.clone(), failing.clone());
+        let created = || agent.params(json_rpc_methods::CREATE_SUBNET).len();
+
+        // the subnet is created and the first validator joins before the failure
+        assert!(bootstrap(&agent, &config(), &progress).await.is_err());
+        assert_eq!(created(), 1);
+        assert_eq!(joined.lock().unwrap().len(), 1);
+
+        // resuming only joins the second validator
+        failing.lock().unwrap().clear();
+        let subnet = bootstrap(&agent, &config(), &progress).await.unwrap();
+        assert_eq!(subnet, "/root/t01002");
+        assert_eq!(created(), 1);
+        assert_eq!(joined.lock().unwrap()[1], second);
+    }
+}
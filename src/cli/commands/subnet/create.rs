@@ -5,12 +5,13 @@
 use async_trait::async_trait;
 use clap::Args;
 use fvm_shared::clock::ChainEpoch;
+use serde_json::json;
 use std::fmt::Debug;
 
-use crate::cli::commands::get_ipc_agent_url;
+use crate::cli::commands::{get_ipc_agent_url, print_json};
 use crate::cli::{CommandLineHandler, GlobalArguments};
 use crate::config::json_rpc_methods;
-use crate::jsonrpc::{JsonRpcClient, JsonRpcClientImpl};
+use crate::jsonrpc::JsonRpcClient;
 use crate::server::create::{CreateSubnetParams, CreateSubnetResponse};
 
 /// The command to create a new subnet actor.
@@ -22,7 +23,7 @@ impl CreateSubnet {
         arguments: &CreateSubnetArgs,
     ) -> anyhow::Result<String> {
         let url = get_ipc_agent_url(&arguments.ipc_agent_url, global)?;
-        let json_rpc_client = JsonRpcClientImpl::new(url, None);
+        let json_rpc_client = global.submit_json_rpc_client(url);
 
         let params = CreateSubnetParams {
             from: arguments.from.clone(),
@@ -53,6 +54,13 @@ impl CommandLineHandler for CreateSubnet {
 
         let address = CreateSubnet::create(global, arguments).await?;
 
+        if global.json_output() {
+            return print_json(&json!({
+                "id": format!("{}/{}", arguments.parent, address),
+                "address": address,
+            }));
+        }
+
         log::info!(
             "created subnet actor with id: {}/{}",
             arguments.parent,
@@ -0,0 +1,66 @@
+// Copyright 2022-2023 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Fund subnet cli command handler.
+
+use async_trait::async_trait;
+use clap::Args;
+use ipc_sdk::subnet_id::SubnetID;
+use std::fmt::Debug;
+
+use crate::cli::commands::parse::parse_subnet_id;
+use crate::cli::commands::{get_ipc_agent_url, print_json};
+use crate::cli::{CommandLineHandler, GlobalArguments};
+use crate::config::json_rpc_methods;
+use crate::jsonrpc::JsonRpcClient;
+use crate::server::fund::{FundParams, FundResponse};
+
+/// The command to fund a subnet from an account of its parent. It sends the fund message to
+/// the gateway of the parent, waits for it to be included and prints its cid.
+pub(crate) struct FundSubnet;
+
+#[async_trait]
+impl CommandLineHandler for FundSubnet {
+    type Arguments = FundSubnetArgs;
+
+    async fn handle(global: &GlobalArguments, arguments: &Self::Arguments) -> anyhow::Result<()> {
+        log::debug!("fund subnet with args: {:?}", arguments);
+
+        let url = get_ipc_agent_url(&arguments.ipc_agent_url, global)?;
+        let json_rpc_client = global.submit_json_rpc_client(url);
+
+        let params = FundParams {
+            subnet: arguments.subnet.to_string(),
+            from: arguments.from.clone(),
+            amount: arguments.amount,
+        };
+        let response = json_rpc_client
+            .request::<FundResponse>(json_rpc_methods::FUND, serde_json::to_value(params)?)
+            .await?;
+
+        if global.json_output() {
+            return print_json(&response);
+        }
+
+        log::info!("funded subnet: {:}", arguments.subnet);
+        println!("{:}", response.cid);
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Args)]
+#[command(about = "Fund a subnet from an account of its parent")]
+pub(crate) struct FundSubnetArgs {
+    #[arg(long, short, help = "The JSON RPC server url for ipc agent")]
+    pub ipc_agent_url: Option<String>,
+    #[arg(long, short, value_parser = parse_subnet_id, help = "The subnet to fund")]
+    pub subnet: SubnetID,
+    #[arg(
+        long,
+        short,
+        help = "The address in the parent to send funds from, the default one if not set"
+    )]
+    pub from: Option<String>,
+    #[arg(long, short, help = "The amount to fund in whole FIL units")]
+    pub amount: u64,
+}
@@ -0,0 +1,204 @@
+// Copyright 2022-2023 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Subnet info cli command
+
+use async_trait::async_trait;
+use clap::Args;
+use fvm_shared::clock::ChainEpoch;
+use ipc_sdk::subnet_id::SubnetID;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::fmt::Debug;
+
+use crate::cli::commands::parse::parse_subnet_id;
+use crate::cli::commands::subnet::list_subnets::{status_name, SubnetInfoWrapper};
+use crate::cli::commands::table::{render, Align};
+use crate::cli::commands::{get_ipc_agent_url, print_json};
+use crate::cli::{CommandLineHandler, GlobalArguments};
+use crate::config::json_rpc_methods;
+use crate::jsonrpc::JsonRpcClient;
+use crate::server::subnet_info::{SubnetInfoParams, ValidatorStake};
+
+/// The command to show the state of a single subnet registered in the gateway of its parent.
+pub(crate) struct SubnetInfo;
+
+#[async_trait]
+impl CommandLineHandler for SubnetInfo {
+    type Arguments = SubnetInfoArgs;
+
+    async fn handle(global: &GlobalArguments, arguments: &Self::Arguments) -> anyhow::Result<()> {
+        log::debug!("subnet info with args: {:?}", arguments);
+
+        let url = get_ipc_agent_url(&arguments.ipc_agent_url, global)?;
+        let json_rpc_client = global.json_rpc_client(url);
+
+        let params = SubnetInfoParams {
+            subnet: arguments.subnet.to_string(),
+        };
+
+        let response = json_rpc_client
+            .request::<SubnetInfoResponseWrapper>(
+                json_rpc_methods::GET_SUBNET_INFO,
+                serde_json::to_value(params)?,
+            )
+            .await?;
+
+        if global.json_output() {
+            return print_json(&summary(&response)?);
+        }
+
+        println!("{}", describe(&response)?);
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Args)]
+#[command(
+    name = "info",
+    about = "Show the status, collateral, stake and validators of a subnet registered in its parent"
+)]
+pub(crate) struct SubnetInfoArgs {
+    #[arg(long, short, help = "The JSON RPC server url for ipc agent")]
+    pub ipc_agent_url: Option<String>,
+    #[arg(long, short, value_parser = parse_subnet_id, help = "The subnet id to show")]
+    pub subnet: SubnetID,
+}
+
+/// The response of the subnet info method of the ipc agent rpc server.
+#[derive(Debug, Deserialize)]
+struct SubnetInfoResponseWrapper {
+    #[serde(flatten)]
+    info: SubnetInfoWrapper,
+    epoch: ChainEpoch,
+    validators: u64,
+    min_validators: u64,
+    total_stake: String,
+    min_validator_stake: String,
+    validator_stakes: Vec<ValidatorStake>,
+    genesis_epoch: ChainEpoch,
+    last_checkpoint_epoch: ChainEpoch,
+}
+
+/// Returns the json printed for the subnet with `--output json`.
+fn summary(response: &SubnetInfoResponseWrapper) -> anyhow::Result<Value> {
+    let (stake, supply) = response.info.amounts()?;
+    Ok(json!({
+        "id": response.info.id,
+        "status": response.info.status,
+        "collateral_fil": stake.to_string(),
+        "circ_supply_fil": supply.to_string(),
+        "validators": response.validators,
+        "min_validators": response.min_validators,
+        "epoch": response.epoch,
+        "total_stake_fil": response.total_stake,
+        "min_validator_stake_fil": response.min_validator_stake,
+        "validator_stakes": response.validator_stakes,
+        "genesis_epoch": response.genesis_epoch,
+        "last_checkpoint_epoch": response.last_checkpoint_epoch,
+    }))
+}
+
+/// Returns the report printed for the subnet, followed by a table of its validators.
+fn describe(response: &SubnetInfoResponseWrapper) -> anyhow::Result<String> {
+    let (stake, supply) = response.info.amounts()?;
+    let lines = [
+        format!(
+            "{} - status: {}, collateral: {} FIL, circ.supply: {} FIL, validators: {} (min {})",
+            response.info.id,
+            status_name(response.info.status),
+            stake,
+            supply,
+            response.validators,
+            response.min_validators,
+        ),
+        format!("parent epoch: {}", response.epoch),
+        format!(
+            "total stake: {} FIL (min {} FIL per validator)",
+            response.total_stake, response.min_validator_stake
+        ),
+        format!("genesis epoch: {}", response.genesis_epoch),
+        format!("last checkpoint epoch: {}", response.last_checkpoint_epoch),
+    ];
+
+    let headers = [
+        ("ADDRESS", Align::Left),
+        ("NET ADDRESS", Align::Left),
+        ("STAKE", Align::Right),
+    ];
+    let rows = response
+        .validator_stakes
+        .iter()
+        .map(|v| {
+            vec![
+                v.addr.clone(),
+                v.net_addr.clone(),
+                format!("{} FIL", v.stake),
+            ]
+        })
+        .collect::<Vec<_>>();
+
+    Ok(format!(
+        "{}\n\n{}",
+        lines.join("\n"),
+        render(&headers, &rows)
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use fvm_shared::econ::TokenAmount;
+    use serde_json::json;
+
+    use crate::cli::commands::subnet::info::{describe, summary, SubnetInfoResponseWrapper};
+
+    #[test]
+    fn test_describe() {
+        let response = json!({
+            "id": "/root/t01002",
+            "stake": "2000000000000000000",
+            "circ_supply": "0",
+            "status": 0,
+            "epoch": 100,
+            "validators": 2,
+            "min_validators": 3,
+            "total_stake": "3.0",
+            "min_validator_stake": "1.0",
+            "validator_stakes": [{
+                "addr": "t1cp4q4lqsdhob23ysywffg2tvbmar5cshia4rweq",
+                "net_addr": "/dns/host/tcp/1349",
+                "stake": "3.0",
+            }],
+            "genesis_epoch": 12,
+            "last_checkpoint_epoch": 40,
+        });
+        let response: SubnetInfoResponseWrapper = serde_json::from_value(response).unwrap();
+        let output = describe(&response).unwrap();
+        let lines = output.lines().collect::<Vec<_>>();
+        assert_eq!(
+            lines[0],
+            format!(
+                "/root/t01002 - status: Active, collateral: {} FIL, circ.supply: {} FIL, validators: 2 (min 3)",
+                TokenAmount::from_whole(2),
+                TokenAmount::from_atto(0)
+            )
+        );
+        assert_eq!(lines[1], "parent epoch: 100");
+        assert_eq!(lines[2], "total stake: 3.0 FIL (min 1.0 FIL per validator)");
+        assert_eq!(lines[4], "last checkpoint epoch: 40");
+        assert!(lines[6].starts_with("ADDRESS"));
+        assert!(lines[7].starts_with("t1cp4q4lqsdhob23ysywffg2tvbmar5cshia4rweq"));
+        assert!(lines[7].ends_with("3.0 FIL"));
+
+        let summary = summary(&response).unwrap();
+        assert_eq!(summary["id"], "/root/t01002");
+        assert_eq!(
+            summary["collateral_fil"],
+            TokenAmount::from_whole(2).to_string()
+        );
+        assert_eq!(summary["validators"], 2);
+        assert_eq!(summary["min_validators"], 3);
+        assert_eq!(summary["total_stake_fil"], "3.0");
+        assert_eq!(summary["validator_stakes"][0]["stake"], "3.0");
+    }
+}
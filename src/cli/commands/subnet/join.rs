@@ -4,13 +4,15 @@
 
 use async_trait::async_trait;
 use clap::Args;
+use ipc_sdk::subnet_id::SubnetID;
 use std::fmt::Debug;
 
-use crate::cli::commands::get_ipc_agent_url;
+use crate::cli::commands::parse::parse_subnet_id;
+use crate::cli::commands::{get_ipc_agent_url, print_json};
 use crate::cli::{CommandLineHandler, GlobalArguments};
 use crate::config::json_rpc_methods;
-use crate::jsonrpc::{JsonRpcClient, JsonRpcClientImpl};
-use crate::server::join::JoinSubnetParams;
+use crate::jsonrpc::JsonRpcClient;
+use crate::server::join::{JoinSubnetParams, JoinSubnetResponse};
 
 /// The command to join a subnet
 pub struct JoinSubnet;
@@ -23,22 +25,41 @@ impl CommandLineHandler for JoinSubnet {
         log::debug!("join subnet with args: {:?}", arguments);
 
         let url = get_ipc_agent_url(&arguments.ipc_agent_url, global)?;
-        let json_rpc_client = JsonRpcClientImpl::new(url, None);
+        let json_rpc_client = global.submit_json_rpc_client(url);
 
         // The json rpc server will handle directing the request to
         // the correct parent.
         let params = JoinSubnetParams {
-            subnet: arguments.subnet.clone(),
+            subnet: arguments.subnet.to_string(),
             from: arguments.from.clone(),
             collateral: arguments.collateral,
             validator_net_addr: arguments.validator_net_addr.clone(),
         };
 
-        json_rpc_client
-            .request::<()>(json_rpc_methods::JOIN_SUBNET, serde_json::to_value(params)?)
+        let response = json_rpc_client
+            .request::<JoinSubnetResponse>(
+                json_rpc_methods::JOIN_SUBNET,
+                serde_json::to_value(params)?,
+            )
             .await?;
 
-        log::info!("joined subnet: {:}", arguments.subnet);
+        if global.json_output() {
+            return print_json(&response);
+        }
+
+        match response.total_stake {
+            Some(total_stake) => log::info!(
+                "joined subnet: {:} in message: {:}, total stake of the subnet: {:} FIL",
+                arguments.subnet,
+                response.cid,
+                total_stake
+            ),
+            None => log::info!(
+                "joined subnet: {:} in message: {:}",
+                arguments.subnet,
+                response.cid
+            ),
+        }
 
         Ok(())
     }
@@ -51,8 +72,8 @@ pub struct JoinSubnetArgs {
     pub ipc_agent_url: Option<String>,
     #[arg(long, short, help = "The address that joins the subnet")]
     pub from: Option<String>,
-    #[arg(long, short, help = "The subnet to join")]
-    pub subnet: String,
+    #[arg(long, short, value_parser = parse_subnet_id, help = "The subnet to join")]
+    pub subnet: SubnetID,
     #[arg(
         long,
         short,
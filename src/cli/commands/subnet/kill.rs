@@ -4,13 +4,15 @@
 
 use async_trait::async_trait;
 use clap::Args;
+use ipc_sdk::subnet_id::SubnetID;
 use std::fmt::Debug;
 
-use crate::cli::commands::get_ipc_agent_url;
+use crate::cli::commands::parse::parse_subnet_id;
+use crate::cli::commands::{get_ipc_agent_url, print_json};
 use crate::cli::{CommandLineHandler, GlobalArguments};
 use crate::config::json_rpc_methods;
-use crate::jsonrpc::{JsonRpcClient, JsonRpcClientImpl};
-use crate::server::kill::KillSubnetParams;
+use crate::jsonrpc::JsonRpcClient;
+use crate::server::kill::{KillSubnetParams, KillSubnetResponse};
 
 /// The command to kill an existing subnet.
 pub struct KillSubnet;
@@ -23,18 +25,29 @@ impl CommandLineHandler for KillSubnet {
         log::debug!("kill subnet with args: {:?}", arguments);
 
         let url = get_ipc_agent_url(&arguments.ipc_agent_url, global)?;
-        let json_rpc_client = JsonRpcClientImpl::new(url, None);
+        let json_rpc_client = global.submit_json_rpc_client(url);
 
         let params = KillSubnetParams {
-            subnet: arguments.subnet.clone(),
+            subnet: arguments.subnet.to_string(),
             from: arguments.from.clone(),
         };
 
-        json_rpc_client
-            .request::<()>(json_rpc_methods::KILL_SUBNET, serde_json::to_value(params)?)
+        let response = json_rpc_client
+            .request::<KillSubnetResponse>(
+                json_rpc_methods::KILL_SUBNET,
+                serde_json::to_value(params)?,
+            )
             .await?;
 
-        log::info!("killed subnet: {:}", arguments.subnet);
+        if global.json_output() {
+            return print_json(&response);
+        }
+
+        log::info!(
+            "killed subnet: {:} in message: {:}",
+            arguments.subnet,
+            response.cid
+        );
 
         Ok(())
     }
@@ -47,6 +60,6 @@ pub struct KillSubnetArgs {
     pub ipc_agent_url: Option<String>,
     #[arg(long, short, help = "The address that kills the subnet")]
     pub from: Option<String>,
-    #[arg(long, short, help = "The subnet to kill")]
-    pub subnet: String,
+    #[arg(long, short, value_parser = parse_subnet_id, help = "The subnet to kill")]
+    pub subnet: SubnetID,
 }
@@ -4,13 +4,15 @@
 
 use async_trait::async_trait;
 use clap::Args;
+use ipc_sdk::subnet_id::SubnetID;
 use std::fmt::Debug;
 
-use crate::cli::commands::get_ipc_agent_url;
+use crate::cli::commands::parse::parse_subnet_id;
+use crate::cli::commands::{get_ipc_agent_url, print_json};
 use crate::cli::{CommandLineHandler, GlobalArguments};
 use crate::config::json_rpc_methods;
-use crate::jsonrpc::{JsonRpcClient, JsonRpcClientImpl};
-use crate::server::leave::LeaveSubnetParams;
+use crate::jsonrpc::JsonRpcClient;
+use crate::server::leave::{LeaveSubnetParams, LeaveSubnetResponse};
 
 /// The command to leave a new subnet.
 pub struct LeaveSubnet;
@@ -23,21 +25,30 @@ impl CommandLineHandler for LeaveSubnet {
         log::debug!("leave subnet with args: {:?}", arguments);
 
         let url = get_ipc_agent_url(&arguments.ipc_agent_url, global)?;
-        let json_rpc_client = JsonRpcClientImpl::new(url, None);
+        let json_rpc_client = global.submit_json_rpc_client(url);
 
         let params = LeaveSubnetParams {
-            subnet: arguments.subnet.clone(),
+            subnet: arguments.subnet.to_string(),
             from: arguments.from.clone(),
         };
 
-        json_rpc_client
-            .request::<()>(
+        let response = json_rpc_client
+            .request::<LeaveSubnetResponse>(
                 json_rpc_methods::LEAVE_SUBNET,
                 serde_json::to_value(params)?,
             )
             .await?;
 
-        log::info!("left subnet: {:}", arguments.subnet);
+        if global.json_output() {
+            return print_json(&response);
+        }
+
+        log::info!(
+            "left subnet: {:} in message: {:}, reclaimed collateral: {:} FIL",
+            arguments.subnet,
+            response.cid,
+            response.collateral
+        );
 
         Ok(())
     }
@@ -50,6 +61,6 @@ pub struct LeaveSubnetArgs {
     pub ipc_agent_url: Option<String>,
     #[arg(long, short, help = "The address that leaves the subnet")]
     pub from: Option<String>,
-    #[arg(long, short, help = "The subnet to leave")]
-    pub subnet: String,
+    #[arg(long, short, value_parser = parse_subnet_id, help = "The subnet to leave")]
+    pub subnet: SubnetID,
 }
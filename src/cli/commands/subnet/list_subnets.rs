@@ -2,20 +2,25 @@
 // SPDX-License-Identifier: MIT
 //! List subnets cli command
 
+use anyhow::anyhow;
 use async_trait::async_trait;
 use clap::Args;
+use fvm_shared::address::Address;
 use fvm_shared::bigint::BigInt;
 use fvm_shared::econ::TokenAmount;
+use ipc_sdk::subnet_id::SubnetID;
 use std::collections::HashMap;
 use std::fmt::Debug;
 use std::str::FromStr;
 
-use crate::cli::commands::get_ipc_agent_url;
+use crate::cli::commands::parse::{parse_address, parse_subnet_id};
+use crate::cli::commands::table::{render, Align};
+use crate::cli::commands::{get_ipc_agent_url, print_json};
 use crate::cli::{CommandLineHandler, GlobalArguments};
 use crate::config::json_rpc_methods;
-use crate::jsonrpc::{JsonRpcClient, JsonRpcClientImpl};
+use crate::jsonrpc::JsonRpcClient;
 use crate::server::list_subnets::ListSubnetsParams;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 /// The command to create a new subnet actor.
 pub(crate) struct ListSubnets;
@@ -28,11 +33,11 @@ impl CommandLineHandler for ListSubnets {
         log::debug!("list subnets with args: {:?}", arguments);
 
         let url = get_ipc_agent_url(&arguments.ipc_agent_url, global)?;
-        let json_rpc_client = JsonRpcClientImpl::new(url, None);
+        let json_rpc_client = global.json_rpc_client(url);
 
         let params = ListSubnetsParams {
-            gateway_address: arguments.gateway_address.clone(),
-            subnet_id: arguments.subnet.clone(),
+            gateway_address: arguments.gateway_address.to_string(),
+            subnet_id: arguments.subnet.to_string(),
         };
 
         let subnets = json_rpc_client
@@ -42,18 +47,13 @@ impl CommandLineHandler for ListSubnets {
             )
             .await?;
 
-        for (_, s) in subnets.iter() {
-            let u = BigInt::from_str(&s.stake).unwrap();
-            let stake = TokenAmount::from_atto(u);
-            let u = BigInt::from_str(&s.circ_supply).unwrap();
-            let supply = TokenAmount::from_atto(u);
-            log::info!(
-                "{} - status: {}, collateral: {} FIL, circ.supply: {} FIL",
-                s.id,
-                s.status,
-                stake,
-                supply,
-            );
+        // a table with a row per subnet for humans, or an array with an object per subnet for
+        // scripts
+        let summaries = summaries(&subnets);
+        if global.json_output() {
+            print_json(&summaries)?;
+        } else {
+            println!("{}", table(&summaries));
         }
 
         Ok(())
@@ -68,10 +68,79 @@ impl CommandLineHandler for ListSubnets {
 pub(crate) struct ListSubnetsArgs {
     #[arg(long, short, help = "The JSON RPC server url for ipc agent")]
     pub ipc_agent_url: Option<String>,
-    #[arg(long, short, help = "The gateway address to query subnets")]
-    pub gateway_address: String,
-    #[arg(long, short, help = "The subnet id to query child subnets")]
-    pub subnet: String,
+    #[arg(long, short, value_parser = parse_address, help = "The gateway address to query subnets")]
+    pub gateway_address: Address,
+    #[arg(
+        long,
+        short,
+        value_parser = parse_subnet_id,
+        help = "The subnet id to query child subnets"
+    )]
+    pub subnet: SubnetID,
+}
+
+/// The state of a subnet as printed by the command.
+#[derive(Debug, Serialize)]
+struct SubnetSummary {
+    id: String,
+    status: i32,
+    collateral_fil: String,
+    circ_supply_fil: String,
+}
+
+/// Returns the summaries of `subnets` sorted by id, skipping those with malformed amounts.
+fn summaries(subnets: &HashMap<String, SubnetInfoWrapper>) -> Vec<SubnetSummary> {
+    let mut summaries = vec![];
+    for s in subnets.values() {
+        // a malformed entry should not hide the rest of the subnets
+        let (stake, supply) = match s.amounts() {
+            Ok(amounts) => amounts,
+            Err(e) => {
+                log::warn!("skipping subnet: {e:}");
+                continue;
+            }
+        };
+        summaries.push(SubnetSummary {
+            id: s.id.clone(),
+            status: s.status,
+            collateral_fil: stake.to_string(),
+            circ_supply_fil: supply.to_string(),
+        });
+    }
+    summaries.sort_by(|a, b| a.id.cmp(&b.id));
+    summaries
+}
+
+/// Returns the name of the `status` of a subnet in the gateway.
+pub(crate) fn status_name(status: i32) -> String {
+    match status {
+        0 => String::from("Active"),
+        1 => String::from("Inactive"),
+        2 => String::from("Killed"),
+        _ => format!("Unknown({status:})"),
+    }
+}
+
+/// Renders `summaries` as a table, in the order given.
+fn table(summaries: &[SubnetSummary]) -> String {
+    let headers = [
+        ("ID", Align::Left),
+        ("STATUS", Align::Left),
+        ("COLLATERAL", Align::Right),
+        ("CIRC.SUPPLY", Align::Right),
+    ];
+    let rows = summaries
+        .iter()
+        .map(|s| {
+            vec![
+                s.id.clone(),
+                status_name(s.status),
+                format!("{} FIL", s.collateral_fil),
+                format!("{} FIL", s.circ_supply_fil),
+            ]
+        })
+        .collect::<Vec<_>>();
+    render(&headers, &rows)
 }
 
 /// A simplified wrapper for Subnet Info response. The SubnetInfo struct is deserialized differently
@@ -79,13 +148,155 @@ pub(crate) struct ListSubnetsArgs {
 /// rpc server, it is using different data structure and casing, i.e. id in actor is represented as
 /// a map, but in ipc-agent rpc server, it is a string.
 #[derive(Debug, Deserialize)]
-struct SubnetInfoWrapper {
-    #[allow(dead_code)]
-    id: String,
-    #[allow(dead_code)]
-    stake: String,
-    #[allow(dead_code)]
-    circ_supply: String,
-    #[allow(dead_code)]
-    status: i32,
+pub(crate) struct SubnetInfoWrapper {
+    pub(crate) id: String,
+    pub(crate) stake: String,
+    pub(crate) circ_supply: String,
+    pub(crate) status: i32,
+}
+
+impl SubnetInfoWrapper {
+    /// Parses the stake and the circulating supply of the subnet, in attoFIL.
+    pub(crate) fn amounts(&self) -> anyhow::Result<(TokenAmount, TokenAmount)> {
+        let parse = |field: &str, value: &str| {
+            BigInt::from_str(value)
+                .map(TokenAmount::from_atto)
+                .map_err(|_| {
+                    anyhow!(
+                        "invalid {field:} of subnet {}: {value:?} is not an amount",
+                        self.id
+                    )
+                })
+        };
+        Ok((
+            parse("stake", &self.stake)?,
+            parse("circ_supply", &self.circ_supply)?,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use fvm_shared::econ::TokenAmount;
+    use serde_json::{json, Value};
+
+    use crate::cli::commands::subnet::list_subnets::{summaries, table, SubnetInfoWrapper};
+
+    #[test]
+    fn test_subnet_info_amounts() {
+        let info = SubnetInfoWrapper {
+            id: String::from("/root/t01002"),
+            stake: String::from("2000000000000000000"),
+            circ_supply: String::from("0"),
+            status: 0,
+        };
+        let (stake, supply) = info.amounts().unwrap();
+        assert_eq!(stake, TokenAmount::from_whole(2));
+        assert_eq!(supply, TokenAmount::from_atto(0));
+
+        let info = SubnetInfoWrapper {
+            stake: String::from("2 FIL"),
+            ..info
+        };
+        let err = info.amounts().unwrap_err().to_string();
+        assert!(err.contains("/root/t01002"));
+        assert!(err.contains("stake"));
+        assert!(err.contains("2 FIL"));
+
+        let info = SubnetInfoWrapper {
+            stake: String::from("0"),
+            circ_supply: String::from("-"),
+            ..info
+        };
+        let err = info.amounts().unwrap_err().to_string();
+        assert!(err.contains("circ_supply"));
+        assert!(err.contains("\"-\""));
+    }
+
+    #[test]
+    fn test_summaries_json() {
+        let subnet = |id: &str, stake: &str| SubnetInfoWrapper {
+            id: String::from(id),
+            stake: String::from(stake),
+            circ_supply: String::from("500000000000000000"),
+            status: 0,
+        };
+        let subnets = HashMap::from([
+            (
+                String::from("b"),
+                subnet("/root/t01003", "1000000000000000000"),
+            ),
+            (
+                String::from("a"),
+                subnet("/root/t01002", "2000000000000000000"),
+            ),
+            (String::from("c"), subnet("/root/t01004", "2 FIL")),
+        ]);
+
+        let output = serde_json::to_string_pretty(&summaries(&subnets)).unwrap();
+        let parsed: Value = serde_json::from_str(&output).unwrap();
+        let supply = TokenAmount::from_nano(500_000_000).to_string();
+        assert_eq!(
+            parsed,
+            json!([
+                {
+                    "id": "/root/t01002",
+                    "status": 0,
+                    "collateral_fil": TokenAmount::from_whole(2).to_string(),
+                    "circ_supply_fil": supply,
+                },
+                {
+                    "id": "/root/t01003",
+                    "status": 0,
+                    "collateral_fil": TokenAmount::from_whole(1).to_string(),
+                    "circ_supply_fil": supply,
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_summaries_table() {
+        let subnet = |id: &str, stake: &str, status: i32| SubnetInfoWrapper {
+            id: String::from(id),
+            stake: String::from(stake),
+            circ_supply: String::from("0"),
+            status,
+        };
+        let subnets = HashMap::from([
+            (
+                String::from("b"),
+                subnet("/root/t01003", "10000000000000000000", 2),
+            ),
+            (
+                String::from("a"),
+                subnet("/root/t01002", "2000000000000000000", 0),
+            ),
+            (String::from("c"), subnet("/root/t01004", "0", 7)),
+        ]);
+
+        let output = table(&summaries(&subnets));
+        let lines = output.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 4);
+        let header = lines[0];
+        assert!(header.starts_with("ID"));
+        assert!(header.ends_with("CIRC.SUPPLY"));
+
+        // the rows are sorted by id and their statuses named
+        assert!(lines[1].starts_with("/root/t01002  Active"));
+        assert!(lines[2].starts_with("/root/t01003  Killed"));
+        assert!(lines[3].starts_with("/root/t01004  Unknown(7)"));
+
+        // the amounts are right-aligned under their headers
+        let collateral_end = header.find("COLLATERAL").unwrap() + "COLLATERAL".len();
+        for line in &lines[1..] {
+            assert_eq!(line.len(), header.len());
+            assert!(line.ends_with(" FIL"));
+            assert_eq!(&line[collateral_end - 4..collateral_end], " FIL");
+        }
+        let stake = format!("{} FIL", TokenAmount::from_whole(10));
+        assert!(lines[2].contains(&stake));
+    }
 }
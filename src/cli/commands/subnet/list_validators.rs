@@ -0,0 +1,188 @@
+// Copyright 2022-2023 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! List validators cli command
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+use clap::Args;
+use fvm_shared::bigint::BigInt;
+use fvm_shared::clock::ChainEpoch;
+use fvm_shared::econ::TokenAmount;
+use ipc_sdk::subnet_id::SubnetID;
+use serde::Serialize;
+use std::fmt::Debug;
+use std::str::FromStr;
+
+use crate::cli::commands::parse::parse_subnet_id;
+use crate::cli::commands::table::{render, Align};
+use crate::cli::commands::{get_ipc_agent_url, print_json};
+use crate::cli::{CommandLineHandler, GlobalArguments};
+use crate::config::json_rpc_methods;
+use crate::jsonrpc::JsonRpcClient;
+use crate::lotus::message::ipc::Validator;
+use crate::server::validator::{QueryValidatorSetParams, QueryValidatorSetResponse};
+
+/// The command to list the validators of a subnet with their stakes.
+pub(crate) struct ListValidators;
+
+#[async_trait]
+impl CommandLineHandler for ListValidators {
+    type Arguments = ListValidatorsArgs;
+
+    async fn handle(global: &GlobalArguments, arguments: &Self::Arguments) -> anyhow::Result<()> {
+        log::debug!("list validators with args: {:?}", arguments);
+
+        let url = get_ipc_agent_url(&arguments.ipc_agent_url, global)?;
+        let json_rpc_client = global.json_rpc_client(url);
+
+        let params = QueryValidatorSetParams {
+            subnet: arguments.subnet.to_string(),
+            epoch: arguments.epoch,
+        };
+        let response = json_rpc_client
+            .request_named::<QueryValidatorSetResponse, _>(
+                json_rpc_methods::QUERY_VALIDATOR_SET,
+                &params,
+            )
+            .await?;
+
+        let validators = stakes(response.validator_set.validators.unwrap_or_default())?;
+        let total = validators
+            .iter()
+            .fold(TokenAmount::from_atto(0), |total, v| total + &v.stake);
+
+        if global.json_output() {
+            return print_json(&ValidatorList {
+                validators: validators.iter().map(ValidatorSummary::from).collect(),
+                total_stake_fil: total.to_string(),
+            });
+        }
+
+        println!("{}", table(&validators, &total));
+
+        Ok(())
+    }
+}
+
+/// A validator with its stake parsed.
+struct ValidatorStake {
+    addr: String,
+    net_addr: String,
+    stake: TokenAmount,
+}
+
+/// The validators as printed by the command with `--output json`.
+#[derive(Debug, Serialize)]
+struct ValidatorList {
+    validators: Vec<ValidatorSummary>,
+    total_stake_fil: String,
+}
+
+#[derive(Debug, Serialize)]
+struct ValidatorSummary {
+    addr: String,
+    net_addr: String,
+    stake_fil: String,
+}
+
+impl From<&ValidatorStake> for ValidatorSummary {
+    fn from(v: &ValidatorStake) -> Self {
+        Self {
+            addr: v.addr.clone(),
+            net_addr: v.net_addr.clone(),
+            stake_fil: v.stake.to_string(),
+        }
+    }
+}
+
+/// Returns `validators` with their stakes, sorted by stake descending, then by address.
+fn stakes(validators: Vec<Validator>) -> anyhow::Result<Vec<ValidatorStake>> {
+    let mut stakes = validators
+        .into_iter()
+        .map(|v| {
+            let weight = BigInt::from_str(&v.weight)
+                .map_err(|_| anyhow!("invalid stake of validator {:}: {:?}", v.addr, v.weight))?;
+            Ok(ValidatorStake {
+                addr: v.addr,
+                net_addr: v.net_addr,
+                stake: TokenAmount::from_atto(weight),
+            })
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    stakes.sort_by(|a, b| b.stake.cmp(&a.stake).then_with(|| a.addr.cmp(&b.addr)));
+    Ok(stakes)
+}
+
+/// Renders `validators` as a table, in the order given, followed by the `total` stake.
+fn table(validators: &[ValidatorStake], total: &TokenAmount) -> String {
+    let headers = [
+        ("ADDRESS", Align::Left),
+        ("STAKE", Align::Right),
+        ("NET ADDRESS", Align::Left),
+    ];
+    let mut rows = validators
+        .iter()
+        .map(|v| {
+            vec![
+                v.addr.clone(),
+                format!("{} FIL", v.stake),
+                v.net_addr.clone(),
+            ]
+        })
+        .collect::<Vec<_>>();
+    rows.push(vec![String::from("TOTAL"), format!("{total} FIL")]);
+    render(&headers, &rows)
+}
+
+#[derive(Debug, Args)]
+#[command(about = "List the validators of a subnet with their stakes")]
+pub(crate) struct ListValidatorsArgs {
+    #[arg(long, short, help = "The JSON RPC server url for ipc agent")]
+    pub ipc_agent_url: Option<String>,
+    #[arg(long, short, value_parser = parse_subnet_id, help = "The subnet to list the validators of")]
+    pub subnet: SubnetID,
+    #[arg(
+        long,
+        help = "The epoch of the parent to read the validators at, the chain head if not set"
+    )]
+    pub epoch: Option<ChainEpoch>,
+}
+
+#[cfg(test)]
+mod tests {
+    use fvm_shared::econ::TokenAmount;
+
+    use crate::cli::commands::subnet::list_validators::{stakes, table};
+    use crate::lotus::message::ipc::Validator;
+
+    fn validator(addr: &str, weight: &str) -> Validator {
+        Validator {
+            addr: String::from(addr),
+            net_addr: format!("/dns/{addr:}/tcp/1349"),
+            weight: String::from(weight),
+        }
+    }
+
+    #[test]
+    fn test_stakes_sorted_by_stake() {
+        let validators = vec![
+            validator("t01001", "1000000000000000000"),
+            validator("t01003", "3000000000000000000"),
+            validator("t01002", "1000000000000000000"),
+        ];
+
+        let stakes = stakes(validators).unwrap();
+        let addrs = stakes.iter().map(|v| v.addr.as_str()).collect::<Vec<_>>();
+        assert_eq!(addrs, vec!["t01003", "t01001", "t01002"]);
+        assert_eq!(stakes[0].stake, TokenAmount::from_whole(3));
+
+        let output = table(&stakes, &TokenAmount::from_whole(5));
+        let lines = output.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 5);
+        assert!(lines[1].starts_with("t01003"));
+        assert!(lines[4].starts_with("TOTAL"));
+        assert!(lines[4].contains(&format!("{} FIL", TokenAmount::from_whole(5))));
+
+        assert!(stakes(vec![validator("t01001", "one")]).is_err());
+    }
+}
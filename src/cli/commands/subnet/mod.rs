@@ -1,22 +1,34 @@
 // Copyright 2022-2023 Protocol Labs
 // SPDX-License-Identifier: MIT
 
+use crate::cli::commands::subnet::activation::{ActivationStatusArgs, ActivationStatusCmd};
+use crate::cli::commands::subnet::bootstrap::{BootstrapSubnet, BootstrapSubnetArgs};
 pub use crate::cli::commands::subnet::create::{CreateSubnet, CreateSubnetArgs};
+use crate::cli::commands::subnet::fund::{FundSubnet, FundSubnetArgs};
+use crate::cli::commands::subnet::info::{SubnetInfo, SubnetInfoArgs};
 pub use crate::cli::commands::subnet::join::{JoinSubnet, JoinSubnetArgs};
 pub use crate::cli::commands::subnet::kill::{KillSubnet, KillSubnetArgs};
 pub use crate::cli::commands::subnet::leave::{LeaveSubnet, LeaveSubnetArgs};
 use crate::cli::commands::subnet::list_subnets::{ListSubnets, ListSubnetsArgs};
+use crate::cli::commands::subnet::list_validators::{ListValidators, ListValidatorsArgs};
 use crate::cli::commands::subnet::net_addr::{SetValidatorNetAddr, SetValidatorNetAddrArgs};
+use crate::cli::commands::subnet::release::{ReleaseSubnet, ReleaseSubnetArgs};
 use crate::cli::commands::subnet::send_value::{SendValue, SendValueArgs};
 use crate::cli::{CommandLineHandler, GlobalArguments};
 use clap::{Args, Subcommand};
 
+pub mod activation;
+pub mod bootstrap;
 pub mod create;
+pub mod fund;
+pub mod info;
 pub mod join;
 pub mod kill;
 pub mod leave;
 pub mod list_subnets;
+pub mod list_validators;
 pub mod net_addr;
+pub mod release;
 pub mod send_value;
 
 #[derive(Debug, Args)]
@@ -35,11 +47,17 @@ impl SubnetCommandsArgs {
         match &self.command {
             Commands::Create(args) => CreateSubnet::handle(global, args).await,
             Commands::List(args) => ListSubnets::handle(global, args).await,
+            Commands::ListValidators(args) => ListValidators::handle(global, args).await,
+            Commands::Info(args) => SubnetInfo::handle(global, args).await,
             Commands::Join(args) => JoinSubnet::handle(global, args).await,
             Commands::Leave(args) => LeaveSubnet::handle(global, args).await,
             Commands::Kill(args) => KillSubnet::handle(global, args).await,
             Commands::SendValue(args) => SendValue::handle(global, args).await,
             Commands::SetValidatorNetAddr(args) => SetValidatorNetAddr::handle(global, args).await,
+            Commands::ActivationStatus(args) => ActivationStatusCmd::handle(global, args).await,
+            Commands::Bootstrap(args) => BootstrapSubnet::handle(global, args).await,
+            Commands::Fund(args) => FundSubnet::handle(global, args).await,
+            Commands::Release(args) => ReleaseSubnet::handle(global, args).await,
         }
     }
 }
@@ -48,9 +66,15 @@ impl SubnetCommandsArgs {
 pub(crate) enum Commands {
     Create(CreateSubnetArgs),
     List(ListSubnetsArgs),
+    ListValidators(ListValidatorsArgs),
+    Info(SubnetInfoArgs),
     Join(JoinSubnetArgs),
     Leave(LeaveSubnetArgs),
     Kill(KillSubnetArgs),
     SendValue(SendValueArgs),
     SetValidatorNetAddr(SetValidatorNetAddrArgs),
+    ActivationStatus(ActivationStatusArgs),
+    Bootstrap(BootstrapSubnetArgs),
+    Fund(FundSubnetArgs),
+    Release(ReleaseSubnetArgs),
 }
@@ -2,13 +2,16 @@
 // SPDX-License-Identifier: MIT
 //! The command to set the validator net address
 
-use crate::cli::commands::get_ipc_agent_url;
+use crate::cli::commands::parse::parse_subnet_id;
+use crate::cli::commands::{get_ipc_agent_url, print_json};
 use crate::cli::{CommandLineHandler, GlobalArguments};
 use crate::config::json_rpc_methods;
-use crate::jsonrpc::{JsonRpcClient, JsonRpcClientImpl};
+use crate::jsonrpc::JsonRpcClient;
 use crate::server::net_addr::SetValidatorNetAddrParams;
 use async_trait::async_trait;
 use clap::Args;
+use ipc_sdk::subnet_id::SubnetID;
+use serde_json::json;
 
 /// Setting the validator net address
 pub(crate) struct SetValidatorNetAddr;
@@ -21,10 +24,10 @@ impl CommandLineHandler for SetValidatorNetAddr {
         log::debug!("set the validator net addr args: {:?}", arguments);
 
         let url = get_ipc_agent_url(&arguments.ipc_agent_url, global)?;
-        let json_rpc_client = JsonRpcClientImpl::new(url, None);
+        let json_rpc_client = global.submit_json_rpc_client(url);
 
         let params = SetValidatorNetAddrParams {
-            subnet: arguments.subnet.clone(),
+            subnet: arguments.subnet.to_string(),
             from: arguments.from.clone(),
             validator_net_addr: arguments.validator_net_addr.clone(),
         };
@@ -36,6 +39,13 @@ impl CommandLineHandler for SetValidatorNetAddr {
             )
             .await?;
 
+        if global.json_output() {
+            return print_json(&json!({
+                "subnet": arguments.subnet.to_string(),
+                "validator_net_addr": arguments.validator_net_addr,
+            }));
+        }
+
         log::info!(
             "set the validator net addr to: {:} in subnet: {:}",
             arguments.validator_net_addr,
@@ -53,8 +63,8 @@ pub(crate) struct SetValidatorNetAddrArgs {
     pub ipc_agent_url: Option<String>,
     #[arg(long, short, help = "Owner address of the validator being updated")]
     pub from: Option<String>,
-    #[arg(long, short, help = "The subnet to set the validator")]
-    pub subnet: String,
+    #[arg(long, short, value_parser = parse_subnet_id, help = "The subnet to set the validator")]
+    pub subnet: SubnetID,
     #[arg(long, short, help = "New validator net address")]
     pub validator_net_addr: String,
 }
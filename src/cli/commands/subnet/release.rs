@@ -0,0 +1,80 @@
+// Copyright 2022-2023 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Release subnet cli command handler.
+
+use async_trait::async_trait;
+use clap::Args;
+use ipc_sdk::subnet_id::SubnetID;
+use std::fmt::Debug;
+
+use crate::cli::commands::parse::parse_subnet_id;
+use crate::cli::commands::{get_ipc_agent_url, print_json};
+use crate::cli::{CommandLineHandler, GlobalArguments};
+use crate::config::json_rpc_methods;
+use crate::jsonrpc::JsonRpcClient;
+use crate::server::release::{ReleaseParams, ReleaseResponse};
+
+/// The command to release funds from a subnet to the same address in its parent. It sends the
+/// release message to the gateway of the subnet and prints its cid, and the exit code of the
+/// message if waiting for it.
+pub(crate) struct ReleaseSubnet;
+
+#[async_trait]
+impl CommandLineHandler for ReleaseSubnet {
+    type Arguments = ReleaseSubnetArgs;
+
+    async fn handle(global: &GlobalArguments, arguments: &Self::Arguments) -> anyhow::Result<()> {
+        log::debug!("release from subnet with args: {:?}", arguments);
+
+        let url = get_ipc_agent_url(&arguments.ipc_agent_url, global)?;
+        let json_rpc_client = if arguments.wait {
+            global.submit_json_rpc_client(url)
+        } else {
+            global.json_rpc_client(url)
+        };
+
+        let params = ReleaseParams {
+            subnet: arguments.subnet.to_string(),
+            from: arguments.from.clone(),
+            amount: arguments.amount,
+            wait: Some(arguments.wait),
+        };
+        let response = json_rpc_client
+            .request::<ReleaseResponse>(json_rpc_methods::RELEASE, serde_json::to_value(params)?)
+            .await?;
+
+        if global.json_output() {
+            return print_json(&response);
+        }
+
+        println!("{:}", response.cid);
+        if let Some(exit_code) = response.exit_code {
+            log::info!("release message executed with exit code: {exit_code:}");
+            println!("exit code: {exit_code:}");
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Args)]
+#[command(about = "Release funds from a subnet to the same address in its parent")]
+pub(crate) struct ReleaseSubnetArgs {
+    #[arg(long, short, help = "The JSON RPC server url for ipc agent")]
+    pub ipc_agent_url: Option<String>,
+    #[arg(long, short, value_parser = parse_subnet_id, help = "The subnet to release funds from")]
+    pub subnet: SubnetID,
+    #[arg(
+        long,
+        short,
+        help = "The address in the subnet to release funds from, the default one if not set"
+    )]
+    pub from: Option<String>,
+    #[arg(long, short, help = "The amount to release in whole FIL units")]
+    pub amount: u64,
+    #[arg(
+        long,
+        help = "Wait for the message to be executed and print its exit code"
+    )]
+    pub wait: bool,
+}
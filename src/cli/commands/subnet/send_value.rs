@@ -4,12 +4,15 @@
 
 use async_trait::async_trait;
 use clap::Args;
+use ipc_sdk::subnet_id::SubnetID;
+use serde_json::json;
 use std::fmt::Debug;
 
-use crate::cli::commands::get_ipc_agent_url;
+use crate::cli::commands::parse::parse_subnet_id;
+use crate::cli::commands::{get_ipc_agent_url, print_json};
 use crate::cli::{CommandLineHandler, GlobalArguments};
 use crate::config::json_rpc_methods;
-use crate::jsonrpc::{JsonRpcClient, JsonRpcClientImpl};
+use crate::jsonrpc::JsonRpcClient;
 use crate::server::send_value::SendValueParams;
 
 pub(crate) struct SendValue;
@@ -22,12 +25,12 @@ impl CommandLineHandler for SendValue {
         log::debug!("send value in subnet with args: {:?}", arguments);
 
         let url = get_ipc_agent_url(&arguments.ipc_agent_url, global)?;
-        let json_rpc_client = JsonRpcClientImpl::new(url, None);
+        let json_rpc_client = global.submit_json_rpc_client(url);
 
         // The json rpc server will handle directing the request to
         // the correct parent.
         let params = SendValueParams {
-            subnet: arguments.subnet.clone(),
+            subnet: arguments.subnet.to_string(),
             from: arguments.from.clone(),
             to: arguments.to.clone(),
             amount: arguments.amount,
@@ -37,6 +40,14 @@ impl CommandLineHandler for SendValue {
             .request::<()>(json_rpc_methods::SEND_VALUE, serde_json::to_value(params)?)
             .await?;
 
+        if global.json_output() {
+            return print_json(&json!({
+                "subnet": arguments.subnet.to_string(),
+                "to": arguments.to,
+                "amount": arguments.amount,
+            }));
+        }
+
         log::info!("sending value in subnet: {:}", arguments.subnet);
 
         Ok(())
@@ -52,8 +63,8 @@ pub(crate) struct SendValueArgs {
     pub from: Option<String>,
     #[arg(long, short, help = "The address to send value to")]
     pub to: String,
-    #[arg(long, short, help = "The subnet of the addresses")]
-    pub subnet: String,
+    #[arg(long, short, value_parser = parse_subnet_id, help = "The subnet of the addresses")]
+    pub subnet: SubnetID,
     #[arg(help = "The amount to send (in whole FIL units)")]
     pub amount: u64,
 }
@@ -0,0 +1,79 @@
+// Copyright 2022-2023 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Fixed-width tables for the text output of the cli commands
+
+/// The alignment of the cells of a column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Align {
+    Left,
+    Right,
+}
+
+/// The spaces between two columns.
+const COLUMN_SEPARATOR: &str = "  ";
+
+/// Renders `rows` under `headers` as a table of fixed-width columns, each as wide as its
+/// widest cell and aligned as its header says. Rows with fewer cells than headers are padded
+/// with empty cells, extra cells are dropped.
+pub(crate) fn render(headers: &[(&str, Align)], rows: &[Vec<String>]) -> String {
+    let mut widths = headers
+        .iter()
+        .map(|(h, _)| h.chars().count())
+        .collect::<Vec<_>>();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let line = |cells: Vec<&str>| {
+        let line = headers
+            .iter()
+            .zip(&widths)
+            .enumerate()
+            .map(|(i, ((_, align), width))| {
+                let cell = cells.get(i).copied().unwrap_or_default();
+                match align {
+                    Align::Left => format!("{cell:<width$}"),
+                    Align::Right => format!("{cell:>width$}"),
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(COLUMN_SEPARATOR);
+        line.trim_end().to_string()
+    };
+
+    let mut lines = vec![line(headers.iter().map(|(h, _)| *h).collect())];
+    for row in rows {
+        lines.push(line(row.iter().map(String::as_str).collect()));
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cli::commands::table::{render, Align};
+
+    #[test]
+    fn test_render() {
+        let headers = [("NAME", Align::Left), ("AMOUNT", Align::Right)];
+        let rows = vec![
+            vec![String::from("a"), String::from("1.5")],
+            vec![String::from("longer name"), String::from("10")],
+            vec![String::from("short")],
+        ];
+        assert_eq!(
+            render(&headers, &rows),
+            [
+                "NAME         AMOUNT",
+                "a               1.5",
+                "longer name      10",
+                "short",
+            ]
+            .join("\n")
+        );
+
+        // headers only
+        assert_eq!(render(&headers, &[]), "NAME  AMOUNT");
+    }
+}
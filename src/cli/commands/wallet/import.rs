@@ -0,0 +1,66 @@
+// Copyright 2022-2023 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Wallet import cli handler
+
+use async_trait::async_trait;
+use clap::Args;
+use std::fmt::Debug;
+use zeroize::Zeroize;
+
+use crate::cli::commands::{get_ipc_agent_url, print_json};
+use crate::cli::{CommandLineHandler, GlobalArguments};
+use crate::config::json_rpc_methods;
+use crate::jsonrpc::JsonRpcClient;
+use crate::server::wallet::import::{WalletImportParams, WalletImportResponse};
+
+pub(crate) struct WalletImport;
+
+#[async_trait]
+impl CommandLineHandler for WalletImport {
+    type Arguments = WalletImportArgs;
+
+    async fn handle(global: &GlobalArguments, arguments: &Self::Arguments) -> anyhow::Result<()> {
+        log::debug!("import wallet with args: {:?}", arguments);
+
+        let url = get_ipc_agent_url(&arguments.ipc_agent_url, global)?;
+        let json_rpc_client = global.json_rpc_client(url);
+
+        let mut params = WalletImportParams {
+            subnet: arguments.subnet.clone(),
+            key_info: std::fs::read_to_string(&arguments.path)?,
+        };
+        let request = serde_json::to_value(&params);
+        params.key_info.zeroize();
+
+        let addr = json_rpc_client
+            .request::<WalletImportResponse>(json_rpc_methods::WALLET_IMPORT, request?)
+            .await?;
+
+        if global.json_output() {
+            return print_json(&addr);
+        }
+
+        log::info!(
+            "imported wallet with address {:?} in subnet {:?}",
+            addr.address,
+            arguments.subnet
+        );
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Args)]
+#[command(about = "Import a key into the wallet of a subnet")]
+pub(crate) struct WalletImportArgs {
+    #[arg(long, short, help = "The JSON RPC server url for ipc agent")]
+    pub ipc_agent_url: Option<String>,
+    #[arg(long, short, help = "The subnet where the key will be imported")]
+    pub subnet: String,
+    #[arg(
+        long,
+        short,
+        help = "Path of the file with the key, as exported by `lotus wallet export`"
+    )]
+    pub path: String,
+}
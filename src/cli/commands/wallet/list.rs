@@ -6,10 +6,10 @@ use async_trait::async_trait;
 use clap::Args;
 use std::fmt::Debug;
 
-use crate::cli::commands::get_ipc_agent_url;
+use crate::cli::commands::{get_ipc_agent_url, print_json};
 use crate::cli::{CommandLineHandler, GlobalArguments};
 use crate::config::json_rpc_methods;
-use crate::jsonrpc::{JsonRpcClient, JsonRpcClientImpl};
+use crate::jsonrpc::JsonRpcClient;
 use crate::server::wallet::list::{WalletListParams, WalletListResponse};
 
 pub(crate) struct WalletList;
@@ -22,10 +22,11 @@ impl CommandLineHandler for WalletList {
         log::debug!("list wallets with args: {:?}", arguments);
 
         let url = get_ipc_agent_url(&arguments.ipc_agent_url, global)?;
-        let json_rpc_client = JsonRpcClientImpl::new(url, None);
+        let json_rpc_client = global.json_rpc_client(url);
 
         let params = WalletListParams {
             subnet: arguments.subnet.clone(),
+            address_format: arguments.address_format.clone(),
         };
 
         let addrs = json_rpc_client
@@ -35,6 +36,10 @@ impl CommandLineHandler for WalletList {
             )
             .await?;
 
+        if global.json_output() {
+            return print_json(&addrs);
+        }
+
         log::info!("wallets in subnet {:} are {:?}", arguments.subnet, addrs);
 
         Ok(())
@@ -48,4 +53,9 @@ pub(crate) struct WalletListArgs {
     pub ipc_agent_url: Option<String>,
     #[arg(long, short, help = "The subnet to list wallets from")]
     pub subnet: String,
+    #[arg(
+        long,
+        help = "The format of the printed addresses (id, robust), defaults to the node's output"
+    )]
+    pub address_format: Option<String>,
 }
@@ -2,12 +2,16 @@
 // SPDX-License-Identifier: MIT
 use crate::cli::{CommandLineHandler, GlobalArguments};
 
+use crate::cli::commands::wallet::import::{WalletImport, WalletImportArgs};
 use crate::cli::commands::wallet::list::{WalletList, WalletListArgs};
 use crate::cli::commands::wallet::new::{WalletNew, WalletNewArgs};
+use crate::cli::commands::wallet::watch_balance::{WatchBalance, WatchBalanceArgs};
 use clap::{Args, Subcommand};
 
+mod import;
 mod list;
 mod new;
+mod watch_balance;
 
 #[derive(Debug, Args)]
 #[command(name = "wallet", about = "wallet related commands")]
@@ -22,6 +26,8 @@ impl WalletCommandsArgs {
         match &self.command {
             Commands::New(args) => WalletNew::handle(global, args).await,
             Commands::List(args) => WalletList::handle(global, args).await,
+            Commands::WatchBalance(args) => WatchBalance::handle(global, args).await,
+            Commands::Import(args) => WalletImport::handle(global, args).await,
         }
     }
 }
@@ -30,4 +36,6 @@ impl WalletCommandsArgs {
 pub(crate) enum Commands {
     New(WalletNewArgs),
     List(WalletListArgs),
+    WatchBalance(WatchBalanceArgs),
+    Import(WalletImportArgs),
 }
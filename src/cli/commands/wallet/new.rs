@@ -6,10 +6,10 @@ use async_trait::async_trait;
 use clap::Args;
 use std::fmt::Debug;
 
-use crate::cli::commands::get_ipc_agent_url;
+use crate::cli::commands::{get_ipc_agent_url, print_json};
 use crate::cli::{CommandLineHandler, GlobalArguments};
 use crate::config::json_rpc_methods;
-use crate::jsonrpc::{JsonRpcClient, JsonRpcClientImpl};
+use crate::jsonrpc::JsonRpcClient;
 use crate::server::wallet::new::{WalletNewParams, WalletNewResponse};
 
 pub(crate) struct WalletNew;
@@ -22,11 +22,12 @@ impl CommandLineHandler for WalletNew {
         log::debug!("create new wallet with args: {:?}", arguments);
 
         let url = get_ipc_agent_url(&arguments.ipc_agent_url, global)?;
-        let json_rpc_client = JsonRpcClientImpl::new(url, None);
+        let json_rpc_client = global.json_rpc_client(url);
 
         let params = WalletNewParams {
             subnet: arguments.subnet.clone(),
             key_type: arguments.key_type.clone(),
+            address_format: arguments.address_format.clone(),
         };
 
         let addr = json_rpc_client
@@ -36,6 +37,10 @@ impl CommandLineHandler for WalletNew {
             )
             .await?;
 
+        if global.json_output() {
+            return print_json(&addr);
+        }
+
         log::info!(
             "created new wallet with address {:?} in subnet {:?}",
             addr,
@@ -59,4 +64,9 @@ pub(crate) struct WalletNewArgs {
         help = "Key type of the wallet (secp256k1, bls, secp256k1-ledger)"
     )]
     pub key_type: String,
+    #[arg(
+        long,
+        help = "The format of the printed address (id, robust), defaults to the node's output"
+    )]
+    pub address_format: Option<String>,
 }
@@ -0,0 +1,215 @@
+// Copyright 2022-2023 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Wallet watch balance cli handler
+
+use std::fmt::Debug;
+use std::future::Future;
+use std::str::FromStr;
+use std::time::Duration;
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+use clap::Args;
+use fvm_shared::bigint::BigInt;
+use fvm_shared::econ::TokenAmount;
+use serde_json::json;
+
+use crate::cli::commands::{get_ipc_agent_url, print_json};
+use crate::cli::{CommandLineHandler, GlobalArguments};
+use crate::config::json_rpc_methods;
+use crate::jsonrpc::JsonRpcClient;
+use crate::server::wallet::balance::{WalletBalanceParams, WalletBalanceResponse};
+
+/// The command to watch the balance of an address until it reaches a target.
+pub(crate) struct WatchBalance;
+
+#[async_trait]
+impl CommandLineHandler for WatchBalance {
+    type Arguments = WatchBalanceArgs;
+
+    async fn handle(global: &GlobalArguments, arguments: &Self::Arguments) -> anyhow::Result<()> {
+        log::debug!("watch balance with args: {:?}", arguments);
+
+        let url = get_ipc_agent_url(&arguments.ipc_agent_url, global)?;
+        let json_rpc_client = global.json_rpc_client(url);
+
+        let get_balance = || async {
+            let params = WalletBalanceParams {
+                subnet: arguments.subnet.clone(),
+                address: arguments.address.clone(),
+            };
+            let r = json_rpc_client
+                .request::<WalletBalanceResponse>(
+                    json_rpc_methods::WALLET_BALANCE,
+                    serde_json::to_value(params)?,
+                )
+                .await?;
+            Ok(TokenAmount::from_atto(BigInt::from_str(&r.balance)?))
+        };
+
+        let balance = watch_balance(
+            get_balance,
+            arguments.target.map(TokenAmount::from_whole),
+            Duration::from_secs(arguments.interval),
+            Duration::from_secs(arguments.watch_timeout),
+        )
+        .await?;
+
+        if global.json_output() {
+            return print_json(&json!({
+                "address": arguments.address,
+                "subnet": arguments.subnet,
+                "balance_fil": balance.to_string(),
+            }));
+        }
+
+        log::info!(
+            "balance of {:} in subnet {:} is {:} FIL",
+            arguments.address,
+            arguments.subnet,
+            balance
+        );
+
+        Ok(())
+    }
+}
+
+/// Polls the balance returned by `get_balance` every `interval` until it reaches `target` or, if
+/// no target is provided, until it changes from its initial value. Each change in the balance is
+/// logged. Returns the last balance observed or an error if `timeout` expires first.
+pub(crate) async fn watch_balance<F, Fut>(
+    mut get_balance: F,
+    target: Option<TokenAmount>,
+    interval: Duration,
+    timeout: Duration,
+) -> anyhow::Result<TokenAmount>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = anyhow::Result<TokenAmount>>,
+{
+    tokio::time::timeout(
+        timeout,
+        poll_balance(&mut get_balance, target.as_ref(), interval),
+    )
+    .await
+    .map_err(|_| anyhow!("timeout after {timeout:?} waiting for the balance"))?
+}
+
+async fn poll_balance<F, Fut>(
+    get_balance: &mut F,
+    target: Option<&TokenAmount>,
+    interval: Duration,
+) -> anyhow::Result<TokenAmount>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = anyhow::Result<TokenAmount>>,
+{
+    let initial = get_balance().await?;
+    log::info!("current balance: {:} FIL", initial);
+
+    let mut balance = initial.clone();
+    loop {
+        let done = match target {
+            Some(target) => balance >= *target,
+            None => balance != initial,
+        };
+        if done {
+            return Ok(balance);
+        }
+
+        tokio::time::sleep(interval).await;
+
+        let new_balance = get_balance().await?;
+        if new_balance != balance {
+            log::info!("balance changed: {:} FIL -> {:} FIL", balance, new_balance);
+            balance = new_balance;
+        }
+    }
+}
+
+#[derive(Debug, Args)]
+#[command(about = "Watch the balance of an address in a subnet until it reaches a target")]
+pub(crate) struct WatchBalanceArgs {
+    #[arg(long, short, help = "The JSON RPC server url for ipc agent")]
+    pub ipc_agent_url: Option<String>,
+    #[arg(long, short, help = "The subnet of the address")]
+    pub subnet: String,
+    #[arg(long, short, help = "The address to watch the balance of")]
+    pub address: String,
+    #[arg(
+        long,
+        short,
+        help = "The balance to wait for (in whole FIL units), waits for any change if not set"
+    )]
+    pub target: Option<u64>,
+    #[arg(long, default_value = "10", help = "The polling interval in seconds")]
+    pub interval: u64,
+    #[arg(
+        long,
+        default_value = "300",
+        help = "The number of seconds to wait before giving up"
+    )]
+    pub watch_timeout: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::time::Duration;
+
+    use fvm_shared::econ::TokenAmount;
+
+    use crate::cli::commands::wallet::watch_balance::watch_balance;
+
+    const INTERVAL: Duration = Duration::from_millis(1);
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    #[tokio::test]
+    async fn test_watch_balance_reaches_target() {
+        // the balance is increased by 1 FIL on every poll
+        let polls = AtomicU64::new(0);
+        let get_balance = || async {
+            let n = polls.fetch_add(1, Ordering::SeqCst);
+            Ok(TokenAmount::from_whole(n))
+        };
+
+        let balance = watch_balance(
+            get_balance,
+            Some(TokenAmount::from_whole(3)),
+            INTERVAL,
+            TIMEOUT,
+        )
+        .await
+        .unwrap();
+        assert_eq!(balance, TokenAmount::from_whole(3));
+        assert_eq!(polls.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn test_watch_balance_change() {
+        let polls = AtomicU64::new(0);
+        let get_balance = || async {
+            let n = polls.fetch_add(1, Ordering::SeqCst);
+            Ok(TokenAmount::from_whole(if n < 2 { 1 } else { 5 }))
+        };
+
+        let balance = watch_balance(get_balance, None, INTERVAL, TIMEOUT)
+            .await
+            .unwrap();
+        assert_eq!(balance, TokenAmount::from_whole(5));
+    }
+
+    #[tokio::test]
+    async fn test_watch_balance_timeout() {
+        let get_balance = || async { Ok(TokenAmount::from_whole(1)) };
+
+        let r = watch_balance(
+            get_balance,
+            Some(TokenAmount::from_whole(2)),
+            INTERVAL,
+            Duration::from_millis(50),
+        )
+        .await;
+        assert!(r.is_err());
+    }
+}
@@ -2,14 +2,20 @@
 // SPDX-License-Identifier: MIT
 use anyhow::Result;
 use async_trait::async_trait;
-use clap::Args;
+use clap::{Args, ValueEnum};
+use std::time::Duration;
+use url::Url;
 
 mod commands;
 
 use crate::config::Config;
+use crate::jsonrpc::JsonRpcClientImpl;
+use crate::lotus::client::DEFAULT_STATE_WAIT_DEADLINE;
 pub use commands::*;
 
 const DEFAULT_CONFIG_PATH: &str = ".ipc-agent/config.toml";
+/// The time the commands sending messages wait for the agent on top of its state wait deadline.
+const SUBMIT_TIMEOUT_MARGIN: Duration = Duration::from_secs(60);
 
 /// The trait that represents the abstraction of a command line handler. To implement a new command
 /// line operation, implement this trait and register it.
@@ -29,15 +35,45 @@ pub trait CommandLineHandler {
     async fn handle(global: &GlobalArguments, arguments: &Self::Arguments) -> anyhow::Result<()>;
 }
 
+/// The format the commands output their results in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Logs the results of the commands, for humans.
+    #[default]
+    Text,
+    /// Prints the results of the commands to stdout as json, for scripts.
+    Json,
+}
+
 /// The global arguments that will be shared by all cli commands.
 #[derive(Debug, Args, Clone, Default)]
 pub struct GlobalArguments {
     #[arg(
         short,
         long,
-        help = "The toml config file path for IPC Agent, default to ${HOME}/.ipc-agent/config.toml"
+        help = "The toml config file path for IPC Agent, or json if it ends in .json, default to ${HOME}/.ipc-agent/config.toml"
     )]
     config_path: Option<String>,
+    #[arg(
+        long,
+        help = "The timeout in seconds of the RPC calls made by the command, default to 30, or to 660 for the commands sending messages"
+    )]
+    timeout: Option<u64>,
+    #[arg(
+        long,
+        env = "IPC_AGENT_AUTH_TOKEN",
+        hide_env_values = true,
+        help = "The token to authenticate to the agent daemon, if its server sets an auth_token"
+    )]
+    auth_token: Option<String>,
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        default_value_t = OutputFormat::Text,
+        help = "The format of the results of the command, logged as text by default, or printed to stdout as json"
+    )]
+    output: OutputFormat,
 }
 
 impl GlobalArguments {
@@ -55,4 +91,104 @@ impl GlobalArguments {
         let config_path = self.config_path();
         Config::from_file(config_path)
     }
+
+    /// Returns whether `--output json` was passed, in which case commands print their result to
+    /// stdout as json instead of logging it.
+    pub fn json_output(&self) -> bool {
+        self.output == OutputFormat::Json
+    }
+
+    /// Creates the json rpc client used to send requests to `url`, applying the `--timeout`
+    /// passed in the command line if any.
+    pub fn json_rpc_client(&self, url: Url) -> JsonRpcClientImpl {
+        let client = JsonRpcClientImpl::new(url, self.auth_token.as_deref());
+        match self.timeout {
+            Some(secs) => client.with_timeout(Duration::from_secs(secs)),
+            None => client,
+        }
+    }
+
+    /// Same as [`GlobalArguments::json_rpc_client`], but for the commands that send a message
+    /// and wait for it to be executed. Unless `--timeout` is passed, requests time out after
+    /// the agent gives up waiting for the message, so that the reason of the failure reaches
+    /// the command.
+    pub fn submit_json_rpc_client(&self, url: Url) -> JsonRpcClientImpl {
+        let client = JsonRpcClientImpl::new(url, self.auth_token.as_deref());
+        match self.timeout {
+            Some(secs) => client.with_timeout(Duration::from_secs(secs)),
+            None => client.with_timeout(DEFAULT_STATE_WAIT_DEADLINE + SUBMIT_TIMEOUT_MARGIN),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+    use std::time::Duration;
+    use url::Url;
+
+    use crate::cli::GlobalArguments;
+
+    #[derive(Debug, Parser)]
+    struct TestCli {
+        #[clap(flatten)]
+        global: GlobalArguments,
+    }
+
+    #[test]
+    fn test_timeout_propagates_to_client() {
+        let url = Url::parse("http://localhost:3030/json_rpc").unwrap();
+
+        let cli = TestCli::parse_from(["ipc", "--timeout", "120"]);
+        let client = cli.global.json_rpc_client(url.clone());
+        assert_eq!(client.timeout(), Duration::from_secs(120));
+
+        let cli = TestCli::parse_from(["ipc"]);
+        let client = cli.global.json_rpc_client(url.clone());
+        assert_eq!(client.timeout(), Duration::from_secs(30));
+
+        // the commands sending messages outlast the wait of the agent
+        let client = cli.global.submit_json_rpc_client(url.clone());
+        assert_eq!(client.timeout(), Duration::from_secs(660));
+
+        let cli = TestCli::parse_from(["ipc", "--timeout", "120"]);
+        let client = cli.global.submit_json_rpc_client(url);
+        assert_eq!(client.timeout(), Duration::from_secs(120));
+    }
+
+    #[test]
+    fn test_json_output() {
+        let cli = TestCli::parse_from(["ipc"]);
+        assert!(!cli.global.json_output());
+
+        let cli = TestCli::parse_from(["ipc", "--output", "text"]);
+        assert!(!cli.global.json_output());
+
+        let cli = TestCli::parse_from(["ipc", "--output", "json"]);
+        assert!(cli.global.json_output());
+
+        assert!(TestCli::try_parse_from(["ipc", "--output", "yaml"]).is_err());
+    }
+
+    #[derive(Debug, Parser)]
+    struct TestCliWithSubcommand {
+        #[clap(flatten)]
+        global: GlobalArguments,
+        #[command(subcommand)]
+        command: TestCommand,
+    }
+
+    #[derive(Debug, clap::Subcommand)]
+    enum TestCommand {
+        List,
+    }
+
+    #[test]
+    fn test_json_output_after_subcommand() {
+        let cli = TestCliWithSubcommand::parse_from(["ipc", "list", "--output", "json"]);
+        assert!(cli.global.json_output());
+
+        let cli = TestCliWithSubcommand::parse_from(["ipc", "--output", "json", "list"]);
+        assert!(cli.global.json_output());
+    }
 }
@@ -3,16 +3,21 @@
 //! Deserialization utils for config mod.
 
 use crate::config::Subnet;
+use cid::Cid;
 use fvm_shared::address::Address;
+use fvm_shared::bigint::BigInt;
+use fvm_shared::econ::TokenAmount;
 use ipc_sdk::subnet_id::SubnetID;
 use serde::de::Error;
 use serde::{Deserialize, Deserializer};
 use std::collections::HashMap;
 use std::fmt::Formatter;
 use std::str::FromStr;
+use url::Url;
 
 /// A serde deserialization method to deserialize a hashmap of subnets with subnet id as key and
-/// Subnet struct as value from a vec of subnets
+/// Subnet struct as value from a vec of subnets. Fails listing the ids declared more than once,
+/// instead of keeping the last declaration.
 pub(crate) fn deserialize_subnets_from_vec<'de, D>(
     deserializer: D,
 ) -> anyhow::Result<HashMap<SubnetID, Subnet>, D::Error>
@@ -22,13 +27,91 @@ where
     let subnets = <Vec<Subnet>>::deserialize(deserializer)?;
 
     let mut hashmap = HashMap::new();
-    for subnet in subnets {
-        hashmap.insert(subnet.id.clone(), subnet);
+    let mut duplicates = vec![];
+    for mut subnet in subnets {
+        resolve_auth_token(&mut subnet).map_err(D::Error::custom)?;
+        let id = subnet.id.clone();
+        if hashmap.insert(id.clone(), subnet).is_some() && !duplicates.contains(&id) {
+            duplicates.push(id);
+        }
+    }
+    if !duplicates.is_empty() {
+        let ids = duplicates
+            .iter()
+            .map(|id| id.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        return Err(D::Error::custom(format!("duplicated subnet ids: {ids:}")));
     }
     Ok(hashmap)
 }
 
-/// A serde deserialization method to deserialize an address from i64
+/// Sets the auth token of `subnet` from the environment, either the variable named by its
+/// `auth_token_env` or those its `auth_token` refers to, see [`expand_env_vars`]. Errors name
+/// the subnet, never the token.
+fn resolve_auth_token(subnet: &mut Subnet) -> anyhow::Result<()> {
+    let id = &subnet.id;
+    let token = match (&subnet.auth_token, &subnet.auth_token_env) {
+        (Some(_), Some(_)) => {
+            return Err(anyhow::anyhow!(
+                "subnet {id:} cannot set both auth_token and auth_token_env"
+            ))
+        }
+        (Some(token), None) => expand_env_vars(token)
+            .map_err(|e| anyhow::anyhow!("invalid auth_token of subnet {id:}: {e:}"))?,
+        (None, Some(name)) => std::env::var(name).map_err(|_| {
+            anyhow::anyhow!(
+                "environment variable {name} is not set for the auth_token of subnet {id:}"
+            )
+        })?,
+        (None, None) => return Ok(()),
+    };
+    subnet.auth_token = Some(token);
+    Ok(())
+}
+
+/// Replaces each `${NAME}` in `s` with the value of the environment variable `NAME`, so that
+/// secrets don't need to be written in the config file. Fails if a variable is not set.
+pub(crate) fn expand_env_vars(s: &str) -> anyhow::Result<String> {
+    let mut expanded = String::new();
+    let mut rest = s;
+    while let Some(start) = rest.find("${") {
+        expanded.push_str(&rest[..start]);
+        let end = rest[start..]
+            .find('}')
+            .ok_or_else(|| anyhow::anyhow!("unclosed environment variable in {s:?}"))?;
+        let name = &rest[start + 2..start + end];
+        let value = std::env::var(name)
+            .map_err(|_| anyhow::anyhow!("environment variable {name} is not set"))?;
+        expanded.push_str(&value);
+        rest = &rest[start + end + 1..];
+    }
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
+/// A serde deserialization method to deserialize a string expanding the environment variables
+/// in it, see [`expand_env_vars`].
+pub(crate) fn deserialize_env_string<'de, D>(deserializer: D) -> anyhow::Result<String, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s = String::deserialize(deserializer)?;
+    expand_env_vars(&s).map_err(D::Error::custom)
+}
+
+/// A serde deserialization method to deserialize a url expanding the environment variables in
+/// it, see [`expand_env_vars`].
+pub(crate) fn deserialize_env_url<'de, D>(deserializer: D) -> anyhow::Result<Url, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s = deserialize_env_string(deserializer)?;
+    Url::parse(&s).map_err(D::Error::custom)
+}
+
+/// A serde deserialization method to deserialize an address from a string, or from an integer
+/// read as an ID address, e.g. `64` for `t064`.
 pub(crate) fn deserialize_address_from_str<'de, D>(
     deserializer: D,
 ) -> anyhow::Result<Address, D::Error>
@@ -40,7 +123,7 @@ where
         type Value = Address;
 
         fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
-            formatter.write_str("an string")
+            formatter.write_str("an string or an actor id")
         }
 
         fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
@@ -49,8 +132,25 @@ where
         {
             Address::from_str(v).map_err(E::custom)
         }
+
+        fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+        where
+            E: Error,
+        {
+            Ok(Address::new_id(v))
+        }
+
+        // toml is interpreting numbers as i64
+        fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E>
+        where
+            E: Error,
+        {
+            u64::try_from(v)
+                .map(Address::new_id)
+                .map_err(|_| E::custom(format!("{v:} is not an actor id")))
+        }
     }
-    deserializer.deserialize_str(Visitor)
+    deserializer.deserialize_any(Visitor)
 }
 
 /// A serde deserialization method to deserialize a subnet path string into a [`SubnetID`].
@@ -90,3 +190,31 @@ where
         .collect();
     addrs.map_err(D::Error::custom)
 }
+
+/// A serde deserialization method to deserialize a CID string into a [`Cid`].
+pub(crate) fn deserialize_optional_cid<'de, D>(
+    deserializer: D,
+) -> anyhow::Result<Option<Cid>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s = String::deserialize(deserializer)?;
+    Cid::from_str(&s).map(Some).map_err(D::Error::custom)
+}
+
+/// A serde deserialization method to deserialize a decimal string of attoFIL into a
+/// [`TokenAmount`].
+pub(crate) fn deserialize_optional_token_amount<'de, D>(
+    deserializer: D,
+) -> anyhow::Result<Option<TokenAmount>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s = String::deserialize(deserializer)?;
+    let atto = BigInt::from_str(&s)
+        .map_err(|_| D::Error::custom(format!("{s:?} is not a decimal amount of attoFIL")))?;
+    if atto < BigInt::from(0) {
+        return Err(D::Error::custom(format!("{s:?} is a negative amount")));
+    }
+    Ok(Some(TokenAmount::from_atto(atto)))
+}
@@ -0,0 +1,177 @@
+// Copyright 2022-2023 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Differences between two configs, to preview the effect of a reload.
+
+use std::fmt::Debug;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::{Config, Subnet};
+
+/// What is shown instead of the value of secret fields.
+const REDACTED: &str = "<redacted>";
+
+/// The changes from a config to another.
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ConfigDiff {
+    pub added_subnets: Vec<String>,
+    pub removed_subnets: Vec<String>,
+    /// The fields that changed, in the subnets present in both configs and the top-level ones.
+    pub changed: Vec<FieldChange>,
+}
+
+/// A field whose value changed, formatted for display.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct FieldChange {
+    /// The path of the field, e.g. `subnets./root.jsonrpc_api_http`
+    pub field: String,
+    pub old: String,
+    pub new: String,
+}
+
+impl ConfigDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added_subnets.is_empty() && self.removed_subnets.is_empty() && self.changed.is_empty()
+    }
+
+    fn compare<T: Debug + PartialEq>(&mut self, field: String, old: &T, new: &T) {
+        if old != new {
+            self.changed.push(FieldChange {
+                field,
+                old: format!("{old:?}"),
+                new: format!("{new:?}"),
+            });
+        }
+    }
+}
+
+/// Returns the changes from `old` to `new`. The auth tokens of the subnets are not disclosed,
+/// only whether they changed.
+pub fn diff(old: &Config, new: &Config) -> ConfigDiff {
+    let mut diff = ConfigDiff::default();
+
+    diff.compare(
+        String::from("server.json_rpc_address"),
+        &old.server.json_rpc_address,
+        &new.server.json_rpc_address,
+    );
+    diff.compare(
+        String::from("server.metrics"),
+        &old.server.metrics,
+        &new.server.metrics,
+    );
+    diff.compare(
+        String::from("server.max_concurrent_requests"),
+        &old.server.max_concurrent_requests,
+        &new.server.max_concurrent_requests,
+    );
+    diff.compare(
+        String::from("server.max_queued_requests"),
+        &old.server.max_queued_requests,
+        &new.server.max_queued_requests,
+    );
+    if old.server.auth_token != new.server.auth_token {
+        diff.changed.push(FieldChange {
+            field: String::from("server.auth_token"),
+            old: String::from(REDACTED),
+            new: String::from(REDACTED),
+        });
+    }
+
+    for (id, new_subnet) in new.subnets.iter() {
+        match old.subnets.get(id) {
+            None => diff.added_subnets.push(id.to_string()),
+            Some(old_subnet) => diff_subnet(&mut diff, old_subnet, new_subnet),
+        }
+    }
+    for id in old
+        .subnets
+        .keys()
+        .filter(|id| !new.subnets.contains_key(id))
+    {
+        diff.removed_subnets.push(id.to_string());
+    }
+
+    // the subnets are kept in a map, sort them so the diff is stable
+    diff.added_subnets.sort();
+    diff.removed_subnets.sort();
+    diff.changed.sort_by(|a, b| a.field.cmp(&b.field));
+    diff
+}
+
+fn diff_subnet(diff: &mut ConfigDiff, old: &Subnet, new: &Subnet) {
+    let field = |name: &str| format!("subnets.{:}.{name:}", old.id);
+
+    diff.compare(field("gateway_addr"), &old.gateway_addr, &new.gateway_addr);
+    diff.compare(field("network_name"), &old.network_name, &new.network_name);
+    diff.compare(
+        field("jsonrpc_api_http"),
+        &old.jsonrpc_api_http,
+        &new.jsonrpc_api_http,
+    );
+    diff.compare(
+        field("jsonrpc_api_http_fallbacks"),
+        &old.jsonrpc_api_http_fallbacks,
+        &new.jsonrpc_api_http_fallbacks,
+    );
+    diff.compare(
+        field("jsonrpc_api_ws"),
+        &old.jsonrpc_api_ws,
+        &new.jsonrpc_api_ws,
+    );
+    diff.compare(
+        field("http_pool_idle_timeout_secs"),
+        &old.http_pool_idle_timeout_secs,
+        &new.http_pool_idle_timeout_secs,
+    );
+    if old.auth_token != new.auth_token {
+        diff.changed.push(FieldChange {
+            field: field("auth_token"),
+            old: String::from(REDACTED),
+            new: String::from(REDACTED),
+        });
+    }
+    // the proxy url may hold credentials
+    if old.proxy_url != new.proxy_url {
+        diff.changed.push(FieldChange {
+            field: field("proxy_url"),
+            old: String::from(REDACTED),
+            new: String::from(REDACTED),
+        });
+    }
+    // the headers may hold api keys
+    if old.extra_headers != new.extra_headers {
+        diff.changed.push(FieldChange {
+            field: field("extra_headers"),
+            old: String::from(REDACTED),
+            new: String::from(REDACTED),
+        });
+    }
+    diff.compare(
+        field("auth_token_env"),
+        &old.auth_token_env,
+        &new.auth_token_env,
+    );
+    diff.compare(field("accounts"), &old.accounts, &new.accounts);
+    diff.compare(
+        field("keystore_path"),
+        &old.keystore_path,
+        &new.keystore_path,
+    );
+    diff.compare(
+        field("stale_threshold"),
+        &old.stale_threshold,
+        &new.stale_threshold,
+    );
+    diff.compare(
+        field("state_wait_confidence"),
+        &old.state_wait_confidence,
+        &new.state_wait_confidence,
+    );
+    diff.compare(field("gas"), &old.gas, &new.gas);
+    diff.compare(
+        field("subnet_actor_code_cid"),
+        &old.subnet_actor_code_cid,
+        &new.subnet_actor_code_cid,
+    );
+}
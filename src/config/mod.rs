@@ -6,6 +6,7 @@
 //! [`Config`] struct.
 
 mod deserialize;
+pub mod diff;
 mod reload;
 mod server;
 pub mod subnet;
@@ -17,30 +18,95 @@ use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
 
-use anyhow::Result;
-use deserialize::deserialize_subnets_from_vec;
+use anyhow::{anyhow, Result};
+use deserialize::{deserialize_subnets_from_vec, expand_env_vars};
+use fvm_shared::address::Address;
 use ipc_sdk::subnet_id::SubnetID;
 pub use reload::ReloadableConfig;
+use reqwest::header::{HeaderName, HeaderValue};
 use serde::Deserialize;
 pub use server::JSON_RPC_ENDPOINT;
 pub use server::{json_rpc_methods, Server};
-pub use subnet::Subnet;
+pub use subnet::{GasConfig, Network, Subnet};
+
+use crate::jsonrpc::proxy;
 
 pub const JSON_RPC_VERSION: &str = "2.0";
 
 /// Default config template
 pub const DEFAULT_CONFIG_TEMPLATE: &str = r#"
+# Configuration of the IPC agent json rpc server.
 [server]
+# The address the json rpc server listens on.
 json_rpc_address = "127.0.0.1:3030"
+# Serves the prometheus metrics of the json rpc methods at /metrics, optional.
+# metrics = true
+# The token clients must send as "Authorization: Bearer <token>", optional. The cli sends the
+# one passed with --auth-token or set in IPC_AGENT_AUTH_TOKEN. Applied on reload.
+# auth_token = "YOUR SERVER TOKEN"
+# The requests handled concurrently, and those waiting for them to finish, beyond which
+# requests are rejected, optional.
+# max_concurrent_requests = 32
+# max_queued_requests = 128
+
+# Defaults shared by all the subnets with the same `network_name`, uncomment to use.
+# [network.root]
+# gateway_addr = "t064"
 
+# One [[subnets]] block per subnet the agent interacts with.
 [[subnets]]
+# The id of the subnet, i.e. its path from the root network.
 id = "/root"
+# The address of the gateway actor in the subnet. Can be omitted if set for the network.
 gateway_addr = "t064"
+# The network name reported by the node of the subnet.
 network_name = "root"
+# The http json rpc endpoint of the lotus node of the subnet.
 jsonrpc_api_http = "http://127.0.0.1:1234/rpc/v1"
+# The http json rpc endpoints requests fail over to when the one above is unreachable, or when
+# its proxy answers with a 502, 503 or 504 status, optional.
+# jsonrpc_api_http_fallbacks = ["http://127.0.0.1:1235/rpc/v1"]
+# The websocket json rpc endpoint of the lotus node, optional.
 jsonrpc_api_ws = "wss://example.org/rpc/v0"
+# The seconds an idle connection to the node is kept open for reuse by later requests,
+# optional. Defaults to 90.
+# http_pool_idle_timeout_secs = 90
+# The http, https or socks5 proxy to reach the node through, optional. Hosts in the NO_PROXY
+# environment variable are reached directly.
+# proxy_url = "http://proxy.example.org:3128"
+# The token to authenticate to the lotus node, optional. Environment variables can be referred
+# to as "${NAME}" here, in `network_name` and in `jsonrpc_api_http`.
 auth_token = "YOUR TOKEN"
+# The environment variable to read the token from instead, optional.
+# auth_token_env = "LOTUS_ROOT_TOKEN"
+# The headers to add to every request to the node, e.g. for proxies in front of hosted nodes,
+# optional.
+# extra_headers = { "X-Api-Key" = "YOUR KEY" }
+# The accounts of the node used by the agent. Checkpoints are submitted with those that are
+# validators of the subnet, or with the validator keys found in the node wallet if none is.
 accounts = ["t01"]
+# The directory of the keys to sign with locally instead of with the node wallet, optional.
+# Each key is stored in a file named after its address, as printed by `lotus wallet export`.
+# keystore_path = "/home/user/.ipc-agent/keystore"
+# The number of epochs the node can lag behind the network before its data is reported as
+# stale, optional.
+# stale_threshold = 10
+# The number of epochs a message must be buried under before it is considered executed,
+# optional. Networks with reorgs such as mainnet need more than the default of 2.
+# state_wait_confidence = 2
+# The code CID of the subnet actor, for nodes that do not expose the code CIDs of the actors,
+# optional.
+# subnet_actor_code_cid = "bafk..."
+# The gas parameters of the messages sent to the subnet that don't set them, estimated by the
+# node if not set, along with the most a message can pay in fees and a factor to multiply the gas
+# limits estimated by the node by, all optional. Fee caps and premiums are decimal strings of
+# attoFIL per unit of gas.
+# [subnets.gas]
+# gas_fee_cap = "100000"
+# gas_premium = "100000"
+# gas_limit = 10000000
+# max_fee = "1000000000000000000"
+# estimation_multiplier = 1.25
 
 [[subnets]]
 id = "/root/t01"
@@ -53,9 +119,12 @@ accounts = ["t01"]
 
 /// The top-level struct representing the config. Calls to [`Config::from_file`] deserialize into
 /// this struct.
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, PartialEq)]
 pub struct Config {
     pub server: Server,
+    /// The defaults of the subnets, keyed by network name.
+    #[serde(default)]
+    pub network: HashMap<String, Network>,
     #[serde(deserialize_with = "deserialize_subnets_from_vec", default)]
     pub subnets: HashMap<SubnetID, Subnet>,
 }
@@ -63,20 +132,194 @@ pub struct Config {
 impl Config {
     /// Reads a TOML configuration in the `s` string and returns a [`Config`] struct.
     pub fn from_toml_str(s: &str) -> Result<Self> {
-        let config = toml::from_str(s)?;
-        Ok(config)
+        let value: toml::Value = toml::from_str(s)?;
+        Config::from_toml_value(value)
     }
 
-    /// Reads a TOML configuration file specified in the `path` and returns a [`Config`] struct.
+    /// Reads a JSON configuration in the `s` string and returns a [`Config`] struct. The JSON
+    /// document has the same structure as the TOML one, `null` fields are treated as absent.
+    pub fn from_json_str(s: &str) -> Result<Self> {
+        let mut value: serde_json::Value = serde_json::from_str(s)?;
+        remove_nulls(&mut value);
+        // going through toml shares the network defaults with the TOML configs, and reads
+        // JSON numbers as the same integers TOML numbers are
+        let value = toml::Value::try_from(value)?;
+        Config::from_toml_value(value)
+    }
+
+    /// Reads a configuration file specified in the `path` and returns a [`Config`] struct. Files
+    /// with a `.json` extension are read as JSON, any other as TOML.
     pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
-        let contents = fs::read_to_string(path)?;
-        let config: Config = Config::from_toml_str(contents.as_str())?;
-        Ok(config)
+        let contents = fs::read_to_string(&path)?;
+        Config::from_str_for_path(&contents, path.as_ref())
     }
 
-    /// Reads a TOML configuration file specified in the `path` and returns a [`Config`] struct.
+    /// Same as [`Config::from_file`], reading the file asynchronously.
     pub async fn from_file_async(path: impl AsRef<Path>) -> Result<Self> {
-        let contents = tokio::fs::read_to_string(path).await?;
-        Config::from_toml_str(contents.as_str())
+        let contents = tokio::fs::read_to_string(&path).await?;
+        Config::from_str_for_path(&contents, path.as_ref())
+    }
+
+    fn from_str_for_path(s: &str, path: &Path) -> Result<Self> {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("json") => Config::from_json_str(s),
+            _ => Config::from_toml_str(s),
+        }
+    }
+
+    fn from_toml_value(mut value: toml::Value) -> Result<Self> {
+        apply_network_defaults(&mut value)?;
+        let config: Config = value.try_into()?;
+        config.validate()?;
+        Ok(config)
     }
+
+    /// Checks the subnets of the config can be connected to, failing with the problems found
+    /// in all of them, each naming its subnet.
+    pub fn validate(&self) -> Result<()> {
+        let mut subnets = self.subnets.values().collect::<Vec<_>>();
+        subnets.sort_by_key(|s| s.id.to_string());
+
+        let roots = subnets.iter().map(|s| root_of(&s.id)).collect::<Vec<_>>();
+        let mut errors = vec![];
+        if self.server.max_concurrent_requests == Some(0) {
+            errors.push(String::from(
+                "server has a max_concurrent_requests of 0, no request would be handled",
+            ));
+        }
+        for (subnet, root) in subnets.iter().zip(&roots) {
+            let id = &subnet.id;
+            if subnet.network_name.trim().is_empty() {
+                errors.push(format!("subnet {id:} has an empty network_name"));
+            }
+            if subnet.gateway_addr == Address::new_id(0) {
+                errors.push(format!("subnet {id:} has the zero address as gateway_addr"));
+            }
+            for url in
+                std::iter::once(&subnet.jsonrpc_api_http).chain(&subnet.jsonrpc_api_http_fallbacks)
+            {
+                if !matches!(url.scheme(), "http" | "https") {
+                    errors.push(format!(
+                        "subnet {id:} has a jsonrpc_api_http {url:} that is not http or https"
+                    ));
+                }
+            }
+            if let Some(url) = &subnet.jsonrpc_api_ws {
+                if !matches!(url.scheme(), "ws" | "wss") {
+                    errors.push(format!(
+                        "subnet {id:} has a jsonrpc_api_ws {url:} that is not ws or wss"
+                    ));
+                }
+            }
+            if let Some(url) = &subnet.proxy_url {
+                if let Err(e) = proxy(url, None) {
+                    errors.push(format!("subnet {id:} has an invalid proxy_url: {e:}"));
+                }
+            }
+            for (name, value) in subnet.extra_headers.iter() {
+                if HeaderName::from_bytes(name.as_bytes()).is_err()
+                    || HeaderValue::from_str(value).is_err()
+                {
+                    errors.push(format!("subnet {id:} has an invalid extra header {name:?}"));
+                }
+            }
+            if let Some(multiplier) = subnet.gas.estimation_multiplier {
+                if !multiplier.is_finite() || multiplier < 1.0 {
+                    errors.push(format!(
+                        "subnet {id:} has a gas.estimation_multiplier {multiplier:} below 1"
+                    ));
+                }
+            }
+            if let Some(path) = &subnet.keystore_path {
+                if !path.is_dir() {
+                    errors.push(format!(
+                        "subnet {id:} has a keystore_path {path:?} that is not a directory"
+                    ));
+                }
+            }
+            // cross-messages and checkpoints only flow between subnets of the same hierarchy
+            if root != &roots[0] {
+                errors.push(format!(
+                    "subnet {id:} is under root {root:}, while subnet {:} is under root {:}",
+                    subnets[0].id, roots[0]
+                ));
+            }
+            let parent_configured = id
+                .parent()
+                .map(|p| self.subnets.contains_key(&p))
+                .unwrap_or_default();
+            if parent_configured && subnet.accounts.is_empty() {
+                log::warn!("subnet {id:} has no accounts, its checkpoints will not be submitted");
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(anyhow!("invalid config: {}", errors.join("; ")))
+        }
+    }
+}
+
+/// Removes the `null` fields of the objects in `value`, which TOML has no representation for.
+fn remove_nulls(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(map) => {
+            map.retain(|_, v| !v.is_null());
+            map.values_mut().for_each(remove_nulls);
+        }
+        serde_json::Value::Array(values) => values.iter_mut().for_each(remove_nulls),
+        _ => {}
+    }
+}
+
+/// Returns the root of the hierarchy `id` is in, e.g. `/root` for `/root/t01002`.
+fn root_of(id: &SubnetID) -> String {
+    let id = id.to_string();
+    let root = id
+        .trim_start_matches('/')
+        .split('/')
+        .next()
+        .unwrap_or_default();
+    format!("/{root}")
+}
+
+/// Sets the `gateway_addr` of the subnets that do not declare one to the default of their
+/// network. Fails if a subnet resolves to no gateway at all.
+fn apply_network_defaults(config: &mut toml::Value) -> Result<()> {
+    let networks = config
+        .get("network")
+        .and_then(|n| n.as_table())
+        .cloned()
+        .unwrap_or_default();
+
+    let subnets = match config.get_mut("subnets").and_then(|s| s.as_array_mut()) {
+        Some(subnets) => subnets,
+        None => return Ok(()),
+    };
+
+    for subnet in subnets.iter_mut().filter_map(|s| s.as_table_mut()) {
+        if subnet.contains_key("gateway_addr") {
+            continue;
+        }
+        // a missing network name is reported when deserializing the subnet
+        let network_name = match subnet.get("network_name").and_then(|n| n.as_str()) {
+            Some(name) => expand_env_vars(name)?,
+            None => continue,
+        };
+
+        let gateway_addr = networks
+            .get(&network_name)
+            .and_then(|n| n.get("gateway_addr"))
+            .cloned()
+            .ok_or_else(|| {
+                let id = subnet.get("id").and_then(|i| i.as_str()).unwrap_or_default();
+                anyhow!(
+                    "subnet {id} has no gateway_addr and network {network_name} has no default gateway_addr"
+                )
+            })?;
+        subnet.insert("gateway_addr".to_string(), gateway_addr);
+    }
+
+    Ok(())
 }
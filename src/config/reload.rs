@@ -2,16 +2,18 @@
 // SPDX-License-Identifier: MIT
 //! Reloadable config
 
+use crate::config::diff::{diff, ConfigDiff};
 use crate::config::Config;
 use anyhow::Result;
 use std::ops::DerefMut;
 use std::sync::{Arc, RwLock};
-use tokio::sync::broadcast;
+use tokio::sync::{broadcast, watch};
 
 /// Reloadable configuration exposes the latest config through `get_config` method. Use this you
 /// will always the latest config. At the same time, it also exposes `new_subscriber`. If caller
 /// needs to be notified when config has updated, just make a new subscription. Once received a
-/// notification, read the config again to obtain the latest config.
+/// notification, read the config again to obtain the latest config. Callers only interested in
+/// actual changes of the config can `watch` it instead.
 pub struct ReloadableConfig {
     path: RwLock<Arc<String>>,
     config: RwLock<Arc<Config>>,
@@ -19,6 +21,8 @@ pub struct ReloadableConfig {
     /// We keep at least one channel active, so that we dont encounter a `SendError`. We might need to use it later.
     #[allow(dead_code)]
     broadcast_rx: broadcast::Receiver<()>,
+    /// Holds the latest config, updated only by reloads that change it.
+    watch_tx: watch::Sender<Arc<Config>>,
 }
 
 impl ReloadableConfig {
@@ -26,13 +30,15 @@ impl ReloadableConfig {
         // we dont really need a big channel, the frequency should be very very low
         let (broadcast_tx, broadcast_rx) = broadcast::channel(8);
 
-        let config = RwLock::new(Arc::new(Config::from_file(path.clone())?));
+        let config = Arc::new(Config::from_file(path.clone())?);
+        let (watch_tx, _) = watch::channel(config.clone());
 
         Ok(Self {
             path: RwLock::new(Arc::new(path)),
-            config,
+            config: RwLock::new(config),
             broadcast_tx,
             broadcast_rx,
+            watch_tx,
         })
     }
 
@@ -48,22 +54,49 @@ impl ReloadableConfig {
         *p = Arc::new(path);
     }
 
-    /// Triggers a reload of the config.
-    pub async fn reload(&self) -> Result<()> {
+    /// Triggers a reload of the config. Returns the changes applied.
+    pub async fn reload(&self) -> Result<ConfigDiff> {
         let path = self.path.read().unwrap().to_string();
         let new_config = Config::from_file_async(path).await?;
-        log::info!("new config loaded: {new_config:?}");
+        log::info!(
+            "new config loaded with {} subnets",
+            new_config.subnets.len()
+        );
 
-        let mut config = self.config.write().unwrap();
-        let r = config.deref_mut();
-        *r = Arc::new(new_config);
+        let new_config = Arc::new(new_config);
+        let changes = {
+            let mut config = self.config.write().unwrap();
+            let changes = diff(&config, &new_config);
+            let r = config.deref_mut();
+            *r = new_config.clone();
+            changes
+        };
 
         self.broadcast_tx.send(()).unwrap_or_default();
+        if !changes.is_empty() {
+            self.watch_tx.send_replace(new_config);
+        }
 
-        Ok(())
+        Ok(changes)
+    }
+
+    /// Loads and validates the config at `path`, or at the path of future reloads if not set,
+    /// and returns its changes from the current config without applying them.
+    pub async fn preview(&self, path: Option<String>) -> Result<ConfigDiff> {
+        let path = match path {
+            Some(path) => path,
+            None => self.path.read().unwrap().to_string(),
+        };
+        let new_config = Config::from_file_async(path).await?;
+        Ok(diff(&self.get_config(), &new_config))
     }
 
     pub fn new_subscriber(&self) -> broadcast::Receiver<()> {
         self.broadcast_tx.subscribe()
     }
+
+    /// Returns a receiver holding the latest config, notified only when a reload changes it.
+    pub fn watch(&self) -> watch::Receiver<Arc<Config>> {
+        self.watch_tx.subscribe()
+    }
 }
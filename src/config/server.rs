@@ -5,9 +5,23 @@ use std::net::SocketAddr;
 
 pub const JSON_RPC_ENDPOINT: &str = "json_rpc";
 
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Deserialize, Clone, Debug, PartialEq, Eq)]
 pub struct Server {
     pub json_rpc_address: SocketAddr,
+    /// Serves the prometheus metrics of the json rpc methods at `/metrics`.
+    #[serde(default)]
+    pub metrics: bool,
+    /// The token requests must present as `Authorization: Bearer <token>`, except for the
+    /// `/health` endpoint. Requests are not authenticated if not set. A new token takes effect
+    /// when the config is reloaded.
+    #[serde(default)]
+    pub auth_token: Option<String>,
+    /// The requests handled concurrently, 32 if not set. Health checks and config reloads are
+    /// not counted, so that they answer even when the server is busy.
+    pub max_concurrent_requests: Option<usize>,
+    /// The requests waiting for one of the concurrent ones to finish, 128 if not set. Requests
+    /// beyond them are rejected until the server catches up.
+    pub max_queued_requests: Option<usize>,
 }
 
 pub mod json_rpc_methods {
@@ -22,10 +36,22 @@ pub mod json_rpc_methods {
     pub const LIST_CHILD_SUBNETS: &str = "ipc_listChildSubnets";
     pub const RELOAD_CONFIG: &str = "ipc_reloadConfig";
     pub const QUERY_VALIDATOR_SET: &str = "ipc_queryValidatorSet";
+    pub const QUERY_VALIDATOR_CHANGES: &str = "ipc_queryValidatorChanges";
+    pub const QUERY_ACTIVATION_STATUS: &str = "ipc_queryActivationStatus";
     pub const SET_VALIDATOR_NET_ADDR: &str = "ipc_setValidatorNetAddr";
     pub const SEND_VALUE: &str = "ipc_sendValue";
     pub const WALLET_NEW: &str = "ipc_walletNew";
     pub const WALLET_LIST: &str = "ipc_walletList";
+    pub const WALLET_BALANCE: &str = "ipc_walletBalance";
+    pub const WALLET_IMPORT: &str = "ipc_walletImport";
     pub const LIST_BOTTOMUP_CHECKPOINTS: &str = "ipc_listBottomUpCheckpoints";
+    pub const EXPORT_BOTTOMUP_CHECKPOINT: &str = "ipc_exportBottomUpCheckpoint";
+    pub const VERIFY_BOTTOMUP_CHECKPOINT_CHAIN: &str = "ipc_verifyBottomUpCheckpointChain";
     pub const LAST_TOPDOWN_EXECUTED: &str = "ipc_lastTopDownCheckpointExecuted";
+    pub const CROSS_MSG_AUDIT: &str = "ipc_crossMsgAudit";
+    pub const CROSS_MSG_ROUTE: &str = "ipc_crossMsgRoute";
+    pub const CROSS_MSG_BACKLOG: &str = "ipc_crossMsgBacklog";
+    pub const CROSS_MSG_LIST: &str = "ipc_crossMsgList";
+    pub const GET_SUBNET_INFO: &str = "ipc_getSubnetInfo";
+    pub const HEALTH: &str = "ipc_health";
 }
@@ -1,26 +1,105 @@
 // Copyright 2022-2023 Protocol Labs
 // SPDX-License-Identifier: MIT
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use cid::Cid;
 use fvm_shared::address::Address;
+use fvm_shared::clock::ChainEpoch;
+use fvm_shared::econ::TokenAmount;
 use ipc_sdk::subnet_id::SubnetID;
 use serde::Deserialize;
 use url::Url;
 
 use crate::config::deserialize::{
-    deserialize_accounts, deserialize_address_from_str, deserialize_subnet_id,
+    deserialize_accounts, deserialize_address_from_str, deserialize_env_string,
+    deserialize_env_url, deserialize_optional_cid, deserialize_optional_token_amount,
+    deserialize_subnet_id,
 };
 
-/// Represents a subnet declaration in the config.
-#[derive(Deserialize, Clone, Debug)]
+/// Represents the defaults shared by the subnets of the same network, declared in the config
+/// under `[network.<network_name>]`.
+#[derive(Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Network {
+    #[serde(deserialize_with = "deserialize_address_from_str")]
+    pub gateway_addr: Address,
+}
+
+/// Represents a subnet declaration in the config. If `gateway_addr` is not set, the one of the
+/// [`Network`] with the subnet's `network_name` is used.
+#[derive(Deserialize, Clone, Debug, PartialEq)]
 pub struct Subnet {
     #[serde(deserialize_with = "deserialize_subnet_id")]
     pub id: SubnetID,
     #[serde(deserialize_with = "deserialize_address_from_str")]
     // toml is interpreting number as i64
     pub gateway_addr: Address,
+    /// The network name, and the http endpoint and auth token below, can refer to environment
+    /// variables as `${NAME}`, which are expanded when the config is read.
+    #[serde(deserialize_with = "deserialize_env_string")]
     pub network_name: String,
+    #[serde(deserialize_with = "deserialize_env_url")]
     pub jsonrpc_api_http: Url,
+    /// The endpoints requests fail over to when `jsonrpc_api_http` cannot be reached, or when
+    /// its proxy answers that it is unavailable.
+    #[serde(default)]
+    pub jsonrpc_api_http_fallbacks: Vec<Url>,
     pub jsonrpc_api_ws: Option<Url>,
+    /// The seconds an idle connection to the node is kept open for reuse, 90 if not set.
+    pub http_pool_idle_timeout_secs: Option<u64>,
+    /// The http, https or socks5 proxy the requests to the node are sent through, unless its
+    /// host is in the `NO_PROXY` environment variable.
+    pub proxy_url: Option<Url>,
+    #[serde(default)]
     pub auth_token: Option<String>,
+    /// The headers added to every request to the node, e.g. the api keys or tenant ids required
+    /// by the proxies in front of hosted nodes.
+    #[serde(default)]
+    pub extra_headers: HashMap<String, String>,
+    /// The environment variable holding the auth token, read into `auth_token` when the config
+    /// is read. Cannot be set along with `auth_token`.
+    #[serde(default)]
+    pub auth_token_env: Option<String>,
     #[serde(deserialize_with = "deserialize_accounts", default)]
     pub accounts: Vec<Address>,
+    /// The directory of the keys the agent signs with locally, one file per address, instead
+    /// of the wallet of the node. Addresses without a key in it are signed by the node.
+    #[serde(default)]
+    pub keystore_path: Option<PathBuf>,
+    /// The number of epochs the node can lag behind the network head before the data read
+    /// from it is reported as stale. Freshness is not checked if not set.
+    pub stale_threshold: Option<ChainEpoch>,
+    /// The number of epochs a message must be buried under before a wait for it returns,
+    /// [`crate::lotus::client::STATE_WAIT_CONFIDENCE`] if not set.
+    pub state_wait_confidence: Option<u8>,
+    /// The gas parameters of the messages sent to the subnet, declared under
+    /// `[subnets.gas]`.
+    #[serde(default)]
+    pub gas: GasConfig,
+    /// The code CID of the subnet actor, used to create subnets if the node does not expose
+    /// `Filecoin.StateActorCodeCIDs`.
+    #[serde(deserialize_with = "deserialize_optional_cid", default)]
+    pub subnet_actor_code_cid: Option<Cid>,
+}
+
+/// The gas parameters of the messages sent to a subnet that don't set them. The parameters not
+/// set are estimated by the node. Amounts are decimal strings of attoFIL, so that they are not
+/// limited to the integers of toml.
+#[derive(Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct GasConfig {
+    /// The gas fee cap, in attoFIL per unit of gas.
+    #[serde(deserialize_with = "deserialize_optional_token_amount", default)]
+    pub gas_fee_cap: Option<TokenAmount>,
+    /// The gas premium, in attoFIL per unit of gas.
+    #[serde(deserialize_with = "deserialize_optional_token_amount", default)]
+    pub gas_premium: Option<TokenAmount>,
+    /// The gas limit, in units of gas.
+    pub gas_limit: Option<u64>,
+    /// The most a message can pay in fees, in attoFIL, capping the fee cap estimated by the
+    /// node.
+    #[serde(deserialize_with = "deserialize_optional_token_amount", default)]
+    pub max_fee: Option<TokenAmount>,
+    /// The factor the gas limit estimated by the node is multiplied by, at least 1, for the
+    /// messages to have room if the state changes before they are executed.
+    pub estimation_multiplier: Option<f64>,
 }
@@ -6,12 +6,14 @@ use std::str::FromStr;
 use std::sync::{Arc, Condvar, Mutex};
 
 use fvm_shared::address::Address;
+use fvm_shared::econ::TokenAmount;
 use indoc::formatdoc;
 use ipc_sdk::subnet_id::{SubnetID, ROOTNET_ID};
 use tempfile::NamedTempFile;
 use url::Url;
 
-use crate::config::{Config, ReloadableConfig};
+use crate::config::diff::diff;
+use crate::config::{Config, ReloadableConfig, DEFAULT_CONFIG_TEMPLATE};
 
 // Arguments for the config's fields
 const SERVER_JSON_RPC_ADDR: &str = "127.0.0.1:3030";
@@ -83,6 +85,87 @@ async fn reload_works() {
     );
 }
 
+#[tokio::test]
+async fn reload_notifies_watchers_of_changes() {
+    let mut file = NamedTempFile::new().unwrap();
+    file.write_all(config_str().as_bytes()).unwrap();
+    let path = file.path().to_str().unwrap().to_string();
+    let h = ReloadableConfig::new(path).unwrap();
+    let mut rx = h.watch();
+
+    // reloading the same config is not a change
+    h.reload().await.unwrap();
+    assert!(!rx.has_changed().unwrap());
+
+    let mut file = file.reopen().unwrap();
+    file.set_len(0).unwrap();
+    file.write_all(config_str_diff_addr().as_bytes()).unwrap();
+    h.reload().await.unwrap();
+
+    rx.changed().await.unwrap();
+    assert_eq!(
+        rx.borrow_and_update().server.json_rpc_address,
+        SocketAddr::from_str("127.0.0.1:3031").unwrap()
+    );
+    assert!(!rx.has_changed().unwrap());
+}
+
+#[tokio::test]
+async fn reload_dry_run() {
+    let mut file = NamedTempFile::new().unwrap();
+    file.write_all(config_str().as_bytes()).unwrap();
+    let path = file.path().to_str().unwrap().to_string();
+    let h = ReloadableConfig::new(path).unwrap();
+
+    // the root subnet changes its endpoint and token, and the child subnet is removed
+    let new_config_str = formatdoc!(
+        r#"
+            [server]
+            json_rpc_address = "127.0.0.1:3031"
+
+            [[subnets]]
+            id = "{ROOT_ID}"
+            gateway_addr = "{GATEWAY_ADDR}"
+            network_name = "root"
+            jsonrpc_api_http = "https://example.org/rpc/v1"
+            jsonrpc_api_ws = "{JSONRPC_API_WS}"
+            auth_token = "NEW_ROOT_AUTH_TOKEN"
+        "#
+    );
+    let mut new_file = NamedTempFile::new().unwrap();
+    new_file.write_all(new_config_str.as_bytes()).unwrap();
+    let new_path = new_file.path().to_str().unwrap().to_string();
+
+    let changes = h.preview(Some(new_path)).await.unwrap();
+    assert!(changes.added_subnets.is_empty());
+    assert_eq!(changes.removed_subnets, vec![CHILD_ID.to_string()]);
+
+    let fields = changes
+        .changed
+        .iter()
+        .map(|c| c.field.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        fields,
+        vec![
+            "server.json_rpc_address",
+            "subnets./root.auth_token",
+            "subnets./root.jsonrpc_api_http",
+        ]
+    );
+    // secrets are not disclosed
+    assert!(!changes.changed[1].new.contains("NEW_ROOT_AUTH_TOKEN"));
+
+    // nothing is applied
+    let current = h.get_config();
+    assert_eq!(
+        current.server.json_rpc_address,
+        SocketAddr::from_str(SERVER_JSON_RPC_ADDR).unwrap()
+    );
+    let original = Config::from_toml_str(&config_str()).unwrap();
+    assert!(diff(&current, &original).is_empty());
+}
+
 #[test]
 fn check_server_config() {
     let config = read_config().server;
@@ -91,6 +174,38 @@ fn check_server_config() {
         SocketAddr::from_str(SERVER_JSON_RPC_ADDR).unwrap(),
         "invalid server rpc address"
     );
+    assert!(!config.metrics, "metrics are enabled by default");
+}
+
+#[test]
+fn check_server_request_limits() {
+    let config_with_limits = |limits: &str| {
+        formatdoc!(
+            r#"
+                [server]
+                json_rpc_address = "{SERVER_JSON_RPC_ADDR}"
+                {limits}
+
+                [[subnets]]
+                id = "{ROOT_ID}"
+                gateway_addr = "{GATEWAY_ADDR}"
+                network_name = "root"
+                jsonrpc_api_http = "{JSONRPC_API_HTTP}"
+            "#
+        )
+    };
+
+    let config = Config::from_toml_str(&config_with_limits(
+        "max_concurrent_requests = 4\nmax_queued_requests = 0",
+    ))
+    .unwrap();
+    assert_eq!(config.server.max_concurrent_requests, Some(4));
+    assert_eq!(config.server.max_queued_requests, Some(0));
+
+    let err = Config::from_toml_str(&config_with_limits("max_concurrent_requests = 0"))
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("max_concurrent_requests of 0"), "{err}");
 }
 
 #[test]
@@ -110,6 +225,7 @@ fn check_subnets_config() {
         &Url::from_str(JSONRPC_API_WS).unwrap()
     );
     assert_eq!(root.auth_token.as_ref().unwrap(), ROOT_AUTH_TOKEN);
+    assert_eq!(root.state_wait_confidence, None);
 
     let child_id = SubnetID::from_str(CHILD_ID).unwrap();
     let child = &config[&child_id];
@@ -128,6 +244,394 @@ fn check_subnets_config() {
             Address::from_str(ACCOUNT_ADDRESS).unwrap()
         ],
     );
+    assert_eq!(child.state_wait_confidence, Some(10));
+    assert_eq!(child.gas.gas_premium, Some(TokenAmount::from_atto(100000)));
+    assert_eq!(child.gas.gas_fee_cap, None);
+}
+
+#[test]
+fn check_invalid_gas_amounts() {
+    let gas_config = |fee_cap: &str| {
+        formatdoc!(
+            r#"
+                [server]
+                json_rpc_address = "{SERVER_JSON_RPC_ADDR}"
+
+                [[subnets]]
+                id = "{ROOT_ID}"
+                gateway_addr = "{GATEWAY_ADDR}"
+                network_name = "root"
+                jsonrpc_api_http = "{JSONRPC_API_HTTP}"
+
+                [subnets.gas]
+                gas_fee_cap = "{fee_cap}"
+            "#
+        )
+    };
+    assert!(Config::from_toml_str(&gas_config("100000")).is_ok());
+    assert!(Config::from_toml_str(&gas_config("0.1")).is_err());
+    assert!(Config::from_toml_str(&gas_config("-1")).is_err());
+}
+
+#[test]
+fn check_default_config_template() {
+    let config = Config::from_toml_str(DEFAULT_CONFIG_TEMPLATE).unwrap();
+    assert_eq!(config.subnets.len(), 2);
+    assert!(config.subnets.contains_key(&ROOTNET_ID));
+}
+
+#[test]
+fn check_network_gateway_defaults() {
+    let other_gateway = "t065";
+    let config_str = formatdoc!(
+        r#"
+            [server]
+            json_rpc_address = "{SERVER_JSON_RPC_ADDR}"
+
+            [network.root]
+            gateway_addr = "{GATEWAY_ADDR}"
+
+            [[subnets]]
+            id = "{ROOT_ID}"
+            network_name = "root"
+            jsonrpc_api_http = "{JSONRPC_API_HTTP}"
+
+            [[subnets]]
+            id = "{CHILD_ID}"
+            network_name = "root"
+            gateway_addr = "{other_gateway}"
+            jsonrpc_api_http = "{JSONRPC_API_HTTP}"
+        "#
+    );
+    let config = Config::from_toml_str(config_str.as_str()).unwrap();
+
+    let root = &config.subnets[&ROOTNET_ID];
+    assert_eq!(root.gateway_addr, Address::from_str(GATEWAY_ADDR).unwrap());
+
+    // the gateway of the subnet overrides the network default
+    let child = &config.subnets[&SubnetID::from_str(CHILD_ID).unwrap()];
+    assert_eq!(
+        child.gateway_addr,
+        Address::from_str(other_gateway).unwrap()
+    );
+
+    let config_str = formatdoc!(
+        r#"
+            [server]
+            json_rpc_address = "{SERVER_JSON_RPC_ADDR}"
+
+            [network.root]
+            gateway_addr = "{GATEWAY_ADDR}"
+
+            [[subnets]]
+            id = "{CHILD_ID}"
+            network_name = "child"
+            jsonrpc_api_http = "{JSONRPC_API_HTTP}"
+        "#
+    );
+    assert!(Config::from_toml_str(config_str.as_str()).is_err());
+}
+
+#[test]
+fn check_duplicated_subnet_ids() {
+    let subnet = |id: &str, endpoint: &str| {
+        formatdoc!(
+            r#"
+                [[subnets]]
+                id = "{id}"
+                gateway_addr = "{GATEWAY_ADDR}"
+                network_name = "root"
+                jsonrpc_api_http = "{endpoint}"
+            "#
+        )
+    };
+    let config_str = [
+        format!("[server]\njson_rpc_address = \"{SERVER_JSON_RPC_ADDR}\"\n"),
+        subnet(ROOT_ID, JSONRPC_API_HTTP),
+        subnet(CHILD_ID, JSONRPC_API_HTTP),
+        subnet(ROOT_ID, "https://example.org/rpc/v1"),
+        subnet(CHILD_ID, JSONRPC_API_HTTP),
+        subnet(CHILD_ID, JSONRPC_API_HTTP),
+    ]
+    .join("\n");
+
+    let err = Config::from_toml_str(&config_str).unwrap_err().to_string();
+    assert!(err.contains(&format!("duplicated subnet ids: {ROOT_ID}, {CHILD_ID}")));
+}
+
+#[test]
+fn check_env_var_substitution() {
+    let env_config = |auth_token: &str| {
+        formatdoc!(
+            r#"
+                [server]
+                json_rpc_address = "{SERVER_JSON_RPC_ADDR}"
+
+                [[subnets]]
+                id = "{ROOT_ID}"
+                gateway_addr = "{GATEWAY_ADDR}"
+                network_name = "${{IPC_AGENT_TEST_NETWORK}}"
+                jsonrpc_api_http = "https://${{IPC_AGENT_TEST_HOST}}/rpc/v0"
+                auth_token = "{auth_token}"
+            "#
+        )
+    };
+    std::env::set_var("IPC_AGENT_TEST_NETWORK", "root");
+    std::env::set_var("IPC_AGENT_TEST_HOST", "example.org");
+    std::env::set_var("IPC_AGENT_TEST_TOKEN", ROOT_AUTH_TOKEN);
+
+    let config = Config::from_toml_str(&env_config("${IPC_AGENT_TEST_TOKEN}")).unwrap();
+    let root = &config.subnets[&ROOTNET_ID];
+    assert_eq!(root.network_name, "root");
+    assert_eq!(
+        root.jsonrpc_api_http,
+        Url::from_str(JSONRPC_API_HTTP).unwrap()
+    );
+    assert_eq!(root.auth_token, Some(ROOT_AUTH_TOKEN.to_string()));
+
+    // strings without placeholders are read as they are
+    let config = Config::from_toml_str(&env_config("literal $token")).unwrap();
+    assert_eq!(
+        config.subnets[&ROOTNET_ID].auth_token,
+        Some("literal $token".to_string())
+    );
+
+    let err = Config::from_toml_str(&env_config("${IPC_AGENT_TEST_UNSET}"))
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("environment variable IPC_AGENT_TEST_UNSET is not set"));
+    assert!(err.contains(ROOT_ID));
+    assert!(Config::from_toml_str(&env_config("${IPC_AGENT_TEST_TOKEN")).is_err());
+}
+
+#[test]
+fn check_auth_token_env() {
+    let env_config = |auth_token: &str| {
+        formatdoc!(
+            r#"
+                [server]
+                json_rpc_address = "{SERVER_JSON_RPC_ADDR}"
+
+                [[subnets]]
+                id = "{ROOT_ID}"
+                gateway_addr = "{GATEWAY_ADDR}"
+                network_name = "root"
+                jsonrpc_api_http = "{JSONRPC_API_HTTP}"
+                {auth_token}
+            "#
+        )
+    };
+    std::env::set_var("IPC_AGENT_TEST_AUTH_TOKEN_ENV", ROOT_AUTH_TOKEN);
+
+    let config = Config::from_toml_str(&env_config(
+        r#"auth_token_env = "IPC_AGENT_TEST_AUTH_TOKEN_ENV""#,
+    ))
+    .unwrap();
+    let root = &config.subnets[&ROOTNET_ID];
+    assert_eq!(root.auth_token, Some(ROOT_AUTH_TOKEN.to_string()));
+
+    let err = Config::from_toml_str(&env_config(
+        r#"auth_token_env = "IPC_AGENT_TEST_UNSET_ENV""#,
+    ))
+    .unwrap_err()
+    .to_string();
+    assert!(err.contains("IPC_AGENT_TEST_UNSET_ENV"));
+    assert!(err.contains(ROOT_ID));
+
+    let both = formatdoc!(
+        r#"
+            auth_token = "{ROOT_AUTH_TOKEN}"
+            auth_token_env = "IPC_AGENT_TEST_AUTH_TOKEN_ENV"
+        "#
+    );
+    let err = Config::from_toml_str(&env_config(&both))
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("cannot set both auth_token and auth_token_env"));
+}
+
+#[test]
+fn check_config_validation() {
+    let invalid_config = |network_name: &str, gateway: &str, http: &str, ws: &str| {
+        formatdoc!(
+            r#"
+                [server]
+                json_rpc_address = "{SERVER_JSON_RPC_ADDR}"
+
+                [[subnets]]
+                id = "{ROOT_ID}"
+                gateway_addr = "{GATEWAY_ADDR}"
+                network_name = "root"
+                jsonrpc_api_http = "{JSONRPC_API_HTTP}"
+
+                [[subnets]]
+                id = "{CHILD_ID}"
+                network_name = "{network_name}"
+                gateway_addr = "{gateway}"
+                jsonrpc_api_http = "{http}"
+                jsonrpc_api_ws = "{ws}"
+                accounts = ["{ACCOUNT_ADDRESS}"]
+            "#
+        )
+    };
+
+    let valid = invalid_config("child", GATEWAY_ADDR, JSONRPC_API_HTTP, JSONRPC_API_WS);
+    assert!(Config::from_toml_str(&valid).is_ok());
+
+    // all the problems are reported, naming the subnet
+    let err = Config::from_toml_str(&invalid_config(
+        " ",
+        "t00",
+        "ftp://example.org/rpc/v0",
+        "https://example.org/rpc/v0",
+    ))
+    .unwrap_err()
+    .to_string();
+    for problem in [
+        "empty network_name",
+        "zero address as gateway_addr",
+        "not http or https",
+        "not ws or wss",
+    ] {
+        assert!(err.contains(&format!("subnet {CHILD_ID} has")), "{err}");
+        assert!(err.contains(problem), "{problem} not in {err}");
+    }
+
+    // subnets of different hierarchies
+    let other_root = formatdoc!(
+        r#"
+            {valid}
+            [[subnets]]
+            id = "/other/t0100"
+            network_name = "other"
+            gateway_addr = "{GATEWAY_ADDR}"
+            jsonrpc_api_http = "{JSONRPC_API_HTTP}"
+        "#
+    );
+    let err = Config::from_toml_str(&other_root).unwrap_err().to_string();
+    assert!(
+        err.contains("subnet /other/t0100 is under root /other"),
+        "{err}"
+    );
+}
+
+#[test]
+fn check_gas_config() {
+    let config_with_gas = |gas: &str| {
+        formatdoc!(
+            r#"
+                [server]
+                json_rpc_address = "{SERVER_JSON_RPC_ADDR}"
+
+                [[subnets]]
+                id = "{ROOT_ID}"
+                gateway_addr = "{GATEWAY_ADDR}"
+                network_name = "root"
+                jsonrpc_api_http = "{JSONRPC_API_HTTP}"
+
+                [subnets.gas]
+                {gas}
+            "#
+        )
+    };
+
+    // the gas block is optional
+    let config = read_config();
+    let subnet = &config.subnets[&SubnetID::from_str(ROOT_ID).unwrap()];
+    assert_eq!(subnet.gas, Default::default());
+
+    let config = Config::from_toml_str(&config_with_gas(r#"gas_fee_cap = "100""#)).unwrap();
+    let subnet = &config.subnets[&SubnetID::from_str(ROOT_ID).unwrap()];
+    assert_eq!(subnet.gas.gas_fee_cap, Some(TokenAmount::from_atto(100)));
+
+    let err = Config::from_toml_str(&config_with_gas("estimation_multiplier = 0.5"))
+        .unwrap_err()
+        .to_string();
+    assert!(
+        err.contains("gas.estimation_multiplier 0.5 below 1"),
+        "{err}"
+    );
+    assert!(Config::from_toml_str(&config_with_gas("gas_fee_cap = 100")).is_err());
+}
+
+#[test]
+fn check_keystore_path() {
+    let config_with_keystore = |path: &str| {
+        formatdoc!(
+            r#"
+                [server]
+                json_rpc_address = "{SERVER_JSON_RPC_ADDR}"
+
+                [[subnets]]
+                id = "{ROOT_ID}"
+                gateway_addr = "{GATEWAY_ADDR}"
+                network_name = "root"
+                jsonrpc_api_http = "{JSONRPC_API_HTTP}"
+                keystore_path = "{path}"
+            "#
+        )
+    };
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().to_str().unwrap();
+    let config = Config::from_toml_str(&config_with_keystore(path)).unwrap();
+    let subnet = &config.subnets[&SubnetID::from_str(ROOT_ID).unwrap()];
+    assert_eq!(subnet.keystore_path.as_deref(), Some(dir.path()));
+
+    let missing = dir.path().join("missing");
+    let err = Config::from_toml_str(&config_with_keystore(missing.to_str().unwrap()))
+        .unwrap_err()
+        .to_string();
+    assert!(
+        err.contains(&format!("subnet {ROOT_ID} has a keystore_path")),
+        "{err}"
+    );
+}
+
+#[test]
+fn check_json_config() {
+    let json_str = formatdoc!(
+        r#"
+            {{
+                "server": {{ "json_rpc_address": "{SERVER_JSON_RPC_ADDR}" }},
+                "subnets": [
+                    {{
+                        "id": "{ROOT_ID}",
+                        "gateway_addr": "{GATEWAY_ADDR}",
+                        "network_name": "root",
+                        "jsonrpc_api_http": "{JSONRPC_API_HTTP}",
+                        "jsonrpc_api_ws": "{JSONRPC_API_WS}",
+                        "auth_token": "{ROOT_AUTH_TOKEN}"
+                    }},
+                    {{
+                        "id": "{CHILD_ID}",
+                        "network_name": "child",
+                        "gateway_addr": "{GATEWAY_ADDR}",
+                        "jsonrpc_api_http": "{JSONRPC_API_HTTP}",
+                        "auth_token": "{CHILD_AUTH_TOKEN}",
+                        "accounts": ["{ACCOUNT_ADDRESS}"],
+                        "stale_threshold": null
+                    }}
+                ]
+            }}
+        "#
+    );
+    let json_config = Config::from_json_str(&json_str).unwrap();
+    assert_eq!(json_config, Config::from_toml_str(&config_str()).unwrap());
+
+    // the format is picked by the extension of the file
+    let mut file = tempfile::Builder::new().suffix(".json").tempfile().unwrap();
+    file.write_all(json_str.as_bytes()).unwrap();
+    assert_eq!(Config::from_file(file.path()).unwrap(), json_config);
+    let mut file = NamedTempFile::new().unwrap();
+    file.write_all(config_str().as_bytes()).unwrap();
+    assert_eq!(Config::from_file(file.path()).unwrap(), json_config);
+
+    // gateways can be written as the number of their ID address
+    let json_str = json_str.replace(&format!("\"{GATEWAY_ADDR}\""), "64");
+    assert_eq!(Config::from_json_str(&json_str).unwrap(), json_config);
+    let json_str = json_str.replacen("\"gateway_addr\": 64", "\"gateway_addr\": -64", 1);
+    assert!(Config::from_json_str(&json_str).is_err());
 }
 
 fn config_str() -> String {
@@ -201,8 +705,92 @@ fn read_config() -> Config {
             jsonrpc_api_http = "{JSONRPC_API_HTTP}"
             auth_token = "{CHILD_AUTH_TOKEN}"
             accounts = ["{ACCOUNT_ADDRESS}", "{ACCOUNT_ADDRESS}"]
+            state_wait_confidence = 10
+
+            [subnets.gas]
+            gas_premium = "100000"
         "#
     );
 
     Config::from_toml_str(config_str.as_str()).unwrap()
 }
+
+#[test]
+fn check_extra_headers() {
+    let config_with_headers = |headers: &str| {
+        formatdoc!(
+            r#"
+                [server]
+                json_rpc_address = "{SERVER_JSON_RPC_ADDR}"
+
+                [[subnets]]
+                id = "{ROOT_ID}"
+                gateway_addr = "{GATEWAY_ADDR}"
+                network_name = "root"
+                jsonrpc_api_http = "{JSONRPC_API_HTTP}"
+                extra_headers = {headers}
+            "#
+        )
+    };
+
+    let config = Config::from_toml_str(&config_with_headers(r#"{ "X-Api-Key" = "key" }"#)).unwrap();
+    let subnet = &config.subnets[&SubnetID::from_str(ROOT_ID).unwrap()];
+    assert_eq!(subnet.extra_headers["X-Api-Key"], "key");
+
+    let err = Config::from_toml_str(&config_with_headers(r#"{ "X Api Key" = "key" }"#))
+        .unwrap_err()
+        .to_string();
+    assert!(
+        err.contains(&format!("subnet {ROOT_ID} has an invalid extra header")),
+        "{err}"
+    );
+
+    // the values are not leaked in the diff of a reload
+    let new = Config::from_toml_str(&config_with_headers(r#"{ "X-Api-Key" = "other" }"#)).unwrap();
+    let changes = diff(&config, &new).changed;
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].field, format!("subnets.{ROOT_ID}.extra_headers"));
+    assert!(!changes[0].new.contains("other"));
+}
+
+#[test]
+fn check_proxy_url() {
+    let config_with_proxy = |url: &str| {
+        formatdoc!(
+            r#"
+                [server]
+                json_rpc_address = "{SERVER_JSON_RPC_ADDR}"
+
+                [[subnets]]
+                id = "{ROOT_ID}"
+                gateway_addr = "{GATEWAY_ADDR}"
+                network_name = "root"
+                jsonrpc_api_http = "{JSONRPC_API_HTTP}"
+                proxy_url = "{url}"
+            "#
+        )
+    };
+
+    let config =
+        Config::from_toml_str(&config_with_proxy("http://proxy.example.org:3128")).unwrap();
+    let subnet = &config.subnets[&SubnetID::from_str(ROOT_ID).unwrap()];
+    assert_eq!(
+        subnet.proxy_url,
+        Some(Url::parse("http://proxy.example.org:3128").unwrap())
+    );
+
+    let config = Config::from_toml_str(&config_with_proxy("socks5://127.0.0.1:1080")).unwrap();
+    let subnet = &config.subnets[&SubnetID::from_str(ROOT_ID).unwrap()];
+    assert_eq!(
+        subnet.proxy_url,
+        Some(Url::parse("socks5://127.0.0.1:1080").unwrap())
+    );
+
+    let err = Config::from_toml_str(&config_with_proxy("ftp://127.0.0.1:21"))
+        .unwrap_err()
+        .to_string();
+    assert!(
+        err.contains(&format!("subnet {ROOT_ID} has an invalid proxy_url")),
+        "{err}"
+    );
+}
@@ -0,0 +1,179 @@
+// Copyright 2022-2023 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Selection of the endpoint a client sends its requests to, failing over to the fallbacks when
+//! the current one is unreachable.
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use url::Url;
+
+/// How long an endpoint that failed is skipped before being tried again.
+pub(crate) const DEFAULT_ENDPOINT_COOLDOWN: Duration = Duration::from_secs(30);
+/// The longest an endpoint that keeps failing is skipped, as its cooldown doubles with each
+/// consecutive failure.
+const MAX_ENDPOINT_COOLDOWN: Duration = Duration::from_secs(600);
+
+/// The endpoints of a client, the first one being the primary. Requests stick to the endpoint
+/// that last succeeded, and endpoints that fail are skipped for a cooldown period, which grows
+/// with their consecutive failures so that a flapping node is tried less and less often.
+pub(crate) struct Endpoints {
+    urls: Vec<Url>,
+    cooldown: Duration,
+    state: Mutex<EndpointsState>,
+}
+
+struct EndpointsState {
+    current: usize,
+    /// The instant until which each endpoint is skipped, if it failed.
+    down_until: Vec<Option<Instant>>,
+    /// The number of times each endpoint failed since it last succeeded.
+    failures: Vec<u32>,
+}
+
+impl Endpoints {
+    pub fn new(urls: Vec<Url>, cooldown: Duration) -> Self {
+        assert!(!urls.is_empty(), "at least one endpoint is required");
+        let down_until = vec![None; urls.len()];
+        let failures = vec![0; urls.len()];
+        Self {
+            urls,
+            cooldown,
+            state: Mutex::new(EndpointsState {
+                current: 0,
+                down_until,
+                failures,
+            }),
+        }
+    }
+
+    pub fn primary(&self) -> &Url {
+        &self.urls[0]
+    }
+
+    pub fn len(&self) -> usize {
+        self.urls.len()
+    }
+
+    /// Returns the endpoints to try at `now` in order, as pairs of index and url: the current
+    /// endpoint first, followed by the next ones in rotation, those that failed the least first,
+    /// skipping those in their cooldown. If all of them are in their cooldown, all are returned,
+    /// so that requests are not refused while the nodes recover.
+    pub fn candidates(&self, now: Instant) -> Vec<(usize, Url)> {
+        let state = self.state.lock().unwrap();
+        let mut rotation = (0..self.urls.len())
+            .map(|i| (state.current + i) % self.urls.len())
+            .collect::<Vec<_>>();
+        // the sort is stable, so endpoints with as many failures keep their rotation order
+        rotation[1..].sort_by_key(|i| state.failures[*i]);
+
+        let up = rotation
+            .iter()
+            .filter(|i| state.down_until[**i].map_or(true, |until| until <= now))
+            .map(|i| (*i, self.urls[*i].clone()))
+            .collect::<Vec<_>>();
+        if !up.is_empty() {
+            return up;
+        }
+        rotation
+            .into_iter()
+            .map(|i| (i, self.urls[i].clone()))
+            .collect()
+    }
+
+    /// Records that the endpoint `index` failed at `now`, skipping it for its cooldown.
+    pub fn failed(&self, index: usize, now: Instant) {
+        let mut state = self.state.lock().unwrap();
+        let failures = state.failures[index];
+        state.failures[index] = failures.saturating_add(1);
+        state.down_until[index] = Some(now + self.cooldown_after(failures + 1));
+    }
+
+    /// Returns how long an endpoint is skipped after `failures` consecutive failures.
+    fn cooldown_after(&self, failures: u32) -> Duration {
+        // past 2^10 the cooldown is above its maximum anyway
+        let factor = 1u32 << failures.saturating_sub(1).min(10);
+        self.cooldown
+            .checked_mul(factor)
+            .map_or(MAX_ENDPOINT_COOLDOWN, |c| c.min(MAX_ENDPOINT_COOLDOWN))
+    }
+
+    /// Records that the endpoint `index` succeeded, making it the current one.
+    pub fn succeeded(&self, index: usize) {
+        let mut state = self.state.lock().unwrap();
+        if state.current != index {
+            log::info!("json rpc requests moved to endpoint {:}", self.urls[index]);
+        }
+        state.current = index;
+        state.down_until[index] = None;
+        state.failures[index] = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use url::Url;
+
+    use crate::jsonrpc::endpoints::Endpoints;
+
+    fn indexes(endpoints: &Endpoints, now: Instant) -> Vec<usize> {
+        endpoints
+            .candidates(now)
+            .into_iter()
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    #[test]
+    fn test_endpoints_failover() {
+        let urls = ["http://a", "http://b", "http://c"]
+            .iter()
+            .map(|u| Url::parse(u).unwrap())
+            .collect();
+        let cooldown = Duration::from_secs(10);
+        let endpoints = Endpoints::new(urls, cooldown);
+        let now = Instant::now();
+        assert_eq!(indexes(&endpoints, now), vec![0, 1, 2]);
+
+        // the primary fails and the first fallback takes over
+        endpoints.failed(0, now);
+        assert_eq!(indexes(&endpoints, now), vec![1, 2]);
+        endpoints.succeeded(1);
+
+        // requests stick to the fallback after the primary cooldown
+        let later = now + cooldown;
+        assert_eq!(indexes(&endpoints, later), vec![1, 2, 0]);
+
+        // all endpoints down, all are tried anyway
+        endpoints.failed(1, now);
+        endpoints.failed(2, now);
+        endpoints.failed(0, now);
+        assert_eq!(indexes(&endpoints, now), vec![1, 2, 0]);
+    }
+
+    #[test]
+    fn test_endpoints_flapping() {
+        let urls = ["http://a", "http://b", "http://c"]
+            .iter()
+            .map(|u| Url::parse(u).unwrap())
+            .collect();
+        let cooldown = Duration::from_secs(10);
+        let endpoints = Endpoints::new(urls, cooldown);
+        let now = Instant::now();
+
+        // the cooldown doubles with each consecutive failure, and the endpoint is tried last
+        endpoints.failed(1, now);
+        assert_eq!(indexes(&endpoints, now + cooldown), vec![0, 2, 1]);
+        endpoints.failed(1, now);
+        assert_eq!(indexes(&endpoints, now + cooldown), vec![0, 2]);
+        assert_eq!(indexes(&endpoints, now + cooldown * 2), vec![0, 2, 1]);
+        assert_eq!(endpoints.cooldown_after(100), Duration::from_secs(600));
+
+        // a success resets the failures of the endpoint
+        endpoints.succeeded(1);
+        endpoints.failed(1, now);
+        assert_eq!(indexes(&endpoints, now + cooldown), vec![1, 2, 0]);
+    }
+}
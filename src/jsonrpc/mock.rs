@@ -0,0 +1,138 @@
+// Copyright 2022-2023 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! A configurable [`JsonRpcClient`] standing for a node in tests.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use async_channel::Receiver;
+use async_trait::async_trait;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::jsonrpc::JsonRpcClient;
+
+type Handler = Arc<dyn Fn(Value) -> Result<Value> + Send + Sync>;
+
+/// A [`JsonRpcClient`] answering each method with the handler registered for it, and recording
+/// the requests it receives. Requests to methods without a handler fail the test. Clones share
+/// their handlers and the requests recorded, so a test can keep one to inspect the requests sent
+/// through the client it passed on.
+///
+/// # Examples
+/// ```ignore
+/// let node = MockJsonRpcClient::default()
+///     .with_result("Filecoin.StateNetworkName", json!("/root"))
+///     .on("Filecoin.WalletHas", |params| Ok(json!(params[0] == "t0100")));
+/// let client = LotusJsonRPCClient::new(node.clone());
+/// ```
+#[derive(Clone, Default)]
+pub(crate) struct MockJsonRpcClient {
+    handlers: Arc<Mutex<HashMap<String, Handler>>>,
+    subscriptions: Arc<Mutex<HashMap<String, Receiver<Value>>>>,
+    requests: Arc<Mutex<Vec<(String, Value)>>>,
+    delay: Option<Duration>,
+}
+
+impl MockJsonRpcClient {
+    /// Answers the requests to `method` with the result of `handler` on their params.
+    pub fn on<F>(self, method: &str, handler: F) -> Self
+    where
+        F: Fn(Value) -> Result<Value> + Send + Sync + 'static,
+    {
+        self.handlers
+            .lock()
+            .unwrap()
+            .insert(method.to_string(), Arc::new(handler));
+        self
+    }
+
+    /// Answers every request to `method` with `result`.
+    pub fn with_result(self, method: &str, result: Value) -> Self {
+        self.on(method, move |_| Ok(result.clone()))
+    }
+
+    /// Subscribes to `method` with the values received from `notifications`.
+    pub fn with_subscription(self, method: &str, notifications: Receiver<Value>) -> Self {
+        self.subscriptions
+            .lock()
+            .unwrap()
+            .insert(method.to_string(), notifications);
+        self
+    }
+
+    /// Answers each request after `delay`, giving concurrent callers the chance to send their
+    /// own requests in the meantime.
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = Some(delay);
+        self
+    }
+
+    /// Returns the method and params of the requests received, in order.
+    pub fn requests(&self) -> Vec<(String, Value)> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    /// Returns the params of the requests to `method` received, in order.
+    pub fn params(&self, method: &str) -> Vec<Value> {
+        self.requests()
+            .into_iter()
+            .filter(|(m, _)| m == method)
+            .map(|(_, params)| params)
+            .collect()
+    }
+
+    /// Forgets the requests received so far.
+    pub fn clear_requests(&self) {
+        self.requests.lock().unwrap().clear();
+    }
+
+    async fn handle(&self, method: &str, params: Value) -> Result<Value> {
+        self.requests
+            .lock()
+            .unwrap()
+            .push((method.to_string(), params.clone()));
+        let handler = self
+            .handlers
+            .lock()
+            .unwrap()
+            .get(method)
+            .cloned()
+            .unwrap_or_else(|| panic!("unexpected method {method:}"));
+        if let Some(delay) = self.delay {
+            tokio::time::sleep(delay).await;
+        }
+        handler(params)
+    }
+}
+
+#[async_trait]
+impl JsonRpcClient for MockJsonRpcClient {
+    async fn request<T: DeserializeOwned>(&self, method: &str, params: Value) -> Result<T> {
+        let r = self.handle(method, params).await?;
+        Ok(serde_json::from_value(r)?)
+    }
+
+    async fn request_batch(&self, requests: Vec<(&str, Value)>) -> Result<Vec<Result<Value>>> {
+        let mut results = vec![];
+        for (method, params) in requests {
+            results.push(self.handle(method, params).await);
+        }
+        Ok(results)
+    }
+
+    async fn subscribe(&self, method: &str) -> Result<Receiver<Value>> {
+        self.requests
+            .lock()
+            .unwrap()
+            .push((method.to_string(), Value::Null));
+        self.subscriptions
+            .lock()
+            .unwrap()
+            .get(method)
+            .cloned()
+            .ok_or_else(|| anyhow!("no subscription to {method:}"))
+    }
+}
@@ -1,4 +1,6 @@
-use std::time::Duration;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 
 // Copyright 2022-2023 Protocol Labs
 // SPDX-License-Identifier: MIT
@@ -6,10 +8,10 @@ use anyhow::{anyhow, Result};
 use async_channel::{Receiver, Sender};
 use async_trait::async_trait;
 use futures_util::{SinkExt, StreamExt};
-use reqwest::header::HeaderValue;
-use reqwest::Client;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::{Client, NoProxy, Proxy, StatusCode};
 use serde::de::DeserializeOwned;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use serde_json::json;
 use serde_json::Value;
 use tokio::net::TcpStream;
@@ -20,24 +22,158 @@ use tokio_tungstenite::MaybeTlsStream;
 use tokio_tungstenite::{connect_async, WebSocketStream};
 use url::Url;
 
+mod endpoints;
+#[cfg(test)]
+pub(crate) mod mock;
 #[cfg(test)]
 mod tests;
 
+use endpoints::{Endpoints, DEFAULT_ENDPOINT_COOLDOWN};
+
 const DEFAULT_JSON_RPC_VERSION: &str = "2.0";
-const DEFAULT_JSON_RPC_ID: u8 = 1;
+const DEFAULT_JSON_RPC_ID: u64 = 1;
 const DEFAULT_REQ_TIMEOUT: Duration = Duration::from_secs(30);
 
+/// The methods whose responses hold secrets, e.g. private keys, and must never be logged.
+const REDACTED_METHODS: [&str; 1] = ["Filecoin.WalletExport"];
+
+/// The JSON-RPC error code returned for methods the server does not expose.
+const METHOD_NOT_FOUND_CODE: i64 = -32601;
+/// The statuses of the proxies in front of a node that cannot reach it, on which requests fail
+/// over to the next endpoint. Lotus answers the requests that fail with a json rpc error with
+/// a 500 status, which is returned to the caller instead.
+const FAILOVER_STATUSES: [StatusCode; 3] = [
+    StatusCode::BAD_GATEWAY,
+    StatusCode::SERVICE_UNAVAILABLE,
+    StatusCode::GATEWAY_TIMEOUT,
+];
+
 /// A convenience constant that represents empty params in a JSON-RPC request.
 pub const NO_PARAMS: Value = json!([]);
 
+/// The settings of the HTTP client of a [`JsonRpcClientImpl`], mostly of its connection pool.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HttpPoolOptions {
+    /// The most idle connections kept open to each host.
+    pub max_idle_per_host: usize,
+    /// How long an idle connection is kept open, forever if not set.
+    pub idle_timeout: Option<Duration>,
+    /// The interval of the TCP keep-alive probes sent on the connections of the pool, none are
+    /// sent if not set.
+    pub tcp_keepalive: Option<Duration>,
+    /// The proxy all the requests are sent through, except those to the hosts excluded by
+    /// `no_proxy`. The proxies of the `HTTP_PROXY` and `HTTPS_PROXY` environment variables are
+    /// used if not set.
+    pub proxy_url: Option<Url>,
+    /// The hosts reached without the proxy, in the format of the `NO_PROXY` environment
+    /// variable, which is read if not set.
+    pub no_proxy: Option<String>,
+}
+
+impl Default for HttpPoolOptions {
+    /// The defaults of [`reqwest`].
+    fn default() -> Self {
+        Self {
+            max_idle_per_host: usize::MAX,
+            idle_timeout: Some(Duration::from_secs(90)),
+            tcp_keepalive: None,
+            proxy_url: None,
+            no_proxy: None,
+        }
+    }
+}
+
+impl HttpPoolOptions {
+    /// Builds a new HTTP client with its own pool of connections.
+    ///
+    /// # Panics
+    ///
+    /// Same as [`Client::new`], if the TLS backend cannot be initialized, or if the proxy url
+    /// is not supported, see [`proxy`].
+    pub fn client(&self) -> Client {
+        let mut builder = Client::builder()
+            .pool_max_idle_per_host(self.max_idle_per_host)
+            .pool_idle_timeout(self.idle_timeout)
+            .tcp_keepalive(self.tcp_keepalive);
+        if let Some(url) = &self.proxy_url {
+            let proxy = proxy(url, self.no_proxy.as_deref()).expect("unsupported proxy url");
+            builder = builder.proxy(proxy);
+        }
+        builder.build().expect("cannot build http client")
+    }
+}
+
+/// The HTTP clients to share between the [`JsonRpcClientImpl`]s with the same pool options, so
+/// that the clients created per request reuse the connections of the previous ones. Their owner
+/// passes them in with [`JsonRpcClientImpl::with_shared_http_client`].
+#[derive(Default)]
+pub struct HttpClients {
+    clients: Mutex<Vec<(HttpPoolOptions, Client)>>,
+}
+
+impl HttpClients {
+    /// Returns the HTTP client for `options`, building it on the first call. The returned
+    /// handles share the pool of connections of the client.
+    pub fn get(&self, options: &HttpPoolOptions) -> Client {
+        let mut clients = self.clients.lock().unwrap();
+        if let Some((_, client)) = clients.iter().find(|(o, _)| o == options) {
+            return client.clone();
+        }
+        let client = options.client();
+        clients.push((options.clone(), client.clone()));
+        client
+    }
+}
+
+/// Returns the proxy of `url` for all requests, except those to the hosts in `no_proxy`, or in
+/// the `NO_PROXY` or `no_proxy` environment variables if not set. HTTP, HTTPS and SOCKS5
+/// proxies are supported, the latter resolving the hosts locally with `socks5` and through the
+/// proxy with `socks5h`.
+pub fn proxy(url: &Url, no_proxy: Option<&str>) -> Result<Proxy> {
+    if !matches!(url.scheme(), "http" | "https" | "socks5" | "socks5h") {
+        return Err(anyhow!(
+            "unsupported proxy scheme {:}, only http, https, socks5 and socks5h proxies are supported",
+            url.scheme()
+        ));
+    }
+    let no_proxy = match no_proxy {
+        Some(hosts) => NoProxy::from_string(hosts),
+        None => NoProxy::from_env(),
+    };
+    Ok(Proxy::all(url.as_str())?.no_proxy(no_proxy))
+}
+
 /// A simple async JSON-RPC client that can send one-shot request via HTTP/HTTPS
 /// and subscribe to push-based notifications via Websockets. The returned
 /// results are of type [`Value`] from the [`serde_json`] crate.
 #[async_trait]
 pub trait JsonRpcClient {
-    /// Sends a JSON-RPC request with `method` and `params` via HTTP/HTTPS.
+    /// Sends a JSON-RPC request with `method` and `params` via HTTP/HTTPS. The params are
+    /// either positional, as an array, or named, as an object.
     async fn request<T: DeserializeOwned>(&self, method: &str, params: Value) -> Result<T>;
 
+    /// Same as [`JsonRpcClient::request`], with the fields of `params` sent as named params.
+    /// Fails if `params` does not serialize to an object.
+    async fn request_named<T: DeserializeOwned, P: Serialize + Sync>(
+        &self,
+        method: &str,
+        params: &P,
+    ) -> Result<T> {
+        // the params are left out of the errors, as they may hold secrets, e.g. private keys
+        let params = serde_json::to_value(params)
+            .map_err(|e| anyhow!("cannot serialize the params of {method:}: {e:}"))?;
+        if !params.is_object() {
+            return Err(anyhow!("named params of {method:} must be an object"));
+        }
+        self.request(method, params).await
+    }
+
+    /// Sends a batch of JSON-RPC requests, each one a `(method, params)` pair, in a single
+    /// HTTP/HTTPS round-trip. The results are returned in the same order as the requests. The
+    /// failure of an individual request is returned as an error in its position of the result
+    /// instead of failing the whole batch.
+    async fn request_batch(&self, requests: Vec<(&str, Value)>) -> Result<Vec<Result<Value>>>;
+
     /// Subscribes to notifications via a Websocket. This returns a [`Receiver`]
     /// channel that is used to receive the messages sent by the server.
     /// TODO: https://github.com/consensus-shipyard/ipc-agent/issues/7.
@@ -47,40 +183,162 @@ pub trait JsonRpcClient {
 /// The implementation of [`JsonRpcClient`].
 pub struct JsonRpcClientImpl {
     http_client: Client,
-    url: Url,
+    endpoints: Endpoints,
     bearer_token: Option<String>,
+    headers: HeaderMap,
+    timeout: Duration,
 }
 
 impl JsonRpcClientImpl {
-    /// Creates a client that sends all requests to `url`.
+    /// Creates a client that sends all requests to `url`, through its own HTTP client with
+    /// the default [`HttpPoolOptions`].
     pub fn new(url: Url, bearer_token: Option<&str>) -> Self {
+        Self::with_pool_options(url, bearer_token, &HttpPoolOptions::default())
+    }
+
+    /// Same as [`JsonRpcClientImpl::new`], but through its own HTTP client with the pool
+    /// `options`.
+    pub fn with_pool_options(
+        url: Url,
+        bearer_token: Option<&str>,
+        options: &HttpPoolOptions,
+    ) -> Self {
+        Self::with_shared_http_client(url, bearer_token, options.client())
+    }
+
+    /// Same as [`JsonRpcClientImpl::new`], but through `http_client`, sharing its pool of
+    /// connections with every other user of the client.
+    pub fn with_shared_http_client(
+        url: Url,
+        bearer_token: Option<&str>,
+        http_client: Client,
+    ) -> Self {
         Self {
-            http_client: Client::default(),
-            url,
+            http_client,
+            endpoints: Endpoints::new(vec![url], DEFAULT_ENDPOINT_COOLDOWN),
             bearer_token: bearer_token.map(String::from),
+            headers: HeaderMap::new(),
+            timeout: DEFAULT_REQ_TIMEOUT,
         }
     }
-}
 
-#[async_trait]
-impl JsonRpcClient for JsonRpcClientImpl {
-    async fn request<T: DeserializeOwned>(&self, method: &str, params: Value) -> Result<T> {
-        let request_body = build_jsonrpc_request(method, params)?;
-        let mut builder = self.http_client.post(self.url.as_str()).json(&request_body);
-        builder = builder.timeout(DEFAULT_REQ_TIMEOUT);
+    /// Sends the HTTP/HTTPS requests through `http_client`, sharing its pool of connections
+    /// with every other user of the client.
+    pub fn with_http_client(mut self, http_client: Client) -> Self {
+        self.http_client = http_client;
+        self
+    }
 
-        // Add the authorization bearer token if present
-        if self.bearer_token.is_some() {
-            builder = builder.bearer_auth(self.bearer_token.as_ref().unwrap());
+    /// Adds `headers` to every request sent by the client, e.g. the api keys required by the
+    /// proxies in front of hosted nodes. Headers with an invalid name or value are skipped.
+    pub fn with_headers(mut self, headers: &HashMap<String, String>) -> Self {
+        for (name, value) in headers {
+            match (
+                HeaderName::from_bytes(name.as_bytes()),
+                HeaderValue::from_str(value),
+            ) {
+                (Ok(name), Ok(value)) => {
+                    self.headers.insert(name, value);
+                }
+                _ => log::warn!("skipping invalid json rpc header: {name:}"),
+            }
         }
+        self
+    }
+
+    /// Sets the timeout of the HTTP/HTTPS requests sent by the client.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
 
-        let response = builder.send().await?;
+    /// Sets the endpoints the HTTP/HTTPS requests fail over to, in order, when the current one
+    /// cannot be reached or its proxy answers that it is unavailable. Requests stick to the
+    /// endpoint that last succeeded, and unreachable endpoints are skipped for a cooldown period.
+    pub fn with_fallbacks(mut self, fallbacks: Vec<Url>) -> Self {
+        let mut urls = vec![self.endpoints.primary().clone()];
+        urls.extend(fallbacks);
+        self.endpoints = Endpoints::new(urls, DEFAULT_ENDPOINT_COOLDOWN);
+        self
+    }
+
+    /// Returns the timeout of the HTTP/HTTPS requests sent by the client.
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// Posts the JSON `body` to the server and returns the raw response body.
+    async fn send(&self, body: &Value) -> Result<String> {
+        self.send_with_logging(body, true).await
+    }
+
+    /// Posts the JSON `body` to the server and returns the raw response body, which is only
+    /// logged if `log_response` is set.
+    async fn send_with_logging(&self, body: &Value, log_response: bool) -> Result<String> {
+        let mut last_err = None;
+        for (index, url) in self.endpoints.candidates(Instant::now()) {
+            let mut builder = self.http_client.post(url.as_str()).json(body);
+            builder = builder.timeout(self.timeout).headers(self.headers.clone());
 
-        let response_body = response.text().await?;
-        log::debug!("received raw response body: {:?}", response_body);
+            // Add the authorization bearer token if present
+            if self.bearer_token.is_some() {
+                builder = builder.bearer_auth(self.bearer_token.as_ref().unwrap());
+            }
+
+            match builder.send().await {
+                // a node its proxy cannot reach is as good as unreachable
+                Ok(response)
+                    if FAILOVER_STATUSES.contains(&response.status())
+                        && self.endpoints.len() > 1 =>
+                {
+                    log::warn!(
+                        "json rpc endpoint {url:} failed with status {:}",
+                        response.status()
+                    );
+                    self.endpoints.failed(index, Instant::now());
+                    last_err = Some(format!("status {:}", response.status()));
+                }
+                Ok(response) => {
+                    self.endpoints.succeeded(index);
+
+                    let response_body = response.text().await?;
+                    if log_response {
+                        log::debug!("received raw response body: {:?}", response_body);
+                    }
+                    return Ok(response_body);
+                }
+                // only fail over if the node cannot be reached, other errors would be returned
+                // by any of the endpoints. A request that timed out may have been executed, e.g.
+                // a message pushed, so it is not sent again to another endpoint
+                Err(e) if e.is_connect() && self.endpoints.len() > 1 => {
+                    log::warn!("json rpc endpoint {url:} unreachable: {e:}");
+                    self.endpoints.failed(index, Instant::now());
+                    last_err = Some(e.to_string());
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+        Err(anyhow!(
+            "all json rpc endpoints unreachable, last error: {:}",
+            last_err.unwrap_or_default()
+        ))
+    }
+}
+
+#[async_trait]
+impl JsonRpcClient for JsonRpcClientImpl {
+    async fn request<T: DeserializeOwned>(&self, method: &str, params: Value) -> Result<T> {
+        let request_body = build_jsonrpc_request(DEFAULT_JSON_RPC_ID, method, params)?;
+        let redacted = REDACTED_METHODS.contains(&method);
+        let response_body = self.send_with_logging(&request_body, !redacted).await?;
 
         let value =
             serde_json::from_str::<JsonRpcResponse<T>>(response_body.as_ref()).map_err(|e| {
+                let response_body = if redacted {
+                    "<redacted>"
+                } else {
+                    response_body.as_str()
+                };
                 log::error!("cannot parse json rpc client response: {:?}", response_body);
                 anyhow!(
                     "cannot parse json rpc response: {:} due to {:}",
@@ -96,8 +354,40 @@ impl JsonRpcClient for JsonRpcClientImpl {
         Result::from(value)
     }
 
+    async fn request_batch(&self, requests: Vec<(&str, Value)>) -> Result<Vec<Result<Value>>> {
+        if requests.is_empty() {
+            return Ok(vec![]);
+        }
+
+        // Each request in the batch is identified by its position, so that we can correlate the
+        // responses, which the server may return in any order, back to their requests.
+        let methods = requests
+            .iter()
+            .map(|(method, _)| *method)
+            .collect::<Vec<_>>();
+        let request_body = requests
+            .into_iter()
+            .enumerate()
+            .map(|(id, (method, params))| build_jsonrpc_request(id as u64, method, params))
+            .collect::<Result<Vec<_>>>()?;
+
+        let response_body = self.send(&Value::Array(request_body)).await?;
+
+        let responses = serde_json::from_str::<Vec<JsonRpcResponse<Value>>>(response_body.as_ref())
+            .map_err(|e| {
+                log::error!("cannot parse json rpc batch response: {:?}", response_body);
+                anyhow!(
+                    "cannot parse json rpc batch response: {:} due to {:}",
+                    response_body,
+                    e.to_string()
+                )
+            })?;
+
+        correlate_batch_responses(&methods, responses)
+    }
+
     async fn subscribe(&self, method: &str) -> Result<Receiver<Value>> {
-        let mut request = self.url.as_str().into_client_request()?;
+        let mut request = self.endpoints.primary().as_str().into_client_request()?;
 
         // Add the authorization bearer token if present
         if self.bearer_token.is_some() {
@@ -105,9 +395,10 @@ impl JsonRpcClient for JsonRpcClientImpl {
             let header_value = HeaderValue::from_str(token_string.as_str())?;
             request.headers_mut().insert("Authorization", header_value);
         }
+        request.headers_mut().extend(self.headers.clone());
 
         let (mut ws_stream, _) = connect_async(request).await?;
-        let request_body = build_jsonrpc_request(method, NO_PARAMS)?;
+        let request_body = build_jsonrpc_request(DEFAULT_JSON_RPC_ID, method, NO_PARAMS)?;
         ws_stream
             .send(Message::text(request_body.to_string()))
             .await?;
@@ -119,12 +410,19 @@ impl JsonRpcClient for JsonRpcClientImpl {
     }
 }
 
+/// Checks if the error returned by a request is caused by the server not exposing the method,
+/// e.g. older lotus nodes lacking recently added methods.
+pub fn is_method_not_found(e: &anyhow::Error) -> bool {
+    let msg = e.to_string();
+    msg.contains(&format!("\"code\":{METHOD_NOT_FOUND_CODE}"))
+}
+
 /// JsonRpcResponse wraps the json rpc response.
 /// We could have encountered success or error, this struct handles the error and result and convert
 /// them into Result.
 #[derive(Debug, Deserialize)]
 struct JsonRpcResponse<T> {
-    id: u8,
+    id: u64,
     jsonrpc: String,
 
     result: Option<T>,
@@ -147,8 +445,40 @@ impl<T: DeserializeOwned> From<JsonRpcResponse<T>> for Result<T> {
     }
 }
 
+// Matches the responses of a batch of requests for `methods` to their requests by id, so that the
+// results are returned in the order the requests were made. The error of a failed request names
+// its id and method so that it can be attributed to the right call.
+fn correlate_batch_responses(
+    methods: &[&str],
+    responses: Vec<JsonRpcResponse<Value>>,
+) -> Result<Vec<Result<Value>>> {
+    let mut results = methods.iter().map(|_| None).collect::<Vec<_>>();
+    for response in responses {
+        if response.jsonrpc != DEFAULT_JSON_RPC_VERSION {
+            return Err(anyhow!("json_rpc version not matching."));
+        }
+
+        let id = response.id;
+        match results.get_mut(id as usize) {
+            Some(r) if r.is_none() => *r = Some(Result::from(response)),
+            _ => return Err(anyhow!("unexpected json_rpc id {id:} in batch response")),
+        }
+    }
+
+    Ok(results
+        .into_iter()
+        .zip(methods)
+        .enumerate()
+        .map(|(id, (r, method))| {
+            r.unwrap_or_else(|| Err(anyhow!("no json_rpc response")))
+                .map_err(|e| e.context(format!("batch request {id:} ({method:}) failed")))
+        })
+        .collect())
+}
+
 // Processes a websocket stream by reading messages from the stream `ws_stream` and sending
-// them to an output channel `chan`.
+// them to an output channel `chan`. When the receiving end of `chan` is dropped, the websocket
+// is closed, which cancels the subscription in the server.
 async fn handle_stream(
     mut ws_stream: WebSocketStream<MaybeTlsStream<TcpStream>>,
     chan: Sender<Value>,
@@ -160,11 +490,25 @@ async fn handle_stream(
                 break;
             }
             Some(result) => match result {
-                Ok(msg) => {
-                    println!("{}", msg);
+                Ok(Message::Text(msg)) => {
                     log::trace!("Read message from websocket stream: {}", msg);
-                    let value = serde_json::from_str(msg.to_text().unwrap()).unwrap();
-                    chan.send(value).await.unwrap();
+                    let value = match serde_json::from_str(&msg) {
+                        Ok(value) => value,
+                        Err(err) => {
+                            log::error!("Cannot parse message from websocket stream: {:?}", err);
+                            continue;
+                        }
+                    };
+                    if chan.send(value).await.is_err() {
+                        log::debug!("Subscription receiver dropped, closing websocket stream");
+                        if let Err(err) = ws_stream.close(None).await {
+                            log::debug!("Error closing websocket stream: {:?}", err);
+                        }
+                        break;
+                    }
+                }
+                Ok(msg) => {
+                    log::trace!("Ignoring non-text message from websocket stream: {}", msg);
                 }
                 Err(err) => {
                     log::error!("Error reading message from websocket stream: {:?}", err);
@@ -177,7 +521,7 @@ async fn handle_stream(
 }
 
 // A convenience function to build a JSON-RPC request.
-fn build_jsonrpc_request(method: &str, params: Value) -> Result<Value> {
+fn build_jsonrpc_request(id: u64, method: &str, params: Value) -> Result<Value> {
     let has_params = if params.is_array() {
         let array_params = params.as_array().unwrap();
         !array_params.is_empty()
@@ -193,14 +537,14 @@ fn build_jsonrpc_request(method: &str, params: Value) -> Result<Value> {
     let request_value = if has_params {
         json!({
             "jsonrpc": DEFAULT_JSON_RPC_VERSION,
-            "id": DEFAULT_JSON_RPC_ID,
+            "id": id,
             "method": method,
             "params": params,
         })
     } else {
         json!({
             "jsonrpc": DEFAULT_JSON_RPC_VERSION,
-            "id": DEFAULT_JSON_RPC_ID,
+            "id": id,
             "method": method,
         })
     };
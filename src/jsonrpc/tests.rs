@@ -1,10 +1,18 @@
 // Copyright 2022-2023 Protocol Labs
 // SPDX-License-Identifier: MIT
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::time::Duration;
+
 use futures_util::StreamExt;
 use serde_json::json;
 use url::Url;
+use warp::Filter;
 
-use crate::jsonrpc::{JsonRpcClient, JsonRpcClientImpl, NO_PARAMS};
+use crate::jsonrpc::{
+    build_jsonrpc_request, correlate_batch_responses, is_method_not_found, proxy, HttpClients,
+    HttpPoolOptions, JsonRpcClient, JsonRpcClientImpl, JsonRpcResponse, NO_PARAMS,
+};
 
 /// The default endpoints for public lotus node. If the urls fail in running tests, need to
 /// check these endpoints again.
@@ -58,6 +66,87 @@ async fn test_request_with_params_error() {
     assert!(response.is_err());
 }
 
+#[tokio::test]
+async fn test_request_batch() {
+    let url = Url::parse(HTTP_ENDPOINT).unwrap();
+    let client = JsonRpcClientImpl::new(url, None);
+
+    // The second request is missing its params, it should fail without failing the batch.
+    let responses = client
+        .request_batch(vec![
+            ("Filecoin.ChainHead", NO_PARAMS),
+            ("Filecoin.ChainGetBlock", NO_PARAMS),
+        ])
+        .await
+        .unwrap();
+    assert_eq!(responses.len(), 2);
+
+    let head = responses[0].as_ref().unwrap();
+    assert!(head.get("Blocks").is_some());
+    assert!(responses[1].is_err());
+}
+
+#[test]
+fn test_correlate_batch_responses() {
+    let responses = serde_json::from_value::<Vec<JsonRpcResponse<serde_json::Value>>>(json!([
+        {"jsonrpc": "2.0", "id": 2, "result": "c"},
+        {"jsonrpc": "2.0", "id": 0, "result": "a"},
+        {"jsonrpc": "2.0", "id": 1, "error": {"code": -32601, "message": "method not found"}},
+    ]))
+    .unwrap();
+
+    let methods = [
+        "Filecoin.ChainHead",
+        "Filecoin.Unknown",
+        "Filecoin.ChainHead",
+    ];
+    let results = correlate_batch_responses(&methods, responses).unwrap();
+    assert_eq!(results[0].as_ref().unwrap(), "a");
+    let err = results[1].as_ref().unwrap_err().to_string();
+    assert_eq!(err, "batch request 1 (Filecoin.Unknown) failed");
+    assert_eq!(results[2].as_ref().unwrap(), "c");
+}
+
+#[test]
+fn test_correlate_batch_responses_missing_id() {
+    let responses = serde_json::from_value::<Vec<JsonRpcResponse<serde_json::Value>>>(json!([
+        {"jsonrpc": "2.0", "id": 0, "result": "a"},
+    ]))
+    .unwrap();
+
+    let methods = ["Filecoin.ChainHead", "Filecoin.StateNetworkName"];
+    let results = correlate_batch_responses(&methods, responses).unwrap();
+    assert!(results[0].is_ok());
+    assert!(results[1].is_err());
+
+    let responses = serde_json::from_value::<Vec<JsonRpcResponse<serde_json::Value>>>(json!([
+        {"jsonrpc": "2.0", "id": 5, "result": "a"},
+    ]))
+    .unwrap();
+    assert!(correlate_batch_responses(&methods[..1], responses).is_err());
+}
+
+#[test]
+fn test_is_method_not_found() {
+    let response = serde_json::from_value::<JsonRpcResponse<()>>(json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "error": {"code": -32601, "message": "method 'Filecoin.StateActorCodeCIDs' not found"},
+    }))
+    .unwrap();
+    let e = anyhow::Result::<()>::from(response).unwrap_err();
+    assert!(is_method_not_found(&e));
+
+    let response = serde_json::from_value::<JsonRpcResponse<()>>(json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "error": {"code": 1, "message": "actor not found"},
+    }))
+    .unwrap();
+    let e = anyhow::Result::<()>::from(response).unwrap_err();
+    assert!(!is_method_not_found(&e));
+}
+
 #[tokio::test]
 #[ignore]
 async fn test_subscribe() {
@@ -68,3 +157,300 @@ async fn test_subscribe() {
         chan.next().await.unwrap();
     }
 }
+
+#[tokio::test]
+async fn test_request_fails_over() {
+    // a fallback node answering every request
+    let route = warp::post()
+        .map(|| warp::reply::json(&json!({"jsonrpc": "2.0", "id": 1, "result": "fallback"})));
+    let (fallback_addr, server) = warp::serve(route).bind_ephemeral(([127, 0, 0, 1], 0));
+    tokio::spawn(server);
+
+    // a primary node that is down
+    let primary_addr = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap();
+
+    let primary = Url::parse(&format!("http://{primary_addr:}/rpc/v1")).unwrap();
+    let fallback = Url::parse(&format!("http://{fallback_addr:}/rpc/v1")).unwrap();
+
+    let client = JsonRpcClientImpl::new(primary.clone(), None);
+    assert!(client
+        .request::<String>("Filecoin.Version", NO_PARAMS)
+        .await
+        .is_err());
+
+    let client = JsonRpcClientImpl::new(primary, None).with_fallbacks(vec![fallback]);
+    for _ in 0..2 {
+        let response = client
+            .request::<String>("Filecoin.Version", NO_PARAMS)
+            .await
+            .unwrap();
+        assert_eq!(response, "fallback");
+    }
+}
+
+#[tokio::test]
+async fn test_request_fails_over_on_server_error() {
+    let route = warp::post()
+        .map(|| warp::reply::json(&json!({"jsonrpc": "2.0", "id": 1, "result": "fallback"})));
+    let (fallback_addr, server) = warp::serve(route).bind_ephemeral(([127, 0, 0, 1], 0));
+    tokio::spawn(server);
+
+    // a primary node that is up but cannot serve requests
+    let route = warp::post()
+        .map(|| warp::reply::with_status("bad gateway", warp::http::StatusCode::BAD_GATEWAY));
+    let (primary_addr, server) = warp::serve(route).bind_ephemeral(([127, 0, 0, 1], 0));
+    tokio::spawn(server);
+
+    let primary = Url::parse(&format!("http://{primary_addr:}/rpc/v1")).unwrap();
+    let fallback = Url::parse(&format!("http://{fallback_addr:}/rpc/v1")).unwrap();
+
+    let client = JsonRpcClientImpl::new(primary, None).with_fallbacks(vec![fallback.clone()]);
+    let response = client
+        .request::<String>("Filecoin.Version", NO_PARAMS)
+        .await
+        .unwrap();
+    assert_eq!(response, "fallback");
+
+    // a primary node answering with a json rpc error, which lotus sends with a 500 status
+    let route = warp::post().map(|| {
+        warp::reply::with_status(
+            warp::reply::json(&json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "error": {"code": 1, "message": "actor not found"}
+            })),
+            warp::http::StatusCode::INTERNAL_SERVER_ERROR,
+        )
+    });
+    let (primary_addr, server) = warp::serve(route).bind_ephemeral(([127, 0, 0, 1], 0));
+    tokio::spawn(server);
+
+    let primary = Url::parse(&format!("http://{primary_addr:}/rpc/v1")).unwrap();
+    let client = JsonRpcClientImpl::new(primary, None).with_fallbacks(vec![fallback]);
+    let err = client
+        .request::<String>("Filecoin.StateLookupID", NO_PARAMS)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("actor not found"), "{err}");
+}
+
+#[tokio::test]
+async fn test_request_does_not_fail_over_on_timeout() {
+    let route = warp::post()
+        .map(|| warp::reply::json(&json!({"jsonrpc": "2.0", "id": 1, "result": "fallback"})));
+    let (fallback_addr, server) = warp::serve(route).bind_ephemeral(([127, 0, 0, 1], 0));
+    tokio::spawn(server);
+
+    // a primary node that may still execute the request after the client gave up on it
+    let route = warp::post().then(|| async {
+        tokio::time::sleep(Duration::from_secs(5)).await;
+        warp::reply::json(&json!({"jsonrpc": "2.0", "id": 1, "result": "primary"}))
+    });
+    let (primary_addr, server) = warp::serve(route).bind_ephemeral(([127, 0, 0, 1], 0));
+    tokio::spawn(server);
+
+    let primary = Url::parse(&format!("http://{primary_addr:}/rpc/v1")).unwrap();
+    let fallback = Url::parse(&format!("http://{fallback_addr:}/rpc/v1")).unwrap();
+
+    let client = JsonRpcClientImpl::new(primary, None)
+        .with_timeout(Duration::from_millis(100))
+        .with_fallbacks(vec![fallback]);
+    assert!(client
+        .request::<String>("Filecoin.MpoolPush", NO_PARAMS)
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn test_injected_http_client_is_reused() {
+    // a node answering with the port the request came from, i.e. the connection it came on
+    let route = warp::post()
+        .and(warp::addr::remote())
+        .map(|remote: Option<SocketAddr>| {
+            warp::reply::json(&json!({"jsonrpc": "2.0", "id": 1, "result": remote.unwrap().port()}))
+        });
+    let (addr, server) = warp::serve(route).bind_ephemeral(([127, 0, 0, 1], 0));
+    tokio::spawn(server);
+    let url = Url::parse(&format!("http://{addr:}/rpc/v1")).unwrap();
+
+    let port = |client: JsonRpcClientImpl| async move {
+        client
+            .request::<u16>("Filecoin.Version", NO_PARAMS)
+            .await
+            .unwrap()
+    };
+
+    // clients with the same http client send their requests on the same connection
+    let http_client = HttpPoolOptions::default().client();
+    let first =
+        port(JsonRpcClientImpl::new(url.clone(), None).with_http_client(http_client.clone())).await;
+    let second =
+        port(JsonRpcClientImpl::new(url.clone(), None).with_http_client(http_client)).await;
+    assert_eq!(first, second);
+
+    // while a client with its own pool opens a new one
+    let other = HttpPoolOptions::default().client();
+    let third = port(JsonRpcClientImpl::new(url, None).with_http_client(other)).await;
+    assert_ne!(first, third);
+}
+
+#[test]
+fn test_http_clients_per_pool_options() {
+    let options = HttpPoolOptions {
+        idle_timeout: Some(Duration::from_secs(5)),
+        ..Default::default()
+    };
+    let clients = HttpClients::default();
+    clients.get(&options);
+    clients.get(&options);
+    clients.get(&HttpPoolOptions::default());
+
+    // the client is built once per options
+    let built = clients.clients.lock().unwrap();
+    assert_eq!(built.len(), 2);
+    assert_eq!(built.iter().filter(|(o, _)| o == &options).count(), 1);
+
+    // each owner has its own clients
+    assert!(HttpClients::default().clients.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_request_sends_extra_headers() {
+    // a proxy that only lets requests with the api key through
+    let route = warp::post()
+        .and(warp::header::exact("x-api-key", "secret"))
+        .and(warp::header::<String>("x-tenant"))
+        .map(|tenant: String| {
+            warp::reply::json(&json!({"jsonrpc": "2.0", "id": 1, "result": tenant}))
+        });
+    let (addr, server) = warp::serve(route).bind_ephemeral(([127, 0, 0, 1], 0));
+    tokio::spawn(server);
+    let url = Url::parse(&format!("http://{addr:}/rpc/v1")).unwrap();
+
+    let client = JsonRpcClientImpl::new(url.clone(), None);
+    assert!(client
+        .request::<String>("Filecoin.Version", NO_PARAMS)
+        .await
+        .is_err());
+
+    let headers = HashMap::from([
+        (String::from("X-Api-Key"), String::from("secret")),
+        (String::from("X-Tenant"), String::from("tenant-1")),
+        (String::from("invalid header"), String::from("skipped")),
+    ]);
+    let client = JsonRpcClientImpl::new(url, None).with_headers(&headers);
+    let response = client
+        .request::<String>("Filecoin.Version", NO_PARAMS)
+        .await
+        .unwrap();
+    assert_eq!(response, "tenant-1");
+}
+
+#[tokio::test]
+async fn test_request_through_proxy() {
+    // a proxy answering every request itself, and a node reached without it
+    let route = warp::post()
+        .map(|| warp::reply::json(&json!({"jsonrpc": "2.0", "id": 1, "result": "proxy"})));
+    let (proxy_addr, server) = warp::serve(route).bind_ephemeral(([127, 0, 0, 1], 0));
+    tokio::spawn(server);
+    let route = warp::post()
+        .map(|| warp::reply::json(&json!({"jsonrpc": "2.0", "id": 1, "result": "direct"})));
+    let (node_addr, server) = warp::serve(route).bind_ephemeral(([127, 0, 0, 1], 0));
+    tokio::spawn(server);
+
+    // only the hosts excluded from the proxy are reached directly
+    let options = HttpPoolOptions {
+        proxy_url: Some(Url::parse(&format!("http://{proxy_addr:}")).unwrap()),
+        no_proxy: Some(String::from("localhost")),
+        ..Default::default()
+    };
+    let request = |url: String| {
+        let client = JsonRpcClientImpl::new(Url::parse(&url).unwrap(), None)
+            .with_http_client(options.client());
+        async move {
+            client
+                .request::<String>("Filecoin.Version", NO_PARAMS)
+                .await
+                .unwrap()
+        }
+    };
+    assert_eq!(
+        request(String::from("http://lotus.invalid/rpc/v1")).await,
+        "proxy"
+    );
+    assert_eq!(
+        request(format!("http://localhost:{:}/rpc/v1", node_addr.port())).await,
+        "direct"
+    );
+
+    assert!(proxy(&Url::parse("socks5://127.0.0.1:1080").unwrap(), None).is_ok());
+    assert!(proxy(&Url::parse("socks5h://127.0.0.1:1080").unwrap(), None).is_ok());
+    assert!(proxy(&Url::parse("ftp://127.0.0.1:21").unwrap(), None).is_err());
+}
+
+#[test]
+fn test_build_request_params() {
+    // positional params
+    let request =
+        build_jsonrpc_request(1, "Filecoin.ChainGetTipSetByHeight", json!([10, null])).unwrap();
+    assert_eq!(request["params"], json!([10, null]));
+
+    // named params
+    let request =
+        build_jsonrpc_request(1, "ipc_getSubnetInfo", json!({"subnet": "/root/t01002"})).unwrap();
+    assert_eq!(request["params"], json!({"subnet": "/root/t01002"}));
+
+    // empty params are omitted
+    let request = build_jsonrpc_request(1, "Filecoin.ChainHead", NO_PARAMS).unwrap();
+    assert!(request.get("params").is_none());
+
+    assert!(build_jsonrpc_request(1, "Filecoin.ChainHead", json!("params")).is_err());
+}
+
+#[tokio::test]
+async fn test_request_named() {
+    // a node answering with the params it received
+    let route = warp::post()
+        .and(warp::body::json())
+        .map(|body: serde_json::Value| {
+            warp::reply::json(&json!({"jsonrpc": "2.0", "id": 1, "result": body["params"]}))
+        });
+    let (addr, server) = warp::serve(route).bind_ephemeral(([127, 0, 0, 1], 0));
+    tokio::spawn(server);
+    let client =
+        JsonRpcClientImpl::new(Url::parse(&format!("http://{addr:}/rpc/v1")).unwrap(), None);
+
+    #[derive(serde::Serialize)]
+    struct Params {
+        subnet: String,
+        from_nonce: u64,
+    }
+    let params = Params {
+        subnet: String::from("/root/t01002"),
+        from_nonce: 3,
+    };
+    let received = client
+        .request_named::<serde_json::Value, _>("ipc_crossMsgList", &params)
+        .await
+        .unwrap();
+    assert_eq!(received, json!({"subnet": "/root/t01002", "from_nonce": 3}));
+
+    let received = client
+        .request::<serde_json::Value>("ipc_crossMsgList", json!(["/root/t01002", 3]))
+        .await
+        .unwrap();
+    assert_eq!(received, json!(["/root/t01002", 3]));
+
+    // params that are not an object cannot be named, and are not echoed in the error
+    let err = client
+        .request_named::<serde_json::Value, _>("ipc_crossMsgList", &vec![1, 2])
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "named params of ipc_crossMsgList must be an object"
+    );
+}
@@ -2,13 +2,18 @@
 // SPDX-License-Identifier: MIT
 use std::collections::HashMap;
 use std::fmt::Debug;
+use std::path::PathBuf;
 use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
 use base64::Engine;
 use cid::Cid;
 use fil_actors_runtime::cbor;
+use futures::stream::BoxStream;
+use futures::StreamExt;
 use fvm_ipld_encoding::RawBytes;
 use fvm_shared::address::Address;
 use fvm_shared::bigint::BigInt;
@@ -17,37 +22,69 @@ use fvm_shared::econ::TokenAmount;
 use ipc_gateway::{BottomUpCheckpoint, CrossMsg};
 use ipc_sdk::subnet_id::SubnetID;
 use num_traits::cast::ToPrimitive;
+use reqwest::Client;
 use serde::de::DeserializeOwned;
-use serde_json::json;
+use serde_json::{json, Value};
+use tokio::sync::{OnceCell, RwLock};
 
 use crate::constants::GATEWAY_ACTOR_ADDRESS;
-use crate::jsonrpc::{JsonRpcClient, JsonRpcClientImpl, NO_PARAMS};
+use crate::jsonrpc::{HttpClients, HttpPoolOptions, JsonRpcClient, JsonRpcClientImpl, NO_PARAMS};
 use crate::lotus::json::ToJson;
-use crate::lotus::message::chain::ChainHeadResponse;
-use crate::lotus::message::ipc::{IPCReadGatewayStateResponse, IPCReadSubnetActorStateResponse};
+use crate::lotus::keystore::Keystore;
+use crate::lotus::message::chain::{
+    BlockMessages, ChainHeadResponse, ChainMessage, HeadChange, HeadChangeType, SyncStateResponse,
+};
+use crate::lotus::message::ipc::{
+    ActivationStatus, CheckpointPage, CrossMsgNonces, IPCReadGatewayStateResponse,
+    IPCReadSubnetActorStateResponse, SubnetCheckpointVoting,
+};
 use crate::lotus::message::mpool::{
-    MpoolPushMessage, MpoolPushMessageResponse, MpoolPushMessageResponseInner,
+    GasEstimate, MpoolPushMessage, MpoolPushMessageResponse, MpoolPushMessageResponseInner,
+    SignedMessage, UnsignedMessage, WalletSignMessageResponse,
+};
+use crate::lotus::message::state::{
+    ReadStateResponse, StateGetActorResponse, StateWaitMsgResponse,
 };
-use crate::lotus::message::state::{ReadStateResponse, StateWaitMsgResponse};
-use crate::lotus::message::wallet::{WalletKeyType, WalletListResponse};
-use crate::lotus::message::CIDMap;
-use crate::lotus::{LotusClient, NetworkVersion};
+use crate::lotus::message::wallet::{KeyInfo, Signature, WalletKeyType, WalletListResponse};
+use crate::lotus::message::{CIDMap, VersionResponse};
+use crate::lotus::{LotusClient, NetworkVersion, StateWaitTimeout};
 use crate::manager::SubnetInfo;
 
 // RPC methods
 mod methods {
     pub const MPOOL_PUSH_MESSAGE: &str = "Filecoin.MpoolPushMessage";
+    pub const MPOOL_PUSH: &str = "Filecoin.MpoolPush";
+    pub const MPOOL_GET_NONCE: &str = "Filecoin.MpoolGetNonce";
+    pub const GAS_ESTIMATE_MESSAGE_GAS: &str = "Filecoin.GasEstimateMessageGas";
     pub const STATE_WAIT_MSG: &str = "Filecoin.StateWaitMsg";
+    pub const STATE_SEARCH_MSG: &str = "Filecoin.StateSearchMsg";
     pub const STATE_NETWORK_NAME: &str = "Filecoin.StateNetworkName";
+    pub const VERSION: &str = "Filecoin.Version";
     pub const STATE_NETWORK_VERSION: &str = "Filecoin.StateNetworkVersion";
     pub const STATE_ACTOR_CODE_CIDS: &str = "Filecoin.StateActorCodeCIDs";
     pub const WALLET_NEW: &str = "Filecoin.WalletNew";
     pub const WALLET_LIST: &str = "Filecoin.WalletList";
     pub const WALLET_BALANCE: &str = "Filecoin.WalletBalance";
     pub const WALLET_DEFAULT_ADDRESS: &str = "Filecoin.WalletDefaultAddress";
+    pub const WALLET_SET_DEFAULT: &str = "Filecoin.WalletSetDefault";
+    pub const WALLET_HAS: &str = "Filecoin.WalletHas";
+    pub const WALLET_DELETE: &str = "Filecoin.WalletDelete";
+    pub const WALLET_EXPORT: &str = "Filecoin.WalletExport";
+    pub const WALLET_IMPORT: &str = "Filecoin.WalletImport";
+    pub const WALLET_SIGN: &str = "Filecoin.WalletSign";
+    pub const WALLET_SIGN_MESSAGE: &str = "Filecoin.WalletSignMessage";
+    pub const WALLET_VERIFY: &str = "Filecoin.WalletVerify";
+    pub const STATE_LOOKUP_ID: &str = "Filecoin.StateLookupID";
+    pub const STATE_ACCOUNT_KEY: &str = "Filecoin.StateAccountKey";
     pub const STATE_READ_STATE: &str = "Filecoin.StateReadState";
+    pub const STATE_GET_ACTOR: &str = "Filecoin.StateGetActor";
     pub const CHAIN_HEAD: &str = "Filecoin.ChainHead";
+    pub const CHAIN_NOTIFY: &str = "Filecoin.ChainNotify";
+    pub const CHAIN_GET_MESSAGE: &str = "Filecoin.ChainGetMessage";
+    pub const CHAIN_GET_BLOCK_MESSAGES: &str = "Filecoin.ChainGetBlockMessages";
+    pub const SYNC_STATE: &str = "Filecoin.SyncState";
     pub const GET_TIPSET_BY_HEIGHT: &str = "Filecoin.ChainGetTipSetByHeight";
+    pub const GET_TIPSET_BY_KEY: &str = "Filecoin.ChainGetTipSet";
     pub const IPC_GET_PREV_CHECKPOINT_FOR_CHILD: &str = "Filecoin.IPCGetPrevCheckpointForChild";
     pub const IPC_GET_CHECKPOINT_TEMPLATE: &str = "Filecoin.IPCGetCheckpointTemplateSerialized";
     pub const IPC_GET_CHECKPOINT: &str = "Filecoin.IPCGetCheckpointSerialized";
@@ -62,16 +99,30 @@ mod methods {
 }
 
 /// The default state wait confidence value
-/// TODO: we can afford 2 epochs confidence (and even one)
-/// with Mir, but with Filecoin mainnet this should be increased
-/// in case there are reorgs.
-const STATE_WAIT_CONFIDENCE: u8 = 2;
+/// We can afford 2 epochs confidence (and even one) with Mir, but with Filecoin mainnet this
+/// should be increased in case there are reorgs, see `state_wait_confidence` in the subnet config.
+pub const STATE_WAIT_CONFIDENCE: u8 = 2;
 /// We dont set a limit on the look back epoch, i.e. check against latest block
-const STATE_WAIT_LOOK_BACK_NO_LIMIT: i8 = -1;
-/// We are not replacing any previous messages.
-/// TODO: when set to false, lotus raises `found message with equal nonce as the one we are looking`
-/// TODO: error. Should check this again.
-const STATE_WAIT_ALLOW_REPLACE: bool = true;
+pub const STATE_WAIT_LOOK_BACK_NO_LIMIT: i64 = -1;
+/// The default time a message is waited for before giving up, long enough for a few epochs of
+/// mainnet.
+pub const DEFAULT_STATE_WAIT_DEADLINE: Duration = Duration::from_secs(10 * 60);
+/// The period at which a message waited for with a deadline is looked up.
+const STATE_WAIT_POLL_PERIOD: Duration = Duration::from_secs(5);
+/// Whether waits resolve on a message replacing the one waited for, e.g. after a fee bump. The
+/// agent does not bump the fees of the messages it sends, so waits only ask for the message
+/// itself unless the caller bumps fees, see [`LotusClient::state_wait_msg_opts`].
+const STATE_WAIT_ALLOW_REPLACE: bool = false;
+/// The error lotus raises when the message looked up without allowing replacements was
+/// replaced by another message with the same nonce.
+const REPLACED_MESSAGE_ERROR: &str = "found message with equal nonce";
+/// The method used by lotus to push the values of a channel to the subscriber.
+const CHANNEL_VALUE_METHOD: &str = "xrpc.ch.val";
+/// An empty tipset key, which lotus interprets as the current chain head
+const NO_TIPSET: [CIDMap; 0] = [];
+/// The time the network version of a tipset is cached for. The version only changes at network
+/// upgrades, but the key of the current chain head is empty, so it can't be cached forever.
+pub const DEFAULT_CACHE_TTL: Duration = Duration::from_secs(30);
 
 /// The struct implementation for Lotus Client API. It allows for multiple different trait
 /// extension.
@@ -91,11 +142,62 @@ const STATE_WAIT_ALLOW_REPLACE: bool = true;
 /// ```
 pub struct LotusJsonRPCClient<T: JsonRpcClient> {
     client: T,
+    /// The code CIDs of the builtin actors, which never change for a network version. Each
+    /// version is fetched once, even if requested concurrently.
+    code_cids: RwLock<HashMap<NetworkVersion, Arc<OnceCell<HashMap<String, Cid>>>>>,
+    /// The network versions by tipset key, with the time they were fetched at.
+    network_versions: RwLock<HashMap<Vec<Cid>, (Instant, NetworkVersion)>>,
+    /// The time the network versions are cached for.
+    cache_ttl: Duration,
+    /// The number of epochs a message waited for must be buried under to be returned.
+    state_wait_confidence: u8,
+    /// The keys signed with locally instead of with the wallet of the node, if any.
+    keystore: Option<Keystore>,
 }
 
 impl<T: JsonRpcClient> LotusJsonRPCClient<T> {
     pub fn new(client: T) -> Self {
-        Self { client }
+        Self {
+            client,
+            code_cids: RwLock::new(HashMap::new()),
+            network_versions: RwLock::new(HashMap::new()),
+            cache_ttl: DEFAULT_CACHE_TTL,
+            state_wait_confidence: STATE_WAIT_CONFIDENCE,
+            keystore: None,
+        }
+    }
+
+    /// Signs with the keys in the keystore at `path` in [`LotusClient::wallet_sign`] and
+    /// [`LotusClient::wallet_sign_message`], falling back to the wallet of the node for the
+    /// addresses without a key in it. The messages signed locally are pushed to the node
+    /// already signed, so their senders don't need to be in its wallet.
+    pub fn with_keystore(mut self, path: PathBuf) -> Self {
+        self.keystore = Some(Keystore::new(path));
+        self
+    }
+
+    /// Sets the confidence used by [`LotusClient::state_wait_msg`], instead of
+    /// [`STATE_WAIT_CONFIDENCE`].
+    pub fn with_state_wait_confidence(mut self, confidence: u8) -> Self {
+        self.state_wait_confidence = confidence;
+        self
+    }
+
+    /// Sets the time the network versions are cached for, instead of [`DEFAULT_CACHE_TTL`].
+    pub fn with_cache_ttl(mut self, ttl: Duration) -> Self {
+        self.cache_ttl = ttl;
+        self
+    }
+
+    /// Drops the cached code CIDs of the builtin actors, so that they are fetched again.
+    pub async fn invalidate_code_cids(&self) {
+        self.code_cids.write().await.clear();
+    }
+
+    /// Drops all the cached network versions and code CIDs, so that they are fetched again.
+    pub async fn clear_cache(&self) {
+        self.network_versions.write().await.clear();
+        self.invalidate_code_cids().await;
     }
 }
 
@@ -105,41 +207,7 @@ impl<T: JsonRpcClient + Send + Sync> LotusClient for LotusJsonRPCClient<T> {
         &self,
         msg: MpoolPushMessage,
     ) -> Result<MpoolPushMessageResponseInner> {
-        let nonce = msg
-            .nonce
-            .map(|n| serde_json::Value::Number(n.into()))
-            .unwrap_or(serde_json::Value::Null);
-
-        let to_value = |t: Option<TokenAmount>| {
-            t.map(|n| serde_json::Value::Number(n.atto().to_u64().unwrap().into()))
-                .unwrap_or(serde_json::Value::Null)
-        };
-        let gas_limit = to_value(msg.gas_limit);
-        let gas_premium = to_value(msg.gas_premium);
-        let gas_fee_cap = to_value(msg.gas_fee_cap);
-        let max_fee = to_value(msg.max_fee);
-
-        // refer to: https://lotus.filecoin.io/reference/lotus/mpool/#mpoolpushmessage
-        let params = json!([
-            {
-                "to": msg.to.to_string(),
-                "from": msg.from.to_string(),
-                "value": msg.value.atto().to_string(),
-                "method": msg.method,
-                "params": msg.params,
-
-                // THESE ALL WILL AUTO POPULATE if null
-                "nonce": nonce,
-                "gas_limit": gas_limit,
-                "gas_fee_cap": gas_fee_cap,
-                "gas_premium": gas_premium,
-                "cid": CIDMap::from(msg.cid),
-                "version": serde_json::Value::Null,
-            },
-            {
-                "max_fee": max_fee
-            }
-        ]);
+        let params = mpool_push_message_params(msg)?;
 
         let r = self
             .client
@@ -150,20 +218,127 @@ impl<T: JsonRpcClient + Send + Sync> LotusClient for LotusJsonRPCClient<T> {
         Ok(r.message)
     }
 
+    async fn mpool_push(&self, msg: SignedMessage) -> Result<Cid> {
+        // refer to: https://lotus.filecoin.io/reference/lotus/mpool/#mpoolpush
+        let r = self
+            .client
+            .request::<CIDMap>(methods::MPOOL_PUSH, json!([msg]))
+            .await?;
+        log::debug!("received mpool_push response: {r:?}");
+        Cid::try_from(r)
+    }
+
+    async fn mpool_get_nonce(&self, address: &Address) -> Result<u64> {
+        // refer to: https://lotus.filecoin.io/reference/lotus/mpool/#mpoolgetnonce
+        let r = self
+            .client
+            .request::<u64>(methods::MPOOL_GET_NONCE, json!([address.to_string()]))
+            .await?;
+        log::debug!("received mpool_get_nonce response: {r:?}");
+        Ok(r)
+    }
+
+    async fn gas_estimate_message_gas(
+        &self,
+        mut msg: MpoolPushMessage,
+        max_fee: Option<TokenAmount>,
+    ) -> Result<GasEstimate> {
+        // refer to: https://lotus.filecoin.io/reference/lotus/gas/#gasestimatemessagegas
+        msg.max_fee = max_fee;
+        let mut params = mpool_push_message_params(msg)?;
+        // estimate against the current chain head
+        if let Value::Array(params) = &mut params {
+            params.push(json!(NO_TIPSET));
+        }
+
+        let r = self
+            .client
+            .request::<GasEstimate>(methods::GAS_ESTIMATE_MESSAGE_GAS, params)
+            .await?;
+        log::debug!("received gas_estimate_message_gas response: {r:?}");
+        Ok(r)
+    }
+
     async fn state_wait_msg(&self, cid: Cid) -> Result<StateWaitMsgResponse> {
-        // refer to: https://lotus.filecoin.io/reference/lotus/state/#statewaitmsg
-        let params = json!([
-            CIDMap::from(cid),
-            STATE_WAIT_CONFIDENCE,
+        self.state_wait_msg_opts(cid, STATE_WAIT_ALLOW_REPLACE)
+            .await
+    }
+
+    async fn state_wait_msg_opts(
+        &self,
+        cid: Cid,
+        allow_replaced: bool,
+    ) -> Result<StateWaitMsgResponse> {
+        let params = state_wait_msg_params(
+            cid,
+            self.state_wait_confidence,
             STATE_WAIT_LOOK_BACK_NO_LIMIT,
-            STATE_WAIT_ALLOW_REPLACE,
-        ]);
+            allow_replaced,
+        );
+        self.state_wait_msg_request(params).await
+    }
 
+    async fn state_wait_msg_with(
+        &self,
+        cid: Cid,
+        confidence: u8,
+        lookback_limit: i64,
+    ) -> Result<StateWaitMsgResponse> {
+        let params =
+            state_wait_msg_params(cid, confidence, lookback_limit, STATE_WAIT_ALLOW_REPLACE);
+        self.state_wait_msg_request(params).await
+    }
+
+    async fn state_wait_msg_deadline(
+        &self,
+        cid: Cid,
+        deadline: Duration,
+    ) -> Result<StateWaitMsgResponse> {
+        self.state_wait_msg_polling(cid, deadline, STATE_WAIT_POLL_PERIOD)
+            .await
+    }
+
+    async fn state_search_msg(&self, cid: Cid) -> Result<Option<StateWaitMsgResponse>> {
+        // refer to: https://lotus.filecoin.io/reference/lotus/state/#statesearchmsg
+        let params = |allow_replaced: bool| {
+            json!([
+                NO_TIPSET,
+                CIDMap::from(cid),
+                STATE_WAIT_LOOK_BACK_NO_LIMIT,
+                allow_replaced,
+            ])
+        };
+
+        let r = match self
+            .client
+            .request::<Option<StateWaitMsgResponse>>(
+                methods::STATE_SEARCH_MSG,
+                params(STATE_WAIT_ALLOW_REPLACE),
+            )
+            .await
+        {
+            Err(e) if is_replaced(&e) => {
+                log::warn!("message {cid:} was replaced, looking up its replacement");
+                self.client
+                    .request::<Option<StateWaitMsgResponse>>(
+                        methods::STATE_SEARCH_MSG,
+                        params(true),
+                    )
+                    .await?
+            }
+            r => r?,
+        };
+        log::debug!("received state_search_msg response: {r:?}");
+        Ok(r)
+    }
+
+    async fn node_version(&self) -> Result<VersionResponse> {
+        // refer to: https://lotus.filecoin.io/reference/lotus/common/#version
         let r = self
             .client
-            .request::<StateWaitMsgResponse>(methods::STATE_WAIT_MSG, params)
+            .request::<VersionResponse>(methods::VERSION, NO_PARAMS)
             .await?;
-        log::debug!("received state_wait_msg response: {r:?}");
+        log::debug!("received node_version response: {r:?}");
         Ok(r)
     }
 
@@ -178,8 +353,18 @@ impl<T: JsonRpcClient + Send + Sync> LotusClient for LotusJsonRPCClient<T> {
     }
 
     async fn state_network_version(&self, tip_sets: Vec<Cid>) -> Result<NetworkVersion> {
+        if let Some((at, version)) = self.network_versions.read().await.get(&tip_sets) {
+            if at.elapsed() < self.cache_ttl {
+                return Ok(*version);
+            }
+        }
+
         // refer to: https://lotus.filecoin.io/reference/lotus/state/#statenetworkversion
-        let params = json!([tip_sets.into_iter().map(CIDMap::from).collect::<Vec<_>>()]);
+        let params = json!([tip_sets
+            .iter()
+            .cloned()
+            .map(CIDMap::from)
+            .collect::<Vec<_>>()]);
 
         let r = self
             .client
@@ -187,6 +372,11 @@ impl<T: JsonRpcClient + Send + Sync> LotusClient for LotusJsonRPCClient<T> {
             .await?;
 
         log::debug!("received state_network_version response: {r:?}");
+
+        // expired entries are dropped so that the cache does not grow with every tipset
+        let mut versions = self.network_versions.write().await;
+        versions.retain(|_, (at, _)| at.elapsed() < self.cache_ttl);
+        versions.insert(tip_sets, (Instant::now(), r));
         Ok(r)
     }
 
@@ -194,21 +384,40 @@ impl<T: JsonRpcClient + Send + Sync> LotusClient for LotusJsonRPCClient<T> {
         &self,
         network_version: NetworkVersion,
     ) -> Result<HashMap<String, Cid>> {
-        // refer to: https://github.com/filecoin-project/lotus/blob/master/documentation/en/api-v1-unstable-methods.md#stateactormanifestcid
-        let params = json!([network_version]);
+        // the read guard must be dropped before taking the write one
+        let cached = self.code_cids.read().await.get(&network_version).cloned();
+        let cell = match cached {
+            Some(cell) => cell,
+            None => self
+                .code_cids
+                .write()
+                .await
+                .entry(network_version)
+                .or_default()
+                .clone(),
+        };
 
-        let r = self
-            .client
-            .request::<HashMap<String, CIDMap>>(methods::STATE_ACTOR_CODE_CIDS, params)
-            .await?;
+        // concurrent callers wait for the first one to fetch them, which is retried on failure
+        let cids = cell
+            .get_or_try_init(|| async {
+                // refer to: https://github.com/filecoin-project/lotus/blob/master/documentation/en/api-v1-unstable-methods.md#stateactormanifestcid
+                let params = json!([network_version]);
 
-        let mut cids = HashMap::new();
-        for (key, cid_map) in r.into_iter() {
-            cids.insert(key, Cid::try_from(cid_map)?);
-        }
+                let r = self
+                    .client
+                    .request::<HashMap<String, CIDMap>>(methods::STATE_ACTOR_CODE_CIDS, params)
+                    .await?;
+
+                let mut cids = HashMap::new();
+                for (key, cid_map) in r.into_iter() {
+                    cids.insert(key, Cid::try_from(cid_map)?);
+                }
 
-        log::debug!("received state_actor_manifest_cid response: {cids:?}");
-        Ok(cids)
+                log::debug!("received state_actor_manifest_cid response: {cids:?}");
+                Ok::<_, anyhow::Error>(cids)
+            })
+            .await?;
+        Ok(cids.clone())
     }
 
     async fn wallet_default(&self) -> Result<Address> {
@@ -256,6 +465,171 @@ impl<T: JsonRpcClient + Send + Sync> LotusClient for LotusJsonRPCClient<T> {
         Ok(TokenAmount::from_atto(v))
     }
 
+    async fn wallet_set_default(&self, address: &Address) -> Result<()> {
+        // refer to: https://lotus.filecoin.io/reference/lotus/wallet/#walletsetdefault
+        self.client
+            .request::<()>(methods::WALLET_SET_DEFAULT, json!([address.to_string()]))
+            .await?;
+        log::debug!("set default wallet to {address:}");
+        Ok(())
+    }
+
+    async fn wallet_has(&self, address: &Address) -> Result<bool> {
+        // refer to: https://lotus.filecoin.io/reference/lotus/wallet/#wallethas
+        let r = self
+            .client
+            .request::<bool>(methods::WALLET_HAS, json!([address.to_string()]))
+            .await?;
+        log::debug!("received wallet_has response: {r:?}");
+        Ok(r)
+    }
+
+    async fn wallet_delete(&self, address: &Address) -> Result<()> {
+        // refer to: https://lotus.filecoin.io/reference/lotus/wallet/#walletdelete
+        self.client
+            .request::<()>(methods::WALLET_DELETE, json!([address.to_string()]))
+            .await?;
+        log::debug!("deleted wallet {address:}");
+        Ok(())
+    }
+
+    async fn wallet_export(&self, address: &Address) -> Result<KeyInfo> {
+        // refer to: https://lotus.filecoin.io/reference/lotus/wallet/#walletexport
+        let r = self
+            .client
+            .request::<KeyInfo>(methods::WALLET_EXPORT, json!([address.to_string()]))
+            .await?;
+        // the response holds the private key, do not log it
+        log::debug!("received wallet_export response for {address:}");
+        Ok(r)
+    }
+
+    async fn wallet_import(&self, key_info: &KeyInfo) -> Result<Address> {
+        // refer to: https://lotus.filecoin.io/reference/lotus/wallet/#walletimport
+        let r = self
+            .client
+            .request::<String>(methods::WALLET_IMPORT, json!([key_info]))
+            .await?;
+        log::debug!("received wallet_import response: {r:?}");
+
+        let addr = Address::from_str(&r)?;
+        Ok(addr)
+    }
+
+    async fn wallet_sign(&self, address: &Address, data: &[u8]) -> Result<Signature> {
+        if let Some(keystore) = &self.keystore {
+            if let Some(signature) = keystore.sign(address, data)? {
+                log::debug!("signed with the key of {address:} in the local keystore");
+                return Ok(signature);
+            }
+        }
+
+        // refer to: https://lotus.filecoin.io/reference/lotus/wallet/#walletsign
+        let data = base64::engine::general_purpose::STANDARD.encode(data);
+        let r = self
+            .client
+            .request::<Signature>(methods::WALLET_SIGN, json!([address.to_string(), data]))
+            .await?;
+        log::debug!("received wallet_sign response: {r:?}");
+        Ok(r)
+    }
+
+    async fn wallet_sign_message(&self, msg: UnsignedMessage) -> Result<SignedMessage> {
+        if let Some(keystore) = &self.keystore {
+            // lotus signs the bytes of the cid of the message
+            if let Some(signature) = keystore.sign(&msg.from, &msg.cid()?.to_bytes())? {
+                log::debug!(
+                    "signed message with the key of {:} in the local keystore",
+                    msg.from
+                );
+                return Ok(SignedMessage {
+                    message: msg,
+                    signature,
+                });
+            }
+        }
+
+        // refer to: https://lotus.filecoin.io/reference/lotus/wallet/#walletsignmessage
+        let r = self
+            .client
+            .request::<WalletSignMessageResponse>(
+                methods::WALLET_SIGN_MESSAGE,
+                json!([msg.from.to_string(), msg]),
+            )
+            .await?;
+        log::debug!("received wallet_sign_message response: {r:?}");
+        Ok(SignedMessage {
+            message: msg,
+            signature: r.signature,
+        })
+    }
+
+    async fn wallet_verify(
+        &self,
+        address: &Address,
+        data: &[u8],
+        signature: &Signature,
+    ) -> Result<bool> {
+        // refer to: https://lotus.filecoin.io/reference/lotus/wallet/#walletverify
+        let data = base64::engine::general_purpose::STANDARD.encode(data);
+        let r = self
+            .client
+            .request::<bool>(
+                methods::WALLET_VERIFY,
+                json!([address.to_string(), data, signature]),
+            )
+            .await?;
+        log::debug!("received wallet_verify response: {r:?}");
+        Ok(r)
+    }
+
+    async fn state_lookup_id(&self, address: &Address, tip_set: Option<Cid>) -> Result<Address> {
+        // refer to: https://lotus.filecoin.io/reference/lotus/state/#statelookupid
+        let r = self
+            .client
+            .request::<String>(
+                methods::STATE_LOOKUP_ID,
+                json!([address.to_string(), tip_set_param(tip_set)]),
+            )
+            .await?;
+        log::debug!("received state_lookup_id response: {r:?}");
+
+        let addr = Address::from_str(&r)?;
+        Ok(addr)
+    }
+
+    async fn state_account_key(&self, address: &Address, tip_set: Option<Cid>) -> Result<Address> {
+        // refer to: https://lotus.filecoin.io/reference/lotus/state/#stateaccountkey
+        let r = self
+            .client
+            .request::<String>(
+                methods::STATE_ACCOUNT_KEY,
+                json!([address.to_string(), tip_set_param(tip_set)]),
+            )
+            .await?;
+        log::debug!("received state_account_key response: {r:?}");
+
+        let addr = Address::from_str(&r)?;
+        Ok(addr)
+    }
+
+    async fn state_get_actor(
+        &self,
+        address: Address,
+        tipset: Cid,
+    ) -> Result<StateGetActorResponse> {
+        // refer to: https://lotus.filecoin.io/reference/lotus/state/#stategetactor
+        let r = self
+            .client
+            .request::<StateGetActorResponse>(
+                methods::STATE_GET_ACTOR,
+                json!([address.to_string(), [CIDMap::from(tipset)]]),
+            )
+            .await?;
+        log::debug!("received state_get_actor response: {r:?}");
+        Ok(r)
+    }
+
     async fn read_state<State: DeserializeOwned + Debug>(
         &self,
         address: Address,
@@ -282,6 +656,46 @@ impl<T: JsonRpcClient + Send + Sync> LotusClient for LotusJsonRPCClient<T> {
         Ok(r)
     }
 
+    async fn sync_state(&self) -> Result<SyncStateResponse> {
+        // refer to: https://lotus.filecoin.io/reference/lotus/sync/#syncstate
+        let r = self
+            .client
+            .request::<SyncStateResponse>(methods::SYNC_STATE, NO_PARAMS)
+            .await?;
+        log::debug!("received sync_state response: {r:?}");
+        Ok(r)
+    }
+
+    async fn chain_notify(&self) -> Result<BoxStream<'static, ChainHeadResponse>> {
+        // refer to: https://lotus.filecoin.io/reference/lotus/chain/#chainnotify
+        let chan = self.client.subscribe(methods::CHAIN_NOTIFY).await?;
+        let stream = chan.flat_map(|v| futures::stream::iter(heads_from_notification(v)));
+        Ok(stream.boxed())
+    }
+
+    async fn chain_get_message(&self, cid: Cid) -> Result<ChainMessage> {
+        // refer to: https://lotus.filecoin.io/reference/lotus/chain/#chaingetmessage
+        let r = self
+            .client
+            .request::<ChainMessage>(methods::CHAIN_GET_MESSAGE, json!([CIDMap::from(cid)]))
+            .await?;
+        log::debug!("received chain_get_message response: {r:?}");
+        Ok(r)
+    }
+
+    async fn chain_get_block_messages(&self, block: Cid) -> Result<BlockMessages> {
+        // refer to: https://lotus.filecoin.io/reference/lotus/chain/#chaingetblockmessages
+        let r = self
+            .client
+            .request::<BlockMessages>(
+                methods::CHAIN_GET_BLOCK_MESSAGES,
+                json!([CIDMap::from(block)]),
+            )
+            .await?;
+        log::debug!("received chain_get_block_messages response: {r:?}");
+        Ok(r)
+    }
+
     async fn get_tipset_by_height(
         &self,
         epoch: ChainEpoch,
@@ -298,10 +712,21 @@ impl<T: JsonRpcClient + Send + Sync> LotusClient for LotusJsonRPCClient<T> {
         Ok(r)
     }
 
+    async fn get_tipset_by_key(&self, key: Vec<Cid>) -> Result<ChainHeadResponse> {
+        // refer to: https://lotus.filecoin.io/reference/lotus/chain/#chaingettipset
+        let key = key.into_iter().map(CIDMap::from).collect::<Vec<_>>();
+        let r = self
+            .client
+            .request::<ChainHeadResponse>(methods::GET_TIPSET_BY_KEY, json!([key]))
+            .await?;
+        log::debug!("received get_tipset_by_key response: {r:?}");
+        Ok(r)
+    }
+
     async fn ipc_get_prev_checkpoint_for_child(
         &self,
         child_subnet_id: SubnetID,
-    ) -> Result<Option<CIDMap>> {
+    ) -> Result<Option<Cid>> {
         if child_subnet_id.parent().is_none() {
             return Err(anyhow!("The child_subnet_id must be a valid child subnet"));
         }
@@ -311,7 +736,9 @@ impl<T: JsonRpcClient + Send + Sync> LotusClient for LotusJsonRPCClient<T> {
             .client
             .request::<Option<CIDMap>>(methods::IPC_GET_PREV_CHECKPOINT_FOR_CHILD, params)
             .await?;
-        Ok(r)
+        log::debug!("received ipc_get_prev_checkpoint_for_child response: {r:?}");
+
+        r.map(Cid::try_from).transpose()
     }
 
     async fn ipc_get_checkpoint_template(&self, epoch: ChainEpoch) -> Result<BottomUpCheckpoint> {
@@ -362,6 +789,43 @@ impl<T: JsonRpcClient + Send + Sync> LotusClient for LotusJsonRPCClient<T> {
         Ok(r)
     }
 
+    async fn ipc_crossmsg_nonces(
+        &self,
+        subnet_id: &SubnetID,
+        gateway_addr: Address,
+        tip_set: Cid,
+        from_topdown: u64,
+    ) -> Result<CrossMsgNonces> {
+        let params = json!([gateway_addr.to_string(), [CIDMap::from(tip_set)]]);
+        let state = self
+            .client
+            .request::<IPCReadGatewayStateResponse>(methods::IPC_READ_GATEWAY_STATE, params)
+            .await?;
+
+        // the top-down nonce of the child is not part of the gateway state, but follows the last
+        // message committed for it. Only the messages from `from_topdown` are read, rather
+        // than the whole history of the subnet.
+        let next_topdown = self
+            .ipc_get_topdown_msgs(subnet_id, gateway_addr, tip_set, from_topdown)
+            .await?
+            .last()
+            .map_or(from_topdown, |m| m.msg.nonce + 1);
+
+        let r = CrossMsgNonces {
+            gateway_applied_topdown: state.applied_topdown_nonce,
+            next_topdown,
+            applied_bottomup: state.applied_bottomup_nonce,
+            next_bottomup: state.bottomup_nonce,
+        };
+        log::debug!("received ipc_crossmsg_nonces response: {r:?}");
+        Ok(r)
+    }
+
+    async fn ipc_topdown_finality_threshold(&self, tip_set: Cid) -> Result<ChainEpoch> {
+        let state = self.ipc_read_gateway_state(tip_set).await?;
+        Ok(state.top_down_finality().threshold)
+    }
+
     async fn ipc_read_subnet_actor_state(
         &self,
         subnet_id: &SubnetID,
@@ -380,13 +844,71 @@ impl<T: JsonRpcClient + Send + Sync> LotusClient for LotusJsonRPCClient<T> {
         Ok(r)
     }
 
-    async fn ipc_list_child_subnets(&self, gateway_addr: Address) -> Result<Vec<SubnetInfo>> {
-        let params = json!([gateway_addr.to_string()]);
+    async fn ipc_last_checkpoint_epoch(
+        &self,
+        subnet_id: &SubnetID,
+        tip_set: Cid,
+    ) -> Result<ChainEpoch> {
+        let params = json!([subnet_id.to_json(), [CIDMap::from(tip_set)]]);
+
+        // only the checkpoint voting is decoded from the state of the subnet actor
         let r = self
             .client
-            .request::<Option<Vec<SubnetInfo>>>(methods::IPC_LIST_CHILD_SUBNETS, params)
+            .request::<SubnetCheckpointVoting>(methods::IPC_READ_SUBNET_ACTOR_STATE, params)
             .await?;
-        Ok(r.unwrap_or_default())
+        log::debug!("received ipc_last_checkpoint_epoch response: {r:?}");
+
+        Ok(r.bottom_up_checkpoint_voting.last_voting_executed)
+    }
+
+    async fn ipc_activation_status(
+        &self,
+        subnet_id: &SubnetID,
+        tip_set: Cid,
+    ) -> Result<ActivationStatus> {
+        let state = self.ipc_read_subnet_actor_state(subnet_id, tip_set).await?;
+        Ok(state.activation_status())
+    }
+
+    async fn ipc_list_child_subnets(
+        &self,
+        gateway_addr: Address,
+        tip_set: Option<Cid>,
+    ) -> Result<Vec<SubnetInfo>> {
+        let params = match tip_set {
+            Some(tip_set) => json!([gateway_addr.to_string(), [CIDMap::from(tip_set)]]),
+            None => json!([gateway_addr.to_string()]),
+        };
+        let r = self
+            .client
+            .request::<Value>(methods::IPC_LIST_CHILD_SUBNETS, params)
+            .await?;
+
+        if !r.is_null() {
+            return serde_json::from_value::<Vec<SubnetInfo>>(r).map_err(|e| {
+                anyhow!("unexpected response listing the child subnets of {gateway_addr:}: {e:}")
+            });
+        }
+
+        // lotus returns null both for a gateway without children and for one that does not
+        // exist, e.g. a wrong address or a node that is not synced yet
+        log::warn!("listing the child subnets of {gateway_addr:} returned null");
+        let tip_set = match tip_set {
+            Some(tip_set) => tip_set,
+            None => {
+                let head = self.chain_head().await?;
+                Cid::try_from(
+                    head.cids
+                        .first()
+                        .ok_or_else(|| anyhow!("chain head has no cids"))?
+                        .clone(),
+                )?
+            }
+        };
+        self.state_get_actor(gateway_addr, tip_set)
+            .await
+            .map_err(|e| anyhow!("cannot find gateway actor {gateway_addr:}: {e:}"))?;
+        Ok(vec![])
     }
 
     async fn ipc_validator_has_voted_bottomup(
@@ -424,6 +946,25 @@ impl<T: JsonRpcClient + Send + Sync> LotusClient for LotusJsonRPCClient<T> {
         tip_set: Cid,
         nonce: u64,
     ) -> Result<Vec<CrossMsg>> {
+        let msgs = self
+            .ipc_get_topdown_msgs_lenient(subnet_id, gateway_addr, tip_set, nonce)
+            .await?;
+        match decoded_prefix(msgs) {
+            (msgs, None) => Ok(msgs),
+            (msgs, Some(e)) => Err(anyhow!(
+                "{e:}, after decoding {:} top-down cross-msgs",
+                msgs.len()
+            )),
+        }
+    }
+
+    async fn ipc_get_topdown_msgs_lenient(
+        &self,
+        subnet_id: &SubnetID,
+        gateway_addr: Address,
+        tip_set: Cid,
+        nonce: u64,
+    ) -> Result<Vec<Result<CrossMsg>>> {
         let params = json!([
             gateway_addr.to_string(),
             subnet_id.to_json(),
@@ -435,19 +976,15 @@ impl<T: JsonRpcClient + Send + Sync> LotusClient for LotusJsonRPCClient<T> {
             .request::<Vec<String>>(methods::IPC_GET_TOPDOWN_MESSAGES, params)
             .await?;
 
-        let msgs = r
-            .iter()
-            .map(|x| {
-                let bytes = base64::engine::general_purpose::STANDARD
-                    .decode(x)
-                    .map_err(|_| anyhow!("cannot decode cross-msgs base64 string"))?;
-
-                cbor::deserialize::<CrossMsg>(&RawBytes::new(bytes), "checkpoint")
-                    .map_err(|_| anyhow!("cannot decode cross-msgs base64 string"))
+        // the messages are returned in nonce order from `nonce`, which names a message that
+        // cannot be decoded
+        Ok(r.iter()
+            .enumerate()
+            .map(|(i, x)| {
+                decode_base64_cbor(x, i, "top-down cross-msg")
+                    .map_err(|e| anyhow!("{e:} (nonce {:})", nonce + i as u64))
             })
-            .collect::<Result<_>>()?;
-
-        Ok(msgs)
+            .collect())
     }
 
     async fn ipc_get_genesis_epoch_for_subnet(
@@ -477,27 +1014,341 @@ impl<T: JsonRpcClient + Send + Sync> LotusClient for LotusJsonRPCClient<T> {
 
         let checkpoints = r
             .iter()
-            .map(|x| {
-                let bytes = base64::engine::general_purpose::STANDARD
-                    .decode(x)
-                    .map_err(|_| anyhow!("cannot decode checkpoint base64 string"))?;
+            .enumerate()
+            .map(|(i, x)| decode_base64_cbor(x, i, "checkpoint"))
+            .collect();
+        match decoded_prefix(checkpoints) {
+            (checkpoints, None) => Ok(checkpoints),
+            (checkpoints, Some(e)) => Err(anyhow!(
+                "{e:}, after decoding {:} checkpoints from epoch {from_epoch:}",
+                checkpoints.len()
+            )),
+        }
+    }
+
+    async fn ipc_list_checkpoints_paged(
+        &self,
+        subnet_id: SubnetID,
+        from_epoch: ChainEpoch,
+        to_epoch: ChainEpoch,
+        period: ChainEpoch,
+        offset: usize,
+        limit: usize,
+    ) -> Result<CheckpointPage> {
+        if period <= 0 {
+            return Err(anyhow!("invalid checkpoint period {period:}"));
+        }
+
+        // lotus cannot page the checkpoints, but at most one is committed per period, so only
+        // the epochs of the `limit` periods of the page are listed
+        let start = (offset as ChainEpoch)
+            .checked_mul(period)
+            .and_then(|skipped| from_epoch.checked_add(skipped))
+            .filter(|start| *start <= to_epoch);
+        let start = match start {
+            None => {
+                return Ok(CheckpointPage {
+                    checkpoints: vec![],
+                    next_offset: None,
+                })
+            }
+            Some(start) => start,
+        };
+        let end = (limit as ChainEpoch)
+            .checked_mul(period)
+            .and_then(|window| start.checked_add(window - 1))
+            .map_or(to_epoch, |end| end.min(to_epoch));
+
+        let params = json!([subnet_id.to_json(), start, end]);
+        let r = self
+            .client
+            .request::<Vec<String>>(methods::IPC_LIST_BOTTOMUP_CHECKPOINTS, params)
+            .await?;
+
+        let checkpoints = r
+            .iter()
+            .enumerate()
+            .take(limit)
+            .map(|(i, x)| decode_base64_cbor(x, offset + i, "checkpoint"))
+            .collect::<Result<Vec<_>>>()?;
+        let next_offset = if end < to_epoch {
+            Some(offset + limit)
+        } else {
+            None
+        };
 
-                cbor::deserialize::<BottomUpCheckpoint>(&RawBytes::new(bytes), "checkpoint")
-                    .map_err(|_| anyhow!("cannot decode checkpoint base64 string"))
-            })
-            .collect::<Result<_>>()?;
+        Ok(CheckpointPage {
+            checkpoints,
+            next_offset,
+        })
+    }
+}
+
+/// Decodes an object returned by lotus as base64 encoded cbor, the `index`-th `what` of the
+/// response, telling the two possible failures apart in the error.
+pub(crate) fn decode_base64_cbor<T: DeserializeOwned>(
+    s: &str,
+    index: usize,
+    what: &str,
+) -> Result<T> {
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(s)
+        .map_err(|e| anyhow!("cannot decode base64 string of {what} {index}: {e}"))?;
+
+    cbor::deserialize::<T>(&RawBytes::new(bytes), what)
+        .map_err(|e| anyhow!("cannot deserialize cbor of {what} {index}: {e}"))
+}
+
+/// Splits the results of decoding the objects of a response into the objects decoded up to the
+/// first one that cannot be, and the error decoding that one if any. The objects after it are
+/// dropped, for callers that need them in order.
+pub(crate) fn decoded_prefix<T>(results: Vec<Result<T>>) -> (Vec<T>, Option<anyhow::Error>) {
+    let mut decoded = vec![];
+    for r in results {
+        match r {
+            Ok(x) => decoded.push(x),
+            Err(e) => return (decoded, Some(e)),
+        }
+    }
+    (decoded, None)
+}
 
-        Ok(checkpoints)
+/// Extracts the heads from a `ChainNotify` subscription message. The first message received is the
+/// response to the subscription request with the id of the channel, after which lotus pushes the
+/// head changes as `xrpc.ch.val` notifications with the channel id and the list of changes as
+/// params. Reverted tipsets are skipped.
+pub(crate) fn heads_from_notification(value: Value) -> Vec<ChainHeadResponse> {
+    if value.get("method").and_then(Value::as_str) != Some(CHANNEL_VALUE_METHOD) {
+        log::debug!("ignoring chain notify message: {value:?}");
+        return vec![];
     }
+
+    let changes = match value
+        .get("params")
+        .and_then(|p| p.get(1))
+        .map(|c| serde_json::from_value::<Vec<HeadChange>>(c.clone()))
+    {
+        Some(Ok(changes)) => changes,
+        _ => {
+            log::error!("cannot parse chain notify head changes: {value:?}");
+            return vec![];
+        }
+    };
+
+    changes
+        .into_iter()
+        .filter(|c| c.change_type != HeadChangeType::Revert)
+        .map(|c| c.val)
+        .collect()
 }
 
 impl LotusJsonRPCClient<JsonRpcClientImpl> {
     /// A constructor that returns a `LotusJsonRPCClient` from a `Subnet`. The returned
-    /// `LotusJsonRPCClient` makes requests to the URL defined in the `Subnet`.
+    /// `LotusJsonRPCClient` makes requests to the URL defined in the `Subnet`, failing over to
+    /// its fallback URLs when it cannot be reached.
     pub fn from_subnet(subnet: &crate::config::Subnet) -> Self {
+        Self::from_subnet_with_http_client(subnet, pool_options(subnet).client())
+    }
+
+    /// Same as [`LotusJsonRPCClient::from_subnet`], but sends the requests through the HTTP
+    /// client of `http_clients` for the pool options of the subnet, sharing its connections
+    /// with the other clients created from them.
+    pub fn from_subnet_with_clients(
+        subnet: &crate::config::Subnet,
+        http_clients: &HttpClients,
+    ) -> Self {
+        Self::from_subnet_with_http_client(subnet, http_clients.get(&pool_options(subnet)))
+    }
+
+    fn from_subnet_with_http_client(subnet: &crate::config::Subnet, http_client: Client) -> Self {
         let url = subnet.jsonrpc_api_http.clone();
         let auth_token = subnet.auth_token.as_deref();
-        let jsonrpc_client = JsonRpcClientImpl::new(url, auth_token);
-        LotusJsonRPCClient::new(jsonrpc_client)
+        let mut jsonrpc_client =
+            JsonRpcClientImpl::with_shared_http_client(url, auth_token, http_client)
+                .with_headers(&subnet.extra_headers);
+        if !subnet.jsonrpc_api_http_fallbacks.is_empty() {
+            jsonrpc_client =
+                jsonrpc_client.with_fallbacks(subnet.jsonrpc_api_http_fallbacks.clone());
+        }
+        LotusJsonRPCClient::new(jsonrpc_client).with_subnet_defaults(subnet)
+    }
+
+    /// Same as [`LotusJsonRPCClient::from_subnet`], but makes requests to the websocket URL
+    /// defined in the `Subnet`, as needed by subscriptions such as [`LotusClient::chain_notify`].
+    pub fn from_subnet_ws(subnet: &crate::config::Subnet) -> Result<Self> {
+        let url = subnet
+            .jsonrpc_api_ws
+            .clone()
+            .ok_or_else(|| anyhow!("no websocket endpoint configured for subnet {:}", subnet.id))?;
+        let auth_token = subnet.auth_token.as_deref();
+        let jsonrpc_client =
+            JsonRpcClientImpl::new(url, auth_token).with_headers(&subnet.extra_headers);
+        Ok(LotusJsonRPCClient::new(jsonrpc_client).with_subnet_defaults(subnet))
+    }
+
+    /// Applies the settings of `subnet` that override the defaults of the client.
+    fn with_subnet_defaults(mut self, subnet: &crate::config::Subnet) -> Self {
+        if let Some(confidence) = subnet.state_wait_confidence {
+            self = self.with_state_wait_confidence(confidence);
+        }
+        if let Some(path) = &subnet.keystore_path {
+            self = self.with_keystore(path.clone());
+        }
+        self
+    }
+}
+
+impl<T: JsonRpcClient + Send + Sync> LotusJsonRPCClient<T> {
+    /// Sends a `Filecoin.StateWaitMsg` request with `params`. If the message was replaced while
+    /// replacements were not allowed, the request is sent again allowing them, so that the wait
+    /// resolves on the replacement instead of failing.
+    async fn state_wait_msg_request(&self, mut params: Value) -> Result<StateWaitMsgResponse> {
+        // refer to: https://lotus.filecoin.io/reference/lotus/state/#statewaitmsg
+        let r = match self
+            .client
+            .request::<StateWaitMsgResponse>(methods::STATE_WAIT_MSG, params.clone())
+            .await
+        {
+            Err(e) if is_replaced(&e) => {
+                log::warn!(
+                    "message {:} was replaced, waiting for its replacement",
+                    params[0]
+                );
+                params[3] = json!(true);
+                self.client
+                    .request::<StateWaitMsgResponse>(methods::STATE_WAIT_MSG, params)
+                    .await?
+            }
+            r => r?,
+        };
+        log::debug!("received state_wait_msg response: {r:?}");
+        Ok(r)
+    }
+
+    /// Looks up `cid` every `period` until it is executed with the confidence of the client, or
+    /// returns a [`StateWaitTimeout`] error once `deadline` elapses. The lookups failing, e.g.
+    /// while the node restarts, are retried until then.
+    pub(crate) async fn state_wait_msg_polling(
+        &self,
+        cid: Cid,
+        deadline: Duration,
+        period: Duration,
+    ) -> Result<StateWaitMsgResponse> {
+        let poll = async {
+            loop {
+                match self.state_search_msg_confident(cid).await {
+                    Ok(Some(r)) => return r,
+                    Ok(None) => {}
+                    Err(e) => log::warn!("cannot look up message {cid:}, retrying: {e:}"),
+                }
+                tokio::time::sleep(period).await;
+            }
+        };
+
+        tokio::time::timeout(deadline, poll)
+            .await
+            .map_err(|_| StateWaitTimeout { cid, deadline }.into())
     }
+
+    /// Returns the execution of `cid` if it is on chain with the confidence of the client.
+    async fn state_search_msg_confident(&self, cid: Cid) -> Result<Option<StateWaitMsgResponse>> {
+        let r = match self.state_search_msg(cid).await? {
+            Some(r) => r,
+            None => return Ok(None),
+        };
+        let head = self.chain_head().await?;
+        Ok((head.height >= r.height + self.state_wait_confidence as u64).then_some(r))
+    }
+}
+
+/// Returns the options of the http client of `subnet`.
+fn pool_options(subnet: &crate::config::Subnet) -> HttpPoolOptions {
+    let mut options = HttpPoolOptions::default();
+    if let Some(secs) = subnet.http_pool_idle_timeout_secs {
+        options.idle_timeout = Some(Duration::from_secs(secs));
+    }
+    options.proxy_url = subnet.proxy_url.clone();
+    options
+}
+
+/// Checks if the error returned by a message lookup is caused by the message being replaced by
+/// another one with the same nonce, when replacements were not allowed.
+fn is_replaced(e: &anyhow::Error) -> bool {
+    e.to_string().contains(REPLACED_MESSAGE_ERROR)
+}
+
+/// Returns the tipset key param of a state request, the empty key standing for the chain head.
+fn tip_set_param(tip_set: Option<Cid>) -> Value {
+    match tip_set {
+        Some(cid) => json!([CIDMap::from(cid)]),
+        None => json!(NO_TIPSET),
+    }
+}
+
+/// Builds the params of a `Filecoin.StateWaitMsg` request.
+pub(crate) fn state_wait_msg_params(
+    cid: Cid,
+    confidence: u8,
+    lookback_limit: i64,
+    allow_replaced: bool,
+) -> Value {
+    json!([
+        CIDMap::from(cid),
+        confidence,
+        lookback_limit,
+        allow_replaced
+    ])
+}
+
+/// Builds the params of a `Filecoin.MpoolPushMessage` request. Token amounts are encoded as
+/// decimal strings of attoFIL, as lotus expects for big ints, so that amounts above the
+/// `u64` range are not truncated. The gas limit is an amount of gas units, which lotus
+/// decodes as an `int64`.
+pub(crate) fn mpool_push_message_params(msg: MpoolPushMessage) -> Result<Value> {
+    let nonce = msg
+        .nonce
+        .map(|n| serde_json::Value::Number(n.into()))
+        .unwrap_or(serde_json::Value::Null);
+
+    let to_value = |t: Option<TokenAmount>| {
+        t.map(|n| serde_json::Value::String(n.atto().to_string()))
+            .unwrap_or(serde_json::Value::Null)
+    };
+    let gas_limit = match msg.gas_limit {
+        Some(n) => {
+            let limit = n
+                .atto()
+                .to_i64()
+                .ok_or_else(|| anyhow!("gas limit {:} out of range", n.atto()))?;
+            serde_json::Value::Number(limit.into())
+        }
+        None => serde_json::Value::Null,
+    };
+    let gas_premium = to_value(msg.gas_premium);
+    let gas_fee_cap = to_value(msg.gas_fee_cap);
+    let max_fee = to_value(msg.max_fee);
+
+    // refer to: https://lotus.filecoin.io/reference/lotus/mpool/#mpoolpushmessage
+    // the keys are the names of the fields of lotus' `Message` and `MessageSendSpec`, which it
+    // matches ignoring their case only, so fields named in snake case would be left unset.
+    Ok(json!([
+        {
+            "To": msg.to.to_string(),
+            "From": msg.from.to_string(),
+            "Value": msg.value.atto().to_string(),
+            "Method": msg.method,
+            "Params": msg.params,
+
+            // THESE ALL WILL AUTO POPULATE if null
+            "Nonce": nonce,
+            "GasLimit": gas_limit,
+            "GasFeeCap": gas_fee_cap,
+            "GasPremium": gas_premium,
+            "CID": CIDMap::from(msg.cid),
+            "Version": serde_json::Value::Null,
+        },
+        {
+            "MaxFee": max_fee
+        }
+    ]))
 }
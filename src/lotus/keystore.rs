@@ -0,0 +1,177 @@
+// Copyright 2022-2023 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! A local keystore, to sign with keys that are not in the wallet of the node.
+
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Result};
+use fvm_shared::address::Address;
+use zeroize::Zeroize;
+
+use crate::lotus::message::wallet::{KeyInfo, Signature, WalletKeyType};
+
+/// The type of the secp256k1 signatures, see [`Signature`].
+const SECP256K1_SIG_TYPE: u8 = 1;
+
+/// A directory holding one key per file, named after the address of the key. Each file holds
+/// the key in the format printed by `lotus wallet export`, or its json key info.
+pub(crate) struct Keystore {
+    path: PathBuf,
+}
+
+impl Keystore {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// Returns the key of `address`, or `None` if the keystore does not hold it.
+    pub fn key_info(&self, address: &Address) -> Result<Option<KeyInfo>> {
+        let path = self.path.join(address.to_string());
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let mut contents = std::fs::read_to_string(&path)?;
+        let key_info = parse_key_info(&contents);
+        contents.zeroize();
+        let key_info =
+            key_info.map_err(|e| anyhow!("cannot read key of {address:} from {path:?}: {e:}"))?;
+
+        if key_address(&key_info)? != *address {
+            return Err(anyhow!(
+                "key in {path:?} does not belong to address {address:}"
+            ));
+        }
+        Ok(Some(key_info))
+    }
+
+    /// Signs `data` with the key of `address`, or returns `None` if the keystore does not
+    /// hold it.
+    pub fn sign(&self, address: &Address, data: &[u8]) -> Result<Option<Signature>> {
+        match self.key_info(address)? {
+            Some(key_info) => Ok(Some(sign(&key_info, data)?)),
+            None => Ok(None),
+        }
+    }
+}
+
+fn parse_key_info(contents: &str) -> Result<KeyInfo> {
+    let contents = contents.trim();
+    if contents.starts_with('{') {
+        Ok(serde_json::from_str(contents)?)
+    } else {
+        KeyInfo::from_lotus_export(contents)
+    }
+}
+
+fn secret_key(key_info: &KeyInfo) -> Result<libsecp256k1::SecretKey> {
+    if key_info.key_type != WalletKeyType::Secp256k1 {
+        return Err(anyhow!(
+            "only secp256k1 keys can sign locally, not {:}",
+            key_info.key_type
+        ));
+    }
+    libsecp256k1::SecretKey::parse_slice(&key_info.private_key)
+        .map_err(|e| anyhow!("invalid secp256k1 private key: {e:?}"))
+}
+
+/// Returns the address of a secp256k1 key.
+pub(crate) fn key_address(key_info: &KeyInfo) -> Result<Address> {
+    let public_key = libsecp256k1::PublicKey::from_secret_key(&secret_key(key_info)?);
+    Ok(Address::new_secp256k1(&public_key.serialize())?)
+}
+
+/// Signs `data` as the wallet of lotus does, i.e. the recoverable secp256k1 signature of the
+/// blake2b-256 hash of `data`.
+pub(crate) fn sign(key_info: &KeyInfo, data: &[u8]) -> Result<Signature> {
+    let secret_key = secret_key(key_info)?;
+    let hash = blake2b_simd::Params::new().hash_length(32).hash(data);
+    let message = libsecp256k1::Message::parse_slice(hash.as_bytes())
+        .map_err(|e| anyhow!("cannot sign data: {e:?}"))?;
+
+    let (signature, recovery_id) = libsecp256k1::sign(&message, &secret_key);
+    let mut data = signature.serialize().to_vec();
+    data.push(recovery_id.serialize());
+    Ok(Signature {
+        sig_type: SECP256K1_SIG_TYPE,
+        data,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use fvm_shared::address::Address;
+
+    use crate::lotus::keystore::{key_address, sign, Keystore};
+    use crate::lotus::message::wallet::{KeyInfo, WalletKeyType};
+
+    fn secp256k1_key() -> KeyInfo {
+        KeyInfo {
+            key_type: WalletKeyType::Secp256k1,
+            private_key: vec![7; 32],
+        }
+    }
+
+    #[test]
+    fn test_sign() {
+        let key_info = secp256k1_key();
+        let data = b"checkpoint";
+        let signature = sign(&key_info, data).unwrap();
+        assert_eq!(signature.sig_type, 1);
+        assert_eq!(signature.data.len(), 65);
+
+        // the signer is recovered from the signature
+        let hash = blake2b_simd::Params::new().hash_length(32).hash(data);
+        let message = libsecp256k1::Message::parse_slice(hash.as_bytes()).unwrap();
+        let recovered = libsecp256k1::recover(
+            &message,
+            &libsecp256k1::Signature::parse_standard_slice(&signature.data[..64]).unwrap(),
+            &libsecp256k1::RecoveryId::parse(signature.data[64]).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            Address::new_secp256k1(&recovered.serialize()).unwrap(),
+            key_address(&key_info).unwrap()
+        );
+
+        let bls = KeyInfo {
+            key_type: WalletKeyType::BLS,
+            private_key: vec![7; 32],
+        };
+        assert!(sign(&bls, data).is_err());
+    }
+
+    #[test]
+    fn test_keystore() {
+        let dir = tempfile::tempdir().unwrap();
+        let keystore = Keystore::new(dir.path().to_path_buf());
+        let key_info = secp256k1_key();
+        let address = key_address(&key_info).unwrap();
+        assert!(keystore.sign(&address, b"data").unwrap().is_none());
+
+        std::fs::write(
+            dir.path().join(address.to_string()),
+            key_info.to_lotus_export().unwrap(),
+        )
+        .unwrap();
+        assert_eq!(keystore.key_info(&address).unwrap(), Some(key_info.clone()));
+        assert!(keystore.sign(&address, b"data").unwrap().is_some());
+
+        // the json key info is read as well
+        std::fs::write(
+            dir.path().join(address.to_string()),
+            serde_json::to_string(&key_info).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(keystore.key_info(&address).unwrap(), Some(key_info));
+
+        // a key stored under the address of another key
+        let other = Address::new_id(1000);
+        std::fs::write(
+            dir.path().join(other.to_string()),
+            serde_json::to_string(&secp256k1_key()).unwrap(),
+        )
+        .unwrap();
+        assert!(keystore.key_info(&other).is_err());
+    }
+}
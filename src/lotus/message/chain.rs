@@ -1,8 +1,23 @@
 // Copyright 2022-2023 Protocol Labs
 // SPDX-License-Identifier: MIT
-use serde::Deserialize;
+use anyhow::anyhow;
+use base64::Engine;
+use cid::Cid;
+use fvm_ipld_encoding::RawBytes;
+use fvm_shared::address::Address;
+use fvm_shared::bigint::BigInt;
+use fvm_shared::clock::ChainEpoch;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::MethodNum;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::str::FromStr;
 
+use crate::lotus::message::deserialize::{
+    deserialize_address_from_str, deserialize_cids_from_maps, deserialize_null_as_default,
+    deserialize_token_amount_from_str,
+};
+use crate::lotus::message::wallet::Signature;
 use crate::lotus::message::CIDMap;
 
 /// A simplified struct representing a `ChainHead` response that does not decode the `blocks` field.
@@ -16,3 +31,195 @@ pub struct ChainHeadResponse {
     #[allow(dead_code)]
     pub height: u64,
 }
+
+impl ChainHeadResponse {
+    /// Returns the base fee the messages of the tipset paid, i.e. the `ParentBaseFee` of its
+    /// blocks, which is the same for all of them.
+    pub fn parent_base_fee(&self) -> anyhow::Result<TokenAmount> {
+        let fee = self
+            .blocks
+            .first()
+            .and_then(|b| b["ParentBaseFee"].as_str())
+            .ok_or_else(|| anyhow!("tipset at height {:} has no base fee", self.height))?;
+        Ok(TokenAmount::from_atto(BigInt::from_str(fee)?))
+    }
+
+    /// Returns the key of the parent tipset, i.e. the `Parents` of its blocks, which are the
+    /// same for all of them.
+    pub fn parents(&self) -> anyhow::Result<Vec<Cid>> {
+        let parents = self
+            .blocks
+            .first()
+            .map(|b| b["Parents"].clone())
+            .ok_or_else(|| anyhow!("tipset at height {:} has no blocks", self.height))?;
+        serde_json::from_value::<Vec<CIDMap>>(parents)?
+            .into_iter()
+            .map(Cid::try_from)
+            .collect()
+    }
+}
+
+/// The direction of the base fee over a series of tipsets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FeeTrend {
+    Rising,
+    Falling,
+    Stable,
+}
+
+/// The base fees of a series of consecutive tipsets, in epoch order.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BaseFeeHistory {
+    pub fees: Vec<(ChainEpoch, TokenAmount)>,
+    pub trend: FeeTrend,
+}
+
+impl BaseFeeHistory {
+    /// The relative change, in percent, between the average base fee of the older and the newer
+    /// half of the series above which the base fee is considered to be rising or falling.
+    const TREND_THRESHOLD_PCT: u64 = 10;
+
+    /// Builds the history from the base fees sampled at different epochs, in any order. Epochs
+    /// sampled more than once, e.g. because of null rounds, are only counted once.
+    pub fn new(mut fees: Vec<(ChainEpoch, TokenAmount)>) -> Self {
+        fees.sort_by_key(|(epoch, _)| *epoch);
+        fees.dedup_by_key(|(epoch, _)| *epoch);
+        let trend = Self::trend(&fees);
+        Self { fees, trend }
+    }
+
+    /// Returns the fee cap of a message to be included while the base fee is rising, twice the
+    /// highest base fee of the series, as the base fee rises by at most 12.5% per epoch. Returns
+    /// `None` if the base fee is not rising, leaving the fee cap to the estimate of the node.
+    pub fn fee_cap(&self) -> Option<TokenAmount> {
+        if self.trend != FeeTrend::Rising {
+            return None;
+        }
+        let highest = self.fees.iter().map(|(_, f)| f.atto()).max()?;
+        Some(TokenAmount::from_atto(highest * 2u64))
+    }
+
+    fn trend(fees: &[(ChainEpoch, TokenAmount)]) -> FeeTrend {
+        if fees.len() < 2 {
+            return FeeTrend::Stable;
+        }
+
+        let (older, newer) = fees.split_at(fees.len() / 2);
+        let average = |fees: &[(ChainEpoch, TokenAmount)]| {
+            fees.iter().map(|(_, f)| f.atto().clone()).sum::<BigInt>() / fees.len()
+        };
+        let older = average(older);
+        let newer = average(newer);
+
+        let threshold = &older * Self::TREND_THRESHOLD_PCT / 100u64;
+        if newer > &older + &threshold {
+            FeeTrend::Rising
+        } else if newer < &older - &threshold {
+            FeeTrend::Falling
+        } else {
+            FeeTrend::Stable
+        }
+    }
+}
+
+/// The type of a head change notified by `ChainNotify`.
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum HeadChangeType {
+    /// The current head, always sent as the first notification of the subscription.
+    Current,
+    /// A tipset applied to the chain.
+    Apply,
+    /// A tipset reverted from the chain.
+    Revert,
+}
+
+/// A head change notified by `ChainNotify`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct HeadChange {
+    #[serde(rename = "Type")]
+    pub change_type: HeadChangeType,
+    pub val: ChainHeadResponse,
+}
+
+/// A simplified struct representing a `SyncState` response, only decoding the fields needed
+/// to find out the head of the network the node is syncing to.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct SyncStateResponse {
+    pub active_syncs: Option<Vec<ActiveSync>>,
+}
+
+/// An active sync of the node towards the `target` tipset.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ActiveSync {
+    pub target: Option<ChainHeadResponse>,
+}
+
+impl SyncStateResponse {
+    /// Returns the height of the highest tipset the node is syncing to, if any.
+    pub fn target_height(&self) -> Option<u64> {
+        self.active_syncs
+            .iter()
+            .flatten()
+            .filter_map(|s| s.target.as_ref().map(|t| t.height))
+            .max()
+    }
+}
+
+/// A message included in the chain, as returned by `ChainGetMessage`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ChainMessage {
+    #[serde(deserialize_with = "deserialize_address_from_str")]
+    pub to: Address,
+    #[serde(deserialize_with = "deserialize_address_from_str")]
+    pub from: Address,
+    pub nonce: u64,
+    #[serde(deserialize_with = "deserialize_token_amount_from_str")]
+    pub value: TokenAmount,
+    pub method: MethodNum,
+    /// The base64 encoded params of the message
+    pub params: Option<String>,
+}
+
+/// A message included in the chain with the signature of its sender, as returned among the
+/// secp messages of `ChainGetBlockMessages`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct SignedChainMessage {
+    pub message: ChainMessage,
+    pub signature: Signature,
+}
+
+/// The messages included in a block, as returned by `ChainGetBlockMessages`. The BLS and secp
+/// messages are kept apart as lotus returns them.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct BlockMessages {
+    #[serde(deserialize_with = "deserialize_null_as_default", default)]
+    pub bls_messages: Vec<ChainMessage>,
+    #[serde(deserialize_with = "deserialize_null_as_default", default)]
+    pub secpk_messages: Vec<SignedChainMessage>,
+    /// The CIDs of the BLS messages followed by those of the secp messages.
+    #[serde(deserialize_with = "deserialize_cids_from_maps", default)]
+    pub cids: Vec<Cid>,
+}
+
+impl ChainMessage {
+    /// Returns the decoded params of the message, empty if the message has no params.
+    pub fn params(&self) -> anyhow::Result<RawBytes> {
+        let params = match &self.params {
+            None => return Ok(RawBytes::default()),
+            Some(p) => p,
+        };
+
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(params)
+            .map_err(|e| anyhow!("cannot decode message params: {e:}"))?;
+        Ok(RawBytes::new(bytes))
+    }
+}
@@ -2,6 +2,8 @@
 // SPDX-License-Identifier: MIT
 //! Deserialization utils for lotus/ipc types.
 
+use base64::Engine;
+use cid::Cid;
 use fvm_shared::address::Address;
 use fvm_shared::bigint::BigInt;
 use fvm_shared::econ::TokenAmount;
@@ -12,6 +14,8 @@ use serde::{Deserialize, Deserializer};
 use std::fmt::Formatter;
 use std::str::FromStr;
 
+use crate::lotus::message::CIDMap;
+
 /// A serde deserialization method to deserialize a ipc address from map
 pub fn deserialize_ipc_address_from_map<'de, D>(
     deserializer: D,
@@ -139,3 +143,49 @@ where
     }
     deserializer.deserialize_str(AddressVisitor)
 }
+
+/// A serde deserialization method to deserialize a cid from a `{"/": "<cid>"}` map
+pub fn deserialize_cid_from_map<'de, D>(deserializer: D) -> anyhow::Result<Cid, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let cid_map = CIDMap::deserialize(deserializer)?;
+    let cid = cid_map
+        .cid
+        .ok_or_else(|| D::Error::custom("cid not found"))?;
+    Cid::from_str(&cid).map_err(D::Error::custom)
+}
+
+/// A serde deserialization method to deserialize cids from a list of `{"/": "<cid>"}` maps, null
+/// being an empty list
+pub fn deserialize_cids_from_maps<'de, D>(deserializer: D) -> anyhow::Result<Vec<Cid>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    <Option<Vec<CIDMap>>>::deserialize(deserializer)?
+        .unwrap_or_default()
+        .into_iter()
+        .map(|m| Cid::try_from(m).map_err(D::Error::custom))
+        .collect()
+}
+
+/// A serde deserialization method to deserialize a value that lotus returns as null when empty,
+/// e.g. nil slices, into its default
+pub fn deserialize_null_as_default<'de, D, T>(deserializer: D) -> anyhow::Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Default + Deserialize<'de>,
+{
+    Ok(<Option<T>>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+/// A serde deserialization method to deserialize bytes from a base64 string
+pub fn deserialize_bytes_from_base64<'de, D>(deserializer: D) -> anyhow::Result<Vec<u8>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s = String::deserialize(deserializer)?;
+    base64::engine::general_purpose::STANDARD
+        .decode(s)
+        .map_err(D::Error::custom)
+}
@@ -1,8 +1,12 @@
 // Copyright 2022-2023 Protocol Labs
 // SPDX-License-Identifier: MIT
 
+use std::str::FromStr;
+
 use cid::Cid;
 use fvm_ipld_encoding::RawBytes;
+use fvm_shared::address::Address;
+use fvm_shared::bigint::BigInt;
 use fvm_shared::clock::ChainEpoch;
 use fvm_shared::econ::TokenAmount;
 use fvm_shared::MethodNum;
@@ -37,21 +41,140 @@ pub struct IPCReadGatewayStateResponse {
     pub bottom_up_check_period: ChainEpoch,
     pub top_down_check_period: ChainEpoch,
     pub applied_topdown_nonce: u64,
+    /// The nonce of the next bottom-up message sent from the network of the gateway.
+    pub bottomup_nonce: u64,
+    pub applied_bottomup_nonce: u64,
     pub top_down_checkpoint_voting: Voting,
     pub initialized: bool,
 }
 
+impl IPCReadGatewayStateResponse {
+    /// Returns when the top-down messages committed in the parent are applied in the subnet.
+    pub fn top_down_finality(&self) -> TopDownFinality {
+        let last_executed_epoch = self.top_down_checkpoint_voting.last_voting_executed;
+        TopDownFinality {
+            threshold: self.top_down_check_period,
+            last_executed_epoch,
+            next_epoch: last_executed_epoch + self.top_down_check_period,
+        }
+    }
+}
+
+/// The cross-message nonces of a gateway and of one of its child subnets, all read at the same
+/// tipset so that they are consistent with each other.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CrossMsgNonces {
+    /// The nonce of the next top-down message from its own parent to be applied in the network
+    /// of the gateway, unrelated to the top-down messages of the child.
+    pub gateway_applied_topdown: u64,
+    /// The nonce of the next top-down message to be committed for the child subnet.
+    pub next_topdown: u64,
+    /// The nonce of the next bottom-up message to be applied in the network of the gateway.
+    pub applied_bottomup: u64,
+    /// The nonce of the next bottom-up message sent from the network of the gateway.
+    pub next_bottomup: u64,
+}
+
+/// A page of the checkpoints committed in a range of epochs.
+#[derive(Debug)]
+pub struct CheckpointPage {
+    pub checkpoints: Vec<BottomUpCheckpoint>,
+    /// The offset of the next page, if there are more checkpoints in the range.
+    pub next_offset: Option<usize>,
+}
+
+/// The delay with which a subnet applies the top-down messages committed in its parent. The
+/// messages committed up to a parent epoch are applied once the top-down checkpoint for that
+/// epoch is executed, so messages that have not reached `next_epoch` are not yet final rather
+/// than stuck.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TopDownFinality {
+    /// The number of parent epochs between top-down checkpoints.
+    pub threshold: ChainEpoch,
+    /// The parent epoch of the last top-down checkpoint executed.
+    pub last_executed_epoch: ChainEpoch,
+    /// The parent epoch of the next top-down checkpoint to be executed.
+    pub next_epoch: ChainEpoch,
+}
+
 /// The state of a subnet actor. The struct omits all fields that are not relevant for the
 /// execution of the IPC agent.
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "PascalCase")]
 pub struct IPCReadSubnetActorStateResponse {
+    /// The collateral a validator needs to stake to join the validator set
+    #[serde(deserialize_with = "deserialize_token_amount_from_str")]
+    pub min_validator_stake: TokenAmount,
+    #[serde(deserialize_with = "deserialize_token_amount_from_str")]
+    pub total_stake: TokenAmount,
     pub bottom_up_check_period: ChainEpoch,
     pub validator_set: ValidatorSet,
     pub min_validators: u64,
     pub bottom_up_checkpoint_voting: Voting,
 }
 
+impl IPCReadSubnetActorStateResponse {
+    /// Returns the activation status of the subnet in this state.
+    pub fn activation_status(&self) -> ActivationStatus {
+        let validators = self
+            .validator_set
+            .validators
+            .as_ref()
+            .map(|v| v.len() as u64)
+            .unwrap_or_default();
+        ActivationStatus {
+            min_validators: self.min_validators,
+            validators,
+            active: validators >= self.min_validators,
+        }
+    }
+
+    /// Returns the stake of `validator` in this state, zero if it is not in the validator set.
+    pub fn validator_stake(&self, validator: &Address) -> anyhow::Result<TokenAmount> {
+        let addr = validator.to_string();
+        match self
+            .validator_set
+            .validators
+            .iter()
+            .flatten()
+            .find(|v| v.addr == addr)
+        {
+            None => Ok(TokenAmount::from_atto(0)),
+            Some(v) => {
+                let weight = BigInt::from_str(&v.weight).map_err(|_| {
+                    anyhow::anyhow!("invalid stake of validator {:}: {:?}", v.addr, v.weight)
+                })?;
+                Ok(TokenAmount::from_atto(weight))
+            }
+        }
+    }
+}
+
+/// The bottom-up checkpoint voting of a subnet actor, read from its state without decoding the
+/// rest of it.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct SubnetCheckpointVoting {
+    pub bottom_up_checkpoint_voting: Voting,
+}
+
+/// Whether a subnet has enough validators to be active or is still collecting them.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ActivationStatus {
+    /// Minimum number of validators required for the subnet to activate.
+    pub min_validators: u64,
+    /// Number of validators currently in the validator set.
+    pub validators: u64,
+    pub active: bool,
+}
+
+impl ActivationStatus {
+    /// Returns the number of validators still needed for the subnet to activate.
+    pub fn missing_validators(&self) -> u64 {
+        self.min_validators.saturating_sub(self.validators)
+    }
+}
+
 /// A subset of the voting structure with information
 /// about a checkpoint voting
 #[derive(Deserialize, Debug)]
@@ -99,7 +222,7 @@ pub struct ValidatorSet {
 }
 
 /// The validator struct. See `ValidatorSet` comment on why we need this duplicated definition.
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
 pub struct Validator {
     pub addr: String,
     pub net_addr: String,
@@ -256,6 +379,8 @@ impl TryFrom<BottomUpCheckpointWrapper> for BottomUpCheckpoint {
 
 #[cfg(test)]
 mod tests {
+    use fvm_shared::econ::TokenAmount;
+
     use crate::lotus::message::ipc::IPCReadSubnetActorStateResponse;
 
     #[test]
@@ -264,7 +389,8 @@ mod tests {
         {"Name":"test2","ParentID":{"Parent":"/root","Actor":"t00"},"IPCGatewayAddr":"t064","Consensus":3,"MinValidatorStake":"1000000000000000000","TotalStake":"10000000000000000000","Stake":{"/":"bafy2bzacebentzoqaapingrxwknlxqcusl23rqaa7cwb42u76fgvb25nxpmhq"},"Status":1,"Genesis":null,"BottomUpCheckPeriod":10,"TopDownCheckPeriod":10,"GenesisEpoch":0,"CommittedCheckpoints":{"/":"bafy2bzaceamp42wmmgr2g2ymg46euououzfyck7szknvfacqscohrvaikwfay"},"ValidatorSet":{"validators":[{"addr":"t1cp4q4lqsdhob23ysywffg2tvbmar5cshia4rweq","net_addr":"test","weight":"10000000000000000000"}],"configuration_number":1},"MinValidators":1,"PreviousExecutedCheckpoint":{"/":"bafy2bzacedkoa623kvi5gfis2yks7xxjl73vg7xwbojz4tpq63dd5jpfz757i"},"BottomUpCheckpointVoting":{"GenesisEpoch":0,"SubmissionPeriod":10,"LastVotingExecuted":0,"ExecutableEpochQueue":null,"EpochVoteSubmission":{"/":"bafy2bzaceamp42wmmgr2g2ymg46euououzfyck7szknvfacqscohrvaikwfay"},"Ratio":{"Num":2,"Denom":3}}}
         "#;
 
-        let r = serde_json::from_str::<IPCReadSubnetActorStateResponse>(raw);
-        assert!(r.is_ok());
+        let r = serde_json::from_str::<IPCReadSubnetActorStateResponse>(raw).unwrap();
+        assert_eq!(r.min_validator_stake, TokenAmount::from_whole(1));
+        assert_eq!(r.total_stake, TokenAmount::from_whole(10));
     }
 }
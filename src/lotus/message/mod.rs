@@ -59,3 +59,39 @@ impl From<Cid> for CIDMap {
         }
     }
 }
+
+/// The version of a lotus node, as returned by `Version`.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "PascalCase")]
+pub struct VersionResponse {
+    /// The version of the node, e.g. `1.21.0+mainnet+git.d8b4f8b`.
+    pub version: String,
+    /// The version of the API, encoded as `major << 16 | minor << 8 | patch`.
+    #[serde(rename = "APIVersion")]
+    pub api_version: u32,
+    /// The number of seconds between epochs.
+    pub block_delay: u64,
+}
+
+impl VersionResponse {
+    /// Returns the major, minor and patch of the version of the node, without its pre-release
+    /// and build metadata, or `None` if it is not a semantic version.
+    pub fn semver(&self) -> Option<(u64, u64, u64)> {
+        let core = self.version.split(['+', '-']).next()?;
+        let mut parts = core.split('.').map(|p| p.parse::<u64>().ok());
+        let version = (parts.next()??, parts.next()??, parts.next()??);
+        match parts.next() {
+            None => Some(version),
+            Some(_) => None,
+        }
+    }
+
+    /// Returns the major, minor and patch of the version of the API.
+    pub fn api_semver(&self) -> (u32, u32, u32) {
+        (
+            self.api_version >> 16,
+            (self.api_version >> 8) & 0xff,
+            self.api_version & 0xff,
+        )
+    }
+}
@@ -1,11 +1,20 @@
 // Copyright 2022-2023 Protocol Labs
 // SPDX-License-Identifier: MIT
+use crate::lotus::message::deserialize::deserialize_token_amount_from_str;
+use crate::lotus::message::serialize::{
+    serialize_address_to_str, serialize_bytes_to_base64, serialize_token_amount_to_atto,
+};
+use crate::lotus::message::wallet::Signature;
 use crate::lotus::message::CIDMap;
+use crate::serialization::car::dag_cbor_cid;
+use anyhow::anyhow;
 use cid::Cid;
+use fil_actors_runtime::cbor;
+use fvm_ipld_encoding::RawBytes;
 use fvm_shared::address::Address;
 use fvm_shared::econ::TokenAmount;
 use fvm_shared::MethodNum;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::str::FromStr;
 
 #[derive(Deserialize, Debug)]
@@ -86,4 +95,215 @@ impl MpoolPushMessage {
             max_fee: None,
         }
     }
+
+    /// Returns a builder of a message, to set its optional fields by name.
+    pub fn builder() -> MpoolPushMessageBuilder {
+        MpoolPushMessageBuilder::default()
+    }
+}
+
+/// A builder of [`MpoolPushMessage`]. The recipient, sender and method must be set, the other
+/// fields are optional, the gas parameters and the nonce being filled by the node if not set.
+///
+/// # Examples
+/// ```
+/// use fvm_shared::address::Address;
+/// use fvm_shared::econ::TokenAmount;
+/// use fvm_shared::METHOD_SEND;
+///
+/// use ipc_agent::lotus::message::mpool::MpoolPushMessage;
+///
+/// let message = MpoolPushMessage::builder()
+///     .to(Address::new_id(1001))
+///     .from(Address::new_id(1000))
+///     .method(METHOD_SEND)
+///     .value(TokenAmount::from_whole(1))
+///     .gas_limit(10_000_000)
+///     .build()
+///     .unwrap();
+/// assert_eq!(message.gas_limit, Some(TokenAmount::from_atto(10_000_000)));
+/// ```
+#[derive(Default)]
+pub struct MpoolPushMessageBuilder {
+    to: Option<Address>,
+    from: Option<Address>,
+    value: Option<TokenAmount>,
+    method: Option<MethodNum>,
+    params: Vec<u8>,
+    nonce: Option<u64>,
+    gas_limit: Option<u64>,
+    gas_fee_cap: Option<TokenAmount>,
+    gas_premium: Option<TokenAmount>,
+    max_fee: Option<TokenAmount>,
+}
+
+impl MpoolPushMessageBuilder {
+    pub fn to(mut self, to: Address) -> Self {
+        self.to = Some(to);
+        self
+    }
+
+    pub fn from(mut self, from: Address) -> Self {
+        self.from = Some(from);
+        self
+    }
+
+    pub fn value(mut self, value: TokenAmount) -> Self {
+        self.value = Some(value);
+        self
+    }
+
+    pub fn method(mut self, method: MethodNum) -> Self {
+        self.method = Some(method);
+        self
+    }
+
+    /// Sets the params of the message, already encoded.
+    pub fn params(mut self, params: Vec<u8>) -> Self {
+        self.params = params;
+        self
+    }
+
+    /// Sets the params of the message to the cbor encoding of `params`, as actors expect them.
+    pub fn cbor_params<P: Serialize>(mut self, params: &P) -> anyhow::Result<Self> {
+        self.params = cbor::serialize(params, "message params")?.to_vec();
+        Ok(self)
+    }
+
+    pub fn nonce(mut self, nonce: u64) -> Self {
+        self.nonce = Some(nonce);
+        self
+    }
+
+    /// Sets the gas limit of the message, in units of gas.
+    pub fn gas_limit(mut self, gas_limit: u64) -> Self {
+        self.gas_limit = Some(gas_limit);
+        self
+    }
+
+    /// Sets the gas fee cap of the message, in attoFIL per unit of gas.
+    pub fn gas_fee_cap(mut self, gas_fee_cap: TokenAmount) -> Self {
+        self.gas_fee_cap = Some(gas_fee_cap);
+        self
+    }
+
+    /// Sets the gas premium of the message, in attoFIL per unit of gas.
+    pub fn gas_premium(mut self, gas_premium: TokenAmount) -> Self {
+        self.gas_premium = Some(gas_premium);
+        self
+    }
+
+    /// Sets the most the message can pay in fees, capping the fee cap estimated by the node.
+    pub fn max_fee(mut self, max_fee: TokenAmount) -> Self {
+        self.max_fee = Some(max_fee);
+        self
+    }
+
+    /// Returns the message, or an error if a required field is not set, or if the gas premium
+    /// is above the gas fee cap, which the node would reject.
+    pub fn build(self) -> anyhow::Result<MpoolPushMessage> {
+        let to = self
+            .to
+            .ok_or_else(|| anyhow!("message recipient not set"))?;
+        let from = self.from.ok_or_else(|| anyhow!("message sender not set"))?;
+        let method = self
+            .method
+            .ok_or_else(|| anyhow!("message method not set"))?;
+        if let (Some(fee_cap), Some(premium)) = (&self.gas_fee_cap, &self.gas_premium) {
+            if premium > fee_cap {
+                return Err(anyhow!(
+                    "gas premium {:} is above the gas fee cap {:}",
+                    premium.atto(),
+                    fee_cap.atto()
+                ));
+            }
+        }
+
+        let mut message = MpoolPushMessage::new(to, from, method, self.params);
+        if let Some(value) = self.value {
+            message.value = value;
+        }
+        message.nonce = self.nonce;
+        message.gas_limit = self.gas_limit.map(TokenAmount::from_atto);
+        message.gas_fee_cap = self.gas_fee_cap;
+        message.gas_premium = self.gas_premium;
+        message.max_fee = self.max_fee;
+        Ok(message)
+    }
+}
+
+/// The gas estimated for a message, decoded from the message returned by `GasEstimateMessageGas`.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct GasEstimate {
+    pub gas_limit: i64,
+    #[serde(deserialize_with = "deserialize_token_amount_from_str")]
+    pub gas_fee_cap: TokenAmount,
+    #[serde(deserialize_with = "deserialize_token_amount_from_str")]
+    pub gas_premium: TokenAmount,
+}
+
+impl GasEstimate {
+    /// The maximum fee the message can be charged, i.e. the gas limit at the fee cap.
+    pub fn max_cost(&self) -> TokenAmount {
+        TokenAmount::from_atto(self.gas_fee_cap.atto() * self.gas_limit)
+    }
+}
+
+/// A message with all its fields set, to be signed by its sender and pushed to the mempool
+/// with `MpoolPush`. Serialized as lotus' `Message`.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct UnsignedMessage {
+    pub version: u64,
+    #[serde(serialize_with = "serialize_address_to_str")]
+    pub to: Address,
+    #[serde(serialize_with = "serialize_address_to_str")]
+    pub from: Address,
+    pub nonce: u64,
+    #[serde(serialize_with = "serialize_token_amount_to_atto")]
+    pub value: TokenAmount,
+    pub gas_limit: i64,
+    #[serde(serialize_with = "serialize_token_amount_to_atto")]
+    pub gas_fee_cap: TokenAmount,
+    #[serde(serialize_with = "serialize_token_amount_to_atto")]
+    pub gas_premium: TokenAmount,
+    pub method: MethodNum,
+    #[serde(serialize_with = "serialize_bytes_to_base64")]
+    pub params: Vec<u8>,
+}
+
+impl UnsignedMessage {
+    /// Returns the CID of the message, i.e. the data its sender signs, computed as lotus does
+    /// from the dag-cbor encoding of its fields.
+    pub fn cid(&self) -> anyhow::Result<Cid> {
+        let fields = (
+            self.version,
+            &self.to,
+            &self.from,
+            self.nonce,
+            &self.value,
+            self.gas_limit,
+            &self.gas_fee_cap,
+            &self.gas_premium,
+            self.method,
+            RawBytes::new(self.params.clone()),
+        );
+        dag_cbor_cid(cbor::serialize(&fields, "message")?.bytes())
+    }
+}
+
+/// A message signed by its sender, as pushed to the mempool with `MpoolPush`.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct SignedMessage {
+    pub message: UnsignedMessage,
+    pub signature: Signature,
+}
+
+/// The signed message returned by `WalletSignMessage`, of which only the signature is read.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct WalletSignMessageResponse {
+    pub signature: Signature,
 }
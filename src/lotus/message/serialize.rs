@@ -1,5 +1,7 @@
 // Copyright 2022-2023 Protocol Labs
 // SPDX-License-Identifier: MIT
+use base64::Engine;
+use fvm_shared::address::Address;
 use fvm_shared::econ::TokenAmount;
 use ipc_sdk::subnet_id::SubnetID;
 use serde::Serializer;
@@ -11,9 +13,23 @@ where
     s.serialize_str(&id.to_string())
 }
 
+pub fn serialize_address_to_str<S>(addr: &Address, s: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    s.serialize_str(&addr.to_string())
+}
+
 pub fn serialize_token_amount_to_atto<S>(amount: &TokenAmount, s: S) -> Result<S::Ok, S::Error>
 where
     S: Serializer,
 {
     s.serialize_str(&amount.atto().to_string())
 }
+
+pub fn serialize_bytes_to_base64<S>(bytes: &[u8], s: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    s.serialize_str(&base64::engine::general_purpose::STANDARD.encode(bytes))
+}
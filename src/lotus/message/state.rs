@@ -2,24 +2,27 @@
 // SPDX-License-Identifier: MIT
 use anyhow::anyhow;
 use base64::Engine;
+use cid::Cid;
 use fil_actors_runtime::cbor;
 use fvm_ipld_encoding::RawBytes;
+use fvm_shared::econ::TokenAmount;
 use serde::de::DeserializeOwned;
 use serde::Deserialize;
 
+use crate::lotus::message::deserialize::{
+    deserialize_cid_from_map, deserialize_token_amount_from_str,
+};
 use crate::lotus::message::CIDMap;
 
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct StateWaitMsgResponse {
-    #[allow(dead_code)]
-    message: CIDMap,
-    #[allow(dead_code)]
+    /// The message the wait resolved on, which differs from the one waited for if replaced.
+    pub(crate) message: CIDMap,
     pub(crate) receipt: Receipt,
     #[allow(dead_code)]
     tip_set: Vec<CIDMap>,
-    #[allow(dead_code)]
-    height: u64,
+    pub(crate) height: u64,
 }
 
 #[derive(Debug, Deserialize)]
@@ -33,10 +36,22 @@ pub struct ReadStateResponse<State> {
     pub state: State,
 }
 
+/// The actor returned by `StateGetActor`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct StateGetActorResponse {
+    #[serde(deserialize_with = "deserialize_cid_from_map")]
+    pub code: Cid,
+    #[serde(deserialize_with = "deserialize_cid_from_map")]
+    pub head: Cid,
+    pub nonce: u64,
+    #[serde(deserialize_with = "deserialize_token_amount_from_str")]
+    pub balance: TokenAmount,
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct Receipt {
-    #[allow(dead_code)]
     exit_code: u32,
     #[serde(rename = "Return")]
     pub result: Option<String>,
@@ -45,25 +60,43 @@ pub struct Receipt {
 }
 
 impl Receipt {
-    pub fn parse_result_into<T: Default + DeserializeOwned>(self) -> anyhow::Result<T> {
-        if self.result.is_none() {
-            return Ok(Default::default());
-        }
+    /// Returns the exit code of the message, 0 if it succeeded.
+    pub fn exit_code(&self) -> u32 {
+        self.exit_code
+    }
+
+    /// Returns the raw return data of the message, empty if the method returned nothing.
+    pub fn return_bytes(&self) -> anyhow::Result<RawBytes> {
+        let r = match &self.result {
+            None => return Ok(RawBytes::default()),
+            Some(r) => r,
+        };
 
         let r = base64::engine::general_purpose::STANDARD
-            .decode(self.result.unwrap())
+            .decode(r)
             .map_err(|e| {
                 log::error!("cannot base64 decode due to {e:?}");
                 anyhow!("cannot decode return string")
             })?;
+        Ok(RawBytes::new(r))
+    }
 
-        cbor::deserialize::<T>(
-            &RawBytes::new(r),
-            "deserialize create subnet return response",
-        )
-        .map_err(|e| {
+    /// Decodes the return data of the message into `T`, the return type of the method
+    /// called. Returns `None` if the method returned nothing.
+    pub fn decode_return<T: DeserializeOwned>(&self) -> anyhow::Result<Option<T>> {
+        let bytes = self.return_bytes()?;
+        if bytes.is_empty() {
+            return Ok(None);
+        }
+
+        let r = cbor::deserialize::<T>(&bytes, "deserialize message return data").map_err(|e| {
             log::error!("cannot decode bytes due to {e:?}");
             anyhow!("cannot cbor deserialize return data")
-        })
+        })?;
+        Ok(Some(r))
+    }
+
+    pub fn parse_result_into<T: Default + DeserializeOwned>(self) -> anyhow::Result<T> {
+        Ok(self.decode_return()?.unwrap_or_default())
     }
 }
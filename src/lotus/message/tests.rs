@@ -3,11 +3,26 @@
 use fvm_shared::address::Address;
 use std::str::FromStr;
 
+use base64::Engine;
+use cid::Cid;
+use fil_actors_runtime::cbor;
+use fil_actors_runtime::types::InitExecReturn;
+
+use crate::lotus::message::chain::{
+    BaseFeeHistory, BlockMessages, ChainHeadResponse, ChainMessage, FeeTrend, SyncStateResponse,
+};
 use crate::lotus::message::deserialize::{
     deserialize_ipc_address_from_map, deserialize_subnet_id_from_map,
     deserialize_token_amount_from_str,
 };
-use crate::lotus::message::ipc::BottomUpCheckpointWrapper;
+use crate::lotus::message::ipc::{
+    ActivationStatus, BottomUpCheckpointWrapper, IPCReadGatewayStateResponse,
+    IPCReadSubnetActorStateResponse, TopDownFinality, Validator, ValidatorSet, Voting,
+};
+use crate::lotus::message::mpool::{GasEstimate, MpoolPushMessage, SignedMessage, UnsignedMessage};
+use crate::lotus::message::state::{Receipt, StateGetActorResponse};
+use crate::lotus::message::wallet::Signature;
+use crate::lotus::message::VersionResponse;
 use crate::manager::SubnetInfo;
 use fvm_shared::econ::TokenAmount;
 use ipc_gateway::Status;
@@ -182,3 +197,505 @@ fn test_checkpoint_template_from_str() {
     let w: BottomUpCheckpointWrapper = serde_json::from_str(raw_str).unwrap();
     assert_eq!(w.data.source, SubnetID::from_str("/root/t01002").unwrap());
 }
+
+#[test]
+fn test_sync_state_from_str() {
+    let raw_str = r#"
+    {
+    "ActiveSyncs": [
+        {
+            "WorkerID": 1,
+            "Base": {"Cids": [], "Blocks": [], "Height": 90},
+            "Target": {"Cids": [], "Blocks": [], "Height": 120},
+            "Stage": 5,
+            "Height": 100
+        },
+        {
+            "WorkerID": 2,
+            "Base": null,
+            "Target": null,
+            "Stage": 0,
+            "Height": 0
+        }
+    ],
+    "VMApplied": 0
+    }
+    "#;
+
+    let r: SyncStateResponse = serde_json::from_str(raw_str).unwrap();
+    assert_eq!(r.target_height(), Some(120));
+
+    let r: SyncStateResponse =
+        serde_json::from_str(r#"{"ActiveSyncs": null, "VMApplied": 0}"#).unwrap();
+    assert_eq!(r.target_height(), None);
+}
+
+#[test]
+fn test_receipt_decode_create_subnet_return() {
+    let exec_return = InitExecReturn {
+        id_address: Address::new_id(1001),
+        robust_address: Address::new_actor(b"subnet"),
+    };
+    let bytes = cbor::serialize(&exec_return, "init exec return").unwrap();
+    let encoded = base64::engine::general_purpose::STANDARD.encode(bytes.bytes());
+
+    let receipt: Receipt = serde_json::from_value(serde_json::json!({
+        "ExitCode": 0,
+        "Return": encoded,
+        "GasUsed": 100,
+    }))
+    .unwrap();
+
+    let r = receipt.decode_return::<InitExecReturn>().unwrap().unwrap();
+    let subnet = SubnetID::new_from_parent(&SubnetID::from_str("/root").unwrap(), r.id_address);
+    assert_eq!(subnet, SubnetID::from_str("/root/t01001").unwrap());
+
+    // falls back to the raw bytes when the return type is not known
+    assert_eq!(receipt.return_bytes().unwrap(), bytes);
+
+    let receipt: Receipt = serde_json::from_value(serde_json::json!({
+        "ExitCode": 0,
+        "Return": null,
+        "GasUsed": 100,
+    }))
+    .unwrap();
+    assert!(receipt.decode_return::<InitExecReturn>().unwrap().is_none());
+}
+
+#[test]
+fn test_gas_estimate_from_str() {
+    // recorded response of `Filecoin.GasEstimateMessageGas` for a value transfer
+    let raw_str = r#"
+    {
+        "Version": 0,
+        "To": "t01001",
+        "From": "t01000",
+        "Nonce": 3,
+        "Value": "1000000000000000000",
+        "GasLimit": 1541562,
+        "GasFeeCap": "101112",
+        "GasPremium": "100058",
+        "Method": 0,
+        "Params": null,
+        "CID": {
+            "/": "bafy2bzacebbpdegvr3i4cosewthysg5xkxpqfn2wfcz6mv2hmoktwbdxkax4s"
+        }
+    }
+    "#;
+
+    let r: GasEstimate = serde_json::from_str(raw_str).unwrap();
+    assert_eq!(r.gas_limit, 1541562);
+    assert_eq!(r.gas_fee_cap, TokenAmount::from_atto(101112));
+    assert_eq!(r.gas_premium, TokenAmount::from_atto(100058));
+    assert_eq!(r.max_cost(), TokenAmount::from_atto(101112u64 * 1541562));
+}
+
+#[test]
+fn test_state_get_actor_from_str() {
+    let raw_str = r#"
+    {
+        "Code": {
+            "/": "bafy2bzaceamp42wmmgr2g2ymg46euououzfyck7szknvfacqscohrvaikwfay"
+        },
+        "Head": {
+            "/": "bafy2bzacebentzoqaapingrxwknlxqcusl23rqaa7cwb42u76fgvb25nxpmhq"
+        },
+        "Nonce": 7,
+        "Balance": "100000000000000000000"
+    }
+    "#;
+
+    let r: StateGetActorResponse = serde_json::from_str(raw_str).unwrap();
+    assert_eq!(
+        r.code.to_string(),
+        "bafy2bzaceamp42wmmgr2g2ymg46euououzfyck7szknvfacqscohrvaikwfay"
+    );
+    assert_eq!(
+        r.head.to_string(),
+        "bafy2bzacebentzoqaapingrxwknlxqcusl23rqaa7cwb42u76fgvb25nxpmhq"
+    );
+    assert_eq!(r.nonce, 7);
+    assert_eq!(r.balance, TokenAmount::from_whole(100));
+
+    let r = serde_json::from_str::<StateGetActorResponse>(&raw_str.replace(
+        "bafy2bzaceamp42wmmgr2g2ymg46euououzfyck7szknvfacqscohrvaikwfay",
+        "invalid",
+    ));
+    assert!(r.is_err());
+}
+
+#[test]
+fn test_chain_message_from_str() {
+    let raw_str = r#"
+    {
+        "Version": 0,
+        "To": "t01001",
+        "From": "t01000",
+        "Nonce": 12,
+        "Value": "5000000000000000000",
+        "GasLimit": 1541562,
+        "GasFeeCap": "101112",
+        "GasPremium": "100058",
+        "Method": 2,
+        "Params": "gkMA6Qc=",
+        "CID": {
+            "/": "bafy2bzacedkoa623kvi5gfis2yks7xxjl73vg7xwbojz4tpq63dd5jpfz757i"
+        }
+    }
+    "#;
+
+    let m: ChainMessage = serde_json::from_str(raw_str).unwrap();
+    assert_eq!(m.to, Address::new_id(1001));
+    assert_eq!(m.from, Address::new_id(1000));
+    assert_eq!(m.nonce, 12);
+    assert_eq!(m.value, TokenAmount::from_whole(5));
+    assert_eq!(m.method, 2);
+    assert_eq!(m.params().unwrap().bytes(), &[0x82, 0x43, 0x00, 0xe9, 0x07]);
+
+    let m: ChainMessage = serde_json::from_str(&raw_str.replace(r#""gkMA6Qc=""#, "null")).unwrap();
+    assert!(m.params().unwrap().is_empty());
+}
+
+#[test]
+fn test_block_messages_from_str() {
+    let message = |nonce: u64| {
+        format!(
+            r#"{{"To": "t01001", "From": "t01000", "Nonce": {nonce}, "Value": "0", "Method": 0, "Params": null}}"#
+        )
+    };
+    let bls = "bafy2bzacedkoa623kvi5gfis2yks7xxjl73vg7xwbojz4tpq63dd5jpfz757i";
+    let secp = "bafy2bzacecnamqgqmifpluoeldx7zzglxcljo6oja4vrmtj7432rphldpdmm2";
+    let raw_str = format!(
+        r#"
+        {{
+            "BlsMessages": [{}],
+            "SecpkMessages": [{{"Message": {}, "Signature": {{"Type": 1, "Data": "AQID"}}}}],
+            "Cids": [{{"/": "{bls}"}}, {{"/": "{secp}"}}]
+        }}
+        "#,
+        message(1),
+        message(2)
+    );
+
+    let m: BlockMessages = serde_json::from_str(&raw_str).unwrap();
+    assert_eq!(m.bls_messages.len(), 1);
+    assert_eq!(m.bls_messages[0].nonce, 1);
+    assert_eq!(m.secpk_messages.len(), 1);
+    assert_eq!(m.secpk_messages[0].message.nonce, 2);
+    assert_eq!(m.secpk_messages[0].signature.data, vec![1, 2, 3]);
+    assert_eq!(
+        m.cids,
+        vec![Cid::from_str(bls).unwrap(), Cid::from_str(secp).unwrap()]
+    );
+
+    // lotus returns empty lists as null
+    let raw_str = r#"{"BlsMessages": null, "SecpkMessages": null, "Cids": null}"#;
+    let m: BlockMessages = serde_json::from_str(raw_str).unwrap();
+    assert!(m.bls_messages.is_empty());
+    assert!(m.secpk_messages.is_empty());
+    assert!(m.cids.is_empty());
+}
+
+#[test]
+fn test_version_response() {
+    let raw_str =
+        r#"{"Version": "1.21.0-rc2+mainnet+git.d8b4f8b", "APIVersion": 131840, "BlockDelay": 30}"#;
+    let version: VersionResponse = serde_json::from_str(raw_str).unwrap();
+    assert_eq!(version.semver(), Some((1, 21, 0)));
+    assert_eq!(version.api_semver(), (2, 3, 0));
+    assert_eq!(version.block_delay, 30);
+
+    let version = VersionResponse {
+        version: String::from("1.21"),
+        ..version
+    };
+    assert_eq!(version.semver(), None);
+}
+
+#[test]
+fn test_activation_status() {
+    let validator = |addr: &str| Validator {
+        addr: addr.to_string(),
+        net_addr: String::from("/ip4/127.0.0.1/tcp/1347"),
+        weight: String::from("1000"),
+    };
+    let mut state = IPCReadSubnetActorStateResponse {
+        min_validator_stake: TokenAmount::from_whole(1),
+        total_stake: TokenAmount::from_whole(1),
+        bottom_up_check_period: 10,
+        validator_set: ValidatorSet {
+            validators: Some(vec![validator("t01000")]),
+            configuration_number: 1,
+        },
+        min_validators: 3,
+        bottom_up_checkpoint_voting: Voting {
+            genesis_epoch: 0,
+            last_voting_executed: 0,
+        },
+    };
+
+    let status = state.activation_status();
+    assert_eq!(
+        status,
+        ActivationStatus {
+            min_validators: 3,
+            validators: 1,
+            active: false,
+        }
+    );
+    assert_eq!(status.missing_validators(), 2);
+
+    state.validator_set.validators = Some(vec![
+        validator("t01000"),
+        validator("t01001"),
+        validator("t01002"),
+    ]);
+    let status = state.activation_status();
+    assert!(status.active);
+    assert_eq!(status.missing_validators(), 0);
+}
+
+#[test]
+fn test_validator_stake() {
+    let mut state = IPCReadSubnetActorStateResponse {
+        min_validator_stake: TokenAmount::from_whole(1),
+        total_stake: TokenAmount::from_whole(1),
+        bottom_up_check_period: 10,
+        validator_set: ValidatorSet {
+            validators: Some(vec![Validator {
+                addr: String::from("t01000"),
+                net_addr: String::from("/ip4/127.0.0.1/tcp/1347"),
+                weight: String::from("1000"),
+            }]),
+            configuration_number: 1,
+        },
+        min_validators: 1,
+        bottom_up_checkpoint_voting: Voting {
+            genesis_epoch: 0,
+            last_voting_executed: 0,
+        },
+    };
+
+    let validator = Address::from_str("t01000").unwrap();
+    assert_eq!(
+        state.validator_stake(&validator).unwrap(),
+        TokenAmount::from_atto(1000)
+    );
+    let other = Address::from_str("t01001").unwrap();
+    assert_eq!(
+        state.validator_stake(&other).unwrap(),
+        TokenAmount::from_atto(0)
+    );
+
+    state.validator_set.validators = None;
+    assert_eq!(
+        state.validator_stake(&validator).unwrap(),
+        TokenAmount::from_atto(0)
+    );
+}
+
+#[test]
+fn test_parent_base_fee() {
+    let raw = r#"
+    {
+        "Cids": [{"/": "bafy2bzacedkoa623kvi5gfis2yks7xxjl73vg7xwbojz4tpq63dd5jpfz757i"}],
+        "Blocks": [{"Height": 100, "ParentBaseFee": "100"}],
+        "Height": 100
+    }"#;
+    let tip_set: ChainHeadResponse = serde_json::from_str(raw).unwrap();
+    assert_eq!(
+        tip_set.parent_base_fee().unwrap(),
+        TokenAmount::from_atto(100)
+    );
+}
+
+#[test]
+fn test_parents() {
+    let raw = r#"
+    {
+        "Cids": [{"/": "bafy2bzacedkoa623kvi5gfis2yks7xxjl73vg7xwbojz4tpq63dd5jpfz757i"}],
+        "Blocks": [{
+            "Height": 100,
+            "Parents": [{"/": "bafy2bzacecnamqgqmifpluoeldx7zzglxcljo6oja4vrmtj7432rphldpdmm2"}]
+        }],
+        "Height": 100
+    }"#;
+    let tip_set: ChainHeadResponse = serde_json::from_str(raw).unwrap();
+    assert_eq!(
+        tip_set.parents().unwrap(),
+        vec![
+            Cid::from_str("bafy2bzacecnamqgqmifpluoeldx7zzglxcljo6oja4vrmtj7432rphldpdmm2")
+                .unwrap()
+        ]
+    );
+
+    let tip_set = ChainHeadResponse {
+        blocks: vec![],
+        ..tip_set
+    };
+    assert!(tip_set.parents().is_err());
+}
+
+#[test]
+fn test_base_fee_history() {
+    let fee = |atto: u64| TokenAmount::from_atto(atto);
+
+    // sampled out of order, with a null round at epoch 12 returning the tipset at 11
+    let history = BaseFeeHistory::new(vec![
+        (13, fee(140)),
+        (10, fee(100)),
+        (11, fee(105)),
+        (11, fee(105)),
+        (14, fee(150)),
+    ]);
+    assert_eq!(
+        history.fees.iter().map(|(e, _)| *e).collect::<Vec<_>>(),
+        vec![10, 11, 13, 14]
+    );
+    assert_eq!(history.trend, FeeTrend::Rising);
+    assert_eq!(history.fee_cap(), Some(fee(300)));
+
+    let history = BaseFeeHistory::new(vec![(10, fee(150)), (11, fee(100))]);
+    assert_eq!(history.trend, FeeTrend::Falling);
+    assert_eq!(history.fee_cap(), None);
+
+    let history = BaseFeeHistory::new(vec![(10, fee(100)), (11, fee(105))]);
+    assert_eq!(history.trend, FeeTrend::Stable);
+    assert_eq!(history.fee_cap(), None);
+
+    let history = BaseFeeHistory::new(vec![(10, fee(100))]);
+    assert_eq!(history.trend, FeeTrend::Stable);
+}
+
+#[test]
+fn test_top_down_finality() {
+    let state = IPCReadGatewayStateResponse {
+        bottom_up_check_period: 10,
+        top_down_check_period: 5,
+        applied_topdown_nonce: 3,
+        bottomup_nonce: 0,
+        applied_bottomup_nonce: 0,
+        top_down_checkpoint_voting: Voting {
+            genesis_epoch: 0,
+            last_voting_executed: 20,
+        },
+        initialized: true,
+    };
+    assert_eq!(
+        state.top_down_finality(),
+        TopDownFinality {
+            threshold: 5,
+            last_executed_epoch: 20,
+            next_epoch: 25,
+        }
+    );
+}
+
+#[test]
+fn test_mpool_push_message_builder() {
+    let to = Address::new_id(1001);
+    let from = Address::new_id(1000);
+    let message = MpoolPushMessage::builder()
+        .to(to)
+        .from(from)
+        .method(2)
+        .cbor_params(&Address::new_id(1002))
+        .unwrap()
+        .nonce(3)
+        .gas_limit(10_000_000)
+        .gas_fee_cap(TokenAmount::from_atto(200))
+        .gas_premium(TokenAmount::from_atto(100))
+        .max_fee(TokenAmount::from_whole(1))
+        .build()
+        .unwrap();
+    assert_eq!(message.to, to);
+    assert_eq!(message.from, from);
+    assert_eq!(message.method, 2);
+    assert_eq!(message.value, TokenAmount::from_atto(0));
+    assert_eq!(
+        message.params,
+        cbor::serialize(&Address::new_id(1002), "")
+            .unwrap()
+            .to_vec()
+    );
+    assert_eq!(message.nonce, Some(3));
+    assert_eq!(message.gas_limit, Some(TokenAmount::from_atto(10_000_000)));
+    assert_eq!(message.max_fee, Some(TokenAmount::from_whole(1)));
+
+    // the recipient, sender and method are required
+    let builder = || MpoolPushMessage::builder().to(to).from(from).method(0);
+    assert!(builder().build().is_ok());
+    assert!(MpoolPushMessage::builder()
+        .from(from)
+        .method(0)
+        .build()
+        .is_err());
+    assert!(MpoolPushMessage::builder()
+        .to(to)
+        .method(0)
+        .build()
+        .is_err());
+    assert!(MpoolPushMessage::builder()
+        .to(to)
+        .from(from)
+        .build()
+        .is_err());
+
+    // the node rejects premiums above the fee cap
+    assert!(builder()
+        .gas_fee_cap(TokenAmount::from_atto(100))
+        .gas_premium(TokenAmount::from_atto(200))
+        .build()
+        .is_err());
+}
+
+#[test]
+fn test_signed_message_json() {
+    let message = UnsignedMessage {
+        version: 0,
+        to: Address::new_id(1001),
+        from: Address::new_id(1000),
+        nonce: 3,
+        value: TokenAmount::from_atto(u64::MAX) + TokenAmount::from_atto(1),
+        gas_limit: 10_000_000,
+        gas_fee_cap: TokenAmount::from_atto(200),
+        gas_premium: TokenAmount::from_atto(100),
+        method: 2,
+        params: vec![1, 2, 3],
+    };
+    let signed = SignedMessage {
+        message: message.clone(),
+        signature: Signature {
+            sig_type: 1,
+            data: vec![4, 5, 6],
+        },
+    };
+
+    // the fields are named and encoded as lotus decodes them
+    let json = serde_json::to_value(&signed).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({
+            "Message": {
+                "Version": 0,
+                "To": "t01001",
+                "From": "t01000",
+                "Nonce": 3,
+                "Value": "18446744073709551616",
+                "GasLimit": 10_000_000,
+                "GasFeeCap": "200",
+                "GasPremium": "100",
+                "Method": 2,
+                "Params": "AQID",
+            },
+            "Signature": {"Type": 1, "Data": "BAUG"},
+        })
+    );
+
+    // the cid changes with any of the fields signed
+    let cid = message.cid().unwrap();
+    assert_eq!(cid, message.cid().unwrap());
+    let mut other = message;
+    other.nonce = 4;
+    assert_ne!(cid, other.cid().unwrap());
+}
@@ -1,25 +1,136 @@
 // Copyright 2022-2023 Protocol Labs
 // SPDX-License-Identifier: MIT
+use std::fmt::{Debug, Formatter};
+
+use fvm_shared::crypto::signature::{Signature as FvmSignature, SignatureType};
 use serde::{Deserialize, Serialize};
 use strum::{AsRefStr, Display, EnumString};
+use zeroize::Zeroize;
+
+use crate::lotus::message::deserialize::deserialize_bytes_from_base64;
+use crate::lotus::message::serialize::serialize_bytes_to_base64;
 
-#[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Display, EnumString, AsRefStr)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, Display, EnumString, AsRefStr)]
 pub enum WalletKeyType {
     #[strum(serialize = "bls")]
+    #[serde(rename = "bls")]
     BLS,
     #[strum(serialize = "secp256k1")]
+    #[serde(rename = "secp256k1")]
     Secp256k1,
     #[strum(serialize = "secp256k1-ledger")]
+    #[serde(rename = "secp256k1-ledger")]
     Secp256k1Ledger,
 }
 
+/// The private key of a wallet, in the format used by `lotus wallet export`. The private key
+/// is zeroized when the struct is dropped and is never printed.
+#[derive(Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct KeyInfo {
+    #[serde(rename = "Type")]
+    pub key_type: WalletKeyType,
+    #[serde(serialize_with = "serialize_bytes_to_base64")]
+    #[serde(deserialize_with = "deserialize_bytes_from_base64")]
+    pub private_key: Vec<u8>,
+}
+
+impl KeyInfo {
+    /// Parses a key in the format printed by `lotus wallet export`, i.e. the hex encoded json
+    /// of the key info.
+    pub fn from_lotus_export(exported: &str) -> anyhow::Result<Self> {
+        let mut json = hex::decode(exported.trim())
+            .map_err(|_| anyhow::anyhow!("exported key is not hex encoded"))?;
+        let key_info = serde_json::from_slice(&json)
+            .map_err(|_| anyhow::anyhow!("exported key is not a valid key info"));
+        json.zeroize();
+        key_info
+    }
+
+    /// Encodes the key in the format printed by `lotus wallet export`, so it can be imported
+    /// back with `lotus wallet import`.
+    pub fn to_lotus_export(&self) -> anyhow::Result<String> {
+        let mut json = serde_json::to_vec(self)?;
+        let exported = hex::encode(&json);
+        json.zeroize();
+        Ok(exported)
+    }
+}
+
+impl Debug for KeyInfo {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KeyInfo")
+            .field("key_type", &self.key_type)
+            .field("private_key", &"<redacted>")
+            .finish()
+    }
+}
+
+impl Drop for KeyInfo {
+    fn drop(&mut self) {
+        self.private_key.zeroize();
+    }
+}
+
 pub type WalletListResponse = Vec<String>;
 
+/// A signature produced by the wallet of a node.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Signature {
+    /// The type of the signature, 1 for secp256k1 and 2 for bls.
+    #[serde(rename = "Type")]
+    pub sig_type: u8,
+    #[serde(serialize_with = "serialize_bytes_to_base64")]
+    #[serde(deserialize_with = "deserialize_bytes_from_base64")]
+    pub data: Vec<u8>,
+}
+
+/// Converts the signature returned by the node into the one carried by FVM messages, e.g. to
+/// attach signatures produced offline to checkpoint votes.
+impl TryFrom<Signature> for FvmSignature {
+    type Error = anyhow::Error;
+
+    fn try_from(sig: Signature) -> Result<Self, Self::Error> {
+        let sig_type = match sig.sig_type {
+            1 => SignatureType::Secp256k1,
+            2 => SignatureType::BLS,
+            t => return Err(anyhow::anyhow!("unknown signature type: {t:}")),
+        };
+        Ok(FvmSignature {
+            sig_type,
+            bytes: sig.data,
+        })
+    }
+}
+
+impl From<FvmSignature> for Signature {
+    fn from(sig: FvmSignature) -> Self {
+        Signature {
+            sig_type: sig.sig_type as u8,
+            data: sig.bytes,
+        }
+    }
+}
+
+/// The form in which addresses are returned to the user
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Display, EnumString, AsRefStr)]
+pub enum AddressFormat {
+    /// The ID address of the actor, i.e. `f0...`
+    #[strum(serialize = "id")]
+    Id,
+    /// The public key address of the account, i.e. `f1...` or `f3...`
+    #[strum(serialize = "robust")]
+    Robust,
+}
+
 #[cfg(test)]
 mod tests {
     use std::str::FromStr;
 
-    use crate::lotus::message::wallet::WalletKeyType;
+    use fvm_shared::crypto::signature::{Signature as FvmSignature, SignatureType};
+
+    use crate::lotus::message::wallet::{AddressFormat, KeyInfo, Signature, WalletKeyType};
 
     #[test]
     fn test_key_types() {
@@ -29,4 +140,84 @@ mod tests {
         let t = WalletKeyType::from_str(t.as_ref()).unwrap();
         assert_eq!(t, WalletKeyType::Secp256k1);
     }
+
+    #[test]
+    fn test_address_formats() {
+        assert_eq!(AddressFormat::from_str("id").unwrap(), AddressFormat::Id);
+        assert_eq!(
+            AddressFormat::from_str("robust").unwrap(),
+            AddressFormat::Robust
+        );
+        assert!(AddressFormat::from_str("f0").is_err());
+    }
+
+    #[test]
+    fn test_signature_serde() {
+        let raw = r#"{"Type":1,"Data":"AQID"}"#;
+        let sig: Signature = serde_json::from_str(raw).unwrap();
+        assert_eq!(
+            sig,
+            Signature {
+                sig_type: 1,
+                data: vec![1, 2, 3],
+            }
+        );
+        assert_eq!(serde_json::to_string(&sig).unwrap(), raw);
+    }
+
+    #[test]
+    fn test_signature_to_fvm() {
+        let sig = Signature {
+            sig_type: 2,
+            data: vec![1, 2, 3],
+        };
+        let fvm_sig = FvmSignature::try_from(sig.clone()).unwrap();
+        assert_eq!(fvm_sig.sig_type, SignatureType::BLS);
+        assert_eq!(fvm_sig.bytes, vec![1, 2, 3]);
+        assert_eq!(Signature::from(fvm_sig), sig);
+
+        let sig = Signature {
+            sig_type: 3,
+            data: vec![],
+        };
+        assert!(FvmSignature::try_from(sig).is_err());
+    }
+
+    #[test]
+    fn test_key_info_round_trip() {
+        // as exported by `lotus wallet export`, hex decoded
+        let raw =
+            r#"{"Type":"secp256k1","PrivateKey":"8VcW07ADswS4BV2cxi5rnIadVsyTDDhY1NfDH19T8Uo="}"#;
+        let key_info: KeyInfo = serde_json::from_str(raw).unwrap();
+        assert_eq!(key_info.key_type, WalletKeyType::Secp256k1);
+        assert_eq!(key_info.private_key.len(), 32);
+
+        // re-importing sends the key in the same format
+        assert_eq!(serde_json::to_string(&key_info).unwrap(), raw);
+
+        let raw = r#"{"Type":"bls","PrivateKey":"AQID"}"#;
+        let key_info: KeyInfo = serde_json::from_str(raw).unwrap();
+        assert_eq!(key_info.key_type, WalletKeyType::BLS);
+        assert_eq!(key_info.private_key, vec![1, 2, 3]);
+
+        // the private key is never printed
+        assert!(!format!("{key_info:?}").contains("AQID"));
+        assert!(!format!("{key_info:?}").contains("[1, 2, 3]"));
+    }
+
+    #[test]
+    fn test_key_info_from_lotus_export() {
+        let exported = "7b2254797065223a22736563703235366b31222c22507269766174654b6579223a2238566357303741447377533442563263786935726e4961645673795444446859314e66444831395438556f3d227d\n";
+        let key_info = KeyInfo::from_lotus_export(exported).unwrap();
+        assert_eq!(key_info.key_type, WalletKeyType::Secp256k1);
+        assert_eq!(
+            serde_json::to_string(&key_info).unwrap(),
+            r#"{"Type":"secp256k1","PrivateKey":"8VcW07ADswS4BV2cxi5rnIadVsyTDDhY1NfDH19T8Uo="}"#
+        );
+
+        assert_eq!(key_info.to_lotus_export().unwrap(), exported.trim_end());
+
+        assert!(KeyInfo::from_lotus_export("not hex").is_err());
+        assert!(KeyInfo::from_lotus_export("7b7d").is_err());
+    }
 }
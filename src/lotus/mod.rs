@@ -3,10 +3,12 @@
 ///! The lotus api to interact with lotus node
 use std::collections::HashMap;
 use std::fmt::Debug;
+use std::time::Duration;
 
 use anyhow::Result;
 use async_trait::async_trait;
 use cid::Cid;
+use futures::stream::BoxStream;
 use fvm_shared::address::Address;
 use fvm_shared::clock::ChainEpoch;
 use fvm_shared::econ::TokenAmount;
@@ -14,18 +16,23 @@ use ipc_gateway::{BottomUpCheckpoint, CrossMsg};
 use ipc_sdk::subnet_id::SubnetID;
 use serde::de::DeserializeOwned;
 
-use message::chain::ChainHeadResponse;
-use message::mpool::{MpoolPushMessage, MpoolPushMessageResponseInner};
-use message::state::{ReadStateResponse, StateWaitMsgResponse};
-use message::wallet::{WalletKeyType, WalletListResponse};
+use message::chain::{BlockMessages, ChainHeadResponse, ChainMessage, SyncStateResponse};
+use message::mpool::{
+    GasEstimate, MpoolPushMessage, MpoolPushMessageResponseInner, SignedMessage, UnsignedMessage,
+};
+use message::state::{ReadStateResponse, StateGetActorResponse, StateWaitMsgResponse};
+use message::wallet::{KeyInfo, Signature, WalletKeyType, WalletListResponse};
+use message::VersionResponse;
 
-use crate::lotus::message::ipc::{IPCReadGatewayStateResponse, IPCReadSubnetActorStateResponse};
+use crate::lotus::message::ipc::{
+    ActivationStatus, CheckpointPage, CrossMsgNonces, IPCReadGatewayStateResponse,
+    IPCReadSubnetActorStateResponse,
+};
 use crate::manager::SubnetInfo;
 
-use self::message::CIDMap;
-
 pub mod client;
 mod json;
+pub(crate) mod keystore;
 pub mod message;
 #[cfg(test)]
 mod tests;
@@ -34,6 +41,15 @@ mod tests;
 /// see https://github.com/filecoin-project/go-state-types/blob/f6fd668a32b4b4a0bc39fd69d8a5f8fb11f49461/network/version.go#L7
 pub type NetworkVersion = u32;
 
+/// The error returned when a message is not executed before the deadline of the wait, e.g.
+/// because it was dropped from the mempool for paying too little gas.
+#[derive(Debug, thiserror::Error)]
+#[error("message {cid:} not executed within {}s, it may have been dropped from the mempool", .deadline.as_secs())]
+pub struct StateWaitTimeout {
+    pub cid: Cid,
+    pub deadline: Duration,
+}
+
 /// The Lotus client api to interact with the Lotus node.
 #[async_trait]
 pub trait LotusClient {
@@ -43,9 +59,61 @@ pub trait LotusClient {
         msg: MpoolPushMessage,
     ) -> Result<MpoolPushMessageResponseInner>;
 
+    /// Estimates the gas limit, fee cap and premium of a message without pushing it, optionally
+    /// capping the fees at `max_fee`, see: https://lotus.filecoin.io/reference/lotus/gas/#gasestimatemessagegas
+    async fn gas_estimate_message_gas(
+        &self,
+        msg: MpoolPushMessage,
+        max_fee: Option<TokenAmount>,
+    ) -> Result<GasEstimate>;
+
+    /// Pushes a message signed by its sender to the memory pool, returning its cid, see: https://lotus.filecoin.io/reference/lotus/mpool/#mpoolpush
+    async fn mpool_push(&self, msg: SignedMessage) -> Result<Cid>;
+
+    /// Returns the next nonce of the account, taking into account the messages pending in the
+    /// mempool, see: https://lotus.filecoin.io/reference/lotus/mpool/#mpoolgetnonce
+    async fn mpool_get_nonce(&self, address: &Address) -> Result<u64>;
+
     /// Wait for the message cid of a particular nonce, see: https://lotus.filecoin.io/reference/lotus/state/#statewaitmsg
     async fn state_wait_msg(&self, cid: Cid) -> Result<StateWaitMsgResponse>;
 
+    /// Same as [`LotusClient::state_wait_msg`], but sets whether the node may return a message
+    /// replacing `cid` with the same nonce, e.g. after a fee bump. Callers that bump fees allow
+    /// it; the others don't, which is the default. If the message was replaced anyway, the wait
+    /// still resolves on the replacement instead of failing with the `found message with equal
+    /// nonce` error of lotus, see: https://lotus.filecoin.io/reference/lotus/state/#statewaitmsg
+    async fn state_wait_msg_opts(
+        &self,
+        cid: Cid,
+        allow_replaced: bool,
+    ) -> Result<StateWaitMsgResponse>;
+
+    /// Same as [`LotusClient::state_wait_msg`], but waits for the message to be buried under
+    /// `confidence` epochs, looking back at most `lookback_limit` epochs for it, or with no limit
+    /// if -1, see: https://lotus.filecoin.io/reference/lotus/state/#statewaitmsg
+    async fn state_wait_msg_with(
+        &self,
+        cid: Cid,
+        confidence: u8,
+        lookback_limit: i64,
+    ) -> Result<StateWaitMsgResponse>;
+
+    /// Same as [`LotusClient::state_wait_msg`], but returns a [`StateWaitTimeout`] error if the
+    /// message is not executed within `deadline`. The message is polled with
+    /// [`LotusClient::state_search_msg`] instead of being waited for in a single request.
+    async fn state_wait_msg_deadline(
+        &self,
+        cid: Cid,
+        deadline: Duration,
+    ) -> Result<StateWaitMsgResponse>;
+
+    /// Looks up the receipt of a message without waiting for it, returning `None` if it is not
+    /// on chain yet, see: https://lotus.filecoin.io/reference/lotus/state/#statesearchmsg
+    async fn state_search_msg(&self, cid: Cid) -> Result<Option<StateWaitMsgResponse>>;
+
+    /// Returns the version of the node and of its API, see https://lotus.filecoin.io/reference/lotus/common/#version
+    async fn node_version(&self) -> Result<VersionResponse>;
+
     /// Returns the name of the network the node is synced to, see https://lotus.filecoin.io/reference/lotus/state/#statenetworkname
     async fn state_network_name(&self) -> Result<String>;
 
@@ -61,6 +129,10 @@ pub trait LotusClient {
     /// Get the default wallet of the node, see: https://lotus.filecoin.io/reference/lotus/wallet/#walletdefaultaddress
     async fn wallet_default(&self) -> Result<Address>;
 
+    /// Sets the default wallet of the node, used as the sender of messages that do not set one,
+    /// see: https://lotus.filecoin.io/reference/lotus/wallet/#walletsetdefault
+    async fn wallet_set_default(&self, address: &Address) -> Result<()>;
+
     /// List the wallets in the node, see: https://lotus.filecoin.io/reference/lotus/wallet/#walletlist
     async fn wallet_list(&self) -> Result<WalletListResponse>;
 
@@ -70,6 +142,44 @@ pub trait LotusClient {
     /// Get the balance of an address
     async fn wallet_balance(&self, address: &Address) -> Result<TokenAmount>;
 
+    /// Checks if the wallet of the node holds the key of `address`, see: https://lotus.filecoin.io/reference/lotus/wallet/#wallethas
+    async fn wallet_has(&self, address: &Address) -> Result<bool>;
+
+    /// Deletes the key of `address` from the wallet of the node, see: https://lotus.filecoin.io/reference/lotus/wallet/#walletdelete
+    async fn wallet_delete(&self, address: &Address) -> Result<()>;
+
+    /// Exports the private key of `address`, see: https://lotus.filecoin.io/reference/lotus/wallet/#walletexport
+    async fn wallet_export(&self, address: &Address) -> Result<KeyInfo>;
+
+    /// Imports a private key into the wallet of the node, returning its address, see: https://lotus.filecoin.io/reference/lotus/wallet/#walletimport
+    async fn wallet_import(&self, key_info: &KeyInfo) -> Result<Address>;
+
+    /// Signs `data` with the key of `address`, see: https://lotus.filecoin.io/reference/lotus/wallet/#walletsign
+    async fn wallet_sign(&self, address: &Address, data: &[u8]) -> Result<Signature>;
+
+    /// Signs `msg` with the key of its sender, see: https://lotus.filecoin.io/reference/lotus/wallet/#walletsignmessage
+    async fn wallet_sign_message(&self, msg: UnsignedMessage) -> Result<SignedMessage>;
+
+    /// Verifies `signature` is a signature of `data` by `address`, see: https://lotus.filecoin.io/reference/lotus/wallet/#walletverify
+    async fn wallet_verify(
+        &self,
+        address: &Address,
+        data: &[u8],
+        signature: &Signature,
+    ) -> Result<bool>;
+
+    /// Returns the ID address of the given address at the tipset, or at the chain head if
+    /// `tip_set` is `None`, see: https://lotus.filecoin.io/reference/lotus/state/#statelookupid
+    async fn state_lookup_id(&self, address: &Address, tip_set: Option<Cid>) -> Result<Address>;
+
+    /// Returns the public key address of the given ID address at the tipset, or at the chain
+    /// head if `tip_set` is `None`, see: https://lotus.filecoin.io/reference/lotus/state/#stateaccountkey
+    async fn state_account_key(&self, address: &Address, tip_set: Option<Cid>) -> Result<Address>;
+
+    /// Returns the nonce, balance and code of the actor at tipset, see: https://lotus.filecoin.io/reference/lotus/state/#stategetactor
+    async fn state_get_actor(&self, address: Address, tipset: Cid)
+        -> Result<StateGetActorResponse>;
+
     /// Read the state of the address at tipset, see: https://lotus.filecoin.io/reference/lotus/state/#statereadstate
     async fn read_state<State: DeserializeOwned + Debug>(
         &self,
@@ -81,6 +191,23 @@ pub trait LotusClient {
     /// See: https://lotus.filecoin.io/reference/lotus/chain/#chainhead
     async fn chain_head(&self) -> Result<ChainHeadResponse>;
 
+    /// Returns the state of the syncs of the node, see: https://lotus.filecoin.io/reference/lotus/sync/#syncstate
+    async fn sync_state(&self) -> Result<SyncStateResponse>;
+
+    /// Subscribes to the head changes of the chain. The returned stream yields the current head
+    /// followed by every tipset applied to the chain; reverted tipsets are skipped. Dropping the
+    /// stream cancels the subscription, and the stream ends when the socket is closed. Requires a
+    /// client to the websocket endpoint of the node, see [`client::LotusJsonRPCClient::from_subnet_ws`].
+    /// See: https://lotus.filecoin.io/reference/lotus/chain/#chainnotify
+    async fn chain_notify(&self) -> Result<BoxStream<'static, ChainHeadResponse>>;
+
+    /// Returns the message with the given cid included in the chain, see: https://lotus.filecoin.io/reference/lotus/chain/#chaingetmessage
+    async fn chain_get_message(&self, cid: Cid) -> Result<ChainMessage>;
+
+    /// Returns the messages included in the block with the given cid, with the BLS and secp
+    /// messages apart, see: https://lotus.filecoin.io/reference/lotus/chain/#chaingetblockmessages
+    async fn chain_get_block_messages(&self, block: Cid) -> Result<BlockMessages>;
+
     /// GetTipsetByHeight from the underlying chain
     async fn get_tipset_by_height(
         &self,
@@ -88,10 +215,16 @@ pub trait LotusClient {
         tip_set: Cid,
     ) -> Result<ChainHeadResponse>;
 
+    /// Returns the tipset with the key `key`, i.e. the CIDs of its blocks, see:
+    /// https://lotus.filecoin.io/reference/lotus/chain/#chaingettipset
+    async fn get_tipset_by_key(&self, key: Vec<Cid>) -> Result<ChainHeadResponse>;
+
+    /// Returns the CID of the last checkpoint committed by `child_subnet_id` in the gateway, or
+    /// `None` if it committed none yet.
     async fn ipc_get_prev_checkpoint_for_child(
         &self,
         child_subnet_id: SubnetID,
-    ) -> Result<Option<CIDMap>>;
+    ) -> Result<Option<Cid>>;
 
     /// Returns the checkpoint template at `epoch`.
     async fn ipc_get_checkpoint_template(&self, epoch: ChainEpoch) -> Result<BottomUpCheckpoint>;
@@ -106,6 +239,24 @@ pub trait LotusClient {
     /// Returns the state of the gateway actor at `tip_set`.
     async fn ipc_read_gateway_state(&self, tip_set: Cid) -> Result<IPCReadGatewayStateResponse>;
 
+    /// Returns the cross-message nonces of the gateway at `gateway_addr` and of its child
+    /// `subnet_id`, read at `tip_set`. Reading them all at the same tipset avoids the skew of
+    /// reading each one at a different chain head. The top-down messages of the child are read
+    /// from nonce `from_topdown`, e.g. the next one the child applies, which is returned as the
+    /// next top-down nonce if none was committed since.
+    async fn ipc_crossmsg_nonces(
+        &self,
+        subnet_id: &SubnetID,
+        gateway_addr: Address,
+        tip_set: Cid,
+        from_topdown: u64,
+    ) -> Result<CrossMsgNonces>;
+
+    /// Returns the number of parent epochs the subnet of the node waits before applying the
+    /// top-down messages committed in the parent, i.e. the top-down checkpoint period of its
+    /// gateway at `tip_set`.
+    async fn ipc_topdown_finality_threshold(&self, tip_set: Cid) -> Result<ChainEpoch>;
+
     /// Returns the state of the subnet actor at `tip_set`.
     async fn ipc_read_subnet_actor_state(
         &self,
@@ -113,8 +264,29 @@ pub trait LotusClient {
         tip_set: Cid,
     ) -> Result<IPCReadSubnetActorStateResponse>;
 
-    /// Returns the list of subnets in a gateway.
-    async fn ipc_list_child_subnets(&self, gateway_addr: Address) -> Result<Vec<SubnetInfo>>;
+    /// Returns the epoch of the last bottom-up checkpoint executed for `subnet_id` in its subnet
+    /// actor at `tip_set`, without listing the checkpoints committed.
+    async fn ipc_last_checkpoint_epoch(
+        &self,
+        subnet_id: &SubnetID,
+        tip_set: Cid,
+    ) -> Result<ChainEpoch>;
+
+    /// Returns the minimum number of validators required by the subnet at `tip_set`, the number
+    /// of validators it currently has and whether it is already active.
+    async fn ipc_activation_status(
+        &self,
+        subnet_id: &SubnetID,
+        tip_set: Cid,
+    ) -> Result<ActivationStatus>;
+
+    /// Returns the list of subnets in a gateway, at `tip_set` if given or else at the head of
+    /// the chain.
+    async fn ipc_list_child_subnets(
+        &self,
+        gateway_addr: Address,
+        tip_set: Option<Cid>,
+    ) -> Result<Vec<SubnetInfo>>;
 
     /// Determines if a validator has already voted for a bottomup checkpoint
     /// at certain epoch
@@ -144,6 +316,16 @@ pub trait LotusClient {
         nonce: u64,
     ) -> Result<Vec<CrossMsg>>;
 
+    /// Returns the top-down messages as [`LotusClient::ipc_get_topdown_msgs`] does, but decoding
+    /// each message on its own, so that a malformed message does not hide the rest.
+    async fn ipc_get_topdown_msgs_lenient(
+        &self,
+        subnet_id: &SubnetID,
+        gateway_addr: Address,
+        tip_set: Cid,
+        nonce: u64,
+    ) -> Result<Vec<Result<CrossMsg>>>;
+
     /// Gets the genesis epoch at which a subnet was registered in the parent
     async fn ipc_get_genesis_epoch_for_subnet(
         &self,
@@ -158,4 +340,18 @@ pub trait LotusClient {
         from_epoch: ChainEpoch,
         to_epoch: ChainEpoch,
     ) -> Result<Vec<BottomUpCheckpoint>>;
+
+    /// Same as [`LotusClient::ipc_list_checkpoints`], but only lists the checkpoints of `limit`
+    /// checkpoint periods of the range, skipping the first `offset` ones. As at most one
+    /// checkpoint is committed per `period`, a page holds up to `limit` checkpoints, fewer if
+    /// some were not committed.
+    async fn ipc_list_checkpoints_paged(
+        &self,
+        subnet_id: SubnetID,
+        from_epoch: ChainEpoch,
+        to_epoch: ChainEpoch,
+        period: ChainEpoch,
+        offset: usize,
+        limit: usize,
+    ) -> Result<CheckpointPage>;
 }
@@ -1,12 +1,43 @@
 // Copyright 2022-2023 Protocol Labs
 // SPDX-License-Identifier: MIT
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use anyhow::anyhow;
+use base64::Engine;
+use cid::Cid;
+use fil_actors_runtime::cbor;
+use futures::StreamExt;
+use fvm_ipld_encoding::RawBytes;
+use fvm_shared::address::Address;
+use fvm_shared::bigint::BigInt;
+use fvm_shared::clock::ChainEpoch;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::METHOD_SEND;
+use ipc_gateway::{BottomUpCheckpoint, CrossMsg, StorableMsg};
+use ipc_sdk::address::IPCAddress;
+use ipc_sdk::subnet_id::{SubnetID, ROOTNET_ID};
+use serde_json::{json, Value};
 use url::Url;
+use warp::Filter;
 
+use crate::jsonrpc::mock::MockJsonRpcClient;
 use crate::jsonrpc::JsonRpcClientImpl;
-use crate::lotus::client::LotusJsonRPCClient;
-use crate::lotus::LotusClient;
+use crate::lotus::client::{
+    decode_base64_cbor, decoded_prefix, heads_from_notification, mpool_push_message_params,
+    LotusJsonRPCClient,
+};
+use crate::lotus::keystore::{self, key_address};
+use crate::lotus::message::ipc::CheckpointPage;
+use crate::lotus::message::mpool::{MpoolPushMessage, UnsignedMessage};
+use crate::lotus::message::wallet::{KeyInfo, WalletKeyType};
+use crate::lotus::message::CIDMap;
+use crate::lotus::{LotusClient, StateWaitTimeout};
+use crate::serialization::car::dag_cbor_cid;
 
 const HTTP_ENDPOINT: &str = "https://api.node.glif.io/rpc/v0";
+const LOCAL_HTTP_ENDPOINT: &str = "http://127.0.0.1:1234/rpc/v1";
 
 fn get_lotus_client() -> LotusJsonRPCClient<JsonRpcClientImpl> {
     let url = Url::parse(HTTP_ENDPOINT).unwrap();
@@ -14,6 +45,635 @@ fn get_lotus_client() -> LotusJsonRPCClient<JsonRpcClientImpl> {
     LotusJsonRPCClient::new(client)
 }
 
+/// A local node holding keys in its wallet, authenticated with the `LOTUS_AUTH_TOKEN` token.
+fn get_local_lotus_client() -> LotusJsonRPCClient<JsonRpcClientImpl> {
+    let url = Url::parse(LOCAL_HTTP_ENDPOINT).unwrap();
+    let client = JsonRpcClientImpl::new(url, std::env::var("LOTUS_AUTH_TOKEN").ok().as_deref());
+    LotusJsonRPCClient::new(client)
+}
+
+/// A node on which the message `original` was replaced by `replacement`, e.g. by a fee bump.
+fn replaced_message_node(original: Cid, replacement: Cid) -> MockJsonRpcClient {
+    MockJsonRpcClient::default().on("Filecoin.StateWaitMsg", move |params| {
+        assert_eq!(params[0], json!(CIDMap::from(original)));
+
+        // mimic lotus, which only resolves on the replacement if allowed to
+        if params[3] != json!(true) {
+            return Err(anyhow!(
+                "found message with equal nonce as the one we are looking for"
+            ));
+        }
+        Ok(json!({
+            "Message": CIDMap::from(replacement),
+            "Receipt": {"ExitCode": 0, "Return": null, "GasUsed": 100},
+            "TipSet": [CIDMap::from(replacement)],
+            "Height": 10,
+        }))
+    })
+}
+
+#[tokio::test]
+async fn state_wait_msg_replaced() {
+    let original = dag_cbor_cid(b"original").unwrap();
+    let replacement = dag_cbor_cid(b"replacement").unwrap();
+    let node = replaced_message_node(original, replacement);
+    let client = LotusJsonRPCClient::new(node.clone());
+    let allowed = |params: Vec<Value>| params.iter().map(|p| p[3].clone()).collect::<Vec<_>>();
+
+    // a fee-bumping caller allows the replacement upfront
+    let r = client.state_wait_msg_opts(original, true).await.unwrap();
+    assert_eq!(r.message, CIDMap::from(replacement));
+    assert_eq!(allowed(node.params("Filecoin.StateWaitMsg")), vec![true]);
+
+    // without allowing replacements, the wait still resolves on the replacement instead of
+    // failing with the equal nonce error
+    node.clear_requests();
+    let r = client.state_wait_msg_opts(original, false).await.unwrap();
+    assert_eq!(r.message, CIDMap::from(replacement));
+    assert_eq!(
+        allowed(node.params("Filecoin.StateWaitMsg")),
+        vec![false, true]
+    );
+
+    // which is the default
+    node.clear_requests();
+    let r = client.state_wait_msg(original).await.unwrap();
+    assert_eq!(r.message, CIDMap::from(replacement));
+    assert_eq!(
+        allowed(node.params("Filecoin.StateWaitMsg")),
+        vec![false, true]
+    );
+}
+
+/// A node on which every message waited for is executed.
+fn executing_node() -> MockJsonRpcClient {
+    MockJsonRpcClient::default().on("Filecoin.StateWaitMsg", |params| {
+        Ok(json!({
+            "Message": params[0],
+            "Receipt": {"ExitCode": 0, "Return": null, "GasUsed": 100},
+            "TipSet": [params[0]],
+            "Height": 10,
+        }))
+    })
+}
+
+#[tokio::test]
+async fn state_wait_msg_params() {
+    let cid = dag_cbor_cid(b"message").unwrap();
+    let node = executing_node();
+    let client = LotusJsonRPCClient::new(node.clone());
+
+    client.state_wait_msg(cid).await.unwrap();
+    client.state_wait_msg_with(cid, 30, 900).await.unwrap();
+    client.state_wait_msg_with(cid, 0, -1).await.unwrap();
+    assert_eq!(
+        node.params("Filecoin.StateWaitMsg"),
+        vec![
+            json!([CIDMap::from(cid), 2, -1, false]),
+            json!([CIDMap::from(cid), 30, 900, false]),
+            json!([CIDMap::from(cid), 0, -1, false]),
+        ]
+    );
+
+    // the confidence of the subnet config is the default of the client
+    let node = executing_node();
+    let client = LotusJsonRPCClient::new(node.clone()).with_state_wait_confidence(10);
+    client.state_wait_msg(cid).await.unwrap();
+    client.state_wait_msg_opts(cid, true).await.unwrap();
+    assert_eq!(
+        node.params("Filecoin.StateWaitMsg"),
+        vec![
+            json!([CIDMap::from(cid), 10, -1, false]),
+            json!([CIDMap::from(cid), 10, -1, true]),
+        ]
+    );
+}
+
+/// A node with a checkpoint committed at each of `epochs`, those at the `malformed` epochs
+/// being returned malformed.
+fn checkpoints_node(epochs: Vec<ChainEpoch>, malformed: Vec<ChainEpoch>) -> MockJsonRpcClient {
+    MockJsonRpcClient::default().on("Filecoin.IPCListCheckpointsSerialized", move |params| {
+        let (from, to) = (params[1].as_i64().unwrap(), params[2].as_i64().unwrap());
+        let checkpoints = epochs
+            .iter()
+            .filter(|e| **e >= from && **e <= to)
+            .map(|e| {
+                if malformed.contains(e) {
+                    return String::from("not base64!");
+                }
+                let checkpoint = BottomUpCheckpoint::new(ROOTNET_ID.clone(), *e);
+                let bytes = cbor::serialize(&checkpoint, "checkpoint").unwrap();
+                base64::engine::general_purpose::STANDARD.encode(bytes.bytes())
+            })
+            .collect::<Vec<_>>();
+        Ok(json!(checkpoints))
+    })
+}
+
+#[tokio::test]
+async fn ipc_list_checkpoints_malformed() {
+    let client = LotusJsonRPCClient::new(checkpoints_node(vec![10, 20, 30, 40], vec![30]));
+    let subnet = SubnetID::from_str("/root/t01002").unwrap();
+
+    let err = client
+        .ipc_list_checkpoints(subnet.clone(), 0, 100)
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(err.starts_with("cannot decode base64 string of checkpoint 2: "));
+    assert!(err.ends_with(", after decoding 2 checkpoints from epoch 0"));
+
+    // the checkpoints before it are listed
+    let checkpoints = client.ipc_list_checkpoints(subnet, 0, 20).await.unwrap();
+    assert_eq!(checkpoints.len(), 2);
+}
+
+#[tokio::test]
+async fn ipc_list_checkpoints_paged() {
+    let node = checkpoints_node(vec![10, 20, 30, 40, 50], vec![]);
+    let client = LotusJsonRPCClient::new(node.clone());
+    let subnet = SubnetID::from_str("/root/t01002").unwrap();
+    let epochs = |page: &CheckpointPage| {
+        page.checkpoints
+            .iter()
+            .map(|c| c.data.epoch)
+            .collect::<Vec<_>>()
+    };
+    let last_range = || {
+        let params = node.params("Filecoin.IPCListCheckpointsSerialized");
+        let last = params.last().unwrap();
+        (last[1].as_i64().unwrap(), last[2].as_i64().unwrap())
+    };
+
+    // only the epochs of the periods of the page are listed
+    let page = client
+        .ipc_list_checkpoints_paged(subnet.clone(), 10, 50, 10, 0, 2)
+        .await
+        .unwrap();
+    assert_eq!(epochs(&page), vec![10, 20]);
+    assert_eq!(page.next_offset, Some(2));
+    assert_eq!(last_range(), (10, 29));
+
+    let page = client
+        .ipc_list_checkpoints_paged(subnet.clone(), 10, 50, 10, 2, 2)
+        .await
+        .unwrap();
+    assert_eq!(epochs(&page), vec![30, 40]);
+    assert_eq!(page.next_offset, Some(4));
+    assert_eq!(last_range(), (30, 49));
+
+    // the last page
+    let page = client
+        .ipc_list_checkpoints_paged(subnet.clone(), 10, 50, 10, 4, 2)
+        .await
+        .unwrap();
+    assert_eq!(epochs(&page), vec![50]);
+    assert_eq!(page.next_offset, None);
+    assert_eq!(last_range(), (50, 50));
+
+    // an unaligned range holds at most one checkpoint per period of a page
+    let page = client
+        .ipc_list_checkpoints_paged(subnet.clone(), 15, 100, 10, 1, 3)
+        .await
+        .unwrap();
+    assert_eq!(epochs(&page), vec![30, 40, 50]);
+    assert_eq!(page.next_offset, Some(4));
+    assert_eq!(last_range(), (25, 54));
+
+    // pages past the range are empty, without listing the checkpoints
+    node.clear_requests();
+    let page = client
+        .ipc_list_checkpoints_paged(subnet.clone(), 0, 100, 10, 11, 2)
+        .await
+        .unwrap();
+    assert!(page.checkpoints.is_empty());
+    assert_eq!(page.next_offset, None);
+    assert!(node.requests().is_empty());
+
+    assert!(client
+        .ipc_list_checkpoints_paged(subnet, 0, 100, 0, 0, 2)
+        .await
+        .is_err());
+}
+
+#[test]
+fn test_decode_base64_cbor_errors() {
+    let checkpoint = BottomUpCheckpoint::new(ROOTNET_ID.clone(), 10);
+    let bytes = cbor::serialize(&checkpoint, "checkpoint").unwrap();
+    let encoded = base64::engine::general_purpose::STANDARD.encode(bytes.bytes());
+
+    let decoded = decode_base64_cbor::<BottomUpCheckpoint>(&encoded, 0, "checkpoint").unwrap();
+    assert_eq!(decoded.data.epoch, 10);
+
+    let err = decode_base64_cbor::<BottomUpCheckpoint>("not base64!", 3, "checkpoint")
+        .unwrap_err()
+        .to_string();
+    assert!(err.starts_with("cannot decode base64 string of checkpoint 3: "));
+
+    // valid base64 of a checkpoint is not a cross-msg
+    let err = decode_base64_cbor::<CrossMsg>(&encoded, 1, "top-down cross-msg")
+        .unwrap_err()
+        .to_string();
+    assert!(err.starts_with("cannot deserialize cbor of top-down cross-msg 1: "));
+}
+
+#[tokio::test]
+async fn ipc_get_topdown_msgs_lenient() {
+    let subnet = SubnetID::from_str("/root/t01002").unwrap();
+    let encoded = |nonce: u64| {
+        let msg = CrossMsg {
+            msg: StorableMsg {
+                from: IPCAddress::new(&ROOTNET_ID, &Address::new_id(100)).unwrap(),
+                to: IPCAddress::new(&subnet, &Address::new_id(100)).unwrap(),
+                method: METHOD_SEND,
+                params: RawBytes::default(),
+                value: TokenAmount::from_whole(1),
+                nonce,
+            },
+            wrapped: false,
+        };
+        let bytes = cbor::serialize(&msg, "cross-msg").unwrap();
+        base64::engine::general_purpose::STANDARD.encode(bytes.bytes())
+    };
+    // a node returning the messages as the top-down messages of any subnet
+    let client = LotusJsonRPCClient::new(MockJsonRpcClient::default().with_result(
+        "Filecoin.IPCGetTopDownMsgsSerialized",
+        json!([encoded(0), "not base64!", encoded(2)]),
+    ));
+    let tip_set = dag_cbor_cid(b"head").unwrap();
+
+    let msgs = client
+        .ipc_get_topdown_msgs_lenient(&subnet, Address::new_id(64), tip_set, 0)
+        .await
+        .unwrap();
+    assert_eq!(msgs.len(), 3);
+    assert_eq!(msgs[0].as_ref().unwrap().msg.nonce, 0);
+    let err = msgs[1].as_ref().unwrap_err().to_string();
+    assert!(err.starts_with("cannot decode base64 string of top-down cross-msg 1: "));
+    assert!(err.ends_with(" (nonce 1)"));
+    assert_eq!(msgs[2].as_ref().unwrap().msg.nonce, 2);
+
+    // the strict version fails on the malformed message, naming it
+    let err = client
+        .ipc_get_topdown_msgs(&subnet, Address::new_id(64), tip_set, 5)
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(err.starts_with("cannot decode base64 string of top-down cross-msg 1: "));
+    assert!(err.ends_with(" (nonce 6), after decoding 1 top-down cross-msgs"));
+
+    let (decoded, err) = decoded_prefix(msgs);
+    assert_eq!(decoded.len(), 1);
+    assert!(err.is_some());
+}
+
+#[tokio::test]
+async fn ipc_crossmsg_nonces() {
+    let subnet = SubnetID::from_str("/root/t01002").unwrap();
+    let encoded = |nonce: u64| {
+        let msg = CrossMsg {
+            msg: StorableMsg {
+                from: IPCAddress::new(&ROOTNET_ID, &Address::new_id(100)).unwrap(),
+                to: IPCAddress::new(&subnet, &Address::new_id(100)).unwrap(),
+                method: METHOD_SEND,
+                params: RawBytes::default(),
+                value: TokenAmount::from_whole(1),
+                nonce,
+            },
+            wrapped: false,
+        };
+        let bytes = cbor::serialize(&msg, "cross-msg").unwrap();
+        base64::engine::general_purpose::STANDARD.encode(bytes.bytes())
+    };
+    // a gateway with top-down messages 0 to 6 committed for the subnet
+    let node = MockJsonRpcClient::default()
+        .with_result(
+            "Filecoin.IPCReadGatewayState",
+            json!({
+                "BottomUpCheckPeriod": 10,
+                "TopDownCheckPeriod": 10,
+                "AppliedTopdownNonce": 2,
+                "BottomupNonce": 9,
+                "AppliedBottomupNonce": 8,
+                "TopDownCheckpointVoting": {"GenesisEpoch": 0, "LastVotingExecuted": 0},
+                "Initialized": true,
+            }),
+        )
+        .on("Filecoin.IPCGetTopDownMsgsSerialized", move |params| {
+            let from = params[3].as_u64().unwrap();
+            Ok(json!((from..7).map(encoded).collect::<Vec<_>>()))
+        });
+    let client = LotusJsonRPCClient::new(node.clone());
+    let tip_set = dag_cbor_cid(b"head").unwrap();
+
+    let nonces = client
+        .ipc_crossmsg_nonces(&subnet, Address::new_id(64), tip_set, 4)
+        .await
+        .unwrap();
+    assert_eq!(nonces.gateway_applied_topdown, 2);
+    assert_eq!(nonces.next_topdown, 7);
+    assert_eq!(nonces.applied_bottomup, 8);
+    assert_eq!(nonces.next_bottomup, 9);
+
+    // the gateway state is read once, and the messages only from the nonce given
+    assert_eq!(node.params("Filecoin.IPCReadGatewayState").len(), 1);
+    let params = node.params("Filecoin.IPCGetTopDownMsgsSerialized");
+    assert_eq!(params.len(), 1);
+    assert_eq!(params[0][2], json!([CIDMap::from(tip_set)]));
+    assert_eq!(params[0][3], json!(4));
+
+    // without messages committed from the nonce given, it is the next one
+    let nonces = client
+        .ipc_crossmsg_nonces(&subnet, Address::new_id(64), tip_set, 9)
+        .await
+        .unwrap();
+    assert_eq!(nonces.next_topdown, 9);
+}
+
+/// Returns the key of the single block of the tipset at `height` of [`chain_node`].
+fn block_key(height: u64) -> Cid {
+    dag_cbor_cid(format!("block {height:}").as_bytes()).unwrap()
+}
+
+/// A node with a chain of tipsets of a single block each, at heights 0 to `height`.
+fn chain_node(height: u64) -> MockJsonRpcClient {
+    MockJsonRpcClient::default().on("Filecoin.ChainGetTipSet", move |params| {
+        let key = serde_json::from_value::<Vec<CIDMap>>(params[0].clone())?;
+        let height = (0..=height)
+            .find(|h| key == vec![CIDMap::from(block_key(*h))])
+            .ok_or_else(|| anyhow!("tipset not found"))?;
+        let parents = if height == 0 {
+            vec![]
+        } else {
+            vec![CIDMap::from(block_key(height - 1))]
+        };
+        Ok(json!({
+            "Cids": key,
+            "Blocks": [{"Height": height, "Parents": parents}],
+            "Height": height,
+        }))
+    })
+}
+
+#[tokio::test]
+async fn get_tipset_by_key_follows_parents() {
+    let client = LotusJsonRPCClient::new(chain_node(3));
+
+    let mut key = vec![block_key(3)];
+    let mut heights = vec![];
+    while !key.is_empty() {
+        let tip_set = client.get_tipset_by_key(key).await.unwrap();
+        heights.push(tip_set.height);
+        key = tip_set.parents().unwrap();
+    }
+    assert_eq!(heights, vec![3, 2, 1, 0]);
+
+    let unknown = dag_cbor_cid(b"unknown").unwrap();
+    assert!(client.get_tipset_by_key(vec![unknown]).await.is_err());
+}
+
+#[tokio::test]
+async fn ipc_last_checkpoint_epoch() {
+    // a subnet actor state whose validator set is not in the format the agent decodes
+    let client = LotusJsonRPCClient::new(MockJsonRpcClient::default().with_result(
+        "Filecoin.IPCReadSubnetActorState",
+        json!({
+            "BottomUpCheckPeriod": 10,
+            "ValidatorSet": "unexpected",
+            "MinValidators": 1,
+            "BottomUpCheckpointVoting": {
+                "GenesisEpoch": 0,
+                "LastVotingExecuted": 40,
+            },
+        }),
+    ));
+    let subnet = SubnetID::from_str("/root/t01002").unwrap();
+    let tip_set = dag_cbor_cid(b"tipset").unwrap();
+
+    // the rest of the state is not decoded
+    assert!(client
+        .ipc_read_subnet_actor_state(&subnet, tip_set)
+        .await
+        .is_err());
+    let epoch = client
+        .ipc_last_checkpoint_epoch(&subnet, tip_set)
+        .await
+        .unwrap();
+    assert_eq!(epoch, 40);
+}
+
+/// A node answering the `StateActorCodeCIDs` and `StateNetworkVersion` requests, slowly enough
+/// for concurrent callers to issue their own request.
+fn actor_code_cids_node() -> MockJsonRpcClient {
+    let cid = "bafy2bzacedkoa623kvi5gfis2yks7xxjl73vg7xwbojz4tpq63dd5jpfz757i";
+    MockJsonRpcClient::default()
+        .with_result("Filecoin.StateNetworkVersion", json!(18))
+        .with_result(
+            "Filecoin.StateActorCodeCIDs",
+            json!({"account": {"/": cid}}),
+        )
+        .with_delay(Duration::from_millis(10))
+}
+
+#[tokio::test]
+async fn state_actor_code_cids_cached() {
+    let node = actor_code_cids_node();
+    let client = LotusJsonRPCClient::new(node.clone());
+    let requests = || node.requests().len();
+
+    let (a, b) = tokio::join!(
+        client.state_actor_code_cids(18),
+        client.state_actor_code_cids(18)
+    );
+    assert_eq!(a.unwrap(), b.unwrap());
+    assert_eq!(requests(), 1);
+
+    client.state_actor_code_cids(18).await.unwrap();
+    assert_eq!(requests(), 1);
+
+    // other versions and invalidated caches are fetched again
+    client.state_actor_code_cids(19).await.unwrap();
+    assert_eq!(requests(), 2);
+    client.invalidate_code_cids().await;
+    client.state_actor_code_cids(18).await.unwrap();
+    assert_eq!(requests(), 3);
+}
+
+#[tokio::test]
+async fn state_network_version_cached() {
+    let node = actor_code_cids_node();
+    let client = LotusJsonRPCClient::new(node.clone());
+    let requests = || node.requests().len();
+    let tip_set = dag_cbor_cid(b"tipset").unwrap();
+
+    assert_eq!(
+        client.state_network_version(vec![tip_set]).await.unwrap(),
+        18
+    );
+    client.state_network_version(vec![tip_set]).await.unwrap();
+    assert_eq!(requests(), 1);
+
+    // other tipsets and cleared caches are fetched again
+    client.state_network_version(vec![]).await.unwrap();
+    assert_eq!(requests(), 2);
+    client.state_actor_code_cids(18).await.unwrap();
+    assert_eq!(requests(), 3);
+    client.clear_cache().await;
+    client.state_network_version(vec![tip_set]).await.unwrap();
+    client.state_actor_code_cids(18).await.unwrap();
+    assert_eq!(requests(), 5);
+
+    // expired versions are fetched again
+    let client = client.with_cache_ttl(Duration::ZERO);
+    client.state_network_version(vec![tip_set]).await.unwrap();
+    client.state_network_version(vec![tip_set]).await.unwrap();
+    assert_eq!(requests(), 7);
+}
+
+/// A node on which listing the child subnets of any gateway returns null, and where only the
+/// `gateway` actor exists.
+fn null_subnets_node(gateway: Address) -> MockJsonRpcClient {
+    let cid = json!({"/": "bafy2bzacedkoa623kvi5gfis2yks7xxjl73vg7xwbojz4tpq63dd5jpfz757i"});
+    MockJsonRpcClient::default()
+        .with_result("Filecoin.IPCListChildSubnets", Value::Null)
+        .with_result(
+            "Filecoin.ChainHead",
+            json!({"Cids": [cid], "Blocks": [], "Height": 10}),
+        )
+        .on("Filecoin.StateGetActor", move |params| {
+            if params[0] != json!(gateway.to_string()) {
+                return Err(anyhow!("resolution lookup failed"));
+            }
+            Ok(json!({"Code": cid, "Head": cid, "Nonce": 0, "Balance": "0"}))
+        })
+}
+
+#[tokio::test]
+async fn ipc_list_child_subnets_null() {
+    let gateway = Address::new_id(64);
+    let client = LotusJsonRPCClient::new(null_subnets_node(gateway));
+
+    // an existing gateway without children
+    let subnets = client.ipc_list_child_subnets(gateway, None).await.unwrap();
+    assert!(subnets.is_empty());
+
+    // a wrong gateway address
+    assert!(client
+        .ipc_list_child_subnets(Address::new_id(65), None)
+        .await
+        .is_err());
+}
+
+/// A node on which only the message `included` is on chain, included at epoch 10. The head
+/// advances one epoch on each `ChainHead` request, from epoch 10.
+fn search_msg_node(included: Cid) -> MockJsonRpcClient {
+    let heads = AtomicUsize::new(0);
+    MockJsonRpcClient::default()
+        .on("Filecoin.ChainHead", move |_| {
+            let height = 10 + heads.fetch_add(1, Ordering::SeqCst);
+            let cid = CIDMap::from(included);
+            Ok(json!({"Cids": [cid], "Blocks": [], "Height": height}))
+        })
+        .on("Filecoin.StateSearchMsg", move |params| {
+            if params[1] != json!(CIDMap::from(included)) {
+                return Ok(Value::Null);
+            }
+            Ok(json!({
+                "Message": CIDMap::from(included),
+                "Receipt": {"ExitCode": 0, "Return": null, "GasUsed": 100},
+                "TipSet": [CIDMap::from(included)],
+                "Height": 10,
+            }))
+        })
+}
+
+#[tokio::test]
+async fn state_search_msg() {
+    let included = dag_cbor_cid(b"included").unwrap();
+    let client = LotusJsonRPCClient::new(search_msg_node(included));
+
+    let r = client.state_search_msg(included).await.unwrap().unwrap();
+    assert_eq!(r.message, CIDMap::from(included));
+
+    let pending = dag_cbor_cid(b"pending").unwrap();
+    assert!(client.state_search_msg(pending).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn state_search_msg_pending_over_http() {
+    // lotus answers with a null result while the message is not on chain
+    let route =
+        warp::post().map(|| warp::reply::json(&json!({"jsonrpc": "2.0", "id": 1, "result": null})));
+    let (addr, server) = warp::serve(route).bind_ephemeral(([127, 0, 0, 1], 0));
+    tokio::spawn(server);
+
+    let url = Url::parse(&format!("http://{addr:}/rpc/v1")).unwrap();
+    let client = LotusJsonRPCClient::new(JsonRpcClientImpl::new(url, None));
+    let pending = dag_cbor_cid(b"pending").unwrap();
+    assert!(client.state_search_msg(pending).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn state_wait_msg_deadline() {
+    let included = dag_cbor_cid(b"included").unwrap();
+    let node = search_msg_node(included);
+    let client = LotusJsonRPCClient::new(node.clone());
+    let period = Duration::from_millis(1);
+
+    // the message included at epoch 10 is returned once the head reaches the confidence
+    let r = client
+        .state_wait_msg_polling(included, Duration::from_secs(10), period)
+        .await
+        .unwrap();
+    assert_eq!(r.message, CIDMap::from(included));
+    assert_eq!(node.params("Filecoin.ChainHead").len(), 3);
+
+    let pending = dag_cbor_cid(b"pending").unwrap();
+    let deadline = Duration::from_millis(50);
+    let e = client
+        .state_wait_msg_polling(pending, deadline, period)
+        .await
+        .unwrap_err();
+    let timeout = e.downcast_ref::<StateWaitTimeout>().unwrap();
+    assert_eq!(timeout.cid, pending);
+    assert_eq!(timeout.deadline, deadline);
+}
+
+#[tokio::test]
+async fn state_wait_msg_deadline_retries_errors() {
+    let included = dag_cbor_cid(b"included").unwrap();
+    let period = Duration::from_millis(1);
+
+    // the first chain heads fail, as if the node was restarting
+    let heads = AtomicUsize::new(0);
+    let node = search_msg_node(included).on("Filecoin.ChainHead", move |_| {
+        let n = heads.fetch_add(1, Ordering::SeqCst);
+        if n < 2 {
+            return Err(anyhow!("connection refused"));
+        }
+        let cid = CIDMap::from(included);
+        Ok(json!({"Cids": [cid], "Blocks": [], "Height": 10 + n}))
+    });
+    let client = LotusJsonRPCClient::new(node);
+    let r = client
+        .state_wait_msg_polling(included, Duration::from_secs(10), period)
+        .await
+        .unwrap();
+    assert_eq!(r.message, CIDMap::from(included));
+
+    // a node failing until the deadline times out
+    let node = search_msg_node(included).on("Filecoin.StateSearchMsg", |_| {
+        Err(anyhow!("connection refused"))
+    });
+    let client = LotusJsonRPCClient::new(node);
+    let deadline = Duration::from_millis(50);
+    let e = client
+        .state_wait_msg_polling(included, deadline, period)
+        .await
+        .unwrap_err();
+    assert!(e.downcast_ref::<StateWaitTimeout>().is_some());
+}
+
 #[tokio::test]
 #[ignore]
 async fn state_network_name() {
@@ -21,6 +681,106 @@ async fn state_network_name() {
     assert_eq!(client.state_network_name().await.unwrap(), "mainnet");
 }
 
+#[tokio::test]
+#[ignore]
+async fn wallet_sign_and_verify() {
+    let client = get_local_lotus_client();
+    let address = client.wallet_default().await.unwrap();
+
+    let data = b"ipc checkpoint";
+    let signature = client.wallet_sign(&address, data).await.unwrap();
+    assert!(client
+        .wallet_verify(&address, data, &signature)
+        .await
+        .unwrap());
+    assert!(!client
+        .wallet_verify(&address, b"another payload", &signature)
+        .await
+        .unwrap());
+}
+
+#[tokio::test]
+async fn wallet_sign_message_with_keystore() {
+    let key_info = KeyInfo {
+        key_type: WalletKeyType::Secp256k1,
+        private_key: vec![7; 32],
+    };
+    let local = key_address(&key_info).unwrap();
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join(local.to_string()),
+        key_info.to_lotus_export().unwrap(),
+    )
+    .unwrap();
+
+    let node = MockJsonRpcClient::default().on("Filecoin.WalletSignMessage", |params| {
+        Ok(json!({"Message": params[1], "Signature": {"Type": 1, "Data": "BAUG"}}))
+    });
+    let client = LotusJsonRPCClient::new(node.clone()).with_keystore(dir.path().to_path_buf());
+    let message = |from| UnsignedMessage {
+        version: 0,
+        to: Address::new_id(1001),
+        from,
+        nonce: 0,
+        value: TokenAmount::from_whole(1),
+        gas_limit: 1000,
+        gas_fee_cap: TokenAmount::from_atto(100),
+        gas_premium: TokenAmount::from_atto(10),
+        method: METHOD_SEND,
+        params: vec![],
+    };
+
+    // the message is signed locally, over the bytes of its cid as lotus does
+    let signed = client.wallet_sign_message(message(local)).await.unwrap();
+    assert!(node.requests().is_empty());
+    let cid = signed.message.cid().unwrap().to_bytes();
+    assert_eq!(signed.signature, keystore::sign(&key_info, &cid).unwrap());
+
+    // the senders without a local key are signed by the node
+    let other = Address::new_id(1000);
+    let signed = client.wallet_sign_message(message(other)).await.unwrap();
+    assert_eq!(signed.signature.data, vec![4, 5, 6]);
+    assert_eq!(node.params("Filecoin.WalletSignMessage")[0][0], "t01000");
+}
+
+#[tokio::test]
+#[ignore]
+async fn wallet_has_and_delete() {
+    let client = get_local_lotus_client();
+    let address = client.wallet_new(WalletKeyType::Secp256k1).await.unwrap();
+    let address = Address::from_str(&address).unwrap();
+    assert!(client.wallet_has(&address).await.unwrap());
+
+    client.wallet_delete(&address).await.unwrap();
+    assert!(!client.wallet_has(&address).await.unwrap());
+    // a key that was never in the wallet
+    assert!(!client.wallet_has(&Address::new_id(99)).await.unwrap());
+}
+
+#[tokio::test]
+#[ignore]
+async fn wallet_set_default() {
+    let client = get_local_lotus_client();
+    let previous = client.wallet_default().await.unwrap();
+    let address = client.wallet_new(WalletKeyType::Secp256k1).await.unwrap();
+    let address = Address::from_str(&address).unwrap();
+
+    client.wallet_set_default(&address).await.unwrap();
+    assert_eq!(client.wallet_default().await.unwrap(), address);
+
+    client.wallet_set_default(&previous).await.unwrap();
+    client.wallet_delete(&address).await.unwrap();
+}
+
+#[tokio::test]
+#[ignore]
+async fn mpool_get_nonce() {
+    let client = get_lotus_client();
+    // the burnt funds account never sends messages
+    let nonce = client.mpool_get_nonce(&Address::new_id(99)).await.unwrap();
+    assert_eq!(nonce, 0);
+}
+
 #[tokio::test]
 async fn state_network_version() {
     let client = get_lotus_client();
@@ -49,3 +809,122 @@ async fn chain_head() {
     assert!(!head.blocks.is_empty());
     assert_eq!(head.cids.len(), head.blocks.len());
 }
+
+#[test]
+fn chain_notify_heads() {
+    // the response to the subscription request only carries the channel id
+    let heads = heads_from_notification(json!({"jsonrpc": "2.0", "id": 1, "result": 1}));
+    assert!(heads.is_empty());
+
+    let tip_set = |height: u64| {
+        json!({
+            "Cids": [{"/": "bafy2bzacedkoa623kvi5gfis2yks7xxjl73vg7xwbojz4tpq63dd5jpfz757i"}],
+            "Blocks": [{}],
+            "Height": height,
+        })
+    };
+    let heads = heads_from_notification(json!({
+        "jsonrpc": "2.0",
+        "method": "xrpc.ch.val",
+        "params": [1, [
+            {"Type": "revert", "Val": tip_set(10)},
+            {"Type": "apply", "Val": tip_set(10)},
+            {"Type": "apply", "Val": tip_set(11)},
+        ]]
+    }));
+    assert_eq!(heads.len(), 2);
+    assert_eq!(heads[0].height, 10);
+    assert_eq!(heads[1].height, 11);
+}
+
+#[tokio::test]
+async fn chain_notify_stream() {
+    let tip_set = |height: u64| {
+        json!({
+            "Cids": [{"/": "bafy2bzacedkoa623kvi5gfis2yks7xxjl73vg7xwbojz4tpq63dd5jpfz757i"}],
+            "Blocks": [{}],
+            "Height": height,
+        })
+    };
+    let change = |change_type: &str, height: u64| {
+        json!({
+            "jsonrpc": "2.0",
+            "method": "xrpc.ch.val",
+            "params": [1, [{"Type": change_type, "Val": tip_set(height)}]]
+        })
+    };
+
+    let (sender, notifications) = async_channel::unbounded();
+    sender
+        .send(json!({"jsonrpc": "2.0", "id": 1, "result": 1}))
+        .await
+        .unwrap();
+    sender.send(change("current", 10)).await.unwrap();
+    sender.send(change("apply", 11)).await.unwrap();
+    sender.send(change("revert", 11)).await.unwrap();
+    sender.send(change("apply", 12)).await.unwrap();
+    // closing the socket ends the stream
+    drop(sender);
+
+    let client = LotusJsonRPCClient::new(
+        MockJsonRpcClient::default().with_subscription("Filecoin.ChainNotify", notifications),
+    );
+    let heights = client
+        .chain_notify()
+        .await
+        .unwrap()
+        .map(|head| head.height)
+        .collect::<Vec<_>>()
+        .await;
+    assert_eq!(heights, vec![10, 11, 12]);
+}
+
+#[test]
+fn mpool_push_message_large_amounts() {
+    let mut message = MpoolPushMessage::new(
+        Address::new_id(1000),
+        Address::new_id(1001),
+        METHOD_SEND,
+        vec![],
+    );
+    message.max_fee = Some(TokenAmount::from_whole(100));
+    message.gas_fee_cap = Some(TokenAmount::from_whole(20));
+
+    let params = mpool_push_message_params(message).unwrap();
+    assert_eq!(params[1]["MaxFee"], "100000000000000000000");
+    assert_eq!(params[0]["GasFeeCap"], "20000000000000000000");
+    assert!(params[0]["GasPremium"].is_null());
+    assert!(params[0]["GasLimit"].is_null());
+}
+
+#[test]
+fn mpool_push_message_above_u64_max() {
+    let above_max = TokenAmount::from_atto(BigInt::from(u64::MAX) + 1);
+    let mut message = MpoolPushMessage::new(
+        Address::new_id(1000),
+        Address::new_id(1001),
+        METHOD_SEND,
+        vec![],
+    );
+    message.value = above_max.clone();
+    message.gas_fee_cap = Some(above_max.clone());
+    message.gas_premium = Some(above_max.clone());
+    message.max_fee = Some(above_max);
+
+    let params = mpool_push_message_params(message).unwrap();
+    let expected = "18446744073709551616";
+    assert_eq!(params[0]["Value"], expected);
+    assert_eq!(params[0]["GasFeeCap"], expected);
+    assert_eq!(params[0]["GasPremium"], expected);
+    assert_eq!(params[1]["MaxFee"], expected);
+
+    // the gas limit is a number of gas units that must fit in an int64
+    let mut message = MpoolPushMessage::new(
+        Address::new_id(1000),
+        Address::new_id(1001),
+        METHOD_SEND,
+        vec![],
+    );
+    message.gas_limit = Some(TokenAmount::from_atto(BigInt::from(u64::MAX)));
+    assert!(mpool_push_message_params(message).is_err());
+}
@@ -1,10 +1,6 @@
 // Copyright 2022-2023 Protocol Labs
 // SPDX-License-Identifier: MIT
 
-use std::collections::hash_map::RandomState;
-use std::collections::HashSet;
-use std::ops::Deref;
-use std::str::FromStr;
 use std::sync::Arc;
 
 use anyhow::{Context, Result};
@@ -12,6 +8,7 @@ use cid::Cid;
 use fil_actors_runtime::cbor;
 use fvm_shared::address::Address;
 use fvm_shared::clock::ChainEpoch;
+use fvm_shared::econ::TokenAmount;
 use fvm_shared::MethodNum;
 use ipc_gateway::BottomUpCheckpoint;
 use primitives::TCid;
@@ -19,10 +16,12 @@ use tokio::sync::Notify;
 
 use crate::config::Subnet;
 use crate::jsonrpc::JsonRpcClient;
-use crate::lotus::client::LotusJsonRPCClient;
+use crate::lotus::client::{LotusJsonRPCClient, DEFAULT_STATE_WAIT_DEADLINE};
 use crate::lotus::message::mpool::MpoolPushMessage;
 use crate::lotus::LotusClient;
-use crate::manager::checkpoint::{wait_next_iteration, CHAIN_HEAD_REQUEST_PERIOD};
+use crate::manager::checkpoint::{
+    checkpoint_fee_cap, validator_accounts, wait_next_iteration, CHAIN_HEAD_REQUEST_PERIOD,
+};
 
 /// Monitors a subnet `child` for checkpoint blocks. It emits an event for every new checkpoint block.
 pub async fn manage_bottomup_checkpoints(
@@ -78,7 +77,9 @@ pub async fn manage_bottomup_checkpoints(
             let subnet_actor_state = parent_client
                 .ipc_read_subnet_actor_state(&child.id, parent_tip_set)
                 .await?;
-            let last_exec = subnet_actor_state
+            // kept up to date with the reads after each submission, so that no account costs an
+            // extra read of the state of the subnet actor
+            let mut last_exec = subnet_actor_state
                 .bottom_up_checkpoint_voting
                 .last_voting_executed;
             let submission_epoch = last_exec + period;
@@ -88,13 +89,26 @@ pub async fn manage_bottomup_checkpoints(
             if child_gw_state.initialized && curr_epoch >= submission_epoch {
                 // First, we check which accounts are in the validator set. This is done by reading
                 // the parent's chain head and requesting the state at that tip set.
-                let mut validator_set: HashSet<Address, RandomState> = HashSet::new();
-                match subnet_actor_state.validator_set.validators {
-                    None => {}
-                    Some(validators) => {
-                        for v in validators {
-                            validator_set.insert(Address::from_str(v.addr.deref())?);
-                        }
+                let validators = subnet_actor_state
+                    .validator_set
+                    .validators
+                    .unwrap_or_default();
+                let accounts = match validator_accounts(
+                    &parent_client,
+                    &parent_client,
+                    &validators,
+                    parent_tip_set,
+                    &child.accounts,
+                )
+                .await
+                {
+                    Ok(accounts) => accounts,
+                    Err(e) => {
+                        log::warn!(
+                            "not submitting bottom-up checkpoints for {:}: {e:}",
+                            child.id
+                        );
+                        vec![]
                     }
                 };
 
@@ -102,90 +116,93 @@ pub async fn manage_bottomup_checkpoints(
                 // submit a checkpoint on its behalf.
                 assert_eq!(child_head.cids.len(), 1); // Again, check key assumption
                 let child_tip_set = Cid::try_from(child_head.cids.first().unwrap().clone())?;
-                for account in child.accounts.iter() {
-                    if validator_set.contains(account) {
-                        // check if the validator already voted
-                        // let has_voted = parent_client
-                        //     .ipc_validator_has_voted_bottomup(&child.id, submission_epoch, account)
-                        //     .await
-                        //     .map_err(|e| {
-                        //         log::error!(
-                        //             "error checking if validator has voted in subnet: {:?}",
-                        //             &child.id
-                        //         );
-                        //         e
-                        //     })?;
-                        // FIXME: There is a nasty bug in the de-serialization of EpochVoteSubmissions in
-                        // the actor due to the fact that we are using Cids and nodes can't be load.
-                        // commenting for now, but needs to be fixed in actors.
-                        let has_voted = false;
-                        if !has_voted {
-                            // submitting the checkpoint synchronously and waiting to be committed.
-                            let r = submit_checkpoint(
-                                child_tip_set,
-                                submission_epoch,
-                                account,
-                                &child,
-                                &child_client,
-                                &parent_client,
-                            )
-                            .await;
-                            if r.is_err() {
-                                log::warn!("error submitting bottom-up checkpoint, waiting to next iteration: {:?}", r);
-                                if !wait_next_iteration(&stop_notify, CHAIN_HEAD_REQUEST_PERIOD)
-                                    .await?
-                                {
-                                    return Ok(());
-                                }
-                                continue;
+                // the fee cap is chosen once for all the accounts from the recent base fees
+                let fee_cap = if accounts.is_empty() {
+                    None
+                } else {
+                    checkpoint_fee_cap(&parent_client).await
+                };
+                for account in accounts.iter() {
+                    // check if the validator already voted
+                    // let has_voted = parent_client
+                    //     .ipc_validator_has_voted_bottomup(&child.id, submission_epoch, account)
+                    //     .await
+                    //     .map_err(|e| {
+                    //         log::error!(
+                    //             "error checking if validator has voted in subnet: {:?}",
+                    //             &child.id
+                    //         );
+                    //         e
+                    //     })?;
+                    // FIXME: There is a nasty bug in the de-serialization of EpochVoteSubmissions in
+                    // the actor due to the fact that we are using Cids and nodes can't be load.
+                    // commenting for now, but needs to be fixed in actors.
+                    let has_voted = false;
+                    // the checkpoint may have been executed with the votes of the accounts
+                    // that already submitted it
+                    if last_exec >= submission_epoch {
+                        break;
+                    }
+                    if !has_voted {
+                        // submitting the checkpoint synchronously and waiting to be committed.
+                        let r = submit_checkpoint(
+                            child_tip_set,
+                            submission_epoch,
+                            account,
+                            &child,
+                            &child_client,
+                            &parent_client,
+                            fee_cap.as_ref(),
+                        )
+                        .await;
+                        if r.is_err() {
+                            log::warn!("error submitting bottom-up checkpoint, waiting to next iteration: {:?}", r);
+                            if !wait_next_iteration(&stop_notify, CHAIN_HEAD_REQUEST_PERIOD).await?
+                            {
+                                return Ok(());
                             }
+                            continue;
+                        }
 
-                            loop {
-                                // check if by any chance we have the opportunity to submit any outstanding checkpoint we may be
-                                // missing in case the previous one was executed successfully.
-                                // - we get the up to date head of the parent and the child.
-                                // - check the last executed checkpoint for the subnet
-                                // - And if we still have the info, submit a new checkpoint
-                                let child_head = child_client.chain_head().await?;
-                                let curr_epoch: ChainEpoch =
-                                    ChainEpoch::try_from(child_head.height)?;
-                                let parent_head = parent_client.chain_head().await?;
-                                let cid_map = parent_head.cids.first().unwrap().clone();
-                                let parent_tip_set = Cid::try_from(cid_map)?;
-                                let subnet_actor_state = parent_client
-                                    .ipc_read_subnet_actor_state(&child.id, parent_tip_set)
-                                    .await?;
-                                let last_exec = subnet_actor_state
-                                    .bottom_up_checkpoint_voting
-                                    .last_voting_executed;
-                                let submission_epoch = last_exec + period;
-                                if curr_epoch >= submission_epoch {
-                                    let r = submit_checkpoint(
-                                        child_tip_set,
-                                        submission_epoch,
-                                        account,
-                                        &child,
-                                        &child_client,
-                                        &parent_client,
-                                    )
-                                    .await;
-                                    if r.is_err() {
-                                        log::warn!("error submitting bottom-up checkpoint, waiting to next iteration: {:?}", r);
-                                        if !wait_next_iteration(
-                                            &stop_notify,
-                                            CHAIN_HEAD_REQUEST_PERIOD,
-                                        )
+                        loop {
+                            // check if by any chance we have the opportunity to submit any outstanding checkpoint we may be
+                            // missing in case the previous one was executed successfully.
+                            // - we get the up to date head of the parent and the child.
+                            // - check the last executed checkpoint for the subnet
+                            // - And if we still have the info, submit a new checkpoint
+                            let child_head = child_client.chain_head().await?;
+                            let curr_epoch: ChainEpoch = ChainEpoch::try_from(child_head.height)?;
+                            let parent_head = parent_client.chain_head().await?;
+                            let cid_map = parent_head.cids.first().unwrap().clone();
+                            let parent_tip_set = Cid::try_from(cid_map)?;
+                            last_exec = parent_client
+                                .ipc_last_checkpoint_epoch(&child.id, parent_tip_set)
+                                .await?;
+                            let submission_epoch = last_exec + period;
+                            if curr_epoch >= submission_epoch {
+                                let r = submit_checkpoint(
+                                    child_tip_set,
+                                    submission_epoch,
+                                    account,
+                                    &child,
+                                    &child_client,
+                                    &parent_client,
+                                    fee_cap.as_ref(),
+                                )
+                                .await;
+                                if r.is_err() {
+                                    log::warn!("error submitting bottom-up checkpoint, waiting to next iteration: {:?}", r);
+                                    if !wait_next_iteration(&stop_notify, CHAIN_HEAD_REQUEST_PERIOD)
                                         .await?
-                                        {
-                                            return Ok(());
-                                        }
-                                        break;
+                                    {
+                                        return Ok(());
                                     }
-                                } else {
-                                    // if no checkpoint lagging we can wait for the
-                                    // next iteration.
                                     break;
                                 }
+                            } else {
+                                // if no checkpoint lagging we can wait for the
+                                // next iteration.
+                                break;
                             }
                         }
                     }
@@ -214,6 +231,7 @@ async fn submit_checkpoint<T: JsonRpcClient + Send + Sync>(
     child_subnet: &Subnet,
     child_client: &LotusJsonRPCClient<T>,
     parent_client: &LotusJsonRPCClient<T>,
+    fee_cap: Option<&TokenAmount>,
 ) -> Result<()> {
     log::info!(
         "Submitting checkpoint bottom-up for account {} and epoch {} from child {}",
@@ -272,8 +290,7 @@ async fn submit_checkpoint<T: JsonRpcClient + Send + Sync>(
         })?;
 
     // if previous checkpoint is set
-    if response.is_some() {
-        let cid = Cid::try_from(response.unwrap())?;
+    if let Some(cid) = response {
         checkpoint.data.prev_check = TCid::from(cid);
     }
     checkpoint.data.proof = child_tip_set.to_bytes();
@@ -286,12 +303,13 @@ async fn submit_checkpoint<T: JsonRpcClient + Send + Sync>(
     );
     let to = child_subnet.id.subnet_actor();
     let from = *account;
-    let message = MpoolPushMessage::new(
+    let mut message = MpoolPushMessage::new(
         to,
         from,
         ipc_subnet_actor::Method::SubmitCheckpoint as MethodNum,
         cbor::serialize(&checkpoint, "checkpoint")?.to_vec(),
     );
+    message.gas_fee_cap = fee_cap.cloned();
     let mem_push_response = parent_client
         .mpool_push_message(message)
         .await
@@ -307,7 +325,9 @@ async fn submit_checkpoint<T: JsonRpcClient + Send + Sync>(
     let message_cid = mem_push_response.cid()?;
     log::debug!("bottom-up checkpoint message published with cid: {message_cid:?}");
     log::info!("waiting bottom-up for checkpoint for epoch {epoch:} to be committed");
-    parent_client.state_wait_msg(message_cid).await?;
+    parent_client
+        .state_wait_msg_deadline(message_cid, DEFAULT_STATE_WAIT_DEADLINE)
+        .await?;
     log::info!("successfully published bottom-up checkpoint submission for epoch {epoch:}");
 
     Ok(())
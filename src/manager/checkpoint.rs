@@ -1,14 +1,19 @@
 // Copyright 2022-2023 Protocol Labs
 // SPDX-License-Identifier: MIT
 
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::str::FromStr;
 use std::sync::Arc;
 use std::time::Duration;
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use async_trait::async_trait;
+use cid::Cid;
 use futures_util::stream::FuturesUnordered;
 use futures_util::StreamExt;
+use fvm_shared::address::{Address, Protocol};
+use fvm_shared::clock::ChainEpoch;
+use fvm_shared::econ::TokenAmount;
 use ipc_sdk::subnet_id::SubnetID;
 use tokio::select;
 use tokio::sync::Notify;
@@ -16,11 +21,19 @@ use tokio::time::sleep;
 use tokio_graceful_shutdown::{IntoSubsystem, SubsystemHandle};
 
 use crate::config::{ReloadableConfig, Subnet};
+use crate::jsonrpc::JsonRpcClient;
+use crate::lotus::client::LotusJsonRPCClient;
+use crate::lotus::message::chain::BaseFeeHistory;
+use crate::lotus::message::ipc::Validator;
+use crate::lotus::message::wallet::WalletKeyType;
+use crate::lotus::LotusClient;
 use crate::manager::bottomup::manage_bottomup_checkpoints;
 use crate::manager::topdown::manage_topdown_checkpoints;
 
 /// The frequency at which to check a new chain head.
 pub(crate) const CHAIN_HEAD_REQUEST_PERIOD: Duration = Duration::from_secs(10);
+/// The tipsets whose base fees the fee cap of the checkpoint submissions is chosen from.
+const FEE_HISTORY_TIPSETS: ChainEpoch = 10;
 
 /// The `CheckpointSubsystem`. When run, it actively monitors subnets and submits checkpoints.
 pub struct CheckpointSubsystem {
@@ -42,8 +55,9 @@ impl IntoSubsystem<anyhow::Error> for CheckpointSubsystem {
     /// For each (account, subnet) that exists in the config, the subnet is monitored and checkpoints
     /// are submitted at the appropriate epochs.
     async fn run(self, subsys: SubsystemHandle) -> Result<()> {
-        // Each event in this channel is notification of a new config.
-        let mut config_chan = self.config.new_subscriber();
+        // Each event in this channel is notification of a change of the config, reloads that
+        // leave it as it is don't restart the subnet managers.
+        let mut config_chan = self.config.watch();
 
         loop {
             // Load the latest config.
@@ -93,7 +107,7 @@ impl IntoSubsystem<anyhow::Error> for CheckpointSubsystem {
                     log::info!("Shutting down checkpointing subsystem");
                     true
                 },
-                r = config_chan.recv() => {
+                r = config_chan.changed() => {
                     log::info!("Config changed, reloading checkpointing subsystem");
                     match r {
                         Ok(_) => { false },
@@ -141,3 +155,271 @@ pub async fn wait_next_iteration(stop_notify: &Arc<Notify>, timeout: Duration) -
         _ = stop_notify.notified() => {Ok(false)}
     }
 }
+
+/// Returns the fee cap of the checkpoints submitted to the parent, chosen from the base fees of
+/// its last tipsets, or `None` to leave it to the estimate of the node.
+pub(crate) async fn checkpoint_fee_cap<T: JsonRpcClient + Send + Sync>(
+    parent_client: &LotusJsonRPCClient<T>,
+) -> Option<TokenAmount> {
+    match base_fee_history(parent_client, FEE_HISTORY_TIPSETS).await {
+        Ok(history) => {
+            log::debug!("base fee of the parent is {:?}", history.trend);
+            history.fee_cap()
+        }
+        Err(e) => {
+            log::warn!(
+                "cannot read the base fees of the parent, using the estimated fee cap: {e:}"
+            );
+            None
+        }
+    }
+}
+
+/// Returns the base fees of the last `tipsets` tipsets of the chain, in epoch order, and
+/// whether they are rising or falling.
+async fn base_fee_history<T: JsonRpcClient + Send + Sync>(
+    client: &LotusJsonRPCClient<T>,
+    tipsets: ChainEpoch,
+) -> Result<BaseFeeHistory> {
+    let head = client.chain_head().await?;
+    let head_tip_set = Cid::try_from(
+        head.cids
+            .first()
+            .ok_or_else(|| anyhow!("chain head has no cids"))?
+            .clone(),
+    )?;
+    let head_epoch = ChainEpoch::try_from(head.height)?;
+
+    let mut fees = vec![(head_epoch, head.parent_base_fee()?)];
+    let from = head_epoch.saturating_sub(tipsets - 1).max(0);
+    for epoch in from..head_epoch {
+        // null rounds return the tipset before the epoch, deduplicated by the history
+        let tip_set = client.get_tipset_by_height(epoch, head_tip_set).await?;
+        fees.push((
+            ChainEpoch::try_from(tip_set.height)?,
+            tip_set.parent_base_fee()?,
+        ));
+    }
+
+    Ok(BaseFeeHistory::new(fees))
+}
+
+/// Returns the accounts to submit the checkpoints of a subnet with, i.e. those holding the key
+/// of one of its `validators`. The subnet actor may record the validators by ID address, so they
+/// are matched by key address, resolved with `parent_client` at the `tip_set` the validator set
+/// was read at for the validators, and with `wallet_client` at its head for the accounts. If
+/// none of the configured `accounts` is a validator, e.g. because they hold a key of another
+/// type, the keys in the wallet of `wallet_client` are used instead. Errors if no key of a
+/// validator is available, naming the key types they use.
+pub(crate) async fn validator_accounts<T: JsonRpcClient + Send + Sync>(
+    parent_client: &LotusJsonRPCClient<T>,
+    wallet_client: &LotusJsonRPCClient<T>,
+    validators: &[Validator],
+    tip_set: Cid,
+    accounts: &[Address],
+) -> Result<Vec<Address>> {
+    if validators.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let mut validator_keys = HashSet::new();
+    for v in validators {
+        let addr = Address::from_str(&v.addr)?;
+        validator_keys.insert(key_address(parent_client, &addr, Some(tip_set)).await?);
+    }
+
+    let mut signers = vec![];
+    for account in accounts {
+        match key_address(wallet_client, account, None).await {
+            Ok(key) if validator_keys.contains(&key) => signers.push(*account),
+            Ok(_) => {}
+            Err(e) => log::debug!("cannot resolve the key of account {account:}: {e:}"),
+        }
+    }
+    if !signers.is_empty() {
+        return Ok(signers);
+    }
+
+    // the wallet lists the key addresses of the keys it holds
+    for key in wallet_client.wallet_list().await? {
+        let key = Address::from_str(&key)?;
+        if validator_keys.contains(&key) {
+            signers.push(key);
+        }
+    }
+    if signers.is_empty() {
+        return Err(anyhow!(
+            "no account holds the key of a validator, the validators use {:} keys",
+            key_types(&validator_keys)
+        ));
+    }
+    log::warn!(
+        "none of the configured accounts is a validator, using the wallet accounts {:}",
+        signers
+            .iter()
+            .map(Address::to_string)
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    Ok(signers)
+}
+
+/// Returns the key address of `address`, looking it up at `tip_set` if it is an ID address.
+async fn key_address<T: JsonRpcClient + Send + Sync>(
+    client: &LotusJsonRPCClient<T>,
+    address: &Address,
+    tip_set: Option<Cid>,
+) -> Result<Address> {
+    match address.protocol() {
+        Protocol::Secp256k1 | Protocol::BLS => Ok(*address),
+        _ => client.state_account_key(address, tip_set).await,
+    }
+}
+
+/// Returns the names of the types of `keys`, e.g. `bls, secp256k1`.
+fn key_types(keys: &HashSet<Address>) -> String {
+    keys.iter()
+        .map(|k| match k.protocol() {
+            Protocol::Secp256k1 => WalletKeyType::Secp256k1.as_ref().to_string(),
+            Protocol::BLS => WalletKeyType::BLS.as_ref().to_string(),
+            p => format!("{p:?}"),
+        })
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use std::sync::{Arc, Mutex};
+
+    use cid::Cid;
+    use fvm_shared::address::Address;
+    use fvm_shared::econ::TokenAmount;
+    use serde_json::json;
+
+    use crate::jsonrpc::mock::MockJsonRpcClient;
+    use crate::lotus::client::LotusJsonRPCClient;
+    use crate::lotus::message::chain::FeeTrend;
+    use crate::lotus::message::ipc::Validator;
+    use crate::lotus::message::CIDMap;
+    use crate::manager::checkpoint::{base_fee_history, checkpoint_fee_cap, validator_accounts};
+    use crate::serialization::car::dag_cbor_cid;
+
+    const VALIDATOR_ID: &str = "t0100";
+    const VALIDATOR_KEY: &str = "t1cp4q4lqsdhob23ysywffg2tvbmar5cshia4rweq";
+    const BLS_KEY: &str =
+        "t3thgjtvoi65yzdcoifgqh6utjbaod3ukidxrx34heu34d6avx6z7r5766t5jqt42a44ehzcnw3u5ehz47n42a";
+
+    /// A node resolving the ID of the validator to its key at `tip_set`, holding the keys in
+    /// `wallet`.
+    fn wallet_node(wallet: Arc<Mutex<Vec<String>>>, tip_set: Cid) -> MockJsonRpcClient {
+        MockJsonRpcClient::default()
+            .on("Filecoin.StateAccountKey", move |params| {
+                assert_eq!(params[0], VALIDATOR_ID);
+                assert_eq!(params[1], json!([CIDMap::from(tip_set)]));
+                Ok(json!(VALIDATOR_KEY))
+            })
+            .on("Filecoin.WalletList", move |_| {
+                Ok(json!(*wallet.lock().unwrap()))
+            })
+    }
+
+    #[tokio::test]
+    async fn test_validator_accounts() {
+        let wallet = Arc::new(Mutex::new(vec![
+            BLS_KEY.to_string(),
+            VALIDATOR_KEY.to_string(),
+        ]));
+        let tip_set = dag_cbor_cid(b"validators").unwrap();
+        let client = LotusJsonRPCClient::new(wallet_node(wallet.clone(), tip_set));
+        let validators = vec![Validator {
+            addr: VALIDATOR_ID.to_string(),
+            net_addr: String::from("/ip4/127.0.0.1/tcp/1347"),
+            weight: String::from("1"),
+        }];
+        let validator_key = Address::from_str(VALIDATOR_KEY).unwrap();
+        let bls_key = Address::from_str(BLS_KEY).unwrap();
+
+        // the validator recorded by ID is matched to its key
+        let accounts = validator_accounts(&client, &client, &validators, tip_set, &[validator_key])
+            .await
+            .unwrap();
+        assert_eq!(accounts, vec![validator_key]);
+
+        // a key of the wrong type is replaced by the matching key of the wallet
+        let accounts = validator_accounts(&client, &client, &validators, tip_set, &[bls_key])
+            .await
+            .unwrap();
+        assert_eq!(accounts, vec![validator_key]);
+
+        // no key of the validators in the wallet
+        wallet.lock().unwrap().retain(|k| k != VALIDATOR_KEY);
+        let e = validator_accounts(&client, &client, &validators, tip_set, &[bls_key])
+            .await
+            .unwrap_err();
+        assert!(e.to_string().contains("secp256k1"));
+
+        // a subnet without validators has no checkpoints to submit
+        assert!(
+            validator_accounts(&client, &client, &[], tip_set, &[bls_key])
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    /// A tipset at `height` whose messages paid `base_fee`.
+    fn tip_set(height: i64, base_fee: u64) -> serde_json::Value {
+        let cid = CIDMap::from(dag_cbor_cid(&height.to_be_bytes()).unwrap());
+        json!({
+            "Cids": [cid],
+            "Blocks": [{"ParentBaseFee": base_fee.to_string()}],
+            "Height": height,
+        })
+    }
+
+    #[tokio::test]
+    async fn test_base_fee_history() {
+        // epoch 12 is a null round, for which the node returns the tipset at 11
+        let node = MockJsonRpcClient::default()
+            .with_result("Filecoin.ChainHead", tip_set(14, 150))
+            .on("Filecoin.ChainGetTipSetByHeight", |params| {
+                Ok(match params[0].as_i64().unwrap() {
+                    11 | 12 => tip_set(11, 105),
+                    13 => tip_set(13, 140),
+                    epoch => tip_set(epoch, 100),
+                })
+            });
+        let client = LotusJsonRPCClient::new(node.clone());
+
+        let history = base_fee_history(&client, 5).await.unwrap();
+        assert_eq!(
+            history.fees,
+            vec![
+                (10, TokenAmount::from_atto(100)),
+                (11, TokenAmount::from_atto(105)),
+                (13, TokenAmount::from_atto(140)),
+                (14, TokenAmount::from_atto(150)),
+            ]
+        );
+        assert_eq!(history.trend, FeeTrend::Rising);
+
+        // the tipsets are looked up from the head, in epoch order
+        let head = CIDMap::from(dag_cbor_cid(&14i64.to_be_bytes()).unwrap());
+        assert_eq!(
+            node.params("Filecoin.ChainGetTipSetByHeight"),
+            (10..14)
+                .map(|epoch| json!([epoch, [head.clone()]]))
+                .collect::<Vec<_>>()
+        );
+
+        // with the base fee rising, the checkpoints pay up to twice the highest base fee
+        assert_eq!(
+            checkpoint_fee_cap(&client).await,
+            Some(TokenAmount::from_atto(300))
+        );
+    }
+}
@@ -2,32 +2,83 @@
 // SPDX-License-Identifier: MIT
 use std::collections::HashMap;
 use std::str::FromStr;
+use std::time::Duration;
 
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
 use cid::Cid;
 use fil_actors_runtime::types::{InitExecParams, InitExecReturn, INIT_EXEC_METHOD_NUM};
 use fil_actors_runtime::{builtin::singletons::INIT_ACTOR_ADDR, cbor};
+use fvm_shared::address::{Address, Protocol};
 use fvm_shared::clock::ChainEpoch;
 use fvm_shared::METHOD_SEND;
-use fvm_shared::{address::Address, econ::TokenAmount, MethodNum};
+use fvm_shared::{econ::TokenAmount, MethodNum};
 use ipc_gateway::{BottomUpCheckpoint, PropagateParams, WhitelistPropagatorParams};
 use ipc_sdk::subnet_id::SubnetID;
 use ipc_subnet_actor::{types::MANIFEST_ID, ConstructParams, JoinParams};
+use tokio::sync::Mutex;
 
-use crate::config::Subnet;
-use crate::jsonrpc::{JsonRpcClient, JsonRpcClientImpl};
-use crate::lotus::client::LotusJsonRPCClient;
-use crate::lotus::message::ipc::SubnetInfo;
-use crate::lotus::message::mpool::MpoolPushMessage;
+use crate::config::{GasConfig, Subnet};
+use crate::jsonrpc::{is_method_not_found, HttpClients, JsonRpcClient, JsonRpcClientImpl};
+use crate::lotus::client::{LotusJsonRPCClient, DEFAULT_STATE_WAIT_DEADLINE};
+use crate::lotus::message::ipc::{CheckpointPage, IPCReadSubnetActorStateResponse, SubnetInfo};
+use crate::lotus::message::mpool::{MpoolPushMessage, UnsignedMessage};
 use crate::lotus::message::state::StateWaitMsgResponse;
-use crate::lotus::message::wallet::WalletKeyType;
+use crate::lotus::message::wallet::{AddressFormat, KeyInfo, WalletKeyType};
 use crate::lotus::LotusClient;
 
+use super::nonce::NonceTracker;
 use super::subnet::SubnetManager;
 
+/// The errors lotus returns when looking up the nonce of an account that has not been
+/// created on chain yet, i.e. that has never received nor sent a message.
+const ACTOR_NOT_FOUND_ERRORS: [&str; 2] = ["actor not found", "resolution lookup failed"];
+
+/// The errors lotus returns when no checkpoint was committed at the epoch requested.
+const CHECKPOINT_NOT_FOUND_ERRORS: [&str; 2] = ["no checkpoint committed", "checkpoint not found"];
+
+/// The errors lotus returns when pushing a message with a nonce that is not the next one of
+/// its sender.
+const NONCE_ERRORS: [&str; 3] = [
+    "nonce too low",
+    "nonce gap",
+    "message with nonce already exists",
+];
+
 pub struct LotusSubnetManager<T: JsonRpcClient> {
     lotus_client: LotusJsonRPCClient<T>,
+    /// The nonces of the accounts sending messages through this manager, so that sequential
+    /// messages from the same account do not reuse the same nonce.
+    nonces: Mutex<NonceTracker>,
+    /// The code CID of the subnet actor to use if the node cannot report it.
+    subnet_actor_code_cid: Option<Cid>,
+    /// How long the messages sent are waited for before giving up.
+    state_wait_deadline: Duration,
+    /// The gas parameters of the messages sent that don't set them.
+    gas: GasConfig,
+}
+
+/// Sets the gas parameters of `gas` on the ones of `message` that are not set, instead of
+/// letting the node estimate them, e.g. for parents where the estimates are too low for
+/// messages to be included.
+fn apply_gas_config(gas: &GasConfig, message: &mut MpoolPushMessage) {
+    if message.gas_fee_cap.is_none() {
+        message.gas_fee_cap = gas.gas_fee_cap.clone();
+    }
+    if message.gas_premium.is_none() {
+        message.gas_premium = gas.gas_premium.clone();
+    }
+    if message.gas_limit.is_none() {
+        message.gas_limit = gas.gas_limit.map(TokenAmount::from_atto);
+    }
+    if message.max_fee.is_none() {
+        message.max_fee = gas.max_fee.clone();
+    }
+}
+
+/// Returns the gas limit `estimated` by the node multiplied by `multiplier`, rounded up.
+fn scale_gas_limit(estimated: i64, multiplier: f64) -> u64 {
+    (estimated.max(0) as f64 * multiplier).ceil() as u64
 }
 
 #[async_trait]
@@ -66,7 +117,7 @@ impl<T: JsonRpcClient + Send + Sync> SubnetManager for LotusSubnetManager<T> {
         from: Address,
         collateral: TokenAmount,
         params: JoinParams,
-    ) -> Result<()> {
+    ) -> Result<Cid> {
         let parent = subnet.parent().ok_or_else(|| anyhow!("cannot join root"))?;
         if !self.is_network_match(&parent).await? {
             return Err(anyhow!("subnet actor being deployed in the wrong parent network, parent network names do not match"));
@@ -81,13 +132,13 @@ impl<T: JsonRpcClient + Send + Sync> SubnetManager for LotusSubnetManager<T> {
         );
         message.value = collateral;
 
-        self.mpool_push_and_wait(message).await?;
+        let r = self.mpool_push_and_wait(message).await?;
         log::info!("joined subnet: {subnet:}");
 
-        Ok(())
+        Cid::try_from(r.message)
     }
 
-    async fn leave_subnet(&self, subnet: SubnetID, from: Address) -> Result<()> {
+    async fn leave_subnet(&self, subnet: SubnetID, from: Address) -> Result<Cid> {
         let parent = subnet
             .parent()
             .ok_or_else(|| anyhow!("cannot leave root"))?;
@@ -95,34 +146,36 @@ impl<T: JsonRpcClient + Send + Sync> SubnetManager for LotusSubnetManager<T> {
             return Err(anyhow!("subnet actor being deployed in the wrong parent network, parent network names do not match"));
         }
 
-        self.mpool_push_and_wait(MpoolPushMessage::new(
-            subnet.subnet_actor(),
-            from,
-            ipc_subnet_actor::Method::Leave as MethodNum,
-            vec![],
-        ))
-        .await?;
+        let r = self
+            .mpool_push_and_wait(MpoolPushMessage::new(
+                subnet.subnet_actor(),
+                from,
+                ipc_subnet_actor::Method::Leave as MethodNum,
+                vec![],
+            ))
+            .await?;
         log::info!("left subnet: {subnet:}");
 
-        Ok(())
+        Cid::try_from(r.message)
     }
 
-    async fn kill_subnet(&self, subnet: SubnetID, from: Address) -> Result<()> {
+    async fn kill_subnet(&self, subnet: SubnetID, from: Address) -> Result<Cid> {
         let parent = subnet.parent().ok_or_else(|| anyhow!("cannot kill root"))?;
         if !self.is_network_match(&parent).await? {
             return Err(anyhow!("subnet actor being deployed in the wrong parent network, parent network names do not match"));
         }
 
-        self.mpool_push_and_wait(MpoolPushMessage::new(
-            subnet.subnet_actor(),
-            from,
-            ipc_subnet_actor::Method::Kill as MethodNum,
-            vec![],
-        ))
-        .await?;
-        log::info!("left subnet: {subnet:}");
+        let r = self
+            .mpool_push_and_wait(MpoolPushMessage::new(
+                subnet.subnet_actor(),
+                from,
+                ipc_subnet_actor::Method::Kill as MethodNum,
+                vec![],
+            ))
+            .await?;
+        log::info!("killed subnet: {subnet:}");
 
-        Ok(())
+        Cid::try_from(r.message)
     }
 
     async fn list_child_subnets(
@@ -131,7 +184,7 @@ impl<T: JsonRpcClient + Send + Sync> SubnetManager for LotusSubnetManager<T> {
     ) -> Result<HashMap<SubnetID, SubnetInfo>> {
         let subnets = self
             .lotus_client
-            .ipc_list_child_subnets(gateway_addr)
+            .ipc_list_child_subnets(gateway_addr, None)
             .await?;
 
         log::debug!("received subnets: {subnets:?}");
@@ -152,7 +205,7 @@ impl<T: JsonRpcClient + Send + Sync> SubnetManager for LotusSubnetManager<T> {
         gateway_addr: Address,
         from: Address,
         amount: TokenAmount,
-    ) -> Result<()> {
+    ) -> Result<Cid> {
         // When we perform the fund, we should send to the gateway of the subnet's parent
         let parent = subnet.parent().ok_or_else(|| anyhow!("cannot fund root"))?;
         if !self.is_network_match(&parent).await? {
@@ -170,8 +223,9 @@ impl<T: JsonRpcClient + Send + Sync> SubnetManager for LotusSubnetManager<T> {
         );
         message.value = amount;
 
-        self.mpool_push_and_wait(message).await?;
-        Ok(())
+        let r = self.mpool_push_and_wait(message).await?;
+        // the message waited on may have been replaced, e.g. to bump its gas
+        Cid::try_from(r.message)
     }
 
     async fn release(
@@ -180,24 +234,12 @@ impl<T: JsonRpcClient + Send + Sync> SubnetManager for LotusSubnetManager<T> {
         gateway_addr: Address,
         from: Address,
         amount: TokenAmount,
-    ) -> Result<()> {
-        // When we perform the release, we should send to the gateway of the subnet
-        if !self.is_network_match(&subnet).await? {
-            return Err(anyhow!(
-                "subnet actor being released not matching current network"
-            ));
-        }
-
-        let mut message = MpoolPushMessage::new(
-            gateway_addr,
-            from,
-            ipc_gateway::Method::Release as MethodNum,
-            vec![],
-        );
-        message.value = amount;
-
-        self.mpool_push_and_wait(message).await?;
-        Ok(())
+    ) -> Result<Cid> {
+        let cid = self
+            .push_release(subnet, gateway_addr, from, amount)
+            .await?;
+        let r = self.wait_msg(cid).await?;
+        Cid::try_from(r.message)
     }
 
     async fn propagate(
@@ -306,6 +348,11 @@ impl<T: JsonRpcClient + Send + Sync> SubnetManager for LotusSubnetManager<T> {
         Address::from_str(&addr_str).map_err(|_| anyhow!("cannot get address from string output"))
     }
 
+    async fn wallet_import(&self, key_info: &KeyInfo) -> Result<Address> {
+        log::info!("importing {:} key into wallet", key_info.key_type);
+        self.lotus_client.wallet_import(key_info).await
+    }
+
     async fn wallet_list(&self) -> Result<Vec<Address>> {
         log::info!("list wallet in subnet");
         self.lotus_client
@@ -321,6 +368,17 @@ impl<T: JsonRpcClient + Send + Sync> SubnetManager for LotusSubnetManager<T> {
         self.lotus_client.wallet_balance(address).await
     }
 
+    async fn resolve_address(&self, address: &Address, format: AddressFormat) -> Result<Address> {
+        let is_id = address.protocol() == Protocol::ID;
+        match format {
+            AddressFormat::Id if !is_id => self.lotus_client.state_lookup_id(address, None).await,
+            AddressFormat::Robust if is_id => {
+                self.lotus_client.state_account_key(address, None).await
+            }
+            _ => Ok(*address),
+        }
+    }
+
     async fn list_checkpoints(
         &self,
         subnet_id: SubnetID,
@@ -334,6 +392,20 @@ impl<T: JsonRpcClient + Send + Sync> SubnetManager for LotusSubnetManager<T> {
         Ok(checkpoints)
     }
 
+    async fn list_checkpoints_paged(
+        &self,
+        subnet_id: SubnetID,
+        from_epoch: ChainEpoch,
+        to_epoch: ChainEpoch,
+        period: ChainEpoch,
+        offset: usize,
+        limit: usize,
+    ) -> Result<CheckpointPage> {
+        self.lotus_client
+            .ipc_list_checkpoints_paged(subnet_id, from_epoch, to_epoch, period, offset, limit)
+            .await
+    }
+
     async fn last_topdown_executed(&self) -> Result<ChainEpoch> {
         let head = self.lotus_client.chain_head().await?;
         let cid_map = head.cids.first().unwrap().clone();
@@ -346,17 +418,246 @@ impl<T: JsonRpcClient + Send + Sync> SubnetManager for LotusSubnetManager<T> {
 
 impl<T: JsonRpcClient + Send + Sync> LotusSubnetManager<T> {
     pub fn new(lotus_client: LotusJsonRPCClient<T>) -> Self {
-        Self { lotus_client }
+        Self {
+            lotus_client,
+            nonces: Mutex::new(NonceTracker::default()),
+            subnet_actor_code_cid: None,
+            state_wait_deadline: DEFAULT_STATE_WAIT_DEADLINE,
+            gas: GasConfig::default(),
+        }
+    }
+
+    /// Sets the gas parameters of the messages sent that don't set them.
+    pub fn with_gas(mut self, gas: GasConfig) -> Self {
+        self.gas = gas;
+        self
+    }
+
+    /// Sets how long the messages sent are waited for before returning a
+    /// [`crate::lotus::StateWaitTimeout`] error.
+    pub fn with_state_wait_deadline(mut self, deadline: Duration) -> Self {
+        self.state_wait_deadline = deadline;
+        self
+    }
+
+    /// Sets the code CID of the subnet actor used to create subnets when the node does not
+    /// expose the code CIDs of the actors.
+    pub fn with_subnet_actor_code_cid(mut self, code_cid: Option<Cid>) -> Self {
+        self.subnet_actor_code_cid = code_cid;
+        self
+    }
+
+    /// Returns the number of epochs the head of the node lags behind the head of the network
+    /// the node is syncing to. A node that is not syncing is considered up to date.
+    pub async fn head_lag(&self) -> Result<ChainEpoch> {
+        let head = self.lotus_client.chain_head().await?;
+        let sync_state = self.lotus_client.sync_state().await?;
+
+        let lag = match sync_state.target_height() {
+            Some(target) => target.saturating_sub(head.height),
+            None => 0,
+        };
+        Ok(ChainEpoch::try_from(lag)?)
+    }
+
+    /// Returns the genesis epoch and the period of the bottom-up checkpoints of `subnet_id`,
+    /// read from its subnet actor. Checkpoints are only committed at epochs `genesis + k * period`.
+    pub async fn bottom_up_checkpoint_schedule(
+        &self,
+        subnet_id: &SubnetID,
+    ) -> Result<(ChainEpoch, ChainEpoch)> {
+        let state = self.subnet_actor_state(subnet_id).await?;
+        Ok((
+            state.bottom_up_checkpoint_voting.genesis_epoch,
+            state.bottom_up_check_period,
+        ))
+    }
+
+    /// Returns the state of the subnet actor of `subnet_id` at the head of the chain.
+    pub async fn subnet_actor_state(
+        &self,
+        subnet_id: &SubnetID,
+    ) -> Result<IPCReadSubnetActorStateResponse> {
+        let head = self.lotus_client.chain_head().await?;
+        let tip_set = Cid::try_from(
+            head.cids
+                .first()
+                .ok_or_else(|| anyhow!("chain head has no cids"))?
+                .clone(),
+        )?;
+        self.lotus_client
+            .ipc_read_subnet_actor_state(subnet_id, tip_set)
+            .await
     }
 
-    /// Publish the message to memory pool and wait for the response
+    /// Returns the checkpoint of `subnet_id` committed in the parent at `epoch`, or `None` if
+    /// the node reports that no checkpoint was committed at that epoch.
+    pub async fn checkpoint_at(
+        &self,
+        subnet_id: &SubnetID,
+        epoch: ChainEpoch,
+    ) -> Result<Option<BottomUpCheckpoint>> {
+        match self.lotus_client.ipc_get_checkpoint(subnet_id, epoch).await {
+            Ok(checkpoint) => Ok(Some(checkpoint)),
+            Err(e) if is_checkpoint_not_found(&e) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Pushes the release of `amount` from `from` in `subnet` to the same address in its parent,
+    /// returning the cid of the message without waiting for it to be executed.
+    pub async fn push_release(
+        &self,
+        subnet: SubnetID,
+        gateway_addr: Address,
+        from: Address,
+        amount: TokenAmount,
+    ) -> Result<Cid> {
+        // When we perform the release, we should send to the gateway of the subnet
+        if !self.is_network_match(&subnet).await? {
+            return Err(anyhow!(
+                "subnet actor being released not matching current network"
+            ));
+        }
+
+        let mut message = MpoolPushMessage::new(
+            gateway_addr,
+            from,
+            ipc_gateway::Method::Release as MethodNum,
+            vec![],
+        );
+        message.value = amount;
+
+        self.mpool_push(message).await
+    }
+
+    /// Waits for the message `cid` to be executed, up to the state wait deadline.
+    pub async fn wait_msg(&self, cid: Cid) -> Result<StateWaitMsgResponse> {
+        self.lotus_client
+            .state_wait_msg_deadline(cid, self.state_wait_deadline)
+            .await
+    }
+
+    /// Publish the message to memory pool and wait for the response, up to the state wait deadline
     async fn mpool_push_and_wait(&self, message: MpoolPushMessage) -> Result<StateWaitMsgResponse> {
-        let mem_push_response = self.lotus_client.mpool_push_message(message).await?;
+        let message_cid = self.mpool_push(message).await?;
+        self.wait_msg(message_cid).await
+    }
 
-        let message_cid = mem_push_response.cid()?;
-        log::debug!("message published with cid: {message_cid:?}");
+    /// Publish the message to memory pool, returning its cid. The gas parameters the message
+    /// doesn't set are estimated by the node, then the message is signed by its sender and
+    /// pushed with the next nonce of the sender, unless it sets its own.
+    async fn mpool_push(&self, mut message: MpoolPushMessage) -> Result<Cid> {
+        apply_gas_config(&self.gas, &mut message);
+        let fixed_nonce = message.nonce;
+        let mut unsigned = self.estimate_gas(message).await?;
+        let from = unsigned.from;
+
+        // hold the lock until the message is in the mempool, so that concurrent sends
+        // from the same account are assigned consecutive nonces
+        let mut nonces = self.nonces.lock().await;
+        let mut resynced = false;
+        loop {
+            unsigned.nonce = match fixed_nonce {
+                Some(nonce) => nonce,
+                None => nonces.next(&from, || self.pending_nonce(&from)).await?,
+            };
+            log::debug!(
+                "sending message from {from:} with nonce {:}",
+                unsigned.nonce
+            );
+
+            let pushed = match self
+                .lotus_client
+                .wallet_sign_message(unsigned.clone())
+                .await
+            {
+                Ok(signed) => self.lotus_client.mpool_push(signed).await,
+                Err(e) => Err(e),
+            };
+            match pushed {
+                Ok(message_cid) => {
+                    log::debug!("message published with cid: {message_cid:?}");
+                    return Ok(message_cid);
+                }
+                Err(e) => {
+                    // the nonce was not consumed, fetch it again from the mempool next time
+                    nonces.reset(&from);
+                    if fixed_nonce.is_some() || resynced || !is_nonce_error(&e) {
+                        return Err(e);
+                    }
+                    log::warn!(
+                        "nonce {:} of {from:} rejected, resyncing with the mempool: {e:}",
+                        unsigned.nonce
+                    );
+                    resynced = true;
+                }
+            }
+        }
+    }
 
-        self.lotus_client.state_wait_msg(message_cid).await
+    /// Returns the message to sign for `message`, with the gas parameters it doesn't set
+    /// estimated by the node. A gas limit estimated is multiplied by the estimation multiplier,
+    /// and the fee cap lowered for the message to pay at most its max fee.
+    async fn estimate_gas(&self, message: MpoolPushMessage) -> Result<UnsignedMessage> {
+        let (to, from, value, method, params) = (
+            message.to,
+            message.from,
+            message.value.clone(),
+            message.method,
+            message.params.clone(),
+        );
+        let limit_set = message.gas_limit.is_some();
+        let max_fee = message.max_fee.clone();
+        let estimate = self
+            .lotus_client
+            .gas_estimate_message_gas(message, max_fee.clone())
+            .await?;
+
+        let mut gas_limit = estimate.gas_limit;
+        if let (false, Some(multiplier)) = (limit_set, self.gas.estimation_multiplier) {
+            let limit = scale_gas_limit(gas_limit, multiplier);
+            log::debug!("gas limit of message to {to:} estimated at {gas_limit:}, set to {limit:}");
+            gas_limit =
+                i64::try_from(limit).map_err(|_| anyhow!("gas limit {limit:} out of range"))?;
+        }
+        let (gas_fee_cap, gas_premium) = cap_gas_fee(
+            max_fee.as_ref(),
+            gas_limit,
+            estimate.gas_fee_cap,
+            estimate.gas_premium,
+        );
+        log::debug!(
+            "gas of message to {to:}: limit {gas_limit:}, fee cap {:}, premium {:}",
+            gas_fee_cap.atto(),
+            gas_premium.atto()
+        );
+
+        Ok(UnsignedMessage {
+            version: 0,
+            to,
+            from,
+            nonce: 0,
+            value,
+            gas_limit,
+            gas_fee_cap,
+            gas_premium,
+            method,
+            params,
+        })
+    }
+
+    /// Returns the next nonce of `address` in the mempool. Accounts that do not exist on chain
+    /// yet have never sent a message, so their first nonce is 0.
+    async fn pending_nonce(&self, address: &Address) -> Result<u64> {
+        match self.lotus_client.mpool_get_nonce(address).await {
+            Ok(nonce) => Ok(nonce),
+            Err(e) if is_actor_not_found(&e) => {
+                log::debug!("account {address:} not found on chain, using nonce 0");
+                Ok(0)
+            }
+            Err(e) => Err(e),
+        }
     }
 
     /// Checks the `network` is the one we are currently talking to.
@@ -376,10 +677,20 @@ impl<T: JsonRpcClient + Send + Sync> LotusSubnetManager<T> {
         let network_version = self.lotus_client.state_network_version(vec![]).await?;
         log::debug!("received network version: {network_version:?}");
 
-        let mut cid_map = self
+        let mut cid_map = match self
             .lotus_client
             .state_actor_code_cids(network_version)
-            .await?;
+            .await
+        {
+            Ok(cid_map) => cid_map,
+            Err(e) if is_method_not_found(&e) => {
+                log::warn!("node does not expose the actor code cids, using the configured subnet actor code cid");
+                return self.subnet_actor_code_cid.ok_or_else(|| {
+                    anyhow!("node does not expose the actor code cids, set the subnet_actor_code_cid of the subnet in the config")
+                });
+            }
+            Err(e) => return Err(e),
+        };
 
         cid_map
             .remove(MANIFEST_ID)
@@ -388,8 +699,262 @@ impl<T: JsonRpcClient + Send + Sync> LotusSubnetManager<T> {
 }
 
 impl LotusSubnetManager<JsonRpcClientImpl> {
-    pub fn from_subnet(subnet: &Subnet) -> Self {
-        let client = LotusJsonRPCClient::from_subnet(subnet);
+    pub fn from_subnet(subnet: &Subnet, http_clients: &HttpClients) -> Self {
+        let client = LotusJsonRPCClient::from_subnet_with_clients(subnet, http_clients);
         LotusSubnetManager::new(client)
+            .with_subnet_actor_code_cid(subnet.subnet_actor_code_cid)
+            .with_gas(subnet.gas.clone())
+    }
+}
+
+/// Returns the fee cap and premium of a message with `gas_limit`, lowered as lotus does for the
+/// message to pay at most `max_fee`, if set. The premium is never above the fee cap.
+fn cap_gas_fee(
+    max_fee: Option<&TokenAmount>,
+    gas_limit: i64,
+    fee_cap: TokenAmount,
+    premium: TokenAmount,
+) -> (TokenAmount, TokenAmount) {
+    let fee_cap = match max_fee {
+        Some(max_fee) if gas_limit > 0 && fee_cap.atto() * gas_limit > *max_fee.atto() => {
+            TokenAmount::from_atto(max_fee.atto() / gas_limit)
+        }
+        _ => fee_cap,
+    };
+    let premium = if premium > fee_cap {
+        fee_cap.clone()
+    } else {
+        premium
+    };
+    (fee_cap, premium)
+}
+
+/// Checks if the error returned by lotus is caused by no checkpoint being committed at the
+/// epoch requested.
+fn is_checkpoint_not_found(e: &anyhow::Error) -> bool {
+    let msg = e.to_string();
+    CHECKPOINT_NOT_FOUND_ERRORS.iter().any(|m| msg.contains(m))
+}
+
+/// Checks if the error returned by lotus is caused by the nonce of the message not being the
+/// next one of the sender, e.g. because the sender sent messages outside of the agent.
+fn is_nonce_error(e: &anyhow::Error) -> bool {
+    let msg = e.to_string();
+    NONCE_ERRORS.iter().any(|m| msg.contains(m))
+}
+
+/// Checks if the error returned by lotus is caused by the actor not existing on chain.
+fn is_actor_not_found(e: &anyhow::Error) -> bool {
+    let msg = e.to_string();
+    ACTOR_NOT_FOUND_ERRORS.iter().any(|m| msg.contains(m))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::Arc;
+
+    use anyhow::anyhow;
+    use fvm_shared::address::Address;
+    use fvm_shared::econ::TokenAmount;
+    use fvm_shared::METHOD_SEND;
+    use indoc::formatdoc;
+    use ipc_sdk::subnet_id::{SubnetID, ROOTNET_ID};
+    use serde_json::json;
+
+    use crate::config::{Config, GasConfig};
+    use crate::jsonrpc::mock::MockJsonRpcClient;
+    use crate::lotus::client::{mpool_push_message_params, LotusJsonRPCClient};
+    use crate::lotus::message::mpool::MpoolPushMessage;
+    use crate::manager::lotus::{
+        apply_gas_config, is_actor_not_found, scale_gas_limit, LotusSubnetManager,
+    };
+
+    fn message() -> MpoolPushMessage {
+        MpoolPushMessage::new(
+            Address::new_id(1001),
+            Address::new_id(1000),
+            METHOD_SEND,
+            vec![],
+        )
+    }
+
+    #[test]
+    fn test_gas_defaults() {
+        let gas = GasConfig {
+            gas_fee_cap: Some(TokenAmount::from_atto(200000)),
+            gas_limit: Some(10000000),
+            ..Default::default()
+        };
+
+        let mut m = message();
+        apply_gas_config(&gas, &mut m);
+        let params = mpool_push_message_params(m).unwrap();
+        assert_eq!(params[0]["GasFeeCap"], "200000");
+        assert_eq!(params[0]["GasLimit"], 10000000);
+        // lotus matches the keys ignoring their case, but not underscores
+        assert!(params[0].get("gas_fee_cap").is_none());
+        // the node still estimates the parameters without defaults
+        assert!(params[0]["GasPremium"].is_null());
+
+        // the parameters set by the caller are kept
+        let mut m = message();
+        m.gas_fee_cap = Some(TokenAmount::from_atto(300000));
+        apply_gas_config(&gas, &mut m);
+        let params = mpool_push_message_params(m).unwrap();
+        assert_eq!(params[0]["GasFeeCap"], "300000");
+    }
+
+    #[test]
+    fn test_gas_config() {
+        let config = Config::from_toml_str(&formatdoc!(
+            r#"
+                [server]
+                json_rpc_address = "127.0.0.1:3030"
+
+                [[subnets]]
+                id = "/root"
+                gateway_addr = "t064"
+                network_name = "root"
+                jsonrpc_api_http = "http://127.0.0.1:1234/rpc/v1"
+
+                [subnets.gas]
+                gas_fee_cap = "18446744073709551616"
+                gas_premium = "100"
+                max_fee = "1000000000000000000"
+                estimation_multiplier = 1.5
+            "#
+        ))
+        .unwrap();
+        let gas = &config.subnets.values().next().unwrap().gas;
+        assert_eq!(
+            gas,
+            &GasConfig {
+                // above u64::MAX
+                gas_fee_cap: Some(TokenAmount::from_atto(u64::MAX) + TokenAmount::from_atto(1)),
+                gas_premium: Some(TokenAmount::from_atto(100)),
+                gas_limit: None,
+                max_fee: Some(TokenAmount::from_whole(1)),
+                estimation_multiplier: Some(1.5),
+            }
+        );
+
+        let mut m = message();
+        apply_gas_config(gas, &mut m);
+        let params = mpool_push_message_params(m).unwrap();
+        assert_eq!(params[0]["GasFeeCap"], "18446744073709551616");
+        assert_eq!(params[0]["GasPremium"], "100");
+        assert_eq!(params[1]["MaxFee"], "1000000000000000000");
+
+        assert_eq!(scale_gas_limit(1000, 1.5), 1500);
+        assert_eq!(scale_gas_limit(1001, 1.25), 1252);
+    }
+
+    const CID: &str = "bafy2bzacedkoa623kvi5gfis2yks7xxjl73vg7xwbojz4tpq63dd5jpfz757i";
+
+    /// A node estimating the gas of the messages, signing them and accepting the ones pushed
+    /// with at least the nonce returned by `MpoolGetNonce`.
+    fn push_node(pending_nonce: Arc<AtomicU64>) -> MockJsonRpcClient {
+        let pushed_nonce = pending_nonce.clone();
+        MockJsonRpcClient::default()
+            .with_result(
+                "Filecoin.GasEstimateMessageGas",
+                json!({"GasLimit": 1000, "GasFeeCap": "100", "GasPremium": "10"}),
+            )
+            .on("Filecoin.MpoolGetNonce", move |_| {
+                Ok(json!(pending_nonce.load(Ordering::SeqCst)))
+            })
+            .on("Filecoin.WalletSignMessage", |params| {
+                Ok(json!({
+                    "Message": params[1],
+                    "Signature": {"Type": 1, "Data": "c2lnbmF0dXJl"},
+                }))
+            })
+            .on("Filecoin.MpoolPush", move |params| {
+                let nonce = params[0]["Message"]["Nonce"].as_u64().unwrap();
+                if nonce < pushed_nonce.load(Ordering::SeqCst) {
+                    return Err(anyhow!(
+                        "minimum expected nonce is 9: message nonce too low"
+                    ));
+                }
+                Ok(json!({ "/": CID }))
+            })
+    }
+
+    #[tokio::test]
+    async fn test_push_scaled_gas_limit() {
+        let node = push_node(Arc::new(AtomicU64::new(0)));
+        let manager =
+            LotusSubnetManager::new(LotusJsonRPCClient::new(node.clone())).with_gas(GasConfig {
+                max_fee: Some(TokenAmount::from_atto(120000)),
+                estimation_multiplier: Some(1.5),
+                ..Default::default()
+            });
+
+        let cid = manager.mpool_push(message()).await.unwrap();
+        assert_eq!(cid.to_string(), CID);
+
+        let estimates = node.params("Filecoin.GasEstimateMessageGas");
+        // the limit is estimated without the gas parameters set, for the node to estimate it
+        assert!(estimates[0][0]["GasLimit"].is_null());
+        assert_eq!(estimates[0][1]["MaxFee"], "120000");
+        let pushed = node.params("Filecoin.MpoolPush");
+        assert_eq!(pushed[0][0]["Message"]["GasLimit"], 1500);
+        // the fee cap is lowered for the scaled limit to stay within the max fee
+        assert_eq!(pushed[0][0]["Message"]["GasFeeCap"], "80");
+        assert_eq!(pushed[0][0]["Message"]["GasPremium"], "10");
+        assert_eq!(pushed[0][0]["Signature"]["Type"], 1);
+    }
+
+    #[tokio::test]
+    async fn test_push_nonces() {
+        let pending_nonce = Arc::new(AtomicU64::new(5));
+        let node = push_node(pending_nonce.clone());
+        let manager = LotusSubnetManager::new(LotusJsonRPCClient::new(node.clone()));
+
+        manager.mpool_push(message()).await.unwrap();
+        manager.mpool_push(message()).await.unwrap();
+        // messages sent from the account outside of the agent
+        pending_nonce.store(9, Ordering::SeqCst);
+        manager.mpool_push(message()).await.unwrap();
+        manager.mpool_push(message()).await.unwrap();
+
+        let nonces = node
+            .params("Filecoin.MpoolPush")
+            .iter()
+            .map(|p| p[0]["Message"]["Nonce"].as_u64().unwrap())
+            .collect::<Vec<_>>();
+        // the rejected nonce is resynced with the mempool
+        assert_eq!(nonces, vec![5, 6, 7, 9, 10]);
+        // the nonce is only assigned once the gas is estimated, right before signing
+        assert!(node
+            .params("Filecoin.GasEstimateMessageGas")
+            .iter()
+            .all(|p| p[0]["Nonce"].is_null()));
+    }
+
+    #[test]
+    fn test_is_actor_not_found() {
+        assert!(is_actor_not_found(&anyhow!(
+            "getting actor: resolution lookup failed (t1abc): actor not found"
+        )));
+        assert!(!is_actor_not_found(&anyhow!("connection refused")));
+    }
+
+    #[tokio::test]
+    async fn test_checkpoint_at_missing() {
+        let node =
+            MockJsonRpcClient::default().on("Filecoin.IPCGetCheckpointSerialized", |params| {
+                match params[1].as_i64() {
+                    Some(10) => Err(anyhow!("no checkpoint committed in epoch: 10")),
+                    _ => Err(anyhow!("connection refused")),
+                }
+            });
+        let manager = LotusSubnetManager::new(LotusJsonRPCClient::new(node));
+        let subnet = SubnetID::new_from_parent(&ROOTNET_ID, Address::new_id(1002));
+
+        // only a checkpoint the node reports missing is not an error
+        assert!(manager.checkpoint_at(&subnet, 10).await.unwrap().is_none());
+        assert!(manager.checkpoint_at(&subnet, 20).await.is_err());
     }
 }
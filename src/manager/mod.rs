@@ -8,5 +8,6 @@ pub use crate::lotus::message::ipc::SubnetInfo;
 pub(crate) mod bottomup;
 pub mod checkpoint;
 mod lotus;
+pub mod nonce;
 mod subnet;
 pub(crate) mod topdown;
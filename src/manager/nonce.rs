@@ -0,0 +1,67 @@
+// Copyright 2022-2023 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Local nonce management for the messages sent by the agent.
+
+use std::collections::HashMap;
+use std::future::Future;
+
+use anyhow::Result;
+use fvm_shared::address::Address;
+
+/// Hands out consecutive nonces to the messages sent from the same account. The first nonce
+/// of an account is its pending nonce in the mempool; the following ones are incremented
+/// locally, so a batch of messages can be pushed in quick succession without lotus
+/// rejecting them for reusing the same nonce.
+#[derive(Debug, Default)]
+pub struct NonceTracker {
+    next: HashMap<Address, u64>,
+}
+
+impl NonceTracker {
+    /// Returns the nonce to use for the next message sent from `address`. The pending nonce
+    /// of the account is obtained from `fetch_pending` the first time the account is used.
+    pub async fn next<F, Fut>(&mut self, address: &Address, fetch_pending: F) -> Result<u64>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<u64>>,
+    {
+        let nonce = match self.next.get(address) {
+            Some(nonce) => *nonce,
+            None => fetch_pending().await?,
+        };
+        self.next.insert(*address, nonce + 1);
+        Ok(nonce)
+    }
+
+    /// Forgets the nonce of `address`, so that it is fetched again from the mempool the next
+    /// time. Used when a message fails to be pushed and the local count is no longer reliable.
+    pub fn reset(&mut self, address: &Address) {
+        self.next.remove(address);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fvm_shared::address::Address;
+
+    use crate::manager::nonce::NonceTracker;
+
+    #[tokio::test]
+    async fn test_nonces_increment_across_batch() {
+        let mut tracker = NonceTracker::default();
+        let a = Address::new_id(1000);
+        let b = Address::new_id(1001);
+
+        assert_eq!(tracker.next(&a, || async { Ok(5) }).await.unwrap(), 5);
+        // the pending nonce is only fetched the first time
+        let fetch_fails = || async { Err(anyhow::anyhow!("should not be fetched")) };
+        assert_eq!(tracker.next(&a, fetch_fails).await.unwrap(), 6);
+        assert_eq!(tracker.next(&a, fetch_fails).await.unwrap(), 7);
+
+        // accounts are tracked independently
+        assert_eq!(tracker.next(&b, || async { Ok(0) }).await.unwrap(), 0);
+
+        tracker.reset(&a);
+        assert_eq!(tracker.next(&a, || async { Ok(9) }).await.unwrap(), 9);
+    }
+}
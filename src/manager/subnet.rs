@@ -12,7 +12,10 @@ use ipc_gateway::BottomUpCheckpoint;
 use ipc_sdk::subnet_id::SubnetID;
 use ipc_subnet_actor::{ConstructParams, JoinParams};
 
-use crate::lotus::message::{ipc::SubnetInfo, wallet::WalletKeyType};
+use crate::lotus::message::{
+    ipc::{CheckpointPage, SubnetInfo},
+    wallet::{AddressFormat, KeyInfo, WalletKeyType},
+};
 
 /// Trait to interact with a subnet and handle its lifecycle.
 #[async_trait]
@@ -26,20 +29,21 @@ pub trait SubnetManager {
     /// Performs the call to join a subnet from a wallet address and staking an amount
     /// of collateral. This function, as well as all of the ones on this trait, can infer
     /// the specific subnet and actors on which to perform the relevant calls from the
-    /// SubnetID given as an argument.
+    /// SubnetID given as an argument. Returns the cid of the join message once included.
     async fn join_subnet(
         &self,
         subnet: SubnetID,
         from: Address,
         collateral: TokenAmount,
         params: JoinParams,
-    ) -> Result<()>;
+    ) -> Result<Cid>;
 
-    /// Sends a request to leave a subnet from a wallet address.
-    async fn leave_subnet(&self, subnet: SubnetID, from: Address) -> Result<()>;
+    /// Sends a request to leave a subnet from a wallet address. Returns the cid of the leave
+    /// message once included.
+    async fn leave_subnet(&self, subnet: SubnetID, from: Address) -> Result<Cid>;
 
-    /// Sends a signal to kill a subnet
-    async fn kill_subnet(&self, subnet: SubnetID, from: Address) -> Result<()>;
+    /// Sends a signal to kill a subnet. Returns the cid of the kill message once included.
+    async fn kill_subnet(&self, subnet: SubnetID, from: Address) -> Result<Cid>;
 
     /// Lists all the registered children in a gateway.
     async fn list_child_subnets(
@@ -47,23 +51,25 @@ pub trait SubnetManager {
         gateway_addr: Address,
     ) -> Result<HashMap<SubnetID, SubnetInfo>>;
 
-    /// Fund injects new funds from an account of the parent chain to a subnet
+    /// Fund injects new funds from an account of the parent chain to a subnet. Returns the
+    /// cid of the fund message once included in the parent chain.
     async fn fund(
         &self,
         subnet: SubnetID,
         gateway_addr: Address,
         from: Address,
         amount: TokenAmount,
-    ) -> Result<()>;
+    ) -> Result<Cid>;
 
-    /// Release creates a new check message to release funds in parent chain
+    /// Release creates a new check message to release funds in parent chain. Returns the cid
+    /// of the release message once executed in the subnet.
     async fn release(
         &self,
         subnet: SubnetID,
         gateway_addr: Address,
         from: Address,
         amount: TokenAmount,
-    ) -> Result<()>;
+    ) -> Result<Cid>;
 
     /// Propagate a cross-net message forward
     async fn propagate(
@@ -98,12 +104,19 @@ pub trait SubnetManager {
     /// Create new wallet in a subnet
     async fn wallet_new(&self, key_type: WalletKeyType) -> Result<Address>;
 
+    /// Import an existing private key into the wallet of the subnet node
+    async fn wallet_import(&self, key_info: &KeyInfo) -> Result<Address>;
+
     /// List wallets in this subnet
     async fn wallet_list(&self) -> Result<Vec<Address>>;
 
     /// Get the balance of an address
     async fn wallet_balance(&self, address: &Address) -> Result<TokenAmount>;
 
+    /// Converts the address into the requested format, i.e. its ID address or
+    /// the public key address of the account.
+    async fn resolve_address(&self, address: &Address, format: AddressFormat) -> Result<Address>;
+
     /// Returns the epoch of the latest top-down checkpoint executed
     async fn last_topdown_executed(&self) -> Result<ChainEpoch>;
 
@@ -114,4 +127,16 @@ pub trait SubnetManager {
         from_epoch: ChainEpoch,
         to_epoch: ChainEpoch,
     ) -> Result<Vec<BottomUpCheckpoint>>;
+
+    /// Returns the checkpoints from a subnet actor committed in `limit` checkpoint periods of
+    /// the given epoch range, skipping the first `offset` periods.
+    async fn list_checkpoints_paged(
+        &self,
+        subnet_id: SubnetID,
+        from_epoch: ChainEpoch,
+        to_epoch: ChainEpoch,
+        period: ChainEpoch,
+        offset: usize,
+        limit: usize,
+    ) -> Result<CheckpointPage>;
 }
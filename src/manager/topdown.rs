@@ -1,9 +1,6 @@
 // Copyright 2022-2023 Protocol Labs
 // SPDX-License-Identifier: MIT
 
-use std::collections::hash_map::RandomState;
-use std::collections::HashSet;
-use std::ops::Deref;
 use std::str::FromStr;
 use std::sync::Arc;
 
@@ -20,10 +17,12 @@ use tokio::sync::Notify;
 use crate::config::Subnet;
 use crate::constants::GATEWAY_ACTOR_ADDRESS;
 use crate::jsonrpc::JsonRpcClient;
-use crate::lotus::client::LotusJsonRPCClient;
+use crate::lotus::client::{LotusJsonRPCClient, DEFAULT_STATE_WAIT_DEADLINE};
 use crate::lotus::message::mpool::MpoolPushMessage;
 use crate::lotus::LotusClient;
-use crate::manager::checkpoint::{wait_next_iteration, CHAIN_HEAD_REQUEST_PERIOD};
+use crate::manager::checkpoint::{
+    validator_accounts, wait_next_iteration, CHAIN_HEAD_REQUEST_PERIOD,
+};
 
 pub async fn manage_topdown_checkpoints(
     (child, parent): (Subnet, Subnet),
@@ -83,115 +82,121 @@ pub async fn manage_topdown_checkpoints(
                         e
                     })?;
 
-                let mut validator_set: HashSet<Address, RandomState> = HashSet::new();
-                match subnet_actor_state.validator_set.validators {
-                    None => {}
-                    Some(validators) => {
-                        for v in validators {
-                            validator_set.insert(Address::from_str(v.addr.deref())?);
-                        }
+                let validators = subnet_actor_state
+                    .validator_set
+                    .validators
+                    .unwrap_or_default();
+                let accounts = match validator_accounts(
+                    &parent_client,
+                    &child_client,
+                    &validators,
+                    parent_tip_set,
+                    &child.accounts,
+                )
+                .await
+                {
+                    Ok(accounts) => accounts,
+                    Err(e) => {
+                        log::warn!(
+                            "not submitting top-down checkpoints for {:}: {e:}",
+                            child.id
+                        );
+                        vec![]
                     }
                 };
 
                 // For each account that we manage that is in the validator set, we submit a topdown
                 // checkpoint.
-                for account in child.accounts.iter() {
+                for account in accounts.iter() {
                     log::debug!("Getting list of validators from subnet");
-                    if validator_set.contains(account) {
-                        // check if the validator already voted the top-down checkpoint
-                        // in the child.
-                        // let has_voted = child_client
-                        //     .ipc_validator_has_voted_topdown(
-                        //         // FIXME: Do not use the default, use the one configured
-                        //         // for the subnet
-                        //         &Address::from_str(GATEWAY_ACTOR_ADDRESS)?,
-                        //         submission_epoch,
-                        //         account,
-                        //     )
-                        //     .await
-                        //     .map_err(|e| {
-                        //         log::error!(
-                        //             "error checking if validator has voted in subnet: {:?}",
-                        //             &child.id
-                        //         );
-                        //         e
-                        //     })?;
-                        // FIXME: There is a nasty bug in the de-serialization of EpochVoteSubmissions in
-                        // the actor due to the fact that we are using Cids and nodes can't be load.
-                        // commenting for now, but needs to be fixed in actors.
-                        let has_voted = false;
+                    // check if the validator already voted the top-down checkpoint
+                    // in the child.
+                    // let has_voted = child_client
+                    //     .ipc_validator_has_voted_topdown(
+                    //         // FIXME: Do not use the default, use the one configured
+                    //         // for the subnet
+                    //         &Address::from_str(GATEWAY_ACTOR_ADDRESS)?,
+                    //         submission_epoch,
+                    //         account,
+                    //     )
+                    //     .await
+                    //     .map_err(|e| {
+                    //         log::error!(
+                    //             "error checking if validator has voted in subnet: {:?}",
+                    //             &child.id
+                    //         );
+                    //         e
+                    //     })?;
+                    // FIXME: There is a nasty bug in the de-serialization of EpochVoteSubmissions in
+                    // the actor due to the fact that we are using Cids and nodes can't be load.
+                    // commenting for now, but needs to be fixed in actors.
+                    let has_voted = false;
 
-                        if !has_voted {
-                            // submitting the checkpoint synchronously and waiting to be committed.
-                            let r = submit_topdown_checkpoint(
-                                submission_epoch,
-                                parent_tip_set,
-                                child_tip_set,
-                                account,
-                                child.id.clone(),
-                                &child_client,
-                                &parent_client,
-                            )
-                            .await;
-                            if r.is_err() {
-                                log::warn!("error submitting top-down checkpoint, waiting to next iteration: {:?}", r);
-                                if !wait_next_iteration(&stop_notify, CHAIN_HEAD_REQUEST_PERIOD)
-                                    .await?
-                                {
-                                    return Ok(());
-                                }
-                                continue;
+                    if !has_voted {
+                        // submitting the checkpoint synchronously and waiting to be committed.
+                        let r = submit_topdown_checkpoint(
+                            submission_epoch,
+                            parent_tip_set,
+                            child_tip_set,
+                            account,
+                            child.id.clone(),
+                            &child_client,
+                            &parent_client,
+                        )
+                        .await;
+                        if r.is_err() {
+                            log::warn!("error submitting top-down checkpoint, waiting to next iteration: {:?}", r);
+                            if !wait_next_iteration(&stop_notify, CHAIN_HEAD_REQUEST_PERIOD).await?
+                            {
+                                return Ok(());
                             }
+                            continue;
+                        }
 
-                            // loop to submit the lagging checkpoints as many as possible
-                            loop {
-                                // check if by any chance we have the opportunity to submit any outstanding checkpoint we may be
-                                // missing in case the previous one was executed successfully.
-                                // - we get the up to date head of the parent and the child.
-                                // - check the last executed checkpoint for the subnet
-                                // - And if we still have the info, submit a new checkpoint
-                                let parent_head = parent_client.chain_head().await?;
-                                let curr_epoch: ChainEpoch =
-                                    ChainEpoch::try_from(parent_head.height)?;
-                                let cid_map = parent_head.cids.first().unwrap().clone();
-                                let parent_tip_set = Cid::try_from(cid_map)?;
-                                let child_head = child_client.chain_head().await?;
-                                let cid_map = child_head.cids.first().unwrap().clone();
-                                let child_tip_set = Cid::try_from(cid_map)?;
-                                let child_gw_state =
-                                    child_client.ipc_read_gateway_state(child_tip_set).await?;
-                                let last_exec = child_gw_state
-                                    .top_down_checkpoint_voting
-                                    .last_voting_executed;
-                                let submission_epoch = last_exec + period;
-                                if curr_epoch >= submission_epoch {
-                                    let r = submit_topdown_checkpoint(
-                                        submission_epoch,
-                                        parent_tip_set,
-                                        child_tip_set,
-                                        account,
-                                        child.id.clone(),
-                                        &child_client,
-                                        &parent_client,
-                                    )
-                                    .await;
-                                    if r.is_err() {
-                                        log::warn!("error submitting top-down checkpoint, waiting to next iteration: {:?}", r);
-                                        if !wait_next_iteration(
-                                            &stop_notify,
-                                            CHAIN_HEAD_REQUEST_PERIOD,
-                                        )
+                        // loop to submit the lagging checkpoints as many as possible
+                        loop {
+                            // check if by any chance we have the opportunity to submit any outstanding checkpoint we may be
+                            // missing in case the previous one was executed successfully.
+                            // - we get the up to date head of the parent and the child.
+                            // - check the last executed checkpoint for the subnet
+                            // - And if we still have the info, submit a new checkpoint
+                            let parent_head = parent_client.chain_head().await?;
+                            let curr_epoch: ChainEpoch = ChainEpoch::try_from(parent_head.height)?;
+                            let cid_map = parent_head.cids.first().unwrap().clone();
+                            let parent_tip_set = Cid::try_from(cid_map)?;
+                            let child_head = child_client.chain_head().await?;
+                            let cid_map = child_head.cids.first().unwrap().clone();
+                            let child_tip_set = Cid::try_from(cid_map)?;
+                            let child_gw_state =
+                                child_client.ipc_read_gateway_state(child_tip_set).await?;
+                            let last_exec = child_gw_state
+                                .top_down_checkpoint_voting
+                                .last_voting_executed;
+                            let submission_epoch = last_exec + period;
+                            if curr_epoch >= submission_epoch {
+                                let r = submit_topdown_checkpoint(
+                                    submission_epoch,
+                                    parent_tip_set,
+                                    child_tip_set,
+                                    account,
+                                    child.id.clone(),
+                                    &child_client,
+                                    &parent_client,
+                                )
+                                .await;
+                                if r.is_err() {
+                                    log::warn!("error submitting top-down checkpoint, waiting to next iteration: {:?}", r);
+                                    if !wait_next_iteration(&stop_notify, CHAIN_HEAD_REQUEST_PERIOD)
                                         .await?
-                                        {
-                                            return Ok(());
-                                        }
-                                        break;
+                                    {
+                                        return Ok(());
                                     }
-                                } else {
-                                    // if no checkpoint lagging we can wait for the
-                                    // next iteration.
                                     break;
                                 }
+                            } else {
+                                // if no checkpoint lagging we can wait for the
+                                // next iteration.
+                                break;
                             }
                         }
                     }
@@ -283,7 +288,9 @@ async fn submit_topdown_checkpoint<T: JsonRpcClient + Send + Sync>(
     let message_cid = mem_push_response.cid()?;
     log::debug!("top-down checkpoint message published with cid: {message_cid:?}");
     log::info!("waiting for top-down checkpoint for epoch {submission_epoch:} to be committed");
-    child_client.state_wait_msg(message_cid).await?;
+    child_client
+        .state_wait_msg_deadline(message_cid, DEFAULT_STATE_WAIT_DEADLINE)
+        .await?;
     log::info!(
         "successfully published top-down checkpoint submission for epoch {submission_epoch:}"
     );
@@ -0,0 +1,200 @@
+// Copyright 2022-2023 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Exports checkpoints as CAR (v1) files, so they can be consumed by other IPLD tooling.
+//! See: https://ipld.io/specs/transport/car/carv1/
+
+use anyhow::{anyhow, Result};
+use cid::multihash::MultihashGeneric;
+use cid::Cid;
+use fil_actors_runtime::cbor;
+use fvm_ipld_encoding::{RawBytes, DAG_CBOR};
+use ipc_gateway::BottomUpCheckpoint;
+use serde::{Deserialize, Serialize};
+
+/// The multihash code of blake2b-256, the hash used by the FVM for the CIDs of cbor objects.
+const BLAKE2B_256: u64 = 0xb220;
+const CAR_VERSION: u64 = 1;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CarHeader {
+    roots: Vec<Cid>,
+    version: u64,
+}
+
+/// Returns the CID of the dag-cbor encoded `bytes`, as computed by the FVM.
+pub(crate) fn dag_cbor_cid(bytes: &[u8]) -> Result<Cid> {
+    let digest = blake2b_simd::Params::new().hash_length(32).hash(bytes);
+    let hash = MultihashGeneric::wrap(BLAKE2B_256, digest.as_bytes())
+        .map_err(|e| anyhow!("cannot build multihash: {e:?}"))?;
+    Ok(Cid::new_v1(DAG_CBOR, hash))
+}
+
+/// Serializes `checkpoint` into a CAR file with the checkpoint as its only root. If
+/// `with_cross_msgs` is set, the cross-messages of the checkpoint are included as blocks too.
+/// Returns the CID of the checkpoint and the bytes of the CAR file.
+pub(crate) fn checkpoint_to_car(
+    checkpoint: &BottomUpCheckpoint,
+    with_cross_msgs: bool,
+) -> Result<(Cid, Vec<u8>)> {
+    let mut blocks = vec![cbor::serialize(checkpoint, "checkpoint")?];
+    if with_cross_msgs {
+        for msg in checkpoint.data.cross_msgs.cross_msgs.iter().flatten() {
+            blocks.push(cbor::serialize(msg, "cross-message")?);
+        }
+    }
+
+    let blocks = blocks
+        .into_iter()
+        .map(|b| Ok((dag_cbor_cid(b.bytes())?, b)))
+        .collect::<Result<Vec<_>>>()?;
+    let root = blocks[0].0;
+
+    let header = cbor::serialize(
+        &CarHeader {
+            roots: vec![root],
+            version: CAR_VERSION,
+        },
+        "car header",
+    )?;
+
+    let mut car = vec![];
+    write_varint(&mut car, header.bytes().len() as u64);
+    car.extend_from_slice(header.bytes());
+    for (cid, data) in blocks {
+        let cid = cid.to_bytes();
+        write_varint(&mut car, (cid.len() + data.bytes().len()) as u64);
+        car.extend_from_slice(&cid);
+        car.extend_from_slice(data.bytes());
+    }
+
+    validate_car(&car, &[root])?;
+    Ok((root, car))
+}
+
+/// Checks the roots of the CAR file in `car` are `roots` and that the CID of every block
+/// matches its data.
+pub(crate) fn validate_car(car: &[u8], roots: &[Cid]) -> Result<()> {
+    let (header, blocks) = read_car(car)?;
+    if header.version != CAR_VERSION {
+        return Err(anyhow!("unsupported car version {:}", header.version));
+    }
+    if header.roots != roots {
+        return Err(anyhow!(
+            "car roots {:?} do not match the expected {roots:?}",
+            header.roots
+        ));
+    }
+    for (cid, data) in blocks {
+        if dag_cbor_cid(&data)? != cid {
+            return Err(anyhow!("car block {cid:} does not match its data"));
+        }
+    }
+    Ok(())
+}
+
+fn read_car(car: &[u8]) -> Result<(CarHeader, Vec<(Cid, Vec<u8>)>)> {
+    let mut offset = 0;
+
+    let header_len = read_varint(car, &mut offset)? as usize;
+    let header = read_slice(car, &mut offset, header_len)?;
+    let header = cbor::deserialize::<CarHeader>(&RawBytes::new(header.to_vec()), "car header")?;
+
+    let mut blocks = vec![];
+    while offset < car.len() {
+        let block_len = read_varint(car, &mut offset)? as usize;
+        let block = read_slice(car, &mut offset, block_len)?;
+
+        let cid_len = cid_len(block)?;
+        let cid = Cid::try_from(&block[..cid_len])?;
+        blocks.push((cid, block[cid_len..].to_vec()));
+    }
+
+    Ok((header, blocks))
+}
+
+/// Returns the length of the binary CIDv1 at the start of `bytes`.
+fn cid_len(bytes: &[u8]) -> Result<usize> {
+    let mut offset = 0;
+    // version, codec and multihash code
+    for _ in 0..3 {
+        read_varint(bytes, &mut offset)?;
+    }
+    let digest_len = read_varint(bytes, &mut offset)? as usize;
+    let len = offset + digest_len;
+    if len > bytes.len() {
+        return Err(anyhow!("truncated cid in car block"));
+    }
+    Ok(len)
+}
+
+fn read_slice<'a>(bytes: &'a [u8], offset: &mut usize, len: usize) -> Result<&'a [u8]> {
+    let end = offset
+        .checked_add(len)
+        .filter(|end| *end <= bytes.len())
+        .ok_or_else(|| anyhow!("truncated car file"))?;
+    let slice = &bytes[*offset..end];
+    *offset = end;
+    Ok(slice)
+}
+
+fn write_varint(buf: &mut Vec<u8>, mut n: u64) {
+    while n >= 0x80 {
+        buf.push((n as u8) | 0x80);
+        n >>= 7;
+    }
+    buf.push(n as u8);
+}
+
+fn read_varint(bytes: &[u8], offset: &mut usize) -> Result<u64> {
+    let mut n = 0u64;
+    for shift in (0..64).step_by(7) {
+        let b = *bytes
+            .get(*offset)
+            .ok_or_else(|| anyhow!("truncated varint"))?;
+        *offset += 1;
+        n |= ((b & 0x7f) as u64) << shift;
+        if b & 0x80 == 0 {
+            return Ok(n);
+        }
+    }
+    Err(anyhow!("varint overflow"))
+}
+
+#[cfg(test)]
+mod tests {
+    use fil_actors_runtime::cbor;
+    use ipc_gateway::BottomUpCheckpoint;
+    use ipc_sdk::subnet_id::ROOTNET_ID;
+
+    use crate::serialization::car::{
+        checkpoint_to_car, dag_cbor_cid, read_car, read_varint, validate_car, write_varint,
+    };
+
+    #[test]
+    fn test_varint_round_trip() {
+        for n in [0, 1, 127, 128, 300, u32::MAX as u64] {
+            let mut buf = vec![];
+            write_varint(&mut buf, n);
+            let mut offset = 0;
+            assert_eq!(read_varint(&buf, &mut offset).unwrap(), n);
+            assert_eq!(offset, buf.len());
+        }
+    }
+
+    #[test]
+    fn test_checkpoint_to_car() {
+        let checkpoint = BottomUpCheckpoint::new(ROOTNET_ID.clone(), 10);
+        let (root, car) = checkpoint_to_car(&checkpoint, true).unwrap();
+
+        let bytes = cbor::serialize(&checkpoint, "checkpoint").unwrap();
+        assert_eq!(root, dag_cbor_cid(bytes.bytes()).unwrap());
+
+        let (header, blocks) = read_car(&car).unwrap();
+        assert_eq!(header.roots, vec![root]);
+        assert_eq!(blocks, vec![(root, bytes.bytes().to_vec())]);
+
+        assert!(validate_car(&car, &[root]).is_ok());
+        assert!(validate_car(&car, &[]).is_err());
+        assert!(validate_car(&car[..car.len() - 1], &[root]).is_err());
+    }
+}
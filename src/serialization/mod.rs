@@ -4,6 +4,7 @@
 //! Handles the serialization of different types between actor cbor tuple serialization and json rpc
 //! json serialization.
 
+pub(crate) mod car;
 mod checkpoint;
 
 /// A helper struct to serialize struct to json.
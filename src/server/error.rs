@@ -0,0 +1,103 @@
+// Copyright 2022-2023 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! The errors of the json rpc handlers, with their json rpc error codes.
+
+/// The code of the errors that are not a [`HandlerError`].
+pub const UNKNOWN_ERROR_CODE: i32 = -1;
+/// The codes defined by the json rpc spec, see: https://www.jsonrpc.org/specification#error_object
+pub const METHOD_NOT_FOUND_CODE: i32 = -32601;
+pub const INVALID_PARAMS_CODE: i32 = -32602;
+/// The codes of the agent, in the range the spec reserves for implementation-defined errors.
+pub const SUBNET_NOT_FOUND_CODE: i32 = -32001;
+pub const PARENT_NOT_FOUND_CODE: i32 = -32002;
+pub const SUBNET_MISCONFIGURED_CODE: i32 = -32003;
+pub const UPSTREAM_CODE: i32 = -32004;
+pub const SERVER_OVERLOADED_CODE: i32 = -32005;
+
+/// The errors the handlers return for clients to tell apart. Handlers return them wrapped in an
+/// [`anyhow::Error`], any other error is reported with [`UNKNOWN_ERROR_CODE`].
+#[derive(Debug, thiserror::Error)]
+pub enum HandlerError {
+    #[error("method {0} not found")]
+    MethodNotFound(String),
+    #[error("invalid params: {0}")]
+    InvalidParams(String),
+    #[error("subnet {0} not found in the config")]
+    SubnetNotFound(String),
+    #[error("parent subnet {0} not found in the config")]
+    ParentNotFound(String),
+    #[error("subnet {0} is misconfigured")]
+    SubnetMisconfigured(String),
+    /// The node of a subnet could not serve a request, e.g. because it is unreachable.
+    #[error("upstream error: {0:#}")]
+    Upstream(anyhow::Error),
+    /// The server is handling as many requests as it can, with as many waiting.
+    #[error("server overloaded, retry later")]
+    Overloaded,
+}
+
+impl HandlerError {
+    /// Returns the json rpc error code of the error.
+    pub fn code(&self) -> i32 {
+        match self {
+            HandlerError::MethodNotFound(_) => METHOD_NOT_FOUND_CODE,
+            HandlerError::InvalidParams(_) => INVALID_PARAMS_CODE,
+            HandlerError::SubnetNotFound(_) => SUBNET_NOT_FOUND_CODE,
+            HandlerError::ParentNotFound(_) => PARENT_NOT_FOUND_CODE,
+            HandlerError::SubnetMisconfigured(_) => SUBNET_MISCONFIGURED_CODE,
+            HandlerError::Upstream(_) => UPSTREAM_CODE,
+            HandlerError::Overloaded => SERVER_OVERLOADED_CODE,
+        }
+    }
+
+    /// Wraps an error of the node of a subnet.
+    pub fn upstream(e: anyhow::Error) -> anyhow::Error {
+        HandlerError::Upstream(e).into()
+    }
+
+    /// Wraps an error reading the params of a request.
+    pub fn invalid_params(e: impl std::fmt::Display) -> anyhow::Error {
+        HandlerError::InvalidParams(e.to_string()).into()
+    }
+}
+
+/// Returns the json rpc error code of an error returned by a handler.
+pub fn error_code(e: &anyhow::Error) -> i32 {
+    e.downcast_ref::<HandlerError>()
+        .map(HandlerError::code)
+        .unwrap_or(UNKNOWN_ERROR_CODE)
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::anyhow;
+
+    use crate::server::error::{error_code, HandlerError};
+
+    #[test]
+    fn test_error_code() {
+        let errors = [
+            (HandlerError::MethodNotFound(String::from("x")), -32601),
+            (HandlerError::InvalidParams(String::from("x")), -32602),
+            (HandlerError::SubnetNotFound(String::from("/root")), -32001),
+            (HandlerError::ParentNotFound(String::from("/root")), -32002),
+            (
+                HandlerError::SubnetMisconfigured(String::from("/root")),
+                -32003,
+            ),
+            (
+                HandlerError::Upstream(anyhow!("connection refused")),
+                -32004,
+            ),
+            (HandlerError::Overloaded, -32005),
+        ];
+        for (e, code) in errors {
+            assert_eq!(error_code(&e.into()), code);
+        }
+
+        // the context added to an error keeps its code
+        let e = HandlerError::upstream(anyhow!("connection refused")).context("listing");
+        assert_eq!(error_code(&e), -32004);
+        assert_eq!(error_code(&anyhow!("something else")), -1);
+    }
+}
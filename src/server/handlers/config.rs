@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: MIT
 //! Triggers a config reloading
 
+use crate::config::diff::ConfigDiff;
 use crate::config::ReloadableConfig;
 use crate::server::JsonRPCRequestHandler;
 use async_trait::async_trait;
@@ -11,6 +12,9 @@ use std::sync::Arc;
 #[derive(Debug, Deserialize, Serialize)]
 pub struct ReloadConfigParams {
     pub path: Option<String>,
+    /// Only report the changes the reload would apply, keeping the current config and path.
+    #[serde(default)]
+    pub dry_run: bool,
 }
 
 /// The create subnet json rpc method handler.
@@ -27,11 +31,14 @@ impl ReloadConfigHandler {
 #[async_trait]
 impl JsonRPCRequestHandler for ReloadConfigHandler {
     type Request = ReloadConfigParams;
-    type Response = ();
+    type Response = ConfigDiff;
 
     async fn handle(&self, request: Self::Request) -> anyhow::Result<Self::Response> {
         log::info!("received request to reload config: {request:?}");
 
+        if request.dry_run {
+            return self.config.preview(request.path).await;
+        }
         if request.path.is_some() {
             self.config.set_path(request.path.unwrap());
         }
@@ -0,0 +1,186 @@
+// Copyright 2022-2023 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Health check handler and parameters
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use futures_util::future::join_all;
+use serde::{Deserialize, Serialize};
+
+use crate::config::{ReloadableConfig, Subnet};
+use crate::jsonrpc::HttpClients;
+use crate::lotus::client::LotusJsonRPCClient;
+use crate::lotus::LotusClient;
+use crate::server::JsonRPCRequestHandler;
+
+/// How long the node of a subnet has to answer before it is reported as unreachable.
+const DEFAULT_HEALTH_TIMEOUT: Duration = Duration::from_secs(3);
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HealthParams {
+    /// The milliseconds the node of each subnet has to answer, 3 seconds if not set.
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HealthResponse {
+    /// The version of the agent.
+    pub version: String,
+    /// The health of the node of each configured subnet, by subnet id.
+    pub subnets: BTreeMap<String, SubnetHealth>,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SubnetHealth {
+    pub reachable: bool,
+    /// The milliseconds the node took to return the chain head, if it did in time.
+    pub latency_ms: Option<u64>,
+}
+
+/// Reports the version of the agent and whether the node of each subnet answers a chain head
+/// request in time. The nodes are queried concurrently, so the check takes at most the timeout.
+pub(crate) struct HealthHandler {
+    config: Arc<ReloadableConfig>,
+    http_clients: Arc<HttpClients>,
+}
+
+impl HealthHandler {
+    pub(crate) fn new(config: Arc<ReloadableConfig>, http_clients: Arc<HttpClients>) -> Self {
+        Self {
+            config,
+            http_clients,
+        }
+    }
+}
+
+#[async_trait]
+impl JsonRPCRequestHandler for HealthHandler {
+    type Request = HealthParams;
+    type Response = HealthResponse;
+
+    async fn handle(&self, request: Self::Request) -> anyhow::Result<Self::Response> {
+        let timeout = request
+            .timeout_ms
+            .map(Duration::from_millis)
+            .unwrap_or(DEFAULT_HEALTH_TIMEOUT);
+
+        let config = self.config.get_config();
+        let checks = config.subnets.values().map(|subnet| async move {
+            let lotus = LotusJsonRPCClient::from_subnet_with_clients(subnet, &self.http_clients);
+            (
+                subnet.id.to_string(),
+                check_subnet(subnet, &lotus, timeout).await,
+            )
+        });
+
+        Ok(HealthResponse {
+            version: String::from(env!("CARGO_PKG_VERSION")),
+            subnets: join_all(checks).await.into_iter().collect(),
+        })
+    }
+}
+
+async fn check_subnet<T: LotusClient>(
+    subnet: &Subnet,
+    lotus: &T,
+    timeout: Duration,
+) -> SubnetHealth {
+    let start = Instant::now();
+    match tokio::time::timeout(timeout, lotus.chain_head()).await {
+        Ok(Ok(_)) => SubnetHealth {
+            reachable: true,
+            latency_ms: Some(start.elapsed().as_millis() as u64),
+        },
+        Ok(Err(e)) => {
+            log::warn!("node of subnet {:} is unreachable: {e:}", subnet.id);
+            SubnetHealth {
+                reachable: false,
+                latency_ms: None,
+            }
+        }
+        Err(_) => {
+            log::warn!(
+                "node of subnet {:} did not answer in {timeout:?}",
+                subnet.id
+            );
+            SubnetHealth {
+                reachable: false,
+                latency_ms: None,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use std::sync::Arc;
+
+    use serde_json::json;
+    use tempfile::NamedTempFile;
+    use warp::Filter;
+
+    use crate::config::ReloadableConfig;
+    use crate::jsonrpc::HttpClients;
+    use crate::server::handlers::health::{HealthHandler, HealthParams};
+    use crate::server::JsonRPCRequestHandler;
+
+    #[tokio::test]
+    async fn test_health() {
+        // a node answering every request with a chain head
+        let route = warp::post().map(|| {
+            warp::reply::json(&json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "result": {"Cids": [], "Blocks": [], "Height": 10}
+            }))
+        });
+        let (addr, server) = warp::serve(route).bind_ephemeral(([127, 0, 0, 1], 0));
+        tokio::spawn(server);
+
+        // a node accepting connections but never answering
+        let hanging = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+
+        let mut file = NamedTempFile::new().unwrap();
+        write!(
+            file,
+            r#"
+            [server]
+            json_rpc_address = "127.0.0.1:3030"
+
+            [[subnets]]
+            id = "/root"
+            gateway_addr = "t064"
+            network_name = "root"
+            jsonrpc_api_http = "http://{addr:}/rpc/v1"
+
+            [[subnets]]
+            id = "/root/t01"
+            gateway_addr = "t064"
+            network_name = "child"
+            jsonrpc_api_http = "http://{:}/rpc/v1"
+            "#,
+            hanging.local_addr().unwrap()
+        )
+        .unwrap();
+        let config = ReloadableConfig::new(file.path().to_str().unwrap().to_string()).unwrap();
+        let handler = HealthHandler::new(Arc::new(config), Arc::new(HttpClients::default()));
+
+        let response = handler
+            .handle(HealthParams {
+                timeout_ms: Some(500),
+            })
+            .await
+            .unwrap();
+        assert_eq!(response.version, env!("CARGO_PKG_VERSION"));
+        assert_eq!(response.subnets.len(), 2);
+        assert!(response.subnets["/root"].reachable);
+        assert!(response.subnets["/root"].latency_ms.is_some());
+        assert!(!response.subnets["/root/t01"].reachable);
+        assert_eq!(response.subnets["/root/t01"].latency_ms, None);
+    }
+}
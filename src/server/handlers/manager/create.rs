@@ -3,10 +3,10 @@
 //! Create subnet handler and parameters
 
 use crate::manager::SubnetManager;
+use crate::server::error::HandlerError;
 use crate::server::handlers::manager::subnet::SubnetManagerPool;
 use crate::server::handlers::manager::{check_subnet, parse_from};
 use crate::server::JsonRPCRequestHandler;
-use anyhow::anyhow;
 use async_trait::async_trait;
 use fvm_shared::clock::ChainEpoch;
 use fvm_shared::econ::TokenAmount;
@@ -52,7 +52,7 @@ impl JsonRPCRequestHandler for CreateSubnetHandler {
     async fn handle(&self, request: Self::Request) -> anyhow::Result<Self::Response> {
         let parent = SubnetID::from_str(&request.parent)?;
         let conn = match self.pool.get(&parent) {
-            None => return Err(anyhow!("target parent subnet not found")),
+            None => return Err(HandlerError::ParentNotFound(parent.to_string()).into()),
             Some(conn) => conn,
         };
 
@@ -0,0 +1,213 @@
+// Copyright 2022-2023 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Cross-message value conservation audit handler and parameters
+
+use crate::config::ReloadableConfig;
+use crate::jsonrpc::HttpClients;
+use crate::lotus::client::LotusJsonRPCClient;
+use crate::lotus::LotusClient;
+use crate::server::error::HandlerError;
+use crate::server::JsonRPCRequestHandler;
+use anyhow::anyhow;
+use async_trait::async_trait;
+use cid::Cid;
+use fvm_shared::bigint::BigInt;
+use fvm_shared::clock::ChainEpoch;
+use fvm_shared::econ::TokenAmount;
+use ipc_sdk::subnet_id::SubnetID;
+use num_traits::Signed;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use std::sync::Arc;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CrossMsgAuditParams {
+    pub subnet: String,
+    pub from_epoch: ChainEpoch,
+    pub to_epoch: ChainEpoch,
+}
+
+/// The cross-net value flows of a subnet over an epoch range. Token amounts are in FIL.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CrossMsgAuditResponse {
+    /// Value sent top-down from the parent into the subnet
+    pub topdown_value: String,
+    /// Value released bottom-up from the subnet to the parent
+    pub bottomup_value: String,
+    /// Fees of the bottom-up cross-messages
+    pub fees: String,
+    /// The current circulating supply of the subnet. Only reported when the range covers the
+    /// whole life of the subnet, as the supply cannot be read at past epochs.
+    pub circ_supply: Option<String>,
+    /// The difference between the circulating supply and the net value sent into the subnet
+    pub discrepancy: Option<String>,
+    /// Whether the discrepancy is larger than the fees paid
+    pub flagged: bool,
+}
+
+/// Sums the value of the cross-messages of a subnet over an epoch range and, when possible,
+/// reconciles them with the circulating supply of the subnet.
+pub(crate) struct CrossMsgAuditHandler {
+    config: Arc<ReloadableConfig>,
+    http_clients: Arc<HttpClients>,
+}
+
+impl CrossMsgAuditHandler {
+    pub(crate) fn new(config: Arc<ReloadableConfig>, http_clients: Arc<HttpClients>) -> Self {
+        Self {
+            config,
+            http_clients,
+        }
+    }
+}
+
+#[async_trait]
+impl JsonRPCRequestHandler for CrossMsgAuditHandler {
+    type Request = CrossMsgAuditParams;
+    type Response = CrossMsgAuditResponse;
+
+    async fn handle(&self, request: Self::Request) -> anyhow::Result<Self::Response> {
+        if request.from_epoch > request.to_epoch {
+            return Err(anyhow!("from epoch is after to epoch"));
+        }
+
+        let subnet_id = SubnetID::from_str(&request.subnet)?;
+        let parent = subnet_id
+            .parent()
+            .ok_or_else(|| anyhow!("cannot audit root"))?;
+
+        let config = self.config.get_config();
+        let parent_subnet = match config.subnets.get(&parent) {
+            None => return Err(HandlerError::ParentNotFound(parent.to_string()).into()),
+            Some(s) => s,
+        };
+        let gateway_addr = parent_subnet.gateway_addr;
+        let lotus = LotusJsonRPCClient::from_subnet_with_clients(parent_subnet, &self.http_clients);
+
+        let head = lotus.chain_head().await?;
+        let head_height = ChainEpoch::try_from(head.height)?;
+        let head_tip_set = Cid::try_from(head.cids.first().unwrap().clone())?;
+        if request.to_epoch > head_height {
+            return Err(anyhow!("to epoch is ahead of the chain head"));
+        }
+
+        // Top-down messages are never removed from the gateway, so the messages sent in the
+        // range are those at `to_epoch` that were not yet there at `from_epoch`.
+        let client = &lotus;
+        let tip_set_at = |epoch: ChainEpoch| async move {
+            let tip_set = client.get_tipset_by_height(epoch, head_tip_set).await?;
+            Cid::try_from(tip_set.cids.first().unwrap().clone())
+        };
+        let nonce = lotus
+            .ipc_get_topdown_msgs(
+                &subnet_id,
+                gateway_addr,
+                tip_set_at(request.from_epoch).await?,
+                0,
+            )
+            .await?
+            .len() as u64;
+        let topdown_value = lotus
+            .ipc_get_topdown_msgs(
+                &subnet_id,
+                gateway_addr,
+                tip_set_at(request.to_epoch).await?,
+                nonce,
+            )
+            .await?
+            .iter()
+            .map(|m| m.msg.value.atto().clone())
+            .sum::<BigInt>();
+
+        let mut bottomup_value = BigInt::default();
+        let mut fees = BigInt::default();
+        let checkpoints = lotus
+            .ipc_list_checkpoints(subnet_id.clone(), request.from_epoch, request.to_epoch)
+            .await?;
+        for checkpoint in checkpoints {
+            let batch = checkpoint.data.cross_msgs;
+            fees += batch.fee.atto();
+            for m in batch.cross_msgs.unwrap_or_default() {
+                bottomup_value += m.msg.value.atto();
+            }
+        }
+
+        let topdown_value = TokenAmount::from_atto(topdown_value);
+        let bottomup_value = TokenAmount::from_atto(bottomup_value);
+        let fees = TokenAmount::from_atto(fees);
+
+        let genesis_epoch = lotus
+            .ipc_get_genesis_epoch_for_subnet(&subnet_id, gateway_addr)
+            .await?;
+        let mut response = CrossMsgAuditResponse {
+            topdown_value: topdown_value.to_string(),
+            bottomup_value: bottomup_value.to_string(),
+            fees: fees.to_string(),
+            circ_supply: None,
+            discrepancy: None,
+            flagged: false,
+        };
+        if request.from_epoch > genesis_epoch || request.to_epoch < head_height {
+            log::info!("range does not cover the life of {subnet_id:}, skipping reconciliation");
+            return Ok(response);
+        }
+
+        let circ_supply = lotus
+            .ipc_list_child_subnets(gateway_addr, None)
+            .await?
+            .into_iter()
+            .find(|s| s.id == subnet_id)
+            .ok_or_else(|| anyhow!("subnet {subnet_id:} not found in parent"))?
+            .circ_supply;
+        let (discrepancy, flagged) =
+            check_conservation(&topdown_value, &bottomup_value, &fees, &circ_supply);
+        response.circ_supply = Some(circ_supply.to_string());
+        response.discrepancy = Some(discrepancy.to_string());
+        response.flagged = flagged;
+
+        Ok(response)
+    }
+}
+
+/// Compares the circulating supply of a subnet with the net value sent into it. Returns the
+/// difference between the two and whether it is larger than the fees paid by cross-messages.
+fn check_conservation(
+    topdown_value: &TokenAmount,
+    bottomup_value: &TokenAmount,
+    fees: &TokenAmount,
+    circ_supply: &TokenAmount,
+) -> (TokenAmount, bool) {
+    let net_value = topdown_value.atto() - bottomup_value.atto();
+    let discrepancy = circ_supply.atto() - net_value;
+    let flagged = discrepancy.abs() > *fees.atto();
+    (TokenAmount::from_atto(discrepancy), flagged)
+}
+
+#[cfg(test)]
+mod tests {
+    use fvm_shared::econ::TokenAmount;
+
+    use crate::server::handlers::manager::crossmsg_audit::check_conservation;
+
+    #[test]
+    fn test_check_conservation() {
+        let topdown = TokenAmount::from_whole(10);
+        let bottomup = TokenAmount::from_whole(4);
+        let fees = TokenAmount::from_atto(100);
+
+        let (discrepancy, flagged) =
+            check_conservation(&topdown, &bottomup, &fees, &TokenAmount::from_whole(6));
+        assert_eq!(discrepancy, TokenAmount::from_atto(0));
+        assert!(!flagged);
+
+        // a difference within the fees paid is not flagged
+        let circ_supply = TokenAmount::from_atto(TokenAmount::from_whole(6).atto() - 100);
+        let (_, flagged) = check_conservation(&topdown, &bottomup, &fees, &circ_supply);
+        assert!(!flagged);
+
+        let (discrepancy, flagged) =
+            check_conservation(&topdown, &bottomup, &fees, &TokenAmount::from_whole(5));
+        assert_eq!(discrepancy, TokenAmount::from_whole(-1));
+        assert!(flagged);
+    }
+}
@@ -0,0 +1,164 @@
+// Copyright 2022-2023 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Cross-message backlog handler and parameters
+
+use std::str::FromStr;
+use std::sync::Arc;
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+use cid::Cid;
+use fvm_shared::clock::ChainEpoch;
+use ipc_sdk::subnet_id::SubnetID;
+use serde::{Deserialize, Serialize};
+
+use crate::config::ReloadableConfig;
+use crate::jsonrpc::{HttpClients, JsonRpcClient};
+use crate::lotus::client::LotusJsonRPCClient;
+use crate::lotus::message::ipc::CrossMsgNonces;
+use crate::lotus::LotusClient;
+use crate::server::error::HandlerError;
+use crate::server::JsonRPCRequestHandler;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CrossMsgBacklogParams {
+    pub subnet: String,
+}
+
+/// The cross-messages of a subnet that are committed but not applied yet, in each direction.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CrossMsgBacklogResponse {
+    /// The epoch of the parent at which its nonces were read
+    pub parent_epoch: ChainEpoch,
+    /// The nonces of the gateway of the parent and of the subnet in it
+    pub parent: CrossMsgNonces,
+    /// The epoch of the subnet at which its nonces were read
+    pub child_epoch: ChainEpoch,
+    /// The nonce of the next top-down message to be applied in the subnet
+    pub child_applied_topdown: u64,
+    /// The nonce of the next bottom-up message sent from the subnet
+    pub child_next_bottomup: u64,
+    /// The number of top-down messages committed in the parent not yet applied in the subnet
+    pub topdown_pending: u64,
+    /// The number of bottom-up messages sent from the subnet not yet applied in the parent
+    pub bottomup_pending: u64,
+}
+
+/// Reports the cross-message backlog of a subnet, from the nonces of the gateways of the subnet
+/// and of its parent, each read at a single tipset.
+pub(crate) struct CrossMsgBacklogHandler {
+    config: Arc<ReloadableConfig>,
+    http_clients: Arc<HttpClients>,
+}
+
+impl CrossMsgBacklogHandler {
+    pub(crate) fn new(config: Arc<ReloadableConfig>, http_clients: Arc<HttpClients>) -> Self {
+        Self {
+            config,
+            http_clients,
+        }
+    }
+}
+
+#[async_trait]
+impl JsonRPCRequestHandler for CrossMsgBacklogHandler {
+    type Request = CrossMsgBacklogParams;
+    type Response = CrossMsgBacklogResponse;
+
+    async fn handle(&self, request: Self::Request) -> anyhow::Result<Self::Response> {
+        let subnet_id = SubnetID::from_str(&request.subnet)?;
+        let parent = subnet_id
+            .parent()
+            .ok_or_else(|| anyhow!("root has no cross-message backlog"))?;
+
+        let config = self.config.get_config();
+        let parent_subnet = config
+            .subnets
+            .get(&parent)
+            .ok_or_else(|| HandlerError::ParentNotFound(parent.to_string()))?;
+        let child_subnet = config
+            .subnets
+            .get(&subnet_id)
+            .ok_or_else(|| HandlerError::SubnetNotFound(subnet_id.to_string()))?;
+
+        // the nonces of the subnet itself are all in the state of its gateway
+        let child_lotus =
+            LotusJsonRPCClient::from_subnet_with_clients(child_subnet, &self.http_clients);
+        let (child_epoch, child_tip_set) = head(&child_lotus).await?;
+        let child = child_lotus.ipc_read_gateway_state(child_tip_set).await?;
+
+        // the top-down messages the subnet already applied are not read from the parent
+        let lotus = LotusJsonRPCClient::from_subnet_with_clients(parent_subnet, &self.http_clients);
+        let (parent_epoch, tip_set) = head(&lotus).await?;
+        let parent = lotus
+            .ipc_crossmsg_nonces(
+                &subnet_id,
+                parent_subnet.gateway_addr,
+                tip_set,
+                child.applied_topdown_nonce,
+            )
+            .await?;
+
+        let (topdown_pending, bottomup_pending) =
+            pending(&parent, child.applied_topdown_nonce, child.bottomup_nonce);
+
+        Ok(CrossMsgBacklogResponse {
+            parent_epoch,
+            parent,
+            child_epoch,
+            child_applied_topdown: child.applied_topdown_nonce,
+            child_next_bottomup: child.bottomup_nonce,
+            topdown_pending,
+            bottomup_pending,
+        })
+    }
+}
+
+/// Returns the height and the tipset of the head of the chain of `lotus`.
+pub(crate) async fn head<T: JsonRpcClient + Send + Sync>(
+    lotus: &LotusJsonRPCClient<T>,
+) -> anyhow::Result<(ChainEpoch, Cid)> {
+    let head = lotus.chain_head().await?;
+    let tip_set = Cid::try_from(
+        head.cids
+            .first()
+            .ok_or_else(|| anyhow!("chain head has no cids"))?
+            .clone(),
+    )?;
+    Ok((ChainEpoch::try_from(head.height)?, tip_set))
+}
+
+/// Returns the number of top-down and bottom-up messages committed on one side and not yet
+/// applied on the other, from the nonces of the gateway of the `parent` and those of the
+/// gateway of the subnet.
+fn pending(
+    parent: &CrossMsgNonces,
+    child_applied_topdown: u64,
+    child_next_bottomup: u64,
+) -> (u64, u64) {
+    // the two chains are read at different heads, so the side applying the messages can
+    // be ahead of the one committing them
+    let topdown = parent.next_topdown.saturating_sub(child_applied_topdown);
+    let bottomup = child_next_bottomup.saturating_sub(parent.applied_bottomup);
+    (topdown, bottomup)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::lotus::message::ipc::CrossMsgNonces;
+    use crate::server::handlers::manager::crossmsg_backlog::pending;
+
+    #[test]
+    fn test_pending() {
+        let parent = CrossMsgNonces {
+            gateway_applied_topdown: 0,
+            next_topdown: 7,
+            applied_bottomup: 2,
+            next_bottomup: 0,
+        };
+        assert_eq!(pending(&parent, 4, 5), (3, 3));
+
+        // the subnet applied messages committed after the head of the parent that was read
+        assert_eq!(pending(&parent, 9, 1), (0, 0));
+    }
+}
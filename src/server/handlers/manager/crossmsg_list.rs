@@ -0,0 +1,200 @@
+// Copyright 2022-2023 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! List top-down cross-messages handler and parameters
+
+use std::str::FromStr;
+use std::sync::Arc;
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+use fvm_shared::clock::ChainEpoch;
+use ipc_gateway::CrossMsg;
+use ipc_sdk::address::IPCAddress;
+use ipc_sdk::subnet_id::SubnetID;
+use serde::{Deserialize, Serialize};
+
+use crate::config::ReloadableConfig;
+use crate::jsonrpc::HttpClients;
+use crate::lotus::client::LotusJsonRPCClient;
+use crate::lotus::LotusClient;
+use crate::server::error::HandlerError;
+use crate::server::handlers::manager::crossmsg_backlog::head;
+use crate::server::JsonRPCRequestHandler;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CrossMsgListParams {
+    pub subnet: String,
+    /// The nonce of the first top-down message to list
+    pub from_nonce: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CrossMsgListResponse {
+    /// The epoch of the parent at which the messages were read
+    pub epoch: ChainEpoch,
+    pub msgs: Vec<CrossMsgEntry>,
+}
+
+/// A top-down message as returned by the parent, either decoded or with the error decoding it.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CrossMsgEntry {
+    /// The position of the message in the list returned by the parent
+    pub index: usize,
+    pub msg: Option<CrossMsgSummary>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CrossMsgSummary {
+    pub from: String,
+    pub to: String,
+    /// The value of the message in FIL
+    pub value: String,
+    pub nonce: u64,
+}
+
+/// Lists the top-down messages committed in the parent of a subnet from a nonce, at the head
+/// of the parent. Messages that cannot be decoded are listed with their error.
+pub(crate) struct CrossMsgListHandler {
+    config: Arc<ReloadableConfig>,
+    http_clients: Arc<HttpClients>,
+}
+
+impl CrossMsgListHandler {
+    pub(crate) fn new(config: Arc<ReloadableConfig>, http_clients: Arc<HttpClients>) -> Self {
+        Self {
+            config,
+            http_clients,
+        }
+    }
+}
+
+#[async_trait]
+impl JsonRPCRequestHandler for CrossMsgListHandler {
+    type Request = CrossMsgListParams;
+    type Response = CrossMsgListResponse;
+
+    async fn handle(&self, request: Self::Request) -> anyhow::Result<Self::Response> {
+        let subnet_id = SubnetID::from_str(&request.subnet)?;
+        let parent = subnet_id
+            .parent()
+            .ok_or_else(|| anyhow!("root has no top-down messages"))?;
+
+        let config = self.config.get_config();
+        let parent_subnet = config
+            .subnets
+            .get(&parent)
+            .ok_or_else(|| HandlerError::ParentNotFound(parent.to_string()))?;
+
+        let lotus = LotusJsonRPCClient::from_subnet_with_clients(parent_subnet, &self.http_clients);
+        let (epoch, tip_set) = head(&lotus).await?;
+        let msgs = lotus
+            .ipc_get_topdown_msgs_lenient(
+                &subnet_id,
+                parent_subnet.gateway_addr,
+                tip_set,
+                request.from_nonce,
+            )
+            .await?;
+
+        Ok(CrossMsgListResponse {
+            epoch,
+            msgs: entries(msgs),
+        })
+    }
+}
+
+/// Returns an entry per message, with the error of those that cannot be decoded or summarized.
+fn entries(msgs: Vec<anyhow::Result<CrossMsg>>) -> Vec<CrossMsgEntry> {
+    msgs.into_iter()
+        .enumerate()
+        .map(|(index, msg)| match msg.and_then(|m| summary(&m)) {
+            Ok(msg) => CrossMsgEntry {
+                index,
+                msg: Some(msg),
+                error: None,
+            },
+            Err(e) => {
+                log::warn!("cannot read top-down message {index:}: {e:}");
+                CrossMsgEntry {
+                    index,
+                    msg: None,
+                    error: Some(e.to_string()),
+                }
+            }
+        })
+        .collect()
+}
+
+fn summary(msg: &CrossMsg) -> anyhow::Result<CrossMsgSummary> {
+    let address = |a: &IPCAddress| {
+        a.to_string()
+            .map_err(|e| anyhow!("invalid address in message: {e:?}"))
+    };
+    Ok(CrossMsgSummary {
+        from: address(&msg.msg.from)?,
+        to: address(&msg.msg.to)?,
+        value: msg.msg.value.to_string(),
+        nonce: msg.msg.nonce,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use anyhow::anyhow;
+    use fvm_ipld_encoding::RawBytes;
+    use fvm_shared::address::Address;
+    use fvm_shared::econ::TokenAmount;
+    use ipc_gateway::{CrossMsg, StorableMsg};
+    use ipc_sdk::address::IPCAddress;
+    use ipc_sdk::subnet_id::{SubnetID, ROOTNET_ID};
+
+    use crate::server::handlers::manager::crossmsg_list::entries;
+
+    fn fund_msg(child: &SubnetID, nonce: u64) -> CrossMsg {
+        CrossMsg {
+            msg: StorableMsg {
+                from: IPCAddress::new(&ROOTNET_ID, &Address::new_id(100)).unwrap(),
+                to: IPCAddress::new(child, &Address::new_id(100)).unwrap(),
+                method: 0,
+                params: RawBytes::default(),
+                value: TokenAmount::from_whole(1),
+                nonce,
+            },
+            wrapped: false,
+        }
+    }
+
+    #[test]
+    fn test_entries() {
+        let child = SubnetID::from_str("/root/t01002").unwrap();
+        let msgs = vec![
+            Ok(fund_msg(&child, 3)),
+            Err(anyhow!("cannot deserialize cbor of top-down cross-msg 1")),
+            Ok(fund_msg(&child, 5)),
+        ];
+
+        let entries = entries(msgs);
+        assert_eq!(entries.len(), 3);
+
+        let first = entries[0].msg.as_ref().unwrap();
+        assert_eq!(first.nonce, 3);
+        assert_eq!(
+            first.from,
+            IPCAddress::new(&ROOTNET_ID, &Address::new_id(100))
+                .unwrap()
+                .to_string()
+                .unwrap()
+        );
+        assert_eq!(first.value, TokenAmount::from_whole(1).to_string());
+        assert_eq!(entries[0].error, None);
+
+        // the malformed message is reported in place, without hiding the next one
+        assert_eq!(entries[1].index, 1);
+        assert_eq!(entries[1].msg, None);
+        assert!(entries[1].error.as_ref().unwrap().contains("cbor"));
+        assert_eq!(entries[2].msg.as_ref().unwrap().nonce, 5);
+    }
+}
@@ -0,0 +1,251 @@
+// Copyright 2022-2023 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Cross-message route handler and parameters
+
+use crate::config::ReloadableConfig;
+use crate::jsonrpc::HttpClients;
+use crate::lotus::client::LotusJsonRPCClient;
+use crate::lotus::message::ipc::TopDownFinality;
+use crate::lotus::LotusClient;
+use crate::serialization::car::dag_cbor_cid;
+use crate::server::error::HandlerError;
+use crate::server::JsonRPCRequestHandler;
+use anyhow::anyhow;
+use async_trait::async_trait;
+use cid::Cid;
+use fil_actors_runtime::cbor;
+use ipc_gateway::CrossMsg;
+use ipc_sdk::subnet_id::SubnetID;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use std::sync::Arc;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CrossMsgRouteParams {
+    /// The subnet the cross-message was sent from
+    pub subnet: String,
+    /// The CID of the cross-message
+    pub cid: String,
+}
+
+/// The stage a cross-message has reached in a subnet of its route.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CrossMsgStage {
+    /// Committed in the gateway of the subnet the message was sent from
+    Committed,
+    /// Waiting to be applied in the subnet
+    Pending,
+    /// Applied in the subnet
+    Applied,
+    /// The subnet is not in the config of the agent, so its stage cannot be checked
+    Unknown,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CrossMsgRouteStage {
+    pub subnet: String,
+    pub stage: CrossMsgStage,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CrossMsgRouteResponse {
+    /// The nonce of the cross-message in the subnet it was sent from
+    pub nonce: u64,
+    /// The subnets the cross-message has gone through with the stage reached in each of them
+    pub route: Vec<CrossMsgRouteStage>,
+    /// When the child subnet applies top-down messages, to tell a message that is not final
+    /// yet from a stuck one. Not set if the child subnet is not in the config.
+    pub finality: Option<TopDownFinality>,
+}
+
+/// Traces the route of a top-down cross-message from the subnet it was sent from to the child
+/// subnet it is applied in. Bottom-up messages are not traced, as the postbox of the gateway
+/// cannot be read through the node's API.
+pub(crate) struct CrossMsgRouteHandler {
+    config: Arc<ReloadableConfig>,
+    http_clients: Arc<HttpClients>,
+}
+
+impl CrossMsgRouteHandler {
+    pub(crate) fn new(config: Arc<ReloadableConfig>, http_clients: Arc<HttpClients>) -> Self {
+        Self {
+            config,
+            http_clients,
+        }
+    }
+}
+
+#[async_trait]
+impl JsonRPCRequestHandler for CrossMsgRouteHandler {
+    type Request = CrossMsgRouteParams;
+    type Response = CrossMsgRouteResponse;
+
+    async fn handle(&self, request: Self::Request) -> anyhow::Result<Self::Response> {
+        let subnet_id = SubnetID::from_str(&request.subnet)?;
+        let cid = Cid::from_str(&request.cid)?;
+
+        let config = self.config.get_config();
+        let subnet = match config.subnets.get(&subnet_id) {
+            None => return Err(HandlerError::SubnetNotFound(subnet_id.to_string()).into()),
+            Some(s) => s,
+        };
+        let lotus = LotusJsonRPCClient::from_subnet_with_clients(subnet, &self.http_clients);
+
+        let head = lotus.chain_head().await?;
+        let tip_set = Cid::try_from(head.cids.first().unwrap().clone())?;
+
+        for child in lotus
+            .ipc_list_child_subnets(subnet.gateway_addr, Some(tip_set))
+            .await?
+        {
+            let msgs = lotus
+                .ipc_get_topdown_msgs(&child.id, subnet.gateway_addr, tip_set, 0)
+                .await?;
+            let nonce = match find_cross_msg(&msgs, &cid)? {
+                None => continue,
+                Some(msg) => msg.msg.nonce,
+            };
+            log::debug!(
+                "cross-message {cid:} found in top-down messages of {:}",
+                child.id
+            );
+
+            let (applied_nonce, finality) = match config.subnets.get(&child.id) {
+                None => (None, None),
+                Some(child_subnet) => {
+                    let child_lotus = LotusJsonRPCClient::from_subnet_with_clients(
+                        child_subnet,
+                        &self.http_clients,
+                    );
+                    let child_head = child_lotus.chain_head().await?;
+                    let child_tip_set = Cid::try_from(child_head.cids.first().unwrap().clone())?;
+                    let state = child_lotus.ipc_read_gateway_state(child_tip_set).await?;
+                    (
+                        Some(state.applied_topdown_nonce),
+                        Some(state.top_down_finality()),
+                    )
+                }
+            };
+
+            return Ok(CrossMsgRouteResponse {
+                nonce,
+                route: topdown_route(&subnet_id, &child.id, nonce, applied_nonce),
+                finality,
+            });
+        }
+
+        Err(anyhow!(
+            "cross-message {cid:} not found in the top-down messages of {subnet_id:}"
+        ))
+    }
+}
+
+/// Returns the CID of a cross-message, i.e. the blake2b-256 hash of its cbor encoding.
+pub(crate) fn cross_msg_cid(msg: &CrossMsg) -> anyhow::Result<Cid> {
+    let bytes = cbor::serialize(msg, "cross-message")?;
+    dag_cbor_cid(bytes.bytes())
+}
+
+fn find_cross_msg<'a>(msgs: &'a [CrossMsg], cid: &Cid) -> anyhow::Result<Option<&'a CrossMsg>> {
+    for msg in msgs {
+        if cross_msg_cid(msg)? == *cid {
+            return Ok(Some(msg));
+        }
+    }
+    Ok(None)
+}
+
+/// Builds the route of a top-down message with `nonce` sent from `parent` to `child`. The
+/// gateway of the child applies top-down messages in order, so the message has been applied
+/// once the next nonce to apply, `applied_nonce`, is past it.
+fn topdown_route(
+    parent: &SubnetID,
+    child: &SubnetID,
+    nonce: u64,
+    applied_nonce: Option<u64>,
+) -> Vec<CrossMsgRouteStage> {
+    let child_stage = match applied_nonce {
+        None => CrossMsgStage::Unknown,
+        Some(applied) if applied > nonce => CrossMsgStage::Applied,
+        Some(_) => CrossMsgStage::Pending,
+    };
+
+    vec![
+        CrossMsgRouteStage {
+            subnet: parent.to_string(),
+            stage: CrossMsgStage::Committed,
+        },
+        CrossMsgRouteStage {
+            subnet: child.to_string(),
+            stage: child_stage,
+        },
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use fvm_ipld_encoding::RawBytes;
+    use fvm_shared::address::Address;
+    use fvm_shared::econ::TokenAmount;
+    use ipc_gateway::{CrossMsg, StorableMsg};
+    use ipc_sdk::address::IPCAddress;
+    use ipc_sdk::subnet_id::SubnetID;
+
+    use crate::server::handlers::manager::crossmsg_route::{
+        cross_msg_cid, find_cross_msg, topdown_route, CrossMsgStage,
+    };
+
+    fn fund_msgs(parent: &SubnetID, child: &SubnetID) -> Vec<CrossMsg> {
+        (0..3)
+            .map(|nonce| CrossMsg {
+                msg: StorableMsg {
+                    from: IPCAddress::new(parent, &Address::new_id(100)).unwrap(),
+                    to: IPCAddress::new(child, &Address::new_id(100)).unwrap(),
+                    method: 0,
+                    params: RawBytes::default(),
+                    value: TokenAmount::from_whole(1),
+                    nonce,
+                },
+                wrapped: false,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_route_applied() {
+        let parent = SubnetID::from_str("/root").unwrap();
+        let child = SubnetID::from_str("/root/t01002").unwrap();
+        let msgs = fund_msgs(&parent, &child);
+
+        let cid = cross_msg_cid(&msgs[1]).unwrap();
+        let msg = find_cross_msg(&msgs, &cid).unwrap().unwrap();
+        assert_eq!(msg.msg.nonce, 1);
+
+        let route = topdown_route(&parent, &child, msg.msg.nonce, Some(3));
+        assert_eq!(route[0].stage, CrossMsgStage::Committed);
+        assert_eq!(route[1].subnet, child.to_string());
+        assert_eq!(route[1].stage, CrossMsgStage::Applied);
+    }
+
+    #[test]
+    fn test_route_pending() {
+        let parent = SubnetID::from_str("/root").unwrap();
+        let child = SubnetID::from_str("/root/t01002").unwrap();
+        let msgs = fund_msgs(&parent, &child);
+
+        let cid = cross_msg_cid(&msgs[2]).unwrap();
+        let msg = find_cross_msg(&msgs, &cid).unwrap().unwrap();
+
+        let route = topdown_route(&parent, &child, msg.msg.nonce, Some(2));
+        assert_eq!(route[1].stage, CrossMsgStage::Pending);
+
+        let route = topdown_route(&parent, &child, msg.msg.nonce, None);
+        assert_eq!(route[1].stage, CrossMsgStage::Unknown);
+
+        let unknown = cross_msg_cid(&fund_msgs(&child, &parent)[0]).unwrap();
+        assert!(find_cross_msg(&msgs, &unknown).unwrap().is_none());
+    }
+}
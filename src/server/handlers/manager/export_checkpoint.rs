@@ -0,0 +1,88 @@
+// Copyright 2022-2023 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Export a bottom-up checkpoint as a CAR file
+
+use std::str::FromStr;
+use std::sync::Arc;
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+use base64::Engine;
+use fvm_shared::clock::ChainEpoch;
+use ipc_sdk::subnet_id::SubnetID;
+use serde::{Deserialize, Serialize};
+
+use crate::manager::SubnetManager;
+use crate::serialization::car::checkpoint_to_car;
+use crate::server::error::HandlerError;
+use crate::server::handlers::manager::check_subnet;
+use crate::server::handlers::manager::subnet::SubnetManagerPool;
+use crate::server::JsonRPCRequestHandler;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportBottomUpCheckpointParams {
+    pub subnet_id: String,
+    pub epoch: ChainEpoch,
+    /// Include the cross-messages of the checkpoint as blocks of the CAR file
+    pub with_cross_msgs: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportBottomUpCheckpointResponse {
+    /// The CID of the checkpoint, the root of the CAR file
+    pub cid: String,
+    /// The base64 encoded CAR file
+    pub car: String,
+}
+
+/// The export checkpoint json rpc method handler.
+pub(crate) struct ExportBottomUpCheckpointHandler {
+    pool: Arc<SubnetManagerPool>,
+}
+
+impl ExportBottomUpCheckpointHandler {
+    pub(crate) fn new(pool: Arc<SubnetManagerPool>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl JsonRPCRequestHandler for ExportBottomUpCheckpointHandler {
+    type Request = ExportBottomUpCheckpointParams;
+    type Response = ExportBottomUpCheckpointResponse;
+
+    async fn handle(&self, request: Self::Request) -> anyhow::Result<Self::Response> {
+        let child_subnet_id = SubnetID::from_str(request.subnet_id.as_str())?;
+        let parent_subnet_id = child_subnet_id
+            .parent()
+            .ok_or_else(|| anyhow!("subnet id does not have a parent"))?;
+
+        let conn = match self.pool.get(&parent_subnet_id) {
+            None => return Err(HandlerError::ParentNotFound(parent_subnet_id.to_string()).into()),
+            Some(conn) => conn,
+        };
+
+        let subnet_config = conn.subnet();
+        check_subnet(subnet_config)?;
+
+        let checkpoint = conn
+            .manager()
+            .list_checkpoints(child_subnet_id.clone(), request.epoch, request.epoch)
+            .await?
+            .into_iter()
+            .find(|c| c.data.epoch == request.epoch)
+            .ok_or_else(|| {
+                anyhow!(
+                    "no checkpoint committed for {child_subnet_id:} at epoch {:}",
+                    request.epoch
+                )
+            })?;
+
+        let (cid, car) = checkpoint_to_car(&checkpoint, request.with_cross_msgs)?;
+
+        Ok(ExportBottomUpCheckpointResponse {
+            cid: cid.to_string(),
+            car: base64::engine::general_purpose::STANDARD.encode(car),
+        })
+    }
+}
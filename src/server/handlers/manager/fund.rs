@@ -3,6 +3,7 @@
 //! Fund operation in the gateway actor
 
 use crate::manager::SubnetManager;
+use crate::server::error::HandlerError;
 use crate::server::handlers::manager::subnet::SubnetManagerPool;
 use crate::server::{check_subnet, parse_from, JsonRPCRequestHandler};
 use anyhow::anyhow;
@@ -20,6 +21,12 @@ pub struct FundParams {
     pub amount: u64,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FundResponse {
+    /// The cid of the fund message included in the parent
+    pub cid: String,
+}
+
 /// The fund json rpc method handler.
 pub(crate) struct FundHandler {
     pool: Arc<SubnetManagerPool>,
@@ -34,13 +41,13 @@ impl FundHandler {
 #[async_trait]
 impl JsonRPCRequestHandler for FundHandler {
     type Request = FundParams;
-    type Response = ();
+    type Response = FundResponse;
 
     async fn handle(&self, request: Self::Request) -> anyhow::Result<Self::Response> {
         let subnet = SubnetID::from_str(&request.subnet)?;
         let parent = subnet.parent().ok_or_else(|| anyhow!("no parent found"))?;
         let conn = match self.pool.get(&parent) {
-            None => return Err(anyhow!("target parent subnet not found")),
+            None => return Err(HandlerError::ParentNotFound(parent.to_string()).into()),
             Some(conn) => conn,
         };
 
@@ -50,8 +57,12 @@ impl JsonRPCRequestHandler for FundHandler {
         let from = parse_from(subnet_config, request.from)?;
         let amount = TokenAmount::from_whole(request.amount);
 
-        conn.manager()
+        let cid = conn
+            .manager()
             .fund(subnet, subnet_config.gateway_addr, from, amount)
-            .await
+            .await?;
+        Ok(FundResponse {
+            cid: cid.to_string(),
+        })
     }
 }
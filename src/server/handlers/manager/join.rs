@@ -3,6 +3,7 @@
 //! Join subnet handler and parameters
 
 use crate::manager::SubnetManager;
+use crate::server::error::HandlerError;
 use crate::server::handlers::manager::subnet::SubnetManagerPool;
 use crate::server::handlers::manager::{check_subnet, parse_from};
 use crate::server::JsonRPCRequestHandler;
@@ -23,6 +24,14 @@ pub struct JoinSubnetParams {
     pub validator_net_addr: String,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JoinSubnetResponse {
+    /// The cid of the join message included in the parent
+    pub cid: String,
+    /// The total stake of the subnet after joining, in FIL, if it could be read
+    pub total_stake: Option<String>,
+}
+
 /// The create subnet json rpc method handler.
 pub(crate) struct JoinSubnetHandler {
     pool: Arc<SubnetManagerPool>,
@@ -37,13 +46,13 @@ impl JoinSubnetHandler {
 #[async_trait]
 impl JsonRPCRequestHandler for JoinSubnetHandler {
     type Request = JoinSubnetParams;
-    type Response = ();
+    type Response = JoinSubnetResponse;
 
     async fn handle(&self, request: Self::Request) -> anyhow::Result<Self::Response> {
         let subnet = SubnetID::from_str(&request.subnet)?;
         let parent = subnet.parent().ok_or_else(|| anyhow!("no parent found"))?;
         let conn = match self.pool.get(&parent) {
-            None => return Err(anyhow!("target parent subnet not found")),
+            None => return Err(HandlerError::ParentNotFound(parent.to_string()).into()),
             Some(conn) => conn,
         };
 
@@ -57,8 +66,70 @@ impl JsonRPCRequestHandler for JoinSubnetHandler {
 
         let from = parse_from(subnet_config, request.from)?;
 
-        conn.manager()
-            .join_subnet(subnet, from, collateral, join_params)
-            .await
+        // the subnet actor accepts less collateral than the minimum stake, but then does not
+        // add the validator to the validator set. The collateral adds up to the stake the
+        // validator already has, if it joined before.
+        let manager = conn.manager();
+        let state = manager.subnet_actor_state(&subnet).await?;
+        let stake = state.validator_stake(&from)?;
+        check_collateral(&subnet, &(stake + &collateral), &state.min_validator_stake)?;
+
+        let cid = manager
+            .join_subnet(subnet.clone(), from, collateral, join_params)
+            .await?;
+
+        // the join message is already included, so failing to report the stake must not fail it
+        let total_stake = match manager.subnet_actor_state(&subnet).await {
+            Ok(state) => Some(state.total_stake.to_string()),
+            Err(e) => {
+                log::warn!("cannot read the total stake of subnet {subnet:} after joining: {e:}");
+                None
+            }
+        };
+
+        Ok(JoinSubnetResponse {
+            cid: cid.to_string(),
+            total_stake,
+        })
+    }
+}
+
+/// Checks that the `stake` of a validator after joining `subnet` is enough to be a validator.
+fn check_collateral(
+    subnet: &SubnetID,
+    stake: &TokenAmount,
+    min_validator_stake: &TokenAmount,
+) -> anyhow::Result<()> {
+    if stake < min_validator_stake {
+        return Err(HandlerError::InvalidParams(format!(
+            "stake of {stake:} FIL is below the minimum validator stake of {min_validator_stake:} FIL of subnet {subnet:}"
+        ))
+        .into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use fvm_shared::econ::TokenAmount;
+    use ipc_sdk::subnet_id::SubnetID;
+
+    use crate::server::error::{error_code, INVALID_PARAMS_CODE};
+    use crate::server::handlers::manager::join::check_collateral;
+
+    #[test]
+    fn test_check_collateral() {
+        let subnet = SubnetID::from_str("/root/t01002").unwrap();
+        let min = TokenAmount::from_whole(2);
+        assert!(check_collateral(&subnet, &TokenAmount::from_whole(2), &min).is_ok());
+        assert!(check_collateral(&subnet, &TokenAmount::from_whole(3), &min).is_ok());
+
+        let err = check_collateral(&subnet, &TokenAmount::from_whole(1), &min).unwrap_err();
+        assert_eq!(error_code(&err), INVALID_PARAMS_CODE);
+        let err = err.to_string();
+        assert!(err.contains(&format!("below the minimum validator stake of {min:} FIL")));
+        assert!(err.contains("/root/t01002"));
     }
 }
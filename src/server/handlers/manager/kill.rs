@@ -3,11 +3,13 @@
 //! Kill subnet handler and parameters
 
 use crate::manager::SubnetManager;
+use crate::server::error::HandlerError;
 use crate::server::handlers::manager::subnet::SubnetManagerPool;
 use crate::server::handlers::manager::{check_subnet, parse_from};
 use crate::server::JsonRPCRequestHandler;
 use anyhow::anyhow;
 use async_trait::async_trait;
+use fvm_shared::econ::TokenAmount;
 use ipc_sdk::subnet_id::SubnetID;
 use serde::{Deserialize, Serialize};
 use std::str::FromStr;
@@ -19,6 +21,12 @@ pub struct KillSubnetParams {
     pub from: Option<String>,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct KillSubnetResponse {
+    /// The cid of the kill message included in the parent
+    pub cid: String,
+}
+
 /// The create subnet json rpc method handler.
 pub(crate) struct KillSubnetHandler {
     pool: Arc<SubnetManagerPool>,
@@ -33,13 +41,13 @@ impl KillSubnetHandler {
 #[async_trait]
 impl JsonRPCRequestHandler for KillSubnetHandler {
     type Request = KillSubnetParams;
-    type Response = ();
+    type Response = KillSubnetResponse;
 
     async fn handle(&self, request: Self::Request) -> anyhow::Result<Self::Response> {
         let subnet = SubnetID::from_str(&request.subnet)?;
         let parent = subnet.parent().ok_or_else(|| anyhow!("no parent found"))?;
         let conn = match self.pool.get(&parent) {
-            None => return Err(anyhow!("target parent subnet not found")),
+            None => return Err(HandlerError::ParentNotFound(parent.to_string()).into()),
             Some(conn) => conn,
         };
 
@@ -48,6 +56,70 @@ impl JsonRPCRequestHandler for KillSubnetHandler {
 
         let from = parse_from(subnet_config, request.from)?;
 
-        conn.manager().kill_subnet(subnet, from).await
+        // the subnet actor refuses to be killed with validators left, check it first to tell why
+        let manager = conn.manager();
+        let state = manager.subnet_actor_state(&subnet).await?;
+        let validators = state
+            .validator_set
+            .validators
+            .map(|v| v.len())
+            .unwrap_or_default();
+        let circ_supply = manager
+            .list_child_subnets(subnet_config.gateway_addr)
+            .await?
+            .remove(&subnet)
+            .map(|info| info.circ_supply)
+            .unwrap_or_default();
+        check_killable(&subnet, validators, &circ_supply)?;
+
+        let cid = manager.kill_subnet(subnet, from).await?;
+        Ok(KillSubnetResponse {
+            cid: cid.to_string(),
+        })
+    }
+}
+
+/// Checks that `subnet` can be killed, i.e. that no validators and no funds are left in it.
+fn check_killable(
+    subnet: &SubnetID,
+    validators: usize,
+    circ_supply: &TokenAmount,
+) -> anyhow::Result<()> {
+    if validators > 0 {
+        return Err(anyhow!(
+            "cannot kill subnet {subnet:}: {validators:} validators have not left it yet"
+        ));
+    }
+    if *circ_supply > TokenAmount::from_atto(0) {
+        return Err(anyhow!(
+            "cannot kill subnet {subnet:}: {circ_supply:} FIL are still in circulation in it, release them first"
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use fvm_shared::econ::TokenAmount;
+    use ipc_sdk::subnet_id::SubnetID;
+
+    use crate::server::handlers::manager::kill::check_killable;
+
+    #[test]
+    fn test_check_killable() {
+        let subnet = SubnetID::from_str("/root/t01002").unwrap();
+        assert!(check_killable(&subnet, 0, &TokenAmount::from_atto(0)).is_ok());
+
+        let err = check_killable(&subnet, 2, &TokenAmount::from_atto(0))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("2 validators have not left"));
+
+        let supply = TokenAmount::from_whole(5);
+        let err = check_killable(&subnet, 0, &supply).unwrap_err().to_string();
+        assert!(err.contains(&format!("{supply:} FIL are still in circulation")));
+        assert!(err.contains("/root/t01002"));
     }
 }
@@ -3,6 +3,7 @@
 //! Leave subnet handler and parameters
 
 use crate::manager::SubnetManager;
+use crate::server::error::HandlerError;
 use crate::server::handlers::manager::subnet::SubnetManagerPool;
 use crate::server::handlers::manager::{check_subnet, parse_from};
 use crate::server::JsonRPCRequestHandler;
@@ -19,6 +20,14 @@ pub struct LeaveSubnetParams {
     pub from: Option<String>,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LeaveSubnetResponse {
+    /// The cid of the leave message included in the parent
+    pub cid: String,
+    /// The collateral returned to the validator, in FIL
+    pub collateral: String,
+}
+
 /// The create subnet json rpc method handler.
 pub(crate) struct LeaveSubnetHandler {
     pool: Arc<SubnetManagerPool>,
@@ -33,13 +42,13 @@ impl LeaveSubnetHandler {
 #[async_trait]
 impl JsonRPCRequestHandler for LeaveSubnetHandler {
     type Request = LeaveSubnetParams;
-    type Response = ();
+    type Response = LeaveSubnetResponse;
 
     async fn handle(&self, request: Self::Request) -> anyhow::Result<Self::Response> {
         let subnet = SubnetID::from_str(&request.subnet)?;
         let parent = subnet.parent().ok_or_else(|| anyhow!("no parent found"))?;
         let conn = match self.pool.get(&parent) {
-            None => return Err(anyhow!("target parent subnet not found")),
+            None => return Err(HandlerError::ParentNotFound(parent.to_string()).into()),
             Some(conn) => conn,
         };
 
@@ -48,6 +57,18 @@ impl JsonRPCRequestHandler for LeaveSubnetHandler {
 
         let from = parse_from(subnet_config, request.from)?;
 
-        conn.manager().leave_subnet(subnet, from).await
+        // the stake of the validator is returned in full when it leaves. It is read before
+        // leaving, as other validators may join or leave in the meantime.
+        let manager = conn.manager();
+        let collateral = manager
+            .subnet_actor_state(&subnet)
+            .await?
+            .validator_stake(&from)?;
+        let cid = manager.leave_subnet(subnet.clone(), from).await?;
+
+        Ok(LeaveSubnetResponse {
+            cid: cid.to_string(),
+            collateral: collateral.to_string(),
+        })
     }
 }
@@ -5,7 +5,6 @@
 use std::str::FromStr;
 use std::sync::Arc;
 
-use anyhow::anyhow;
 use async_trait::async_trait;
 use fvm_shared::clock::ChainEpoch;
 use ipc_gateway::BottomUpCheckpoint;
@@ -14,15 +13,62 @@ use serde::{Deserialize, Serialize};
 
 use crate::manager::SubnetManager;
 use crate::serialization::SerializeToJson;
+use crate::server::error::HandlerError;
 use crate::server::handlers::manager::check_subnet;
 use crate::server::handlers::manager::subnet::SubnetManagerPool;
 use crate::server::JsonRPCRequestHandler;
 
+/// The maximum number of checkpoints returned by a request, also used when none is requested.
+const MAX_CHECKPOINTS_LIMIT: usize = 100;
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ListBottomUpCheckpointsParams {
     pub subnet_id: String,
     pub from_epoch: ChainEpoch,
     pub to_epoch: ChainEpoch,
+    /// Snap the range to the epochs checkpoints are committed at, so that only those are
+    /// queried.
+    #[serde(default)]
+    pub align: bool,
+    /// The number of checkpoint periods of the range to skip. As checkpoints may be missing
+    /// for some periods, use the `next_offset` of the previous page.
+    #[serde(default)]
+    pub offset: usize,
+    /// The number of checkpoint periods to list, and so the maximum number of checkpoints to
+    /// return, capped at 100.
+    #[serde(default)]
+    pub limit: Option<usize>,
+    /// Return the checkpoints in a [`BottomUpCheckpointsPage`], along with how the range was
+    /// aligned and the offset of the next page, instead of a bare array.
+    #[serde(default)]
+    pub with_page_info: bool,
+}
+
+/// The checkpoints listed, as a bare array unless the page info is requested.
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum ListBottomUpCheckpointsResponse {
+    Checkpoints(Vec<SerializeToJson<BottomUpCheckpoint>>),
+    Page(BottomUpCheckpointsPage),
+}
+
+impl ListBottomUpCheckpointsResponse {
+    fn new(page: BottomUpCheckpointsPage, with_page_info: bool) -> Self {
+        if with_page_info {
+            Self::Page(page)
+        } else {
+            Self::Checkpoints(page.checkpoints)
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct BottomUpCheckpointsPage {
+    pub checkpoints: Vec<SerializeToJson<BottomUpCheckpoint>>,
+    /// Explains how the requested range was aligned, if it was.
+    pub note: Option<String>,
+    /// The offset to request the next page of the range with, if there are more checkpoints.
+    pub next_offset: Option<usize>,
 }
 
 /// The list checkpoints json rpc method handler.
@@ -39,30 +85,235 @@ impl ListBottomUpCheckpointsHandler {
 #[async_trait]
 impl JsonRPCRequestHandler for ListBottomUpCheckpointsHandler {
     type Request = ListBottomUpCheckpointsParams;
-    type Response = Vec<SerializeToJson<BottomUpCheckpoint>>;
+    type Response = ListBottomUpCheckpointsResponse;
 
     async fn handle(&self, request: Self::Request) -> anyhow::Result<Self::Response> {
-        let child_subnet_id = SubnetID::from_str(request.subnet_id.as_str())?;
+        let child_subnet_id =
+            SubnetID::from_str(request.subnet_id.as_str()).map_err(HandlerError::invalid_params)?;
         let parent_subnet_id = child_subnet_id
             .parent()
-            .ok_or_else(|| anyhow!("subnet id does not have a parent"))?;
+            .ok_or_else(|| HandlerError::invalid_params("subnet id does not have a parent"))?;
 
         let conn = match self.pool.get(&parent_subnet_id) {
-            None => return Err(anyhow!("target parent subnet not found")),
+            None => return Err(HandlerError::ParentNotFound(parent_subnet_id.to_string()).into()),
             Some(conn) => conn,
         };
+        conn.check_staleness().await;
 
         let subnet_config = conn.subnet();
         check_subnet(subnet_config)?;
 
-        let checkpoints = conn
+        let limit = page_limit(request.limit)?;
+        let (mut from_epoch, mut to_epoch) = (request.from_epoch, request.to_epoch);
+        let mut note = None;
+        // the period bounds the epochs of a page, so that only its checkpoints are listed
+        let (genesis, period) = conn
+            .manager()
+            .bottom_up_checkpoint_schedule(&child_subnet_id)
+            .await
+            .map_err(HandlerError::upstream)?;
+        if request.align {
+            match align_checkpoint_range(from_epoch, to_epoch, genesis, period) {
+                None => {
+                    let page = BottomUpCheckpointsPage {
+                        checkpoints: vec![],
+                        next_offset: None,
+                        note: Some(format!(
+                            "no checkpoint epochs in [{from_epoch:}, {to_epoch:}] for period {period:} from genesis {genesis:}"
+                        )),
+                    };
+                    return Ok(ListBottomUpCheckpointsResponse::new(
+                        page,
+                        request.with_page_info,
+                    ));
+                }
+                Some((from, to)) => {
+                    note = Some(format!(
+                        "range [{from_epoch:}, {to_epoch:}] aligned to [{from:}, {to:}] for period {period:} from genesis {genesis:}"
+                    ));
+                    (from_epoch, to_epoch) = (from, to);
+                }
+            }
+        }
+
+        let page = conn
             .manager()
-            .list_checkpoints(child_subnet_id, request.from_epoch, request.to_epoch)
-            .await?
-            .into_iter()
-            .map(SerializeToJson)
-            .collect();
+            .list_checkpoints_paged(
+                child_subnet_id,
+                from_epoch,
+                to_epoch,
+                period,
+                request.offset,
+                limit,
+            )
+            .await
+            .map_err(HandlerError::upstream)?;
+        let checkpoints = page.checkpoints.into_iter().map(SerializeToJson).collect();
+
+        let page = BottomUpCheckpointsPage {
+            checkpoints,
+            note,
+            next_offset: page.next_offset,
+        };
+        Ok(ListBottomUpCheckpointsResponse::new(
+            page,
+            request.with_page_info,
+        ))
+    }
+}
+
+/// Returns the number of checkpoints to return for the requested `limit`, which is capped at
+/// [`MAX_CHECKPOINTS_LIMIT`] so that a single response stays bounded.
+fn page_limit(limit: Option<usize>) -> anyhow::Result<usize> {
+    match limit {
+        Some(0) => Err(HandlerError::invalid_params("limit must be positive")),
+        Some(limit) => Ok(limit.min(MAX_CHECKPOINTS_LIMIT)),
+        None => Ok(MAX_CHECKPOINTS_LIMIT),
+    }
+}
+
+/// Snaps `[from, to]` to the first and last epochs in the range at which checkpoints are
+/// committed, i.e. `genesis + k * period`. Returns `None` if there are none.
+fn align_checkpoint_range(
+    from: ChainEpoch,
+    to: ChainEpoch,
+    genesis: ChainEpoch,
+    period: ChainEpoch,
+) -> Option<(ChainEpoch, ChainEpoch)> {
+    if period <= 0 || to < genesis {
+        return None;
+    }
+
+    let from = from.max(genesis);
+    // round up to the next checkpoint epoch
+    let first = genesis + (from - genesis + period - 1) / period * period;
+    // round down to the previous checkpoint epoch
+    let last = genesis + (to - genesis) / period * period;
+
+    if first > last {
+        None
+    } else {
+        Some((first, last))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use std::sync::Arc;
+
+    use fvm_shared::address::Address;
+    use ipc_gateway::BottomUpCheckpoint;
+    use ipc_sdk::subnet_id::{SubnetID, ROOTNET_ID};
+    use tempfile::NamedTempFile;
+
+    use crate::config::ReloadableConfig;
+    use crate::jsonrpc::HttpClients;
+    use crate::serialization::SerializeToJson;
+    use crate::server::error::{
+        error_code, INVALID_PARAMS_CODE, PARENT_NOT_FOUND_CODE, SUBNET_MISCONFIGURED_CODE,
+        UPSTREAM_CODE,
+    };
+    use crate::server::handlers::manager::list_checkpoints::{
+        align_checkpoint_range, page_limit, BottomUpCheckpointsPage,
+        ListBottomUpCheckpointsHandler, ListBottomUpCheckpointsParams,
+        ListBottomUpCheckpointsResponse, MAX_CHECKPOINTS_LIMIT,
+    };
+    use crate::server::handlers::manager::subnet::SubnetManagerPool;
+    use crate::server::JsonRPCRequestHandler;
+
+    #[test]
+    fn test_align_checkpoint_range() {
+        // checkpoints at 5, 15, 25, 35...
+        assert_eq!(align_checkpoint_range(12, 33, 5, 10), Some((15, 25)));
+        assert_eq!(align_checkpoint_range(15, 35, 5, 10), Some((15, 35)));
+        // ranges before genesis start at the genesis
+        assert_eq!(align_checkpoint_range(0, 16, 5, 10), Some((5, 15)));
+        // no checkpoint epoch in the range
+        assert_eq!(align_checkpoint_range(16, 24, 5, 10), None);
+        assert_eq!(align_checkpoint_range(0, 4, 5, 10), None);
+    }
+
+    #[test]
+    fn test_page_limit() {
+        assert_eq!(page_limit(Some(10)).unwrap(), 10);
+        assert_eq!(page_limit(None).unwrap(), MAX_CHECKPOINTS_LIMIT);
+        assert_eq!(page_limit(Some(100_000)).unwrap(), MAX_CHECKPOINTS_LIMIT);
+        assert!(page_limit(Some(0)).is_err());
+    }
+
+    #[test]
+    fn test_response_shape() {
+        let child = SubnetID::new_from_parent(&ROOTNET_ID, Address::new_id(1002));
+        let page = || BottomUpCheckpointsPage {
+            checkpoints: vec![SerializeToJson(BottomUpCheckpoint::new(child.clone(), 10))],
+            note: Some(String::from("aligned")),
+            next_offset: Some(1),
+        };
+
+        // a bare array unless the page info is requested
+        let json = serde_json::to_value(ListBottomUpCheckpointsResponse::new(page(), false));
+        let json = json.unwrap();
+        assert_eq!(json.as_array().unwrap().len(), 1);
+        assert_eq!(json[0]["data"]["epoch"], 10);
+
+        let json = serde_json::to_value(ListBottomUpCheckpointsResponse::new(page(), true));
+        let json = json.unwrap();
+        assert_eq!(json["checkpoints"].as_array().unwrap().len(), 1);
+        assert_eq!(json["note"], "aligned");
+        assert_eq!(json["next_offset"], 1);
+    }
+
+    #[tokio::test]
+    async fn test_error_codes() {
+        // the node of the root is not listening, the one of the child has no auth token
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(
+            br#"
+            [server]
+            json_rpc_address = "127.0.0.1:3030"
+
+            [[subnets]]
+            id = "/root"
+            gateway_addr = "t064"
+            network_name = "root"
+            jsonrpc_api_http = "http://127.0.0.1:1/rpc/v1"
+            auth_token = "token"
+
+            [[subnets]]
+            id = "/root/t01"
+            gateway_addr = "t064"
+            network_name = "child"
+            jsonrpc_api_http = "http://127.0.0.1:1/rpc/v1"
+            "#,
+        )
+        .unwrap();
+        let config = ReloadableConfig::new(file.path().to_str().unwrap().to_string()).unwrap();
+        let pool = SubnetManagerPool::from_reload_config(
+            Arc::new(config),
+            Arc::new(HttpClients::default()),
+        );
+        let handler = ListBottomUpCheckpointsHandler::new(Arc::new(pool));
+
+        let code = |subnet_id: &str, limit: Option<usize>| {
+            let request = ListBottomUpCheckpointsParams {
+                subnet_id: subnet_id.to_string(),
+                from_epoch: 0,
+                to_epoch: 10,
+                align: false,
+                offset: 0,
+                limit,
+                with_page_info: false,
+            };
+            let handler = &handler;
+            async move { error_code(&handler.handle(request).await.unwrap_err()) }
+        };
 
-        Ok(checkpoints)
+        assert_eq!(code("/root/invalid", None).await, INVALID_PARAMS_CODE);
+        assert_eq!(code("/root", None).await, INVALID_PARAMS_CODE);
+        assert_eq!(code("/root/t01/t02", None).await, SUBNET_MISCONFIGURED_CODE);
+        assert_eq!(code("/root/t02/t03", None).await, PARENT_NOT_FOUND_CODE);
+        assert_eq!(code("/root/t01", Some(0)).await, INVALID_PARAMS_CODE);
+        assert_eq!(code("/root/t01", None).await, UPSTREAM_CODE);
     }
 }
@@ -4,10 +4,10 @@
 
 use crate::lotus::message::ipc::SubnetInfo;
 use crate::manager::SubnetManager;
+use crate::server::error::HandlerError;
 use crate::server::handlers::manager::check_subnet;
 use crate::server::handlers::manager::subnet::SubnetManagerPool;
 use crate::server::JsonRPCRequestHandler;
-use anyhow::anyhow;
 use async_trait::async_trait;
 use fvm_shared::address::Address;
 use ipc_sdk::subnet_id::SubnetID;
@@ -41,9 +41,10 @@ impl JsonRPCRequestHandler for ListSubnetsHandler {
     async fn handle(&self, request: Self::Request) -> anyhow::Result<Self::Response> {
         let subnet = SubnetID::from_str(&request.subnet_id)?;
         let conn = match self.pool.get(&subnet) {
-            None => return Err(anyhow!("target parent subnet not found")),
+            None => return Err(HandlerError::ParentNotFound(subnet.to_string()).into()),
             Some(conn) => conn,
         };
+        conn.check_staleness().await;
 
         let subnet_config = conn.subnet();
         check_subnet(subnet_config)?;
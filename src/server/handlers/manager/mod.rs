@@ -7,8 +7,14 @@ use anyhow::{anyhow, Result};
 use fvm_shared::address::Address;
 
 use crate::config::Subnet;
+use crate::server::error::HandlerError;
 
 pub mod create;
+pub mod crossmsg_audit;
+pub mod crossmsg_backlog;
+pub mod crossmsg_list;
+pub mod crossmsg_route;
+pub mod export_checkpoint;
 pub mod fund;
 pub mod join;
 pub mod kill;
@@ -20,13 +26,15 @@ pub mod propagate;
 pub mod release;
 pub mod send_value;
 pub mod subnet;
+pub mod subnet_info;
 pub mod topdown_executed;
+pub mod verify_checkpoint_chain;
 pub mod whitelist;
 
 pub(crate) fn check_subnet(subnet: &Subnet) -> Result<()> {
     if subnet.auth_token.is_none() {
         log::error!("subnet {:?} does not have auth token", subnet.id);
-        return Err(anyhow!("Internal server error"));
+        return Err(HandlerError::SubnetMisconfigured(subnet.id.to_string()).into());
     }
     Ok(())
 }
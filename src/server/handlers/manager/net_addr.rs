@@ -3,6 +3,7 @@
 //! Set the subnet actor validator net addr
 
 use crate::manager::SubnetManager;
+use crate::server::error::HandlerError;
 use crate::server::subnet::SubnetManagerPool;
 use crate::server::{check_subnet, parse_from, JsonRPCRequestHandler};
 use anyhow::anyhow;
@@ -39,7 +40,7 @@ impl JsonRPCRequestHandler for SetValidatorNetAddrHandler {
         let subnet = SubnetID::from_str(&request.subnet)?;
         let parent = subnet.parent().ok_or_else(|| anyhow!("no parent found"))?;
         let conn = match self.pool.get(&parent) {
-            None => return Err(anyhow!("target parent subnet not found")),
+            None => return Err(HandlerError::ParentNotFound(parent.to_string()).into()),
             Some(conn) => conn,
         };
 
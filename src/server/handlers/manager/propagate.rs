@@ -3,9 +3,9 @@
 //! Propagate operation in the gateway actor
 
 use crate::manager::SubnetManager;
+use crate::server::error::HandlerError;
 use crate::server::handlers::manager::subnet::SubnetManagerPool;
 use crate::server::{check_subnet, parse_from, JsonRPCRequestHandler};
-use anyhow::anyhow;
 use async_trait::async_trait;
 use cid::Cid;
 use ipc_sdk::subnet_id::SubnetID;
@@ -39,7 +39,7 @@ impl JsonRPCRequestHandler for PropagateHandler {
     async fn handle(&self, request: Self::Request) -> anyhow::Result<Self::Response> {
         let subnet = SubnetID::from_str(&request.subnet)?;
         let conn = match self.pool.get(&subnet) {
-            None => return Err(anyhow!("target subnet not found")),
+            None => return Err(HandlerError::SubnetNotFound(subnet.to_string()).into()),
             Some(conn) => conn,
         };
 
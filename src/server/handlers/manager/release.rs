@@ -2,11 +2,11 @@
 // SPDX-License-Identifier: MIT
 //! Release operation in the gateway actor
 
-use crate::manager::SubnetManager;
+use crate::server::error::HandlerError;
 use crate::server::handlers::manager::subnet::SubnetManagerPool;
 use crate::server::{check_subnet, parse_from, JsonRPCRequestHandler};
-use anyhow::anyhow;
 use async_trait::async_trait;
+use cid::Cid;
 use fvm_shared::econ::TokenAmount;
 use ipc_sdk::subnet_id::SubnetID;
 use serde::{Deserialize, Serialize};
@@ -18,6 +18,17 @@ pub struct ReleaseParams {
     pub subnet: String,
     pub from: Option<String>,
     pub amount: u64,
+    /// Whether to wait for the message to be executed, true if not set
+    #[serde(default)]
+    pub wait: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReleaseResponse {
+    /// The cid of the release message, that of the executed one if waited for
+    pub cid: String,
+    /// The exit code of the message, if waited for
+    pub exit_code: Option<u32>,
 }
 
 /// The Release json rpc method handler.
@@ -34,12 +45,12 @@ impl ReleaseHandler {
 #[async_trait]
 impl JsonRPCRequestHandler for ReleaseHandler {
     type Request = ReleaseParams;
-    type Response = ();
+    type Response = ReleaseResponse;
 
     async fn handle(&self, request: Self::Request) -> anyhow::Result<Self::Response> {
         let subnet = SubnetID::from_str(&request.subnet)?;
         let conn = match self.pool.get(&subnet) {
-            None => return Err(anyhow!("target subnet not found")),
+            None => return Err(HandlerError::SubnetNotFound(subnet.to_string()).into()),
             Some(conn) => conn,
         };
 
@@ -49,8 +60,22 @@ impl JsonRPCRequestHandler for ReleaseHandler {
         let amount = TokenAmount::from_whole(request.amount);
         let from = parse_from(subnet_config, request.from)?;
 
-        conn.manager()
-            .release(subnet, subnet_config.gateway_addr, from, amount)
-            .await
+        let manager = conn.manager();
+        let cid = manager
+            .push_release(subnet, subnet_config.gateway_addr, from, amount)
+            .await?;
+        if !request.wait.unwrap_or(true) {
+            return Ok(ReleaseResponse {
+                cid: cid.to_string(),
+                exit_code: None,
+            });
+        }
+
+        // the message waited on may have been replaced, e.g. to bump its gas
+        let r = manager.wait_msg(cid).await?;
+        Ok(ReleaseResponse {
+            cid: Cid::try_from(r.message)?.to_string(),
+            exit_code: Some(r.receipt.exit_code()),
+        })
     }
 }
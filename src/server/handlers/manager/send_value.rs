@@ -3,10 +3,10 @@
 //! SendValue subnet handler and parameters
 
 use crate::manager::SubnetManager;
+use crate::server::error::HandlerError;
 use crate::server::handlers::manager::subnet::SubnetManagerPool;
 use crate::server::handlers::manager::{check_subnet, parse_from};
 use crate::server::JsonRPCRequestHandler;
-use anyhow::anyhow;
 use async_trait::async_trait;
 use fvm_shared::address::Address;
 use fvm_shared::econ::TokenAmount;
@@ -42,7 +42,7 @@ impl JsonRPCRequestHandler for SendValueHandler {
     async fn handle(&self, request: Self::Request) -> anyhow::Result<Self::Response> {
         let subnet = SubnetID::from_str(&request.subnet)?;
         let conn = match self.pool.get(&subnet) {
-            None => return Err(anyhow!("target parent subnet not found")),
+            None => return Err(HandlerError::ParentNotFound(subnet.to_string()).into()),
             Some(conn) => conn,
         };
 
@@ -2,11 +2,13 @@
 // SPDX-License-Identifier: MIT
 //! The shared subnet manager module for all subnet management related RPC method calls.
 
-use crate::config::{ReloadableConfig, Subnet};
-use crate::jsonrpc::{JsonRpcClient, JsonRpcClientImpl};
+use crate::config::{Config, ReloadableConfig, Subnet};
+use crate::jsonrpc::{HttpClients, JsonRpcClient, JsonRpcClientImpl};
 use crate::manager::LotusSubnetManager;
 use ipc_sdk::subnet_id::SubnetID;
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::watch;
 
 /// The subnet manager connection that holds the subnet config and the manager instance.
 pub struct Connection<T: JsonRpcClient> {
@@ -24,33 +26,192 @@ impl<T: JsonRpcClient> Connection<T> {
     }
 }
 
+impl<T: JsonRpcClient + Send + Sync> Connection<T> {
+    /// Logs a warning if the node of the subnet lags behind the network head by more than the
+    /// `stale_threshold` of the subnet, as the state read from it may be stale.
+    pub async fn check_staleness(&self) {
+        let threshold = match self.subnet.stale_threshold {
+            None => return,
+            Some(t) => t,
+        };
+
+        match self.manager.head_lag().await {
+            Ok(lag) if lag > threshold => log::warn!(
+                "node of subnet {:} is {lag:} epochs behind the network head, reads may be stale",
+                self.subnet.id
+            ),
+            Ok(_) => {}
+            Err(e) => log::warn!(
+                "cannot check the freshness of subnet {:}: {e:}",
+                self.subnet.id
+            ),
+        }
+    }
+}
+
 /// The json rpc subnet manager connection pool. This struct can be shared by all the subnet methods.
 /// As such, there is no need to re-init the same SubnetManager for different methods to reuse connections.
 pub struct SubnetManagerPool {
-    config: Arc<ReloadableConfig>,
+    state: Mutex<PoolState>,
+    /// The HTTP clients the connections send their requests through, kept across the reloads.
+    http_clients: Arc<HttpClients>,
+}
+
+struct PoolState {
+    /// Notified when a reload changes the config.
+    config: watch::Receiver<Arc<Config>>,
+    /// The connections created for the current config, those of the subnets a reload changes
+    /// or removes are dropped when the change is seen.
+    connections: HashMap<SubnetID, Arc<Connection<JsonRpcClientImpl>>>,
 }
 
 impl SubnetManagerPool {
-    pub fn from_reload_config(reload_config: Arc<ReloadableConfig>) -> Self {
+    pub fn from_reload_config(
+        reload_config: Arc<ReloadableConfig>,
+        http_clients: Arc<HttpClients>,
+    ) -> Self {
         Self {
-            config: reload_config,
+            state: Mutex::new(PoolState {
+                config: reload_config.watch(),
+                connections: HashMap::new(),
+            }),
+            http_clients,
         }
     }
 
     /// Get the connection instance for the subnet.
-    pub fn get(&self, subnet: &SubnetID) -> Option<Connection<JsonRpcClientImpl>> {
-        let config = self.config.get_config();
-        let subnets = &config.subnets;
-
-        match subnets.get(subnet) {
-            Some(subnet) => {
-                let manager = LotusSubnetManager::from_subnet(subnet);
-                Some(Connection {
-                    manager,
-                    subnet: subnet.clone(),
-                })
-            }
-            None => None,
+    pub fn get(&self, subnet: &SubnetID) -> Option<Arc<Connection<JsonRpcClientImpl>>> {
+        // the state is locked until the connection is cached, so that no connection is created
+        // for a config a concurrent call has seen replaced
+        let mut state = self.state.lock().unwrap();
+        let config = state.latest_config();
+        let subnet = config.subnets.get(subnet)?;
+
+        if let Some(conn) = state.connections.get(&subnet.id) {
+            return Some(conn.clone());
         }
+
+        log::debug!("creating new connection for subnet: {:}", subnet.id);
+        let conn = Arc::new(Connection {
+            manager: LotusSubnetManager::from_subnet(subnet, &self.http_clients),
+            subnet: subnet.clone(),
+        });
+        state.connections.insert(subnet.id.clone(), conn.clone());
+        Some(conn)
+    }
+}
+
+impl PoolState {
+    /// Returns the latest config, dropping the connections it invalidates if it changed since
+    /// the last call.
+    fn latest_config(&mut self) -> Arc<Config> {
+        if !self.config.has_changed().unwrap_or_default() {
+            return self.config.borrow().clone();
+        }
+
+        let config = self.config.borrow_and_update().clone();
+        self.connections.retain(|id, conn| {
+            let keep = config.subnets.get(id) == Some(&conn.subnet);
+            if !keep {
+                log::debug!("dropping connection of changed subnet: {id:}");
+            }
+            keep
+        });
+        config
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use std::str::FromStr;
+    use std::sync::Arc;
+
+    use ipc_sdk::subnet_id::SubnetID;
+    use tempfile::NamedTempFile;
+
+    use crate::config::ReloadableConfig;
+    use crate::jsonrpc::HttpClients;
+    use crate::server::handlers::manager::subnet::SubnetManagerPool;
+
+    fn config_file(child_http: &str) -> NamedTempFile {
+        let config = format!(
+            r#"
+            [server]
+            json_rpc_address = "127.0.0.1:3030"
+
+            [[subnets]]
+            id = "/root"
+            gateway_addr = "t064"
+            network_name = "root"
+            jsonrpc_api_http = "http://127.0.0.1:1234/rpc/v1"
+
+            [[subnets]]
+            id = "/root/t01"
+            gateway_addr = "t064"
+            network_name = "child"
+            jsonrpc_api_http = "{child_http}"
+            "#
+        );
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(config.as_bytes()).unwrap();
+        file
+    }
+
+    fn path(file: &NamedTempFile) -> String {
+        file.path().to_str().unwrap().to_string()
+    }
+
+    #[tokio::test]
+    async fn test_connections_reused_until_subnet_changes() {
+        let root = SubnetID::from_str("/root").unwrap();
+        let child = SubnetID::from_str("/root/t01").unwrap();
+
+        let file = config_file("http://127.0.0.1:1250/rpc/v1");
+        let config = Arc::new(ReloadableConfig::new(path(&file)).unwrap());
+        let pool =
+            SubnetManagerPool::from_reload_config(config.clone(), Arc::new(HttpClients::default()));
+
+        let root_conn = pool.get(&root).unwrap();
+        let child_conn = pool.get(&child).unwrap();
+        assert!(Arc::ptr_eq(&root_conn, &pool.get(&root).unwrap()));
+        assert!(pool
+            .get(&SubnetID::from_str("/root/t02").unwrap())
+            .is_none());
+
+        // only the connection of the subnet whose config changed is recreated
+        let file = config_file("http://127.0.0.1:1251/rpc/v1");
+        config.set_path(path(&file));
+        config.reload().await.unwrap();
+
+        assert!(Arc::ptr_eq(&root_conn, &pool.get(&root).unwrap()));
+        let new_child_conn = pool.get(&child).unwrap();
+        assert!(!Arc::ptr_eq(&child_conn, &new_child_conn));
+        assert_eq!(
+            new_child_conn.subnet().jsonrpc_api_http.as_str(),
+            "http://127.0.0.1:1251/rpc/v1"
+        );
+
+        // the connections of removed subnets are dropped once the change is seen
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(
+            br#"
+            [server]
+            json_rpc_address = "127.0.0.1:3030"
+
+            [[subnets]]
+            id = "/root"
+            gateway_addr = "t064"
+            network_name = "root"
+            jsonrpc_api_http = "http://127.0.0.1:1234/rpc/v1"
+            "#,
+        )
+        .unwrap();
+        config.set_path(path(&file));
+        config.reload().await.unwrap();
+
+        assert!(Arc::ptr_eq(&root_conn, &pool.get(&root).unwrap()));
+        let state = pool.state.lock().unwrap();
+        assert_eq!(state.connections.keys().collect::<Vec<_>>(), vec![&root]);
     }
 }
@@ -0,0 +1,230 @@
+// Copyright 2022-2023 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Subnet info handler and parameters
+
+use std::str::FromStr;
+use std::sync::Arc;
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+use fvm_shared::bigint::BigInt;
+use fvm_shared::clock::ChainEpoch;
+use fvm_shared::econ::TokenAmount;
+use ipc_sdk::subnet_id::SubnetID;
+use serde::{Deserialize, Serialize};
+
+use crate::config::ReloadableConfig;
+use crate::jsonrpc::HttpClients;
+use crate::lotus::client::LotusJsonRPCClient;
+use crate::lotus::message::ipc::{IPCReadSubnetActorStateResponse, SubnetInfo};
+use crate::lotus::LotusClient;
+use crate::server::error::HandlerError;
+use crate::server::handlers::manager::crossmsg_backlog::head;
+use crate::server::JsonRPCRequestHandler;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SubnetInfoParams {
+    pub subnet: String,
+}
+
+/// The state of a subnet registered in the gateway of its parent, along with the state of its
+/// subnet actor, both read at the same epoch of the parent.
+#[derive(Debug, Serialize)]
+pub struct SubnetInfoResponse {
+    #[serde(flatten)]
+    pub info: SubnetInfo,
+    /// The epoch of the parent at which the subnet was read
+    pub epoch: ChainEpoch,
+    /// The number of validators of the subnet
+    pub validators: u64,
+    /// The number of validators the subnet needs to be active
+    pub min_validators: u64,
+    /// The collateral staked by all the validators, in FIL
+    pub total_stake: String,
+    /// The collateral a validator needs to stake to join the subnet, in FIL
+    pub min_validator_stake: String,
+    /// The validators of the subnet, with their stake
+    pub validator_stakes: Vec<ValidatorStake>,
+    /// The epoch of the parent at which the subnet started committing checkpoints
+    pub genesis_epoch: ChainEpoch,
+    /// The epoch of the last bottom-up checkpoint executed in the parent
+    pub last_checkpoint_epoch: ChainEpoch,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValidatorStake {
+    pub addr: String,
+    pub net_addr: String,
+    /// The collateral staked by the validator, in FIL
+    pub stake: String,
+}
+
+/// Returns the state of a single subnet, as registered in the gateway of its parent, along with
+/// the state of its subnet actor.
+pub(crate) struct SubnetInfoHandler {
+    config: Arc<ReloadableConfig>,
+    http_clients: Arc<HttpClients>,
+}
+
+impl SubnetInfoHandler {
+    pub(crate) fn new(config: Arc<ReloadableConfig>, http_clients: Arc<HttpClients>) -> Self {
+        Self {
+            config,
+            http_clients,
+        }
+    }
+}
+
+#[async_trait]
+impl JsonRPCRequestHandler for SubnetInfoHandler {
+    type Request = SubnetInfoParams;
+    type Response = SubnetInfoResponse;
+
+    async fn handle(&self, request: Self::Request) -> anyhow::Result<Self::Response> {
+        let subnet_id = SubnetID::from_str(&request.subnet)?;
+        let parent = subnet_id
+            .parent()
+            .ok_or_else(|| anyhow!("root is not registered in any gateway"))?;
+
+        let config = self.config.get_config();
+        let parent_subnet = config
+            .subnets
+            .get(&parent)
+            .ok_or_else(|| HandlerError::ParentNotFound(parent.to_string()))?;
+
+        // the gateway and the subnet actor are read at the same tipset, so that they agree
+        let lotus = LotusJsonRPCClient::from_subnet_with_clients(parent_subnet, &self.http_clients);
+        let (epoch, tip_set) = head(&lotus).await?;
+        let info = lotus
+            .ipc_list_child_subnets(parent_subnet.gateway_addr, Some(tip_set))
+            .await?
+            .into_iter()
+            .find(|s| s.id == subnet_id)
+            .ok_or_else(|| {
+                anyhow!(
+                    "subnet {subnet_id:} is not registered in gateway {:} of {parent:}",
+                    parent_subnet.gateway_addr
+                )
+            })?;
+        let state = lotus
+            .ipc_read_subnet_actor_state(&subnet_id, tip_set)
+            .await?;
+
+        response(info, epoch, state)
+    }
+}
+
+/// Returns the response for the subnet registered in the gateway with `info`, whose subnet
+/// actor has `state` at `epoch`.
+fn response(
+    info: SubnetInfo,
+    epoch: ChainEpoch,
+    state: IPCReadSubnetActorStateResponse,
+) -> anyhow::Result<SubnetInfoResponse> {
+    let status = state.activation_status();
+    let validator_stakes = state
+        .validator_set
+        .validators
+        .unwrap_or_default()
+        .into_iter()
+        .map(|v| {
+            let weight = BigInt::from_str(&v.weight).map_err(|_| {
+                anyhow!(
+                    "invalid stake of validator {:} in subnet {:}: {:?}",
+                    v.addr,
+                    info.id,
+                    v.weight
+                )
+            })?;
+            Ok(ValidatorStake {
+                addr: v.addr,
+                net_addr: v.net_addr,
+                stake: TokenAmount::from_atto(weight).to_string(),
+            })
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    Ok(SubnetInfoResponse {
+        info,
+        epoch,
+        validators: status.validators,
+        min_validators: status.min_validators,
+        total_stake: state.total_stake.to_string(),
+        min_validator_stake: state.min_validator_stake.to_string(),
+        validator_stakes,
+        genesis_epoch: state.bottom_up_checkpoint_voting.genesis_epoch,
+        last_checkpoint_epoch: state.bottom_up_checkpoint_voting.last_voting_executed,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use fvm_shared::econ::TokenAmount;
+    use ipc_gateway::Status;
+    use ipc_sdk::subnet_id::SubnetID;
+    use serde_json::json;
+
+    use crate::lotus::message::ipc::{IPCReadSubnetActorStateResponse, SubnetInfo};
+    use crate::server::handlers::manager::subnet_info::{response, ValidatorStake};
+
+    fn state(weight: &str) -> IPCReadSubnetActorStateResponse {
+        serde_json::from_value(json!({
+            "MinValidatorStake": "1000000000000000000",
+            "TotalStake": "3000000000000000000",
+            "BottomUpCheckPeriod": 10,
+            "ValidatorSet": {
+                "validators": [{
+                    "addr": "t1cp4q4lqsdhob23ysywffg2tvbmar5cshia4rweq",
+                    "net_addr": "/dns/host/tcp/1349",
+                    "weight": weight,
+                }],
+                "configuration_number": 1,
+            },
+            "MinValidators": 2,
+            "BottomUpCheckpointVoting": {
+                "GenesisEpoch": 12,
+                "LastVotingExecuted": 40,
+            },
+        }))
+        .unwrap()
+    }
+
+    fn info() -> SubnetInfo {
+        SubnetInfo {
+            id: SubnetID::from_str("/root/t01002").unwrap(),
+            stake: TokenAmount::from_whole(3),
+            circ_supply: TokenAmount::from_whole(1),
+            status: Status::Active,
+        }
+    }
+
+    #[test]
+    fn test_response() {
+        let r = response(info(), 100, state("3000000000000000000")).unwrap();
+        assert_eq!(r.epoch, 100);
+        assert_eq!(r.validators, 1);
+        assert_eq!(r.min_validators, 2);
+        assert_eq!(r.total_stake, TokenAmount::from_whole(3).to_string());
+        assert_eq!(
+            r.validator_stakes,
+            vec![ValidatorStake {
+                addr: String::from("t1cp4q4lqsdhob23ysywffg2tvbmar5cshia4rweq"),
+                net_addr: String::from("/dns/host/tcp/1349"),
+                stake: TokenAmount::from_whole(3).to_string(),
+            }]
+        );
+        assert_eq!(r.genesis_epoch, 12);
+        assert_eq!(r.last_checkpoint_epoch, 40);
+
+        // the info of the gateway is flattened into the response
+        let json = serde_json::to_value(&r).unwrap();
+        assert_eq!(json["id"], "/root/t01002");
+        assert_eq!(json["stake"], "3000000000000000000");
+
+        let err = response(info(), 100, state("three")).unwrap_err();
+        assert!(err.to_string().contains("invalid stake of validator"));
+        assert!(err.to_string().contains("/root/t01002"));
+    }
+}
@@ -6,12 +6,12 @@ use std::str::FromStr;
 use std::sync::Arc;
 
 use crate::manager::SubnetManager;
-use anyhow::anyhow;
 use async_trait::async_trait;
 use fvm_shared::clock::ChainEpoch;
 use ipc_sdk::subnet_id::SubnetID;
 use serde::{Deserialize, Serialize};
 
+use crate::server::error::HandlerError;
 use crate::server::handlers::manager::check_subnet;
 use crate::server::handlers::manager::subnet::SubnetManagerPool;
 use crate::server::JsonRPCRequestHandler;
@@ -40,9 +40,10 @@ impl JsonRPCRequestHandler for LastTopDownExecHandler {
     async fn handle(&self, request: Self::Request) -> anyhow::Result<Self::Response> {
         let child_subnet_id = SubnetID::from_str(request.subnet_id.as_str())?;
         let conn = match self.pool.get(&child_subnet_id) {
-            None => return Err(anyhow!("target subnet not found")),
+            None => return Err(HandlerError::SubnetNotFound(child_subnet_id.to_string()).into()),
             Some(conn) => conn,
         };
+        conn.check_staleness().await;
 
         let subnet_config = conn.subnet();
         check_subnet(subnet_config)?;
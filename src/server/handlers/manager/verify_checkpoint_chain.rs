@@ -0,0 +1,202 @@
+// Copyright 2022-2023 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Verify the chain of bottom-up checkpoints committed by a subnet
+
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+use cid::Cid;
+use fil_actors_runtime::cbor;
+use fvm_shared::clock::ChainEpoch;
+use ipc_gateway::BottomUpCheckpoint;
+use ipc_sdk::subnet_id::SubnetID;
+use primitives::TCid;
+use serde::{Deserialize, Serialize};
+
+use crate::manager::SubnetManager;
+use crate::serialization::car::dag_cbor_cid;
+use crate::server::error::HandlerError;
+use crate::server::handlers::manager::check_subnet;
+use crate::server::handlers::manager::subnet::SubnetManagerPool;
+use crate::server::JsonRPCRequestHandler;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VerifyCheckpointChainParams {
+    pub subnet_id: String,
+    pub from_epoch: ChainEpoch,
+    pub to_epoch: ChainEpoch,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VerifyCheckpointChainResponse {
+    /// The number of checkpoints verified
+    pub checkpoints: usize,
+    /// The broken links found in the chain, empty if it is valid
+    pub errors: Vec<String>,
+}
+
+/// The verify checkpoint chain json rpc method handler.
+pub(crate) struct VerifyCheckpointChainHandler {
+    pool: Arc<SubnetManagerPool>,
+}
+
+impl VerifyCheckpointChainHandler {
+    pub(crate) fn new(pool: Arc<SubnetManagerPool>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl JsonRPCRequestHandler for VerifyCheckpointChainHandler {
+    type Request = VerifyCheckpointChainParams;
+    type Response = VerifyCheckpointChainResponse;
+
+    async fn handle(&self, request: Self::Request) -> anyhow::Result<Self::Response> {
+        let child_subnet_id = SubnetID::from_str(request.subnet_id.as_str())?;
+        let parent_subnet_id = child_subnet_id
+            .parent()
+            .ok_or_else(|| anyhow!("subnet id does not have a parent"))?;
+
+        let conn = match self.pool.get(&parent_subnet_id) {
+            None => return Err(HandlerError::ParentNotFound(parent_subnet_id.to_string()).into()),
+            Some(conn) => conn,
+        };
+
+        let subnet_config = conn.subnet();
+        check_subnet(subnet_config)?;
+
+        let mut checkpoints = conn
+            .manager()
+            .list_checkpoints(
+                child_subnet_id.clone(),
+                request.from_epoch,
+                request.to_epoch,
+            )
+            .await?;
+        checkpoints.sort_by_key(|c| c.data.epoch);
+
+        // the prev-links are checked against the checkpoints the parent committed at the
+        // claimed epochs, not only against the ones listed
+        let (_, period) = conn
+            .manager()
+            .bottom_up_checkpoint_schedule(&child_subnet_id)
+            .await?;
+        let mut committed = HashMap::new();
+        for checkpoint in checkpoints.iter().filter(|c| has_prev(c)) {
+            let prev_epoch = checkpoint.data.epoch - period;
+            if committed.contains_key(&prev_epoch) {
+                continue;
+            }
+            // a checkpoint the node reports missing is a broken link, other errors mean the
+            // chain cannot be verified
+            let prev = conn
+                .manager()
+                .checkpoint_at(&child_subnet_id, prev_epoch)
+                .await
+                .map_err(HandlerError::upstream)?;
+            if let Some(prev) = prev {
+                committed.insert(prev_epoch, checkpoint_cid(&prev)?);
+            }
+        }
+
+        let errors = verify_prev_links(&checkpoints, &committed, period)?;
+        Ok(VerifyCheckpointChainResponse {
+            checkpoints: checkpoints.len(),
+            errors,
+        })
+    }
+}
+
+fn has_prev(checkpoint: &BottomUpCheckpoint) -> bool {
+    checkpoint.data.prev_check != TCid::default()
+}
+
+/// Returns the CID of the checkpoint as committed in the parent.
+fn checkpoint_cid(checkpoint: &BottomUpCheckpoint) -> anyhow::Result<Cid> {
+    dag_cbor_cid(cbor::serialize(checkpoint, "checkpoint")?.bytes())
+}
+
+/// Checks that the prev-link of each checkpoint in `checkpoints`, sorted by epoch, points to the
+/// checkpoint committed one `period` earlier, whose CIDs are in `committed` by epoch. Returns a
+/// description of each broken link.
+fn verify_prev_links(
+    checkpoints: &[BottomUpCheckpoint],
+    committed: &HashMap<ChainEpoch, Cid>,
+    period: ChainEpoch,
+) -> anyhow::Result<Vec<String>> {
+    let mut errors = vec![];
+    for (i, checkpoint) in checkpoints.iter().enumerate() {
+        let epoch = checkpoint.data.epoch;
+        if !has_prev(checkpoint) {
+            // only the first checkpoint of the subnet has no previous one
+            if i > 0 {
+                errors.push(format!("checkpoint at epoch {epoch:} has no prev-link"));
+            }
+            continue;
+        }
+
+        let prev = checkpoint.data.prev_check.cid();
+        let prev_epoch = epoch - period;
+        match committed.get(&prev_epoch) {
+            None => errors.push(format!(
+                "checkpoint at epoch {epoch:} links to {prev:}, but no checkpoint was committed at epoch {prev_epoch:}"
+            )),
+            Some(cid) if *cid != prev => errors.push(format!(
+                "checkpoint at epoch {epoch:} links to {prev:}, but the checkpoint committed at epoch {prev_epoch:} is {cid:}"
+            )),
+            Some(_) => {}
+        }
+
+        // the listed checkpoints must agree with what the parent committed
+        if let Some(listed) = checkpoints[..i].iter().find(|c| c.data.epoch == prev_epoch) {
+            if checkpoint_cid(listed)? != prev {
+                errors.push(format!(
+                    "checkpoint at epoch {epoch:} does not link to the listed checkpoint at epoch {prev_epoch:}"
+                ));
+            }
+        }
+    }
+    Ok(errors)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use ipc_gateway::BottomUpCheckpoint;
+    use ipc_sdk::subnet_id::ROOTNET_ID;
+    use primitives::TCid;
+
+    use crate::server::handlers::manager::verify_checkpoint_chain::{
+        checkpoint_cid, verify_prev_links,
+    };
+
+    #[test]
+    fn test_verify_prev_links() {
+        let genesis = BottomUpCheckpoint::new(ROOTNET_ID.clone(), 10);
+        let mut second = BottomUpCheckpoint::new(ROOTNET_ID.clone(), 20);
+        second.data.prev_check = TCid::from(checkpoint_cid(&genesis).unwrap());
+        let chain = vec![genesis.clone(), second.clone()];
+
+        let committed = HashMap::from([(10, checkpoint_cid(&genesis).unwrap())]);
+        assert!(verify_prev_links(&chain, &committed, 10)
+            .unwrap()
+            .is_empty());
+
+        // the link is consistent with the listed chain, but not with what the parent committed
+        let forged = BottomUpCheckpoint::new(ROOTNET_ID.clone(), 11);
+        let committed = HashMap::from([(10, checkpoint_cid(&forged).unwrap())]);
+        assert_eq!(verify_prev_links(&chain, &committed, 10).unwrap().len(), 1);
+
+        // nothing committed at the claimed epoch
+        assert_eq!(
+            verify_prev_links(&[second], &HashMap::new(), 10)
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+}
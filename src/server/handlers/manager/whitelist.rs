@@ -3,9 +3,9 @@
 //! WhitelistPropagator operation in the gateway actor
 
 use crate::manager::SubnetManager;
+use crate::server::error::HandlerError;
 use crate::server::handlers::manager::subnet::SubnetManagerPool;
 use crate::server::{check_subnet, parse_from, JsonRPCRequestHandler};
-use anyhow::anyhow;
 use async_trait::async_trait;
 use cid::Cid;
 use fvm_shared::address::Address;
@@ -41,7 +41,7 @@ impl JsonRPCRequestHandler for WhitelistPropagatorHandler {
     async fn handle(&self, request: Self::Request) -> anyhow::Result<Self::Response> {
         let subnet = SubnetID::from_str(&request.subnet)?;
         let conn = match self.pool.get(&subnet) {
-            None => return Err(anyhow!("target subnet not found")),
+            None => return Err(HandlerError::SubnetNotFound(subnet.to_string()).into()),
             Some(conn) => conn,
         };
 
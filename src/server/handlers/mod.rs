@@ -5,7 +5,7 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 
-use anyhow::{anyhow, Result};
+use anyhow::Result;
 use async_trait::async_trait;
 use serde_json::Value;
 
@@ -19,32 +19,56 @@ pub use manager::*;
 
 use crate::config::json_rpc_methods;
 use crate::config::ReloadableConfig;
+use crate::jsonrpc::HttpClients;
+use crate::server::error::HandlerError;
+use crate::server::export_checkpoint::ExportBottomUpCheckpointHandler;
 use crate::server::handlers::config::ReloadConfigHandler;
+use crate::server::handlers::health::HealthHandler;
+use crate::server::handlers::manager::crossmsg_audit::CrossMsgAuditHandler;
+use crate::server::handlers::manager::crossmsg_backlog::CrossMsgBacklogHandler;
+use crate::server::handlers::manager::crossmsg_list::CrossMsgListHandler;
+use crate::server::handlers::manager::crossmsg_route::CrossMsgRouteHandler;
 use crate::server::handlers::manager::fund::FundHandler;
 use crate::server::handlers::manager::list_subnets::ListSubnetsHandler;
 use crate::server::handlers::manager::propagate::PropagateHandler;
 use crate::server::handlers::manager::release::ReleaseHandler;
+use crate::server::handlers::manager::subnet_info::SubnetInfoHandler;
 use crate::server::handlers::manager::whitelist::WhitelistPropagatorHandler;
 use crate::server::handlers::send_value::SendValueHandler;
-use crate::server::handlers::validator::QueryValidatorSetHandler;
+use crate::server::handlers::validator::{
+    QueryActivationStatusHandler, QueryValidatorChangesHandler, QueryValidatorSetHandler,
+};
+use crate::server::handlers::wallet::address::AddressResolver;
+use crate::server::handlers::wallet::balance::WalletBalanceHandler;
+use crate::server::handlers::wallet::import::WalletImportHandler;
 use crate::server::handlers::wallet::list::WalletListHandler;
 use crate::server::handlers::wallet::new::WalletNewHandler;
+use crate::server::limiter::{
+    RequestLimiter, DEFAULT_MAX_CONCURRENT_REQUESTS, DEFAULT_MAX_QUEUED_REQUESTS,
+};
 use crate::server::list_checkpoints::ListBottomUpCheckpointsHandler;
 use crate::server::net_addr::SetValidatorNetAddrHandler;
+use crate::server::verify_checkpoint_chain::VerifyCheckpointChainHandler;
 use crate::server::JsonRPCRequestHandler;
 
 use self::topdown_executed::LastTopDownExecHandler;
 
 mod config;
+mod health;
 mod manager;
-mod validator;
+pub mod validator;
 pub mod wallet;
 
 pub type Method = String;
 
+/// The methods handled without waiting for a slot of the [`RequestLimiter`]: they are cheap and
+/// must still answer while the slots are taken by long-running requests.
+const UNLIMITED_METHODS: [&str; 2] = [json_rpc_methods::HEALTH, json_rpc_methods::RELOAD_CONFIG];
+
 /// The collection of all json rpc handlers
 pub struct Handlers {
     handlers: HashMap<Method, Box<dyn HandlerWrapper>>,
+    limiter: RequestLimiter,
 }
 
 /// A util trait to avoid Box<dyn> and associated type mess in Handlers struct
@@ -56,7 +80,7 @@ trait HandlerWrapper: Send + Sync {
 #[async_trait]
 impl<H: JsonRPCRequestHandler + Send + Sync> HandlerWrapper for H {
     async fn handle(&self, params: Value) -> Result<Value> {
-        let p = serde_json::from_value(params)?;
+        let p = serde_json::from_value(params).map_err(HandlerError::invalid_params)?;
         let r = self.handle(p).await?;
         Ok(serde_json::to_value(r)?)
     }
@@ -69,17 +93,38 @@ impl Handlers {
     pub fn empty_handlers() -> Self {
         Self {
             handlers: HashMap::new(),
+            limiter: RequestLimiter::default(),
         }
     }
 
     pub fn new(config: Arc<ReloadableConfig>) -> Result<Self> {
         let mut handlers = HashMap::new();
 
+        let server = config.get_config().server.clone();
+        let limiter = RequestLimiter::new(
+            server
+                .max_concurrent_requests
+                .unwrap_or(DEFAULT_MAX_CONCURRENT_REQUESTS),
+            server
+                .max_queued_requests
+                .unwrap_or(DEFAULT_MAX_QUEUED_REQUESTS),
+        );
+
         let h: Box<dyn HandlerWrapper> = Box::new(ReloadConfigHandler::new(config.clone()));
         handlers.insert(String::from(json_rpc_methods::RELOAD_CONFIG), h);
 
+        // the http clients shared by the connections to the same subnet nodes
+        let http_clients = Arc::new(HttpClients::default());
+
+        let h: Box<dyn HandlerWrapper> =
+            Box::new(HealthHandler::new(config.clone(), http_clients.clone()));
+        handlers.insert(String::from(json_rpc_methods::HEALTH), h);
+
         // subnet manager methods
-        let pool = Arc::new(SubnetManagerPool::from_reload_config(config.clone()));
+        let pool = Arc::new(SubnetManagerPool::from_reload_config(
+            config.clone(),
+            http_clients.clone(),
+        ));
         let h: Box<dyn HandlerWrapper> = Box::new(CreateSubnetHandler::new(pool.clone()));
         handlers.insert(String::from(json_rpc_methods::CREATE_SUBNET), h);
 
@@ -107,12 +152,20 @@ impl Handlers {
         let h: Box<dyn HandlerWrapper> = Box::new(SendValueHandler::new(pool.clone()));
         handlers.insert(String::from(json_rpc_methods::SEND_VALUE), h);
 
-        let h: Box<dyn HandlerWrapper> = Box::new(WalletNewHandler::new(pool.clone()));
+        let resolver = Arc::new(AddressResolver::default());
+        let h: Box<dyn HandlerWrapper> =
+            Box::new(WalletNewHandler::new(pool.clone(), resolver.clone()));
         handlers.insert(String::from(json_rpc_methods::WALLET_NEW), h);
 
-        let h: Box<dyn HandlerWrapper> = Box::new(WalletListHandler::new(pool.clone()));
+        let h: Box<dyn HandlerWrapper> = Box::new(WalletListHandler::new(pool.clone(), resolver));
         handlers.insert(String::from(json_rpc_methods::WALLET_LIST), h);
 
+        let h: Box<dyn HandlerWrapper> = Box::new(WalletBalanceHandler::new(pool.clone()));
+        handlers.insert(String::from(json_rpc_methods::WALLET_BALANCE), h);
+
+        let h: Box<dyn HandlerWrapper> = Box::new(WalletImportHandler::new(pool.clone()));
+        handlers.insert(String::from(json_rpc_methods::WALLET_IMPORT), h);
+
         let h: Box<dyn HandlerWrapper> = Box::new(SetValidatorNetAddrHandler::new(pool.clone()));
         handlers.insert(String::from(json_rpc_methods::SET_VALIDATOR_NET_ADDR), h);
 
@@ -123,21 +176,85 @@ impl Handlers {
             Box::new(ListBottomUpCheckpointsHandler::new(pool.clone()));
         handlers.insert(String::from(json_rpc_methods::LIST_BOTTOMUP_CHECKPOINTS), h);
 
+        let h: Box<dyn HandlerWrapper> =
+            Box::new(ExportBottomUpCheckpointHandler::new(pool.clone()));
+        handlers.insert(
+            String::from(json_rpc_methods::EXPORT_BOTTOMUP_CHECKPOINT),
+            h,
+        );
+
+        let h: Box<dyn HandlerWrapper> = Box::new(VerifyCheckpointChainHandler::new(pool.clone()));
+        handlers.insert(
+            String::from(json_rpc_methods::VERIFY_BOTTOMUP_CHECKPOINT_CHAIN),
+            h,
+        );
+
         let h: Box<dyn HandlerWrapper> = Box::new(LastTopDownExecHandler::new(pool));
         handlers.insert(String::from(json_rpc_methods::LAST_TOPDOWN_EXECUTED), h);
 
+        let h: Box<dyn HandlerWrapper> = Box::new(CrossMsgAuditHandler::new(
+            config.clone(),
+            http_clients.clone(),
+        ));
+        handlers.insert(String::from(json_rpc_methods::CROSS_MSG_AUDIT), h);
+
+        let h: Box<dyn HandlerWrapper> = Box::new(CrossMsgRouteHandler::new(
+            config.clone(),
+            http_clients.clone(),
+        ));
+        handlers.insert(String::from(json_rpc_methods::CROSS_MSG_ROUTE), h);
+
+        let h: Box<dyn HandlerWrapper> = Box::new(CrossMsgBacklogHandler::new(
+            config.clone(),
+            http_clients.clone(),
+        ));
+        handlers.insert(String::from(json_rpc_methods::CROSS_MSG_BACKLOG), h);
+
+        let h: Box<dyn HandlerWrapper> = Box::new(CrossMsgListHandler::new(
+            config.clone(),
+            http_clients.clone(),
+        ));
+        handlers.insert(String::from(json_rpc_methods::CROSS_MSG_LIST), h);
+
+        let h: Box<dyn HandlerWrapper> =
+            Box::new(SubnetInfoHandler::new(config.clone(), http_clients.clone()));
+        handlers.insert(String::from(json_rpc_methods::GET_SUBNET_INFO), h);
+
         // query validator
-        let h: Box<dyn HandlerWrapper> = Box::new(QueryValidatorSetHandler::new(config));
+        let h: Box<dyn HandlerWrapper> = Box::new(QueryValidatorSetHandler::new(
+            config.clone(),
+            http_clients.clone(),
+        ));
         handlers.insert(String::from(json_rpc_methods::QUERY_VALIDATOR_SET), h);
 
-        Ok(Self { handlers })
+        let h: Box<dyn HandlerWrapper> = Box::new(QueryActivationStatusHandler::new(
+            config.clone(),
+            http_clients.clone(),
+        ));
+        handlers.insert(String::from(json_rpc_methods::QUERY_ACTIVATION_STATUS), h);
+
+        let h: Box<dyn HandlerWrapper> =
+            Box::new(QueryValidatorChangesHandler::new(config, http_clients));
+        handlers.insert(String::from(json_rpc_methods::QUERY_VALIDATOR_CHANGES), h);
+
+        Ok(Self { handlers, limiter })
+    }
+
+    /// Returns whether there is a handler for `method`.
+    pub fn contains(&self, method: &str) -> bool {
+        self.handlers.contains_key(method)
     }
 
     pub async fn handle(&self, method: Method, params: Value) -> Result<Value> {
         if let Some(wrapper) = self.handlers.get(&method) {
+            let _permit = if UNLIMITED_METHODS.contains(&method.as_str()) {
+                None
+            } else {
+                Some(self.limiter.acquire().await?)
+            };
             wrapper.handle(params).await
         } else {
-            Err(anyhow!("method not supported"))
+            Err(HandlerError::MethodNotFound(method).into())
         }
     }
 }
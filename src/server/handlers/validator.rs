@@ -3,21 +3,28 @@
 //! Expose the subnet actor validator set
 
 use crate::config::ReloadableConfig;
+use crate::jsonrpc::HttpClients;
 use crate::lotus::client::LotusJsonRPCClient;
-use crate::lotus::message::ipc::ValidatorSet;
+use crate::lotus::message::ipc::{ActivationStatus, Validator, ValidatorSet};
 use crate::lotus::LotusClient;
+use crate::server::error::HandlerError;
 use crate::server::JsonRPCRequestHandler;
 use anyhow::anyhow;
 use async_trait::async_trait;
 use cid::Cid;
+use fvm_shared::clock::ChainEpoch;
 use ipc_sdk::subnet_id::SubnetID;
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::str::FromStr;
 use std::sync::Arc;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct QueryValidatorSetParams {
     pub subnet: String,
+    /// The epoch to read the validator set at, defaults to the current chain head. Use it to
+    /// obtain the validator set a past checkpoint was signed with.
+    pub epoch: Option<ChainEpoch>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -33,11 +40,15 @@ pub struct QueryValidatorSetResponse {
 /// The create subnet json rpc method handler.
 pub(crate) struct QueryValidatorSetHandler {
     config: Arc<ReloadableConfig>,
+    http_clients: Arc<HttpClients>,
 }
 
 impl QueryValidatorSetHandler {
-    pub(crate) fn new(config: Arc<ReloadableConfig>) -> Self {
-        Self { config }
+    pub(crate) fn new(config: Arc<ReloadableConfig>, http_clients: Arc<HttpClients>) -> Self {
+        Self {
+            config,
+            http_clients,
+        }
     }
 }
 
@@ -54,18 +65,34 @@ impl JsonRPCRequestHandler for QueryValidatorSetHandler {
 
         let config = self.config.get_config();
         let subnet = match config.subnets.get(&parent) {
-            None => return Err(anyhow!("target parent subnet not found")),
+            None => return Err(HandlerError::ParentNotFound(parent.to_string()).into()),
             Some(s) => s,
         };
 
-        let lotus = LotusJsonRPCClient::from_subnet(subnet);
+        let lotus = LotusJsonRPCClient::from_subnet_with_clients(subnet, &self.http_clients);
 
         // Read the parent's chain head and obtain the tip set CID.
         // FIXME: This is used all over the place, make it a more
         // compact function
         let parent_head = lotus.chain_head().await?;
         let cid_map = parent_head.cids.first().unwrap().clone();
-        let tip_set = Cid::try_from(cid_map)?;
+        let head_tip_set = Cid::try_from(cid_map)?;
+
+        let head_epoch = ChainEpoch::try_from(parent_head.height)?;
+        let tip_set = match validator_set_epoch(request.epoch, head_epoch)? {
+            None => head_tip_set,
+            Some(epoch) => {
+                let tip_set = lotus.get_tipset_by_height(epoch, head_tip_set).await?;
+                Cid::try_from(tip_set.cids.first().unwrap().clone())?
+            }
+        };
+
+        // Check the subnet actor is deployed, as reading the state of a missing actor fails
+        // with an opaque deserialization error.
+        lotus
+            .state_get_actor(subnet_id.subnet_actor(), tip_set)
+            .await
+            .map_err(|e| anyhow!("subnet actor of {subnet_id:} not found: {e:}"))?;
 
         let response = lotus
             .ipc_read_subnet_actor_state(&subnet_id, tip_set)
@@ -82,3 +109,290 @@ impl JsonRPCRequestHandler for QueryValidatorSetHandler {
         })
     }
 }
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct QueryActivationStatusParams {
+    pub subnet: String,
+}
+
+/// The query activation status json rpc method handler. Reads from the parent whether the
+/// subnet has collected enough validators to be active.
+pub(crate) struct QueryActivationStatusHandler {
+    config: Arc<ReloadableConfig>,
+    http_clients: Arc<HttpClients>,
+}
+
+impl QueryActivationStatusHandler {
+    pub(crate) fn new(config: Arc<ReloadableConfig>, http_clients: Arc<HttpClients>) -> Self {
+        Self {
+            config,
+            http_clients,
+        }
+    }
+}
+
+#[async_trait]
+impl JsonRPCRequestHandler for QueryActivationStatusHandler {
+    type Request = QueryActivationStatusParams;
+    type Response = ActivationStatus;
+
+    async fn handle(&self, request: Self::Request) -> anyhow::Result<Self::Response> {
+        let subnet_id = SubnetID::from_str(&request.subnet)?;
+        let parent = subnet_id
+            .parent()
+            .ok_or_else(|| anyhow!("cannot get for root"))?;
+
+        let config = self.config.get_config();
+        let subnet = match config.subnets.get(&parent) {
+            None => return Err(HandlerError::ParentNotFound(parent.to_string()).into()),
+            Some(s) => s,
+        };
+
+        let lotus = LotusJsonRPCClient::from_subnet_with_clients(subnet, &self.http_clients);
+
+        let parent_head = lotus.chain_head().await?;
+        let tip_set = Cid::try_from(parent_head.cids.first().unwrap().clone())?;
+
+        lotus.ipc_activation_status(&subnet_id, tip_set).await
+    }
+}
+
+/// The maximum number of epochs the validator set is read at in a single changes query.
+const MAX_VALIDATOR_SET_SAMPLES: usize = 100;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct QueryValidatorChangesParams {
+    pub subnet: String,
+    pub from_epoch: ChainEpoch,
+    pub to_epoch: ChainEpoch,
+    /// The number of epochs between consecutive reads of the validator set. Defaults to
+    /// comparing the validator sets at `from_epoch` and `to_epoch` only.
+    pub step: Option<ChainEpoch>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct QueryValidatorChangesResponse {
+    /// The changes of the validator set between the sampled epochs, in epoch order.
+    pub changes: Vec<ValidatorSetChange>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ValidatorSetChange {
+    /// The epoch at which the change was observed.
+    pub epoch: ChainEpoch,
+    pub configuration_number: u64,
+    /// The validators that joined since the previous sampled epoch.
+    pub added: Vec<Validator>,
+    /// The validators that left since the previous sampled epoch.
+    pub removed: Vec<Validator>,
+}
+
+/// The query validator changes json rpc method handler. Reads the validator set of the subnet
+/// at several epochs of the parent and returns the joins and leaves between them.
+pub(crate) struct QueryValidatorChangesHandler {
+    config: Arc<ReloadableConfig>,
+    http_clients: Arc<HttpClients>,
+}
+
+impl QueryValidatorChangesHandler {
+    pub(crate) fn new(config: Arc<ReloadableConfig>, http_clients: Arc<HttpClients>) -> Self {
+        Self {
+            config,
+            http_clients,
+        }
+    }
+}
+
+#[async_trait]
+impl JsonRPCRequestHandler for QueryValidatorChangesHandler {
+    type Request = QueryValidatorChangesParams;
+    type Response = QueryValidatorChangesResponse;
+
+    async fn handle(&self, request: Self::Request) -> anyhow::Result<Self::Response> {
+        let subnet_id = SubnetID::from_str(&request.subnet)?;
+        let parent = subnet_id
+            .parent()
+            .ok_or_else(|| anyhow!("cannot get for root"))?;
+
+        let config = self.config.get_config();
+        let subnet = match config.subnets.get(&parent) {
+            None => return Err(HandlerError::ParentNotFound(parent.to_string()).into()),
+            Some(s) => s,
+        };
+
+        let lotus = LotusJsonRPCClient::from_subnet_with_clients(subnet, &self.http_clients);
+
+        let parent_head = lotus.chain_head().await?;
+        let head_tip_set = Cid::try_from(parent_head.cids.first().unwrap().clone())?;
+        let head_epoch = ChainEpoch::try_from(parent_head.height)?;
+
+        let epochs = sample_epochs(request.from_epoch, request.to_epoch, request.step)?;
+
+        let mut changes = vec![];
+        let mut previous: Option<ValidatorSet> = None;
+        for epoch in epochs {
+            let tip_set = match validator_set_epoch(Some(epoch), head_epoch)? {
+                None => head_tip_set,
+                Some(epoch) => {
+                    let tip_set = lotus.get_tipset_by_height(epoch, head_tip_set).await?;
+                    Cid::try_from(tip_set.cids.first().unwrap().clone())?
+                }
+            };
+            let validator_set = lotus
+                .ipc_read_subnet_actor_state(&subnet_id, tip_set)
+                .await?
+                .validator_set;
+
+            if let Some(previous) = &previous {
+                let (added, removed) = validator_set_diff(previous, &validator_set);
+                if !added.is_empty() || !removed.is_empty() {
+                    changes.push(ValidatorSetChange {
+                        epoch,
+                        configuration_number: validator_set.configuration_number,
+                        added,
+                        removed,
+                    });
+                }
+            }
+            previous = Some(validator_set);
+        }
+
+        Ok(QueryValidatorChangesResponse { changes })
+    }
+}
+
+/// Returns the epochs in `[from, to]` at which the validator set is read, `step` epochs apart.
+/// The last epoch is always `to`.
+fn sample_epochs(
+    from: ChainEpoch,
+    to: ChainEpoch,
+    step: Option<ChainEpoch>,
+) -> anyhow::Result<Vec<ChainEpoch>> {
+    if from > to {
+        return Err(anyhow!("from epoch {from:} is after to epoch {to:}"));
+    }
+    let step = step.unwrap_or(to - from).max(1);
+
+    let mut epochs = vec![];
+    let mut epoch = from;
+    while epoch < to {
+        epochs.push(epoch);
+        epoch += step;
+    }
+    epochs.push(to);
+
+    if epochs.len() > MAX_VALIDATOR_SET_SAMPLES {
+        return Err(anyhow!(
+            "too many epochs to read: {:}, max {MAX_VALIDATOR_SET_SAMPLES:}, increase the step",
+            epochs.len()
+        ));
+    }
+    Ok(epochs)
+}
+
+/// Returns the validators that joined and left between the `previous` and `next` validator
+/// sets. Validators are identified by their address.
+fn validator_set_diff(
+    previous: &ValidatorSet,
+    next: &ValidatorSet,
+) -> (Vec<Validator>, Vec<Validator>) {
+    let previous = previous.validators.as_deref().unwrap_or_default();
+    let next = next.validators.as_deref().unwrap_or_default();
+
+    let previous_addrs = previous.iter().map(|v| &v.addr).collect::<HashSet<_>>();
+    let next_addrs = next.iter().map(|v| &v.addr).collect::<HashSet<_>>();
+
+    let added = next
+        .iter()
+        .filter(|v| !previous_addrs.contains(&v.addr))
+        .cloned()
+        .collect();
+    let removed = previous
+        .iter()
+        .filter(|v| !next_addrs.contains(&v.addr))
+        .cloned()
+        .collect();
+    (added, removed)
+}
+
+/// Returns the epoch, other than the head, at which the validator set is to be read. Fails
+/// for epochs ahead of the chain head, for which there is no validator set yet.
+fn validator_set_epoch(
+    requested: Option<ChainEpoch>,
+    head: ChainEpoch,
+) -> anyhow::Result<Option<ChainEpoch>> {
+    match requested {
+        Some(epoch) if epoch > head => Err(anyhow!(
+            "epoch {epoch:} is ahead of the chain head at {head:}"
+        )),
+        Some(epoch) if epoch < head => Ok(Some(epoch)),
+        _ => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::lotus::message::ipc::{Validator, ValidatorSet};
+    use crate::server::handlers::validator::{
+        sample_epochs, validator_set_diff, validator_set_epoch,
+    };
+
+    fn validator(addr: &str) -> Validator {
+        Validator {
+            addr: addr.to_string(),
+            net_addr: format!("/ip4/127.0.0.1/tcp/1347/{addr}"),
+            weight: "1000".to_string(),
+        }
+    }
+
+    fn validator_set(addrs: &[&str], configuration_number: u64) -> ValidatorSet {
+        ValidatorSet {
+            validators: Some(addrs.iter().map(|a| validator(a)).collect()),
+            configuration_number,
+        }
+    }
+
+    #[test]
+    fn test_validator_set_diff() {
+        // at the first epoch t01000 and t01001 are validating, by the second one t01001 has
+        // left and t01002 has joined
+        let first = validator_set(&["t01000", "t01001"], 2);
+        let second = validator_set(&["t01000", "t01002"], 4);
+
+        let (added, removed) = validator_set_diff(&first, &second);
+        assert_eq!(added, vec![validator("t01002")]);
+        assert_eq!(removed, vec![validator("t01001")]);
+
+        let (added, removed) = validator_set_diff(&second, &second);
+        assert!(added.is_empty() && removed.is_empty());
+
+        let empty = ValidatorSet {
+            validators: None,
+            configuration_number: 0,
+        };
+        let (added, removed) = validator_set_diff(&empty, &first);
+        assert_eq!(added.len(), 2);
+        assert!(removed.is_empty());
+    }
+
+    #[test]
+    fn test_sample_epochs() {
+        assert_eq!(sample_epochs(10, 20, None).unwrap(), vec![10, 20]);
+        assert_eq!(
+            sample_epochs(10, 20, Some(4)).unwrap(),
+            vec![10, 14, 18, 20]
+        );
+        assert_eq!(sample_epochs(10, 10, None).unwrap(), vec![10]);
+        assert!(sample_epochs(20, 10, None).is_err());
+        assert!(sample_epochs(0, 1000, Some(1)).is_err());
+    }
+
+    #[test]
+    fn test_validator_set_epoch() {
+        // the validator set of a past checkpoint is read at the checkpoint epoch
+        assert_eq!(validator_set_epoch(Some(40), 100).unwrap(), Some(40));
+        assert_eq!(validator_set_epoch(Some(100), 100).unwrap(), None);
+        assert_eq!(validator_set_epoch(None, 100).unwrap(), None);
+        assert!(validator_set_epoch(Some(101), 100).is_err());
+    }
+}
@@ -0,0 +1,131 @@
+// Copyright 2022-2023 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Address format resolution shared by the wallet handlers
+
+use crate::lotus::message::wallet::AddressFormat;
+use crate::manager::SubnetManager;
+use fvm_shared::address::Address;
+use ipc_sdk::subnet_id::SubnetID;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::RwLock;
+
+type CacheKey = (SubnetID, Address, AddressFormat);
+
+/// Converts addresses returned by the node into the format requested by the user, caching
+/// the results of the lookups performed against the subnet.
+#[derive(Default)]
+pub(crate) struct AddressResolver {
+    cache: RwLock<HashMap<CacheKey, Address>>,
+}
+
+impl AddressResolver {
+    /// Parses the optional address format passed in the request params.
+    pub fn parse_format(format: &Option<String>) -> anyhow::Result<Option<AddressFormat>> {
+        format
+            .as_ref()
+            .map(|f| AddressFormat::from_str(f))
+            .transpose()
+            .map_err(|e| anyhow::anyhow!("invalid address format: {e}"))
+    }
+
+    /// Returns `address` in the requested `format`. If no format is requested the address is
+    /// returned as is. Fails if the address cannot be resolved, for instance because the account
+    /// does not exist on chain yet.
+    pub async fn resolve<M: SubnetManager + Sync>(
+        &self,
+        manager: &M,
+        subnet: &SubnetID,
+        address: Address,
+        format: Option<AddressFormat>,
+    ) -> anyhow::Result<Address> {
+        let format = match format {
+            None => return Ok(address),
+            Some(f) => f,
+        };
+
+        let key = (subnet.clone(), address, format);
+        let cached = self.cache.read().unwrap().get(&key).cloned();
+        if let Some(resolved) = cached {
+            return Ok(resolved);
+        }
+
+        let resolved = manager
+            .resolve_address(&address, format)
+            .await
+            .map_err(|e| {
+                anyhow::anyhow!("cannot resolve {address:} into {format:} address: {e:}")
+            })?;
+        self.cache.write().unwrap().insert(key, resolved);
+        Ok(resolved)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use anyhow::anyhow;
+    use fvm_shared::address::Address;
+    use ipc_sdk::subnet_id::SubnetID;
+    use serde_json::json;
+
+    use crate::jsonrpc::mock::MockJsonRpcClient;
+    use crate::lotus::client::LotusJsonRPCClient;
+    use crate::lotus::message::wallet::AddressFormat;
+    use crate::manager::LotusSubnetManager;
+    use crate::server::handlers::wallet::address::AddressResolver;
+
+    const KEY: &str = "t1cp4q4lqsdhob23ysywffg2tvbmar5cshia4rweq";
+
+    #[tokio::test]
+    async fn test_resolve() {
+        let node = MockJsonRpcClient::default()
+            .on("Filecoin.StateLookupID", |params| {
+                if params[0] != KEY {
+                    return Err(anyhow!("resolution lookup failed"));
+                }
+                Ok(json!("t01001"))
+            })
+            .with_result("Filecoin.StateAccountKey", json!(KEY));
+        let manager = LotusSubnetManager::new(LotusJsonRPCClient::new(node.clone()));
+        let resolver = AddressResolver::default();
+        let subnet = SubnetID::from_str("/root").unwrap();
+        let key = Address::from_str(KEY).unwrap();
+        let id = Address::new_id(1001);
+
+        let resolved = resolver
+            .resolve(&manager, &subnet, key, Some(AddressFormat::Id))
+            .await
+            .unwrap();
+        assert_eq!(resolved, id);
+        let resolved = resolver
+            .resolve(&manager, &subnet, id, Some(AddressFormat::Robust))
+            .await
+            .unwrap();
+        assert_eq!(resolved, key);
+
+        // the lookups are cached, and addresses already in the format are not looked up
+        resolver
+            .resolve(&manager, &subnet, key, Some(AddressFormat::Id))
+            .await
+            .unwrap();
+        resolver
+            .resolve(&manager, &subnet, id, Some(AddressFormat::Id))
+            .await
+            .unwrap();
+        resolver
+            .resolve(&manager, &subnet, key, None)
+            .await
+            .unwrap();
+        assert_eq!(node.requests().len(), 2);
+
+        // an account that does not exist on chain yet
+        let unknown = Address::from_str("t1d2xrzcslx7xlbbylc5c3d5lvandqw4iwl6epxba").unwrap();
+        let err = resolver
+            .resolve(&manager, &subnet, unknown, Some(AddressFormat::Id))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("resolution lookup failed"));
+    }
+}
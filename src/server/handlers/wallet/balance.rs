@@ -0,0 +1,58 @@
+// Copyright 2022-2023 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Wallet balance handler and parameters
+
+use crate::manager::SubnetManager;
+use crate::server::error::HandlerError;
+use crate::server::handlers::manager::subnet::SubnetManagerPool;
+use crate::server::JsonRPCRequestHandler;
+use async_trait::async_trait;
+use fvm_shared::address::Address;
+use ipc_sdk::subnet_id::SubnetID;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use std::sync::Arc;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WalletBalanceParams {
+    pub subnet: String,
+    pub address: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WalletBalanceResponse {
+    /// The balance of the address in attoFIL
+    pub balance: String,
+}
+
+/// Get the balance of an address in a subnet
+pub(crate) struct WalletBalanceHandler {
+    pool: Arc<SubnetManagerPool>,
+}
+
+impl WalletBalanceHandler {
+    pub(crate) fn new(pool: Arc<SubnetManagerPool>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl JsonRPCRequestHandler for WalletBalanceHandler {
+    type Request = WalletBalanceParams;
+    type Response = WalletBalanceResponse;
+
+    async fn handle(&self, request: Self::Request) -> anyhow::Result<Self::Response> {
+        let subnet = SubnetID::from_str(&request.subnet)?;
+        let conn = match self.pool.get(&subnet) {
+            None => return Err(HandlerError::SubnetNotFound(subnet.to_string()).into()),
+            Some(conn) => conn,
+        };
+        conn.check_staleness().await;
+
+        let address = Address::from_str(&request.address)?;
+        let balance = conn.manager().wallet_balance(&address).await?;
+        Ok(WalletBalanceResponse {
+            balance: balance.atto().to_string(),
+        })
+    }
+}
@@ -0,0 +1,67 @@
+// Copyright 2022-2023 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Wallet import handler and parameters
+
+use crate::lotus::message::wallet::KeyInfo;
+use crate::manager::SubnetManager;
+use crate::server::error::HandlerError;
+use crate::server::handlers::manager::subnet::SubnetManagerPool;
+use crate::server::JsonRPCRequestHandler;
+use async_trait::async_trait;
+use ipc_sdk::subnet_id::SubnetID;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Debug, Formatter};
+use std::str::FromStr;
+use std::sync::Arc;
+
+#[derive(Serialize, Deserialize)]
+pub struct WalletImportParams {
+    pub subnet: String,
+    /// The key to import, in the hex encoded format printed by `lotus wallet export`
+    pub key_info: String,
+}
+
+impl Debug for WalletImportParams {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WalletImportParams")
+            .field("subnet", &self.subnet)
+            .field("key_info", &"<redacted>")
+            .finish()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WalletImportResponse {
+    pub address: String,
+}
+
+/// Import an existing private key into the wallet of a subnet node
+pub(crate) struct WalletImportHandler {
+    pool: Arc<SubnetManagerPool>,
+}
+
+impl WalletImportHandler {
+    pub(crate) fn new(pool: Arc<SubnetManagerPool>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl JsonRPCRequestHandler for WalletImportHandler {
+    type Request = WalletImportParams;
+    type Response = WalletImportResponse;
+
+    async fn handle(&self, request: Self::Request) -> anyhow::Result<Self::Response> {
+        let subnet = SubnetID::from_str(&request.subnet)?;
+        let conn = match self.pool.get(&subnet) {
+            None => return Err(HandlerError::SubnetNotFound(subnet.to_string()).into()),
+            Some(conn) => conn,
+        };
+
+        let key_info = KeyInfo::from_lotus_export(&request.key_info)?;
+        let address = conn.manager().wallet_import(&key_info).await?;
+        Ok(WalletImportResponse {
+            address: address.to_string(),
+        })
+    }
+}
@@ -2,9 +2,10 @@ use std::collections::HashMap;
 // Copyright 2022-2023 Protocol Labs
 // SPDX-License-Identifier: MIT
 use crate::manager::SubnetManager;
+use crate::server::error::HandlerError;
 use crate::server::handlers::manager::subnet::SubnetManagerPool;
+use crate::server::handlers::wallet::address::AddressResolver;
 use crate::server::JsonRPCRequestHandler;
-use anyhow::anyhow;
 use async_trait::async_trait;
 use futures_util::future::join_all;
 use fvm_shared::address::Address;
@@ -17,6 +18,8 @@ use std::sync::Arc;
 #[derive(Debug, Serialize, Deserialize)]
 pub struct WalletListParams {
     pub subnet: String,
+    /// The format of the returned addresses (`id` or `robust`), defaults to the node's output
+    pub address_format: Option<String>,
 }
 
 /// Key is the address as string and value is the token amount as string
@@ -25,11 +28,12 @@ pub type WalletListResponse = HashMap<String, String>;
 /// Send value between two addresses within a subnet
 pub(crate) struct WalletListHandler {
     pool: Arc<SubnetManagerPool>,
+    resolver: Arc<AddressResolver>,
 }
 
 impl WalletListHandler {
-    pub(crate) fn new(pool: Arc<SubnetManagerPool>) -> Self {
-        Self { pool }
+    pub(crate) fn new(pool: Arc<SubnetManagerPool>, resolver: Arc<AddressResolver>) -> Self {
+        Self { pool, resolver }
     }
 }
 
@@ -41,10 +45,12 @@ impl JsonRPCRequestHandler for WalletListHandler {
     async fn handle(&self, request: Self::Request) -> anyhow::Result<Self::Response> {
         let subnet = SubnetID::from_str(&request.subnet)?;
         let conn = match self.pool.get(&subnet) {
-            None => return Err(anyhow!("target subnet not found")),
+            None => return Err(HandlerError::SubnetNotFound(subnet.to_string()).into()),
             Some(conn) => conn,
         };
+        conn.check_staleness().await;
 
+        let format = AddressResolver::parse_format(&request.address_format)?;
         let manager = conn.manager();
         let addresses = manager.wallet_list().await?;
 
@@ -64,6 +70,10 @@ impl JsonRPCRequestHandler for WalletListHandler {
             .into_iter()
             .collect::<anyhow::Result<Vec<(TokenAmount, &Address)>>>()?;
         for (balance, addr) in r {
+            let addr = self
+                .resolver
+                .resolve(manager, &subnet, *addr, format)
+                .await?;
             hashmap.insert(addr.to_string(), balance.to_string());
         }
         Ok(hashmap)
@@ -1,4 +1,7 @@
 // Copyright 2022-2023 Protocol Labs
 // SPDX-License-Identifier: MIT
+pub(crate) mod address;
+pub mod balance;
+pub mod import;
 pub mod list;
 pub mod new;
@@ -4,9 +4,10 @@
 
 use crate::lotus::message::wallet::WalletKeyType;
 use crate::manager::SubnetManager;
+use crate::server::error::HandlerError;
 use crate::server::handlers::manager::subnet::SubnetManagerPool;
+use crate::server::handlers::wallet::address::AddressResolver;
 use crate::server::JsonRPCRequestHandler;
-use anyhow::anyhow;
 use async_trait::async_trait;
 use ipc_sdk::subnet_id::SubnetID;
 use serde::{Deserialize, Serialize};
@@ -17,6 +18,8 @@ use std::sync::Arc;
 pub struct WalletNewParams {
     pub subnet: String,
     pub key_type: String,
+    /// The format of the returned address (`id` or `robust`), defaults to the node's output
+    pub address_format: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -27,11 +30,12 @@ pub struct WalletNewResponse {
 /// Send value between two addresses within a subnet
 pub(crate) struct WalletNewHandler {
     pool: Arc<SubnetManagerPool>,
+    resolver: Arc<AddressResolver>,
 }
 
 impl WalletNewHandler {
-    pub(crate) fn new(pool: Arc<SubnetManagerPool>) -> Self {
-        Self { pool }
+    pub(crate) fn new(pool: Arc<SubnetManagerPool>, resolver: Arc<AddressResolver>) -> Self {
+        Self { pool, resolver }
     }
 }
 
@@ -43,14 +47,19 @@ impl JsonRPCRequestHandler for WalletNewHandler {
     async fn handle(&self, request: Self::Request) -> anyhow::Result<Self::Response> {
         let subnet = SubnetID::from_str(&request.subnet)?;
         let conn = match self.pool.get(&subnet) {
-            None => return Err(anyhow!("target subnet not found")),
+            None => return Err(HandlerError::SubnetNotFound(subnet.to_string()).into()),
             Some(conn) => conn,
         };
 
+        let format = AddressResolver::parse_format(&request.address_format)?;
         let address = conn
             .manager()
             .wallet_new(WalletKeyType::from_str(&request.key_type)?)
             .await?;
+        let address = self
+            .resolver
+            .resolve(conn.manager(), &subnet, address, format)
+            .await?;
         Ok(WalletNewResponse {
             address: address.to_string(),
         })
@@ -1,6 +1,7 @@
 // Copyright 2022-2023 Protocol Labs
 // SPDX-License-Identifier: MIT
 use std::sync::Arc;
+use std::time::Instant;
 
 use anyhow::Result;
 use async_trait::async_trait;
@@ -13,12 +14,24 @@ use warp::reply::with_status;
 use warp::{Filter, Rejection, Reply};
 
 use crate::config::JSON_RPC_VERSION;
-use crate::config::{ReloadableConfig, JSON_RPC_ENDPOINT};
+use crate::config::{json_rpc_methods, ReloadableConfig, JSON_RPC_ENDPOINT};
+use crate::server::error::error_code;
+use crate::server::metrics::{Metrics, METRICS_ENDPOINT};
 use crate::server::request::JSONRPCRequest;
 use crate::server::response::{JSONRPCError, JSONRPCErrorResponse, JSONRPCResultResponse};
 use crate::server::Handlers;
 
 type ArcHandlers = Arc<Handlers>;
+type ArcMetrics = Option<Arc<Metrics>>;
+/// Returns the token the requests must present, read on every request so that a new token in
+/// the config takes effect on reload.
+type AuthToken = Arc<dyn Fn() -> Option<String> + Send + Sync>;
+
+/// The path of the liveness probe of the server, which is never authenticated.
+pub const HEALTH_ENDPOINT: &str = "health";
+
+/// The methods whose params hold secrets, e.g. private keys, and must never be logged.
+const REDACTED_METHODS: [&str; 1] = [json_rpc_methods::WALLET_IMPORT];
 
 /// The IPC JSON RPC node that contains all the methods and handlers. The underlying implementation
 /// is using `warp`.
@@ -73,10 +86,19 @@ impl IntoSubsystem<anyhow::Error> for JsonRPCServer {
 
         // Start the server.
         let handlers = Arc::new(Handlers::new(self.config.clone())?);
-        let (_, server) = warp::serve(json_rpc_filter(handlers)).bind_with_graceful_shutdown(
-            self.config.get_config().server.json_rpc_address,
-            async move { notify_recv.notified().await },
-        );
+        let metrics = if self.config.get_config().server.metrics {
+            log::info!("serving metrics at /{METRICS_ENDPOINT:}");
+            Some(Arc::new(Metrics::new()?))
+        } else {
+            None
+        };
+        let config = self.config.clone();
+        let auth_token: AuthToken = Arc::new(move || config.get_config().server.auth_token.clone());
+        let (_, server) = warp::serve(json_rpc_filter(handlers, metrics, auth_token))
+            .bind_with_graceful_shutdown(
+                self.config.get_config().server.json_rpc_address,
+                async move { notify_recv.notified().await },
+            );
         let server_handle = tokio::spawn(server);
 
         // Wait for the shutdown signal and gracefully shutdown.
@@ -95,28 +117,93 @@ impl IntoSubsystem<anyhow::Error> for JsonRPCServer {
 /// - Listen to POST requests on the DEFAULT_JSON_RPC_ENDPOINT
 /// - Extract the body of the request.
 /// - Pass it to to the json_rpc_filter to deserialize into a jsonrpc request.
+/// - Serve the metrics on GET requests on the METRICS_ENDPOINT, if they are enabled.
+/// - Reject the requests without the current `auth_token`, if set, except those to the
+///   HEALTH_ENDPOINT.
 fn json_rpc_filter(
     handlers: ArcHandlers,
+    metrics: ArcMetrics,
+    auth_token: AuthToken,
 ) -> impl Filter<Extract = (impl Reply,), Error = warp::Rejection> + Clone {
-    warp::post()
+    let json_rpc = warp::post()
         .and(warp::path(JSON_RPC_ENDPOINT))
         .and(warp::body::bytes())
         .and_then(to_json_rpc_request)
         .and(with_handlers(handlers))
-        .and_then(handle_request)
+        .and(with_metrics(metrics.clone()))
+        .and_then(handle_request);
+    let metrics = warp::path(METRICS_ENDPOINT)
+        .and(warp::get())
+        .and(with_metrics(metrics))
+        .and_then(handle_metrics);
+    let health = warp::path(HEALTH_ENDPOINT)
+        .and(warp::get())
+        .map(|| with_status("OK", StatusCode::OK));
+    health
+        .or(with_auth(auth_token).and(json_rpc.or(metrics)))
         .recover(handle_rejection)
 }
 
+/// Filter that rejects the requests without the current bearer `auth_token` in their
+/// `Authorization` header, if set.
+fn with_auth(auth_token: AuthToken) -> impl Filter<Extract = (), Error = warp::Rejection> + Clone {
+    warp::header::optional::<String>("authorization")
+        .and_then(move |header: Option<String>| {
+            let authorized = is_authorized(auth_token().as_deref(), header.as_deref());
+            async move {
+                if authorized {
+                    Ok(())
+                } else {
+                    Err(warp::reject::custom(Unauthorized))
+                }
+            }
+        })
+        .untuple_one()
+}
+
+/// Returns whether the `Authorization` header presents the bearer `auth_token`, if any. The
+/// tokens are compared in constant time so the comparison does not leak how much matched.
+fn is_authorized(auth_token: Option<&str>, header: Option<&str>) -> bool {
+    let expected = match auth_token {
+        Some(token) => token.as_bytes(),
+        None => return true,
+    };
+    let presented = match header.and_then(|h| h.strip_prefix("Bearer ")) {
+        Some(token) => token.as_bytes(),
+        None => return false,
+    };
+    presented.len() == expected.len()
+        && presented
+            .iter()
+            .zip(expected)
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
 fn with_handlers(
     handlers: ArcHandlers,
 ) -> impl Filter<Extract = (ArcHandlers,), Error = std::convert::Infallible> + Clone {
     warp::any().map(move || handlers.clone())
 }
 
+fn with_metrics(
+    metrics: ArcMetrics,
+) -> impl Filter<Extract = (ArcMetrics,), Error = std::convert::Infallible> + Clone {
+    warp::any().map(move || metrics.clone())
+}
+
 // Filter that deserializes the body of the request into a jsonrpc request.
 async fn to_json_rpc_request(bytes: Bytes) -> Result<JSONRPCRequest, warp::Rejection> {
     serde_json::from_slice::<JSONRPCRequest>(bytes.as_ref()).map_err(|e| {
-        log::debug!("cannot deserialize {bytes:?} due to {e:?}");
+        // neither the body nor the error message, which may quote its values, are logged, as
+        // the body may hold private keys, e.g. those of wallet imports
+        log::debug!(
+            "cannot deserialize a request of {:} bytes: {:?} error at line {:} column {:}",
+            bytes.len(),
+            e.classify(),
+            e.line(),
+            e.column()
+        );
         warp::reject::custom(InvalidParameter)
     })
 }
@@ -125,8 +212,13 @@ async fn to_json_rpc_request(bytes: Bytes) -> Result<JSONRPCRequest, warp::Rejec
 async fn handle_request(
     json_rpc_request: JSONRPCRequest,
     handlers: ArcHandlers,
+    metrics: ArcMetrics,
 ) -> Result<impl Reply, warp::Rejection> {
-    log::debug!("received json rpc request = {:?}", json_rpc_request);
+    // the params of some methods hold private keys and must not be logged
+    let redacted = REDACTED_METHODS.contains(&json_rpc_request.method.as_str());
+    if !redacted {
+        log::debug!("received json rpc request = {:?}", json_rpc_request);
+    }
 
     let JSONRPCRequest {
         id,
@@ -141,12 +233,21 @@ async fn handle_request(
         )));
     }
 
-    log::debug!("received method = {method:?} and params = {params:?}");
-    match handlers.handle(method, params).await {
+    if !redacted {
+        log::debug!("received method = {method:?} and params = {params:?}");
+    }
+    let known = handlers.contains(&method);
+    let start = Instant::now();
+    let result = handlers.handle(method.clone(), params).await;
+    if let Some(metrics) = metrics {
+        metrics.observe(&method, known, start.elapsed(), result.is_err());
+    }
+
+    match result {
         Ok(response) => Ok(warp::reply::json(&JSONRPCResultResponse::new(id, response))),
         Err(e) => {
             let error: JSONRPCError<()> = JSONRPCError {
-                code: -1,
+                code: error_code(&e),
                 data: None,
                 message: e.to_string(),
             };
@@ -155,17 +256,53 @@ async fn handle_request(
     }
 }
 
+/// Serves the metrics in the prometheus text format, or nothing if they are not enabled.
+async fn handle_metrics(metrics: ArcMetrics) -> Result<impl Reply, warp::Rejection> {
+    let metrics = match metrics {
+        Some(metrics) => metrics,
+        None => {
+            return Ok(with_status(
+                String::from("NOT_FOUND"),
+                StatusCode::NOT_FOUND,
+            ))
+        }
+    };
+    match metrics.encode() {
+        Ok(encoded) => Ok(with_status(encoded, StatusCode::OK)),
+        Err(e) => {
+            log::error!("cannot encode metrics: {e:}");
+            Ok(with_status(
+                String::from("INTERNAL_SERVER_ERROR"),
+                StatusCode::INTERNAL_SERVER_ERROR,
+            ))
+        }
+    }
+}
+
 /// The invalid parameter warp rejection error handling
 #[derive(Debug)]
 struct InvalidParameter;
 
 impl Reject for InvalidParameter {}
 
+/// The rejection of the requests without the auth token of the server.
+#[derive(Debug)]
+struct Unauthorized;
+
+impl Reject for Unauthorized {}
+
 async fn handle_rejection(err: Rejection) -> Result<impl Reply, warp::Rejection> {
     if err.is_not_found() {
         Ok(with_status("NOT_FOUND", StatusCode::NOT_FOUND))
+    } else if err.find::<Unauthorized>().is_some() {
+        Ok(with_status("UNAUTHORIZED", StatusCode::UNAUTHORIZED))
     } else if err.find::<InvalidParameter>().is_some() {
         Ok(with_status("BAD_REQUEST", StatusCode::BAD_REQUEST))
+    } else if err.find::<warp::reject::MethodNotAllowed>().is_some() {
+        Ok(with_status(
+            "METHOD_NOT_ALLOWED",
+            StatusCode::METHOD_NOT_ALLOWED,
+        ))
     } else {
         log::error!("unhandled rejection: {:?}", err);
         Ok(with_status(
@@ -177,12 +314,16 @@ async fn handle_rejection(err: Rejection) -> Result<impl Reply, warp::Rejection>
 
 #[cfg(test)]
 mod tests {
-    use std::sync::Arc;
+    use std::sync::{Arc, Mutex};
 
     use warp::http::StatusCode;
 
     use crate::config::{JSON_RPC_ENDPOINT, JSON_RPC_VERSION};
-    use crate::server::jsonrpc::{json_rpc_filter, ArcHandlers, JSONRPCResultResponse};
+    use crate::server::jsonrpc::{
+        is_authorized, json_rpc_filter, ArcHandlers, AuthToken, JSONRPCResultResponse,
+        HEALTH_ENDPOINT,
+    };
+    use crate::server::metrics::{Metrics, METRICS_ENDPOINT};
     use crate::server::request::JSONRPCRequest;
     use crate::server::Handlers;
 
@@ -190,10 +331,14 @@ mod tests {
         Arc::new(Handlers::empty_handlers())
     }
 
+    fn no_auth() -> AuthToken {
+        Arc::new(|| None)
+    }
+
     #[tokio::test]
     #[ignore]
     async fn test_json_rpc_filter_works() {
-        let filter = json_rpc_filter(get_empty_handlers());
+        let filter = json_rpc_filter(get_empty_handlers(), None, no_auth());
 
         let foo = "foo".to_string();
         let jsonrpc = String::from(JSON_RPC_VERSION);
@@ -222,7 +367,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_json_rpc_filter_cannot_parse_param() {
-        let filter = json_rpc_filter(get_empty_handlers());
+        let filter = json_rpc_filter(get_empty_handlers(), None, no_auth());
 
         let value = warp::test::request()
             .method("POST")
@@ -236,7 +381,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_json_rpc_filter_not_found() {
-        let filter = json_rpc_filter(get_empty_handlers());
+        let filter = json_rpc_filter(get_empty_handlers(), None, no_auth());
 
         let value = warp::test::request()
             .method("POST")
@@ -247,4 +392,104 @@ mod tests {
 
         assert_eq!(StatusCode::NOT_FOUND, value.status());
     }
+
+    #[tokio::test]
+    async fn test_json_rpc_filter_metrics() {
+        let metrics = Arc::new(Metrics::new().unwrap());
+        let filter = json_rpc_filter(get_empty_handlers(), Some(metrics), no_auth());
+
+        let req = JSONRPCRequest {
+            id: 0,
+            jsonrpc: String::from(JSON_RPC_VERSION),
+            method: String::from("ipc_foo"),
+            params: Default::default(),
+        };
+        warp::test::request()
+            .method("POST")
+            .path(&format!("/{JSON_RPC_ENDPOINT:}"))
+            .json(&req)
+            .reply(&filter)
+            .await;
+
+        let value = warp::test::request()
+            .method("GET")
+            .path(&format!("/{METRICS_ENDPOINT:}"))
+            .reply(&filter)
+            .await;
+        assert_eq!(StatusCode::OK, value.status());
+        let body = String::from_utf8(value.body().to_vec()).unwrap();
+        // the method is not served by the handlers
+        for line in [
+            r#"ipc_agent_json_rpc_requests_total{method="unknown"} 1"#,
+            r#"ipc_agent_json_rpc_errors_total{method="unknown"} 1"#,
+            r#"ipc_agent_json_rpc_latency_seconds_count{method="unknown"} 1"#,
+        ] {
+            assert!(body.contains(line), "{line} not in {body}");
+        }
+
+        // metrics are not served unless enabled
+        let filter = json_rpc_filter(get_empty_handlers(), None, no_auth());
+        let value = warp::test::request()
+            .method("GET")
+            .path(&format!("/{METRICS_ENDPOINT:}"))
+            .reply(&filter)
+            .await;
+        assert_eq!(StatusCode::NOT_FOUND, value.status());
+    }
+
+    #[tokio::test]
+    async fn test_json_rpc_filter_auth() {
+        let token = Arc::new(Mutex::new(String::from("secret")));
+        let current = token.clone();
+        let auth_token: AuthToken = Arc::new(move || Some(current.lock().unwrap().clone()));
+        let filter = json_rpc_filter(get_empty_handlers(), None, auth_token);
+        let req = JSONRPCRequest {
+            id: 0,
+            jsonrpc: String::from(JSON_RPC_VERSION),
+            method: String::from("ipc_foo"),
+            params: Default::default(),
+        };
+
+        let status = |header: Option<&'static str>| {
+            let mut request = warp::test::request()
+                .method("POST")
+                .path(&format!("/{JSON_RPC_ENDPOINT:}"))
+                .json(&req);
+            if let Some(header) = header {
+                request = request.header("authorization", header);
+            }
+            let filter = filter.clone();
+            async move { request.reply(&filter).await.status() }
+        };
+        assert_eq!(status(None).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(status(Some("Bearer wrong")).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(status(Some("secret")).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(status(Some("Bearer secret")).await, StatusCode::OK);
+
+        // a token reloaded from the config replaces the previous one right away
+        *token.lock().unwrap() = String::from("rotated");
+        assert_eq!(
+            status(Some("Bearer secret")).await,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(status(Some("Bearer rotated")).await, StatusCode::OK);
+
+        // the health endpoint is not authenticated
+        let value = warp::test::request()
+            .method("GET")
+            .path(&format!("/{HEALTH_ENDPOINT:}"))
+            .reply(&filter)
+            .await;
+        assert_eq!(StatusCode::OK, value.status());
+    }
+
+    #[test]
+    fn test_is_authorized() {
+        assert!(is_authorized(None, None));
+        assert!(is_authorized(None, Some("Bearer anything")));
+        assert!(is_authorized(Some("secret"), Some("Bearer secret")));
+        assert!(!is_authorized(Some("secret"), None));
+        assert!(!is_authorized(Some("secret"), Some("Bearer secret2")));
+        assert!(!is_authorized(Some("secret"), Some("Basic secret")));
+    }
 }
@@ -0,0 +1,107 @@
+// Copyright 2022-2023 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! The limit of the requests the server handles concurrently.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use tokio::sync::{Semaphore, SemaphorePermit};
+
+use crate::server::error::HandlerError;
+
+/// The requests handled concurrently if not set in the config.
+pub const DEFAULT_MAX_CONCURRENT_REQUESTS: usize = 32;
+/// The requests waiting for one of the concurrent slots if not set in the config.
+pub const DEFAULT_MAX_QUEUED_REQUESTS: usize = 128;
+
+/// Limits the requests handled concurrently, so that a burst of requests does not open as many
+/// connections to the nodes. Requests above the limit wait for a slot in a bounded queue, and
+/// are rejected right away when the queue is full.
+pub(crate) struct RequestLimiter {
+    permits: Semaphore,
+    queued: AtomicUsize,
+    max_queued: usize,
+}
+
+impl RequestLimiter {
+    pub fn new(max_concurrent: usize, max_queued: usize) -> Self {
+        Self {
+            permits: Semaphore::new(max_concurrent),
+            queued: AtomicUsize::new(0),
+            max_queued,
+        }
+    }
+
+    /// Waits for a slot to handle a request in, or returns a [`HandlerError::Overloaded`] if
+    /// the queue is full. The slot is released when the returned permit is dropped.
+    pub async fn acquire(&self) -> anyhow::Result<SemaphorePermit<'_>> {
+        if let Ok(permit) = self.permits.try_acquire() {
+            return Ok(permit);
+        }
+
+        let slot = QueueSlot::take(&self.queued);
+        if slot.position >= self.max_queued {
+            return Err(HandlerError::Overloaded.into());
+        }
+        let permit = self.permits.acquire().await;
+        // the semaphore is never closed
+        Ok(permit.expect("request semaphore closed"))
+    }
+}
+
+impl Default for RequestLimiter {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_CONCURRENT_REQUESTS, DEFAULT_MAX_QUEUED_REQUESTS)
+    }
+}
+
+/// A slot in the queue of the requests waiting, freed when dropped, including when the request
+/// is dropped while waiting, e.g. because the client went away.
+struct QueueSlot<'a> {
+    queued: &'a AtomicUsize,
+    /// The number of requests that were waiting before this one.
+    position: usize,
+}
+
+impl<'a> QueueSlot<'a> {
+    fn take(queued: &'a AtomicUsize) -> Self {
+        let position = queued.fetch_add(1, Ordering::SeqCst);
+        Self { queued, position }
+    }
+}
+
+impl Drop for QueueSlot<'_> {
+    fn drop(&mut self) {
+        self.queued.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::server::error::{error_code, SERVER_OVERLOADED_CODE};
+    use crate::server::limiter::RequestLimiter;
+
+    #[tokio::test]
+    async fn test_request_limiter() {
+        let limiter = RequestLimiter::new(1, 1);
+        let permit = limiter.acquire().await.unwrap();
+
+        // the second request waits for the first one
+        let queued = limiter.acquire();
+        tokio::pin!(queued);
+        assert!(tokio::time::timeout(Duration::from_millis(50), &mut queued)
+            .await
+            .is_err());
+
+        // the third one finds the queue full
+        let err = limiter.acquire().await.unwrap_err();
+        assert_eq!(error_code(&err), SERVER_OVERLOADED_CODE);
+
+        // and the second one is handled once the first one is done
+        drop(permit);
+        let permit = queued.await.unwrap();
+        drop(permit);
+        assert!(limiter.acquire().await.is_ok());
+    }
+}
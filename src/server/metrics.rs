@@ -0,0 +1,82 @@
+// Copyright 2022-2023 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! The prometheus metrics of the json rpc methods served by the agent.
+
+use std::time::Duration;
+
+use anyhow::Result;
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounterVec, Opts, Registry, TextEncoder,
+};
+
+/// The path the metrics are served at, if enabled in the config.
+pub const METRICS_ENDPOINT: &str = "metrics";
+
+/// The label of the requests for methods the agent does not serve, so that arbitrary method
+/// names cannot grow the number of series without bounds.
+const UNKNOWN_METHOD: &str = "unknown";
+
+/// The number of requests, failed requests and the latency of each json rpc method.
+pub struct Metrics {
+    registry: Registry,
+    requests: IntCounterVec,
+    errors: IntCounterVec,
+    latency: HistogramVec,
+}
+
+impl Metrics {
+    pub fn new() -> Result<Self> {
+        let requests = IntCounterVec::new(
+            Opts::new(
+                "ipc_agent_json_rpc_requests_total",
+                "Number of json rpc requests by method",
+            ),
+            &["method"],
+        )?;
+        let errors = IntCounterVec::new(
+            Opts::new(
+                "ipc_agent_json_rpc_errors_total",
+                "Number of json rpc requests that returned an error by method",
+            ),
+            &["method"],
+        )?;
+        let latency = HistogramVec::new(
+            HistogramOpts::new(
+                "ipc_agent_json_rpc_latency_seconds",
+                "Time to handle json rpc requests by method",
+            ),
+            &["method"],
+        )?;
+
+        let registry = Registry::new();
+        registry.register(Box::new(requests.clone()))?;
+        registry.register(Box::new(errors.clone()))?;
+        registry.register(Box::new(latency.clone()))?;
+        Ok(Self {
+            registry,
+            requests,
+            errors,
+            latency,
+        })
+    }
+
+    /// Records a request for `method`, which took `elapsed` to handle and failed if `failed`.
+    /// Methods that are not `known` are recorded under a single label.
+    pub fn observe(&self, method: &str, known: bool, elapsed: Duration, failed: bool) {
+        let method = if known { method } else { UNKNOWN_METHOD };
+        self.requests.with_label_values(&[method]).inc();
+        if failed {
+            self.errors.with_label_values(&[method]).inc();
+        }
+        self.latency
+            .with_label_values(&[method])
+            .observe(elapsed.as_secs_f64());
+    }
+
+    /// Returns the metrics in the prometheus text format.
+    pub fn encode(&self) -> Result<String> {
+        let mut buffer = vec![];
+        TextEncoder::new().encode(&self.registry.gather(), &mut buffer)?;
+        Ok(String::from_utf8(buffer)?)
+    }
+}
@@ -8,8 +8,11 @@ use serde::de::DeserializeOwned;
 use serde::Serialize;
 use std::fmt::Debug;
 
+pub mod error;
 mod handlers;
 pub mod jsonrpc;
+mod limiter;
+pub mod metrics;
 pub mod request;
 pub mod response;
 
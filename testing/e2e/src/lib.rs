@@ -44,7 +44,7 @@ impl TestClient {
             &JoinSubnetArgs {
                 ipc_agent_url: self.json_rpc_url.clone(),
                 from: None,
-                subnet: subnet_id.to_string(),
+                subnet: subnet_id.clone(),
                 collateral: 10,
                 validator_net_addr,
             },
@@ -58,7 +58,7 @@ impl TestClient {
             &KillSubnetArgs {
                 ipc_agent_url: self.json_rpc_url.clone(),
                 from: None,
-                subnet: subnet_id.to_string(),
+                subnet: subnet_id.clone(),
             },
         )
         .await
@@ -70,7 +70,7 @@ impl TestClient {
             &LeaveSubnetArgs {
                 ipc_agent_url: self.json_rpc_url.clone(),
                 from: None,
-                subnet: subnet_id.to_string(),
+                subnet: subnet_id.clone(),
             },
         )
         .await